@@ -12,6 +12,7 @@
 use rustc_span::{self, Pos, Span};
 
 use smallvec::SmallVec;
+use std::path::Path;
 use std::rc::Rc;
 
 // These macros all relate to the file system; they either return
@@ -109,6 +110,7 @@ pub fn expand_include<'cx>(
             return DummyResult::any(sp);
         }
     };
+    record_included_file(cx, &file);
     let p = new_parser_from_file(cx.parse_sess(), &file, Some(sp));
 
     // If in the included file we have e.g., `mod bar;`,
@@ -181,6 +183,7 @@ pub fn expand_include_str(
             return DummyResult::any(sp);
         }
     };
+    record_included_file(cx, &file);
     match cx.source_map().load_binary_file(&file) {
         Ok(bytes) => match std::str::from_utf8(&bytes) {
             Ok(src) => {
@@ -215,6 +218,7 @@ pub fn expand_include_bytes(
             return DummyResult::any(sp);
         }
     };
+    record_included_file(cx, &file);
     match cx.source_map().load_binary_file(&file) {
         Ok(bytes) => base::MacEager::expr(cx.expr_lit(sp, ast::LitKind::ByteStr(bytes.into()))),
         Err(e) => {
@@ -223,3 +227,9 @@ pub fn expand_include_bytes(
         }
     }
 }
+
+/// Records `file` for the JSON dep-info, which lists included files separately from the other
+/// source files.
+fn record_included_file(cx: &ExtCtxt<'_>, file: &Path) {
+    cx.sess.parse_sess.include_depinfo.borrow_mut().insert(Symbol::intern(&file.to_string_lossy()));
+}
@@ -6,6 +6,7 @@
 use rustc_ast::attr;
 use rustc_ast::ptr::P;
 use rustc_ast_pretty::pprust;
+use rustc_data_structures::sync::Lrc;
 use rustc_errors::Applicability;
 use rustc_expand::base::*;
 use rustc_session::Session;
@@ -50,6 +51,61 @@ pub fn expand_test_case(
     return vec![Annotatable::Item(item)];
 }
 
+// #[test_setup] and #[test_teardown] mark functions that run before and after all
+// tests. Like #[test], they remove the item when not building for test. Otherwise,
+// they mark it with the inert attribute "rustc_test_hook", whose value is the name
+// of the macro, and the test harness checks that the item is a function it can call.
+
+pub fn expand_test_setup(
+    cx: &mut ExtCtxt<'_>,
+    attr_sp: Span,
+    meta_item: &ast::MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    check_builtin_macro_attribute(cx, meta_item, sym::test_setup);
+    warn_on_duplicate_attribute(&cx, &item, sym::test_setup);
+    expand_test_hook(cx, attr_sp, item, sym::test_setup)
+}
+
+pub fn expand_test_teardown(
+    cx: &mut ExtCtxt<'_>,
+    attr_sp: Span,
+    meta_item: &ast::MetaItem,
+    item: Annotatable,
+) -> Vec<Annotatable> {
+    check_builtin_macro_attribute(cx, meta_item, sym::test_teardown);
+    warn_on_duplicate_attribute(&cx, &item, sym::test_teardown);
+    expand_test_hook(cx, attr_sp, item, sym::test_teardown)
+}
+
+fn expand_test_hook(
+    cx: &mut ExtCtxt<'_>,
+    attr_sp: Span,
+    item: Annotatable,
+    name: Symbol,
+) -> Vec<Annotatable> {
+    if !cx.ecfg.should_test {
+        return vec![];
+    }
+
+    match item {
+        Annotatable::Item(mut item) => {
+            let sp = cx.with_def_site_ctxt(attr_sp);
+            let marker =
+                attr::mk_name_value_item_str(Ident::new(sym::rustc_test_hook, sp), name, sp);
+            item.attrs.push(cx.attribute(marker));
+            vec![Annotatable::Item(item)]
+        }
+        other => {
+            cx.span_err(
+                other.span(),
+                &format!("the `#[{name}]` attribute may only be used on a function in a module"),
+            );
+            vec![other]
+        }
+    }
+}
+
 pub fn expand_test(
     cx: &mut ExtCtxt<'_>,
     attr_sp: Span,
@@ -133,6 +189,17 @@ pub fn expand_test_or_bench(
         return vec![Annotatable::Item(item)];
     }
 
+    // The lines from the `#[test]` attribute to the end of the function, which
+    // `--at` uses to select a test by its location.
+    let source_map = cx.source_map();
+    let end = source_map.lookup_char_pos(item.span.hi());
+    let mut start = source_map.lookup_char_pos(attr_sp.lo());
+    if !Lrc::ptr_eq(&start.file, &end.file) || start.line > end.line {
+        // The attribute comes from a macro expanded elsewhere.
+        start = source_map.lookup_char_pos(item.span.lo());
+    }
+    let source_file = Symbol::intern(&end.file.name.prefer_remapped().to_string());
+
     let (sp, attr_sp) = (cx.with_def_site_ctxt(item.span), cx.with_def_site_ctxt(attr_sp));
 
     let test_id = Ident::new(sym::test, attr_sp);
@@ -275,6 +342,23 @@ pub fn expand_test_or_bench(
                                             cx.expr_none(sp)
                                         },
                                     ),
+                                    // source_file: "src/lib.rs"
+                                    field("source_file", cx.expr_str(sp, source_file)),
+                                    // start_line: 42
+                                    field("start_line", cx.expr_usize(sp, start.line)),
+                                    // end_line: 47
+                                    field("end_line", cx.expr_usize(sp, end.line)),
+                                    // item_path: None
+                                    field("item_path", cx.expr_none(sp)),
+                                    // timeout_secs: Some(60) | None
+                                    field(
+                                        "timeout_secs",
+                                        if let Some(secs) = test_timeout(cx, &item) {
+                                            cx.expr_some(sp, cx.expr_u64(sp, secs))
+                                        } else {
+                                            cx.expr_none(sp)
+                                        },
+                                    ),
                                     // compile_fail: true | false
                                     field("compile_fail", cx.expr_bool(sp, false)),
                                     // no_run: true | false
@@ -391,6 +475,19 @@ fn should_ignore_message(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<Symbol> {
     }
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<u64> {
+    let attr = cx.sess.find_by_name(&i.attrs, sym::test_timeout)?;
+    // Malformed attributes were already reported.
+    let secs = attr.value_str()?;
+    match secs.as_str().parse() {
+        Ok(0) | Err(_) => {
+            cx.span_err(attr.span, "the timeout of a test must be a positive number of seconds");
+            None
+        }
+        Ok(secs) => Some(secs),
+    }
+}
+
 fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     match cx.sess.find_by_name(&i.attrs, sym::should_panic) {
         Some(attr) => {
@@ -23,11 +23,30 @@ struct Test {
     ident: Ident,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum HookKind {
+    Setup,
+    Teardown,
+}
+
+/// A function marked with `#[test_setup]` or `#[test_teardown]`.
+struct Hook {
+    /// The position of the hook in the crate, as hooks of the same kind run in
+    /// the order they are declared.
+    index: usize,
+    kind: HookKind,
+    span: Span,
+    ident: Ident,
+    /// The path of the function relative to the crate root, used in reports.
+    name: Symbol,
+}
+
 struct TestCtxt<'a> {
     ext_cx: ExtCtxt<'a>,
     panic_strategy: PanicStrategy,
     def_site: Span,
     test_cases: Vec<Test>,
+    hooks: Vec<Hook>,
     reexport_test_harness_main: Option<Symbol>,
     test_runner: Option<ast::Path>,
 }
@@ -82,13 +101,24 @@ pub fn inject(sess: &Session, resolver: &mut dyn ResolverExpand, krate: &mut ast
 struct TestHarnessGenerator<'a> {
     cx: TestCtxt<'a>,
     tests: Vec<Test>,
+    hooks: Vec<Hook>,
+    hook_count: usize,
+    // The path of the module that is being visited
+    mod_path: Vec<Ident>,
 }
 
 impl TestHarnessGenerator<'_> {
-    fn add_test_cases(&mut self, node_id: ast::NodeId, span: Span, prev_tests: Vec<Test>) {
+    fn add_test_cases(
+        &mut self,
+        node_id: ast::NodeId,
+        span: Span,
+        prev_tests: Vec<Test>,
+        prev_hooks: Vec<Hook>,
+    ) {
         let mut tests = mem::replace(&mut self.tests, prev_tests);
+        let mut hooks = mem::replace(&mut self.hooks, prev_hooks);
 
-        if !tests.is_empty() {
+        if !tests.is_empty() || !hooks.is_empty() {
             // Create an identifier that will hygienically resolve the test
             // case name, even in another module.
             let expn_id = self.cx.ext_cx.resolver.expansion_for_ast_pass(
@@ -103,16 +133,56 @@ fn add_test_cases(&mut self, node_id: ast::NodeId, span: Span, prev_tests: Vec<T
                 test.ident.span =
                     test.ident.span.apply_mark(expn_id.to_expn_id(), Transparency::Opaque);
             }
+            for hook in &mut hooks {
+                hook.ident.span =
+                    hook.ident.span.apply_mark(expn_id.to_expn_id(), Transparency::Opaque);
+            }
             self.cx.test_cases.extend(tests);
+            self.cx.hooks.extend(hooks);
+            self.cx.hooks.sort_by_key(|hook| hook.index);
         }
     }
+
+    fn add_hook(&mut self, kind: HookKind, item: &ast::Item) {
+        let attr_name = match kind {
+            HookKind::Setup => "test_setup",
+            HookKind::Teardown => "test_teardown",
+        };
+        match &item.kind {
+            ast::ItemKind::Fn(box ast::Fn { sig, generics, .. })
+                if sig.decl.inputs.is_empty() && generics.params.is_empty() => {}
+            _ => {
+                self.cx.ext_cx.span_err(
+                    item.span,
+                    &format!(
+                        "the `#[{attr_name}]` attribute may only be used on a function \
+                         without parameters or generics"
+                    ),
+                );
+                return;
+            }
+        }
+
+        let name = self
+            .mod_path
+            .iter()
+            .chain(iter::once(&item.ident))
+            .map(|ident| ident.as_str())
+            .collect::<Vec<_>>()
+            .join("::");
+        let index = self.hook_count;
+        self.hook_count += 1;
+        let name = Symbol::intern(&name);
+        self.hooks.push(Hook { index, kind, span: item.span, ident: item.ident, name });
+    }
 }
 
 impl<'a> MutVisitor for TestHarnessGenerator<'a> {
     fn visit_crate(&mut self, c: &mut ast::Crate) {
         let prev_tests = mem::take(&mut self.tests);
+        let prev_hooks = mem::take(&mut self.hooks);
         noop_visit_crate(c, self);
-        self.add_test_cases(ast::CRATE_NODE_ID, c.spans.inner_span, prev_tests);
+        self.add_test_cases(ast::CRATE_NODE_ID, c.spans.inner_span, prev_tests, prev_hooks);
 
         // Create a main function to run our tests
         c.items.push(mk_main(&mut self.cx));
@@ -127,13 +197,23 @@ fn visit_crate(&mut self, c: &mut ast::Crate) {
             self.tests.push(test);
         }
 
+        let sess = self.cx.ext_cx.sess;
+        match sess.first_attr_value_str_by_name(&item.attrs, sym::rustc_test_hook) {
+            Some(sym::test_setup) => self.add_hook(HookKind::Setup, &item),
+            Some(sym::test_teardown) => self.add_hook(HookKind::Teardown, &item),
+            _ => {}
+        }
+
         // We don't want to recurse into anything other than mods, since
         // mods or tests inside of functions will break things
         if let ast::ItemKind::Mod(_, ModKind::Loaded(.., ref spans)) = item.kind {
             let ast::ModSpans { inner_span: span, inject_use_span: _ } = *spans;
             let prev_tests = mem::take(&mut self.tests);
+            let prev_hooks = mem::take(&mut self.hooks);
+            self.mod_path.push(item.ident);
             noop_visit_item_kind(&mut item.kind, self);
-            self.add_test_cases(item.id, span, prev_tests);
+            self.mod_path.pop();
+            self.add_test_cases(item.id, span, prev_tests, prev_hooks);
         }
         smallvec![P(item)]
     }
@@ -238,11 +318,19 @@ fn generate_test_harness(
         panic_strategy,
         def_site,
         test_cases: Vec::new(),
+        hooks: Vec::new(),
         reexport_test_harness_main,
         test_runner,
     };
 
-    TestHarnessGenerator { cx, tests: Vec::new() }.visit_crate(krate);
+    TestHarnessGenerator {
+        cx,
+        tests: Vec::new(),
+        hooks: Vec::new(),
+        hook_count: 0,
+        mod_path: Vec::new(),
+    }
+    .visit_crate(krate);
 }
 
 /// Creates a function item for use as the main function of a test build.
@@ -275,14 +363,36 @@ fn generate_test_harness(
 /// [`TestCtxt::reexport_test_harness_main`] provides a different name for the `main`
 /// function and [`TestCtxt::test_runner`] provides a path that replaces
 /// `test::test_main_static`.
+///
+/// If the crate has `#[test_setup]` or `#[test_teardown]` functions, they are
+/// passed to `test::test_main_static_with_hooks` instead:
+///
+/// ```ignore (illustrative)
+/// test::test_main_static_with_hooks(
+///     &[&test_const1, &test_const2],
+///     &[test::TestHook { name: "setup", hook_fn: || test::assert_test_result(setup()) }],
+///     &[],
+/// );
+/// ```
 fn mk_main(cx: &mut TestCtxt<'_>) -> P<ast::Item> {
     let sp = cx.def_site;
     let ecx = &cx.ext_cx;
     let test_id = Ident::new(sym::test, sp);
 
-    let runner_name = match cx.panic_strategy {
-        PanicStrategy::Unwind => "test_main_static",
-        PanicStrategy::Abort => "test_main_static_abort",
+    let with_hooks = !cx.hooks.is_empty();
+    if with_hooks && cx.test_runner.is_some() {
+        let spans: Vec<_> = cx.hooks.iter().map(|hook| hook.span).collect();
+        ecx.span_err(
+            spans,
+            "`#[test_setup]` and `#[test_teardown]` are not supported with a custom test runner",
+        );
+    }
+
+    let runner_name = match (cx.panic_strategy, with_hooks) {
+        (PanicStrategy::Unwind, false) => "test_main_static",
+        (PanicStrategy::Unwind, true) => "test_main_static_with_hooks",
+        (PanicStrategy::Abort, false) => "test_main_static_abort",
+        (PanicStrategy::Abort, true) => "test_main_static_abort_with_hooks",
     };
 
     // test::test_main_static(...)
@@ -294,7 +404,12 @@ fn mk_main(cx: &mut TestCtxt<'_>) -> P<ast::Item> {
     test_runner.span = sp;
 
     let test_main_path_expr = ecx.expr_path(test_runner);
-    let call_test_main = ecx.expr_call(sp, test_main_path_expr, vec![mk_tests_slice(cx, sp)]);
+    let mut args = vec![mk_tests_slice(cx, sp)];
+    if with_hooks && cx.test_runner.is_none() {
+        args.push(mk_hooks_slice(cx, sp, HookKind::Setup));
+        args.push(mk_hooks_slice(cx, sp, HookKind::Teardown));
+    }
+    let call_test_main = ecx.expr_call(sp, test_main_path_expr, args);
     let call_test_main = ecx.stmt_expr(call_test_main);
 
     // extern crate test
@@ -363,6 +478,46 @@ fn mk_tests_slice(cx: &TestCtxt<'_>, sp: Span) -> P<ast::Expr> {
     )
 }
 
+/// Creates a slice containing the hooks of the given kind like so:
+/// &[test::TestHook { name: "setup", hook_fn: || test::assert_test_result(setup()) }]
+fn mk_hooks_slice(cx: &TestCtxt<'_>, sp: Span, kind: HookKind) -> P<ast::Expr> {
+    let ecx = &cx.ext_cx;
+    let test_path =
+        |name| ecx.path(sp, vec![Ident::new(sym::test, sp), Ident::from_str_and_span(name, sp)]);
+
+    ecx.expr_array_ref(
+        sp,
+        cx.hooks
+            .iter()
+            .filter(|hook| hook.kind == kind)
+            .map(|hook| {
+                // || test::assert_test_result(hook())
+                let call_hook = ecx.expr_call(
+                    hook.span,
+                    ecx.expr_path(ecx.path(hook.span, vec![hook.ident])),
+                    vec![],
+                );
+                let hook_fn = ecx.lambda0(
+                    sp,
+                    ecx.expr_call(
+                        sp,
+                        ecx.expr_path(test_path("assert_test_result")),
+                        vec![call_hook],
+                    ),
+                );
+                ecx.expr_struct(
+                    sp,
+                    test_path("TestHook"),
+                    vec![
+                        ecx.field_imm(sp, Ident::new(sym::name, sp), ecx.expr_str(sp, hook.name)),
+                        ecx.field_imm(sp, Ident::from_str_and_span("hook_fn", sp), hook_fn),
+                    ],
+                )
+            })
+            .collect(),
+    )
+}
+
 fn is_test_case(sess: &Session, i: &ast::Item) -> bool {
     sess.contains_name(&i.attrs, sym::rustc_test_marker)
 }
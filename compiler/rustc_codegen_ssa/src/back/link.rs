@@ -26,6 +26,7 @@
 use super::archive::{find_library, ArchiveBuilder};
 use super::command::Command;
 use super::linker::{self, Linker};
+use super::manifest::write_build_manifest;
 use super::metadata::{create_rmeta_file, MetadataPosition};
 use super::rpath::{self, RPathConfig};
 use crate::{
@@ -65,6 +66,7 @@ pub fn link_binary<'a, B: ArchiveBuilder<'a>>(
 ) -> Result<(), ErrorGuaranteed> {
     let _timer = sess.timer("link_binary");
     let output_metadata = sess.opts.output_types.contains_key(&OutputType::Metadata);
+    let mut linked_outputs = Vec::new();
     for &crate_type in sess.crate_types().iter() {
         // Ignore executable crates if we have -Z no-codegen, as they will error.
         if (sess.opts.debugging_opts.no_codegen || !sess.opts.output_types.should_codegen())
@@ -129,6 +131,8 @@ pub fn link_binary<'a, B: ArchiveBuilder<'a>>(
                 sess.parse_sess.span_diagnostic.emit_artifact_notification(&out_filename, "link");
             }
 
+            linked_outputs.push((crate_type, out_filename.clone()));
+
             if sess.prof.enabled() {
                 if let Some(artifact_name) = out_filename.file_name() {
                     // Record size for self-profiling
@@ -144,6 +148,11 @@ pub fn link_binary<'a, B: ArchiveBuilder<'a>>(
         }
     }
 
+    // The object files are about to be removed, so hash them now.
+    sess.time("link_binary_write_build_manifest", || {
+        write_build_manifest(sess, codegen_results, outputs, &linked_outputs)
+    });
+
     // Remove the temporary object file and metadata if we aren't saving temps.
     sess.time("link_binary_remove_temps", || {
         // If the user requests that temporaries are saved, don't delete any.
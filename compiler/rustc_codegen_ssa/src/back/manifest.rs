@@ -0,0 +1,205 @@
+//! Implementation of `-Z build-manifest`.
+//!
+//! The manifest lists the content hash of every file a compilation produced: object files,
+//! the metadata blob, the members of rlibs and static libraries, and the linked artifacts. It
+//! also lists the inputs that affect these files, such as the source files (under their
+//! remapped names), the `--cfg`s, the target specification and the environment variables read
+//! with `env!`. The lines are sorted and do not contain the output directory, so the manifests
+//! of two builds that should be bit-for-bit identical can be compared with `diff` to find the
+//! outputs that differ and the inputs that could be responsible.
+
+use rustc_session::config::{CrateType, OutputFilenames, OutputType, SwitchWithOptPath};
+use rustc_session::Session;
+use rustc_span::FileName;
+use rustc_target::json::ToJson;
+use sha2::{Digest, Sha256};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{CodegenResults, CompiledModule, RUSTC_VERSION};
+
+/// Writes the build manifest if `-Z build-manifest` was given. `linked_outputs` are the
+/// artifacts produced by `link_binary`.
+pub fn write_build_manifest(
+    sess: &Session,
+    codegen_results: &CodegenResults,
+    outputs: &OutputFilenames,
+    linked_outputs: &[(CrateType, PathBuf)],
+) {
+    let path = match &sess.opts.debugging_opts.build_manifest {
+        SwitchWithOptPath::Enabled(Some(path)) => path.clone(),
+        SwitchWithOptPath::Enabled(None) => outputs.with_extension("manifest"),
+        SwitchWithOptPath::Disabled => return,
+    };
+
+    let result = build_manifest(sess, codegen_results, outputs, linked_outputs)
+        .and_then(|manifest| fs::write(&path, manifest));
+    if let Err(err) = result {
+        sess.err(&format!("failed to write build manifest `{}`: {}", path.display(), err));
+    }
+}
+
+fn build_manifest(
+    sess: &Session,
+    codegen_results: &CodegenResults,
+    outputs: &OutputFilenames,
+    linked_outputs: &[(CrateType, PathBuf)],
+) -> io::Result<String> {
+    let mut manifest = String::new();
+    let mut section = |mut lines: Vec<String>| {
+        lines.sort();
+        lines.dedup();
+        for line in lines {
+            manifest.push_str(&line);
+            manifest.push('\n');
+        }
+    };
+
+    // Inputs.
+    section(vec![
+        format!("rustc-version {}", RUSTC_VERSION.unwrap_or("unknown")),
+        format!("crate-name {}", codegen_results.crate_info.local_crate_name),
+        format!(
+            "target {} {}",
+            sess.opts.target_triple.triple(),
+            hash(sess.target.to_json().to_string().as_bytes())
+        ),
+    ]);
+    section(
+        sess.parse_sess
+            .config
+            .iter()
+            .map(|&(name, value)| match value {
+                Some(value) => format!("cfg {}={:?}", name, value.as_str()),
+                None => format!("cfg {}", name),
+            })
+            .collect(),
+    );
+    section(
+        sess.opts
+            .remap_path_prefix
+            .iter()
+            .map(|(from, to)| format!("remap-path-prefix {}={}", from.display(), to.display()))
+            .collect(),
+    );
+    section(
+        sess.parse_sess
+            .env_depinfo
+            .borrow()
+            .iter()
+            .map(|&(name, value)| match value {
+                Some(value) => format!("env {}={:?}", name, value.as_str()),
+                None => format!("env {} (unset)", name),
+            })
+            .collect(),
+    );
+    section(
+        sess.source_map()
+            .files()
+            .iter()
+            .filter(|file| !file.is_imported() && matches!(file.name, FileName::Real(_)))
+            .map(|file| {
+                format!(
+                    "source {} {}:{}",
+                    file.name.prefer_remapped(),
+                    format!("{:?}", file.src_hash.kind).to_lowercase(),
+                    hex(file.src_hash.hash_bytes())
+                )
+            })
+            .collect(),
+    );
+    // Files that proc macros declared they depend on.
+    let mut tracked_files = Vec::new();
+    for path in sess.parse_sess.file_depinfo.borrow().iter() {
+        let (mapped, _) = sess.source_map().path_mapping().map_prefix(PathBuf::from(path.as_str()));
+        let content_hash =
+            fs::read(path.as_str()).map_or_else(|_| "(unreadable)".to_string(), |data| hash(&data));
+        tracked_files.push(format!("tracked-file {} {}", mapped.display(), content_hash));
+    }
+    section(tracked_files);
+
+    // Outputs.
+    let modules = codegen_results
+        .modules
+        .iter()
+        .chain(&codegen_results.allocator_module)
+        .chain(&codegen_results.metadata_module);
+    let mut objects = Vec::new();
+    for module in modules {
+        let CompiledModule { object, dwarf_object, .. } = module;
+        if let Some(object) = object {
+            objects.push(format!("object {} {}", file_name(object), hash_file(object)?));
+        }
+        if let Some(dwarf_object) = dwarf_object {
+            objects.push(format!(
+                "dwarf-object {} {}",
+                file_name(dwarf_object),
+                hash_file(dwarf_object)?
+            ));
+        }
+    }
+    section(objects);
+
+    let metadata = codegen_results.metadata.raw_data();
+    if !metadata.is_empty() {
+        section(vec![format!("metadata {}", hash(metadata))]);
+    }
+
+    let mut artifacts = Vec::new();
+    if sess.opts.output_types.contains_key(&OutputType::Metadata) {
+        let rmeta = outputs.path(OutputType::Metadata);
+        if rmeta.exists() {
+            artifacts.push(format!("output {} {}", file_name(&rmeta), hash_file(&rmeta)?));
+        }
+    }
+    for (crate_type, path) in linked_outputs {
+        let data = read_file(path)?;
+        artifacts.push(format!("output {} {}", file_name(path), hash(&data)));
+
+        if matches!(crate_type, CrateType::Rlib | CrateType::Staticlib) {
+            let archive = object::read::archive::ArchiveFile::parse(&*data)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            for member in archive.members() {
+                let member =
+                    member.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                let member_data = member
+                    .data(&*data)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                artifacts.push(format!(
+                    "archive-member {} {} {}",
+                    file_name(path),
+                    String::from_utf8_lossy(member.name()),
+                    hash(member_data)
+                ));
+            }
+        }
+    }
+    section(artifacts);
+
+    Ok(manifest)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    fs::read(path).map_err(|err| {
+        io::Error::new(err.kind(), format!("could not read `{}`: {}", path.display(), err))
+    })
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    read_file(path).map(|data| hash(&data))
+}
+
+fn hash(data: &[u8]) -> String {
+    format!("sha256:{}", hex(&Sha256::digest(data)))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
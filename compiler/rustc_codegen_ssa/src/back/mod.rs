@@ -3,6 +3,7 @@
 pub mod link;
 pub mod linker;
 pub mod lto;
+pub mod manifest;
 pub mod metadata;
 pub mod rpath;
 pub mod symbol_export;
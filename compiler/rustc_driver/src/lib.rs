@@ -268,7 +268,8 @@ fn run_compiler(
                         compiler.output_dir(),
                         compiler.output_file(),
                         compiler.temps_dir(),
-                    );
+                    )
+                    .and_then(|| dump_incr_cache(compiler.session()));
 
                     if should_stop == Compilation::Stop {
                         return;
@@ -301,6 +302,7 @@ fn run_compiler(
         .and_then(|| {
             list_metadata(sess, &*compiler.codegen_backend().metadata_loader(), compiler.input())
         })
+        .and_then(|| dump_incr_cache(sess))
         .and_then(|| try_process_rlink(sess, compiler));
 
         if should_stop == Compilation::Stop {
@@ -402,7 +404,10 @@ fn run_compiler(
             queries.ongoing_codegen()?;
 
             if sess.opts.debugging_opts.print_type_sizes {
-                sess.code_stats.print_type_sizes();
+                sess.code_stats.print_type_sizes(
+                    sess.opts.debugging_opts.print_type_sizes_format,
+                    sess.opts.debugging_opts.print_type_sizes_filter.as_deref(),
+                );
             }
 
             let linker = queries.linker()?;
@@ -624,6 +629,15 @@ pub fn list_metadata(
     Compilation::Continue
 }
 
+pub fn dump_incr_cache(sess: &Session) -> Compilation {
+    if sess.opts.debugging_opts.dump_incr_cache.is_some() {
+        rustc_incremental::dump_incr_cache::<rustc_query_impl::OnDiskCache<'_>>(sess);
+        return Compilation::Stop;
+    }
+
+    Compilation::Continue
+}
+
 fn print_crate_info(
     codegen_backend: &dyn CodegenBackend,
     sess: &Session,
@@ -1307,6 +1321,7 @@ pub(super) fn install() {}
 }
 
 pub fn main() -> ! {
+    rustc_expand::proc_macro_sandbox::run_sandbox_process_if_requested();
     let start_time = Instant::now();
     let start_rss = get_resident_set_size();
     init_rustc_env_logger();
@@ -108,6 +108,21 @@ pub struct Diagnostic {
     /// If diagnostic is from Lint, custom hash function ignores notes
     /// otherwise hash is based on the all the fields
     pub is_lint: bool,
+
+    /// For lint diagnostics, where the level of the lint was set. This is also explained in a
+    /// note for human readers, but kept here so that structured emitters can report it.
+    pub lint_level_origin: Option<LintLevelOrigin>,
+}
+
+/// Where the level of the lint that produced a diagnostic was set.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Encodable, Decodable)]
+pub enum LintLevelOrigin {
+    /// The lint is at its default level.
+    Default,
+    /// The level was set by a command line flag, e.g. `-D warnings`.
+    CommandLine(String),
+    /// The level was set by the lint attribute at this span.
+    Attribute(Span),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Encodable, Decodable)]
@@ -193,6 +208,7 @@ pub fn new_with_code<M: Into<DiagnosticMessage>>(
             args: vec![],
             sort_span: DUMMY_SP,
             is_lint: false,
+            lint_level_origin: None,
         }
     }
 
@@ -805,6 +821,11 @@ pub fn set_is_lint(&mut self) -> &mut Self {
         self
     }
 
+    pub fn set_lint_level_origin(&mut self, origin: LintLevelOrigin) -> &mut Self {
+        self.lint_level_origin = Some(origin);
+        self
+    }
+
     pub fn code(&mut self, s: DiagnosticId) -> &mut Self {
         self.code = Some(s);
         self
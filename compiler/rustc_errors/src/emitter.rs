@@ -219,6 +219,12 @@ fn emit_unused_externs(
     ) {
     }
 
+    /// Called once all diagnostics of the session have been emitted. Emitters that write their
+    /// output as a single document, like the SARIF one, write it here.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
     /// Checks if should show explanations about "rustc --explain"
     fn should_show_explain(&self) -> bool {
         true
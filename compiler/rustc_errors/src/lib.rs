@@ -56,6 +56,7 @@
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 
@@ -371,7 +372,7 @@ impl error::Error for ExplicitBug {}
 
 pub use diagnostic::{
     AddSubdiagnostic, Diagnostic, DiagnosticArg, DiagnosticArgValue, DiagnosticId,
-    DiagnosticStyledString, IntoDiagnosticArg, SubDiagnostic,
+    DiagnosticStyledString, IntoDiagnosticArg, LintLevelOrigin, SubDiagnostic,
 };
 pub use diagnostic_builder::{DiagnosticBuilder, EmissionGuarantee};
 use std::backtrace::Backtrace;
@@ -994,6 +995,12 @@ pub fn print_error_count(&self, registry: &Registry) {
         self.inner.borrow_mut().print_error_count(registry)
     }
 
+    /// Lets the emitter write the output it buffered, like the SARIF log, once no more
+    /// diagnostics are going to be emitted.
+    pub fn finish(&self) {
+        self.inner.borrow_mut().finish()
+    }
+
     pub fn take_future_breakage_diagnostics(&self) -> Vec<Diagnostic> {
         std::mem::take(&mut self.inner.borrow_mut().future_breakage_diagnostics)
     }
@@ -1229,6 +1236,12 @@ fn treat_err_as_bug(&self) -> bool {
             .map_or(false, |c| self.err_count() + self.lint_err_count >= c.get())
     }
 
+    fn finish(&mut self) {
+        if let Err(e) = self.emitter.finish() {
+            self.err(&format!("failed to write diagnostics: {}", e));
+        }
+    }
+
     fn print_error_count(&mut self, registry: &Registry) {
         self.emit_stashed_diagnostics();
 
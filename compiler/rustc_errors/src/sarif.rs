@@ -0,0 +1,559 @@
+//! An emitter that writes diagnostics as a [SARIF 2.1.0] log.
+//!
+//! Unlike the JSON emitter, which writes one object per diagnostic as soon as it is emitted,
+//! SARIF describes the whole analysis in a single document. Diagnostics are therefore collected
+//! as they are emitted and the log is written by [`Emitter::finish`] when the compilation session
+//! ends.
+//!
+//! Error codes and lint names become SARIF rules, primary spans become result locations and
+//! labelled secondary spans and sub-diagnostics become related locations. Code suggestions are
+//! written as SARIF `fixes`.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::{
+    CodeSuggestion, DiagnosticId, FluentBundle, LazyFallbackBundle, Level, LintLevelOrigin,
+    MultiSpan, SubDiagnostic,
+};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_error_messages::FluentArgs;
+use rustc_span::{FileName, Span};
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    ui_testing: bool,
+    /// Every rule referenced by a result so far, in order of first appearance.
+    rules: Vec<Rule>,
+    rule_indices: FxHashMap<String, usize>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+        )
+    }
+
+    pub fn basic(
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(
+            None,
+            Lrc::new(SourceMap::new(file_path_mapping)),
+            fluent_bundle,
+            fallback_bundle,
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            ui_testing: false,
+            rules: Vec::new(),
+            rule_indices: FxHashMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    pub fn ui_testing(self, ui_testing: bool) -> Self {
+        Self { ui_testing, ..self }
+    }
+
+    fn rule_index(&mut self, code: &DiagnosticId) -> (String, usize) {
+        let id = match code {
+            DiagnosticId::Error(code) => code,
+            DiagnosticId::Lint { name, .. } => name,
+        };
+        if let Some(&index) = self.rule_indices.get(id) {
+            return (id.clone(), index);
+        }
+
+        let rule = Rule::new(code, self.registry.as_ref());
+        let index = self.rules.len();
+        self.rules.push(rule);
+        self.rule_indices.insert(id.clone(), index);
+        (id.clone(), index)
+    }
+
+    fn log(&self) -> SarifLog<'_> {
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        // Keep the output stable across compiler versions in UI tests.
+                        version: if self.ui_testing { None } else { option_env!("CFG_VERSION") },
+                        rules: &self.rules,
+                    },
+                },
+                results: &self.results,
+                column_kind: "unicodeCodePoints",
+            }],
+        }
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // Failure notes such as "aborting due to previous error" summarize other results and
+        // are not results of their own.
+        if matches!(diag.level, Level::FailureNote | Level::Allow | Level::Expect(_)) {
+            return;
+        }
+
+        let rule = diag.code.as_ref().map(|code| self.rule_index(code));
+        let result = SarifResult::from_errors_diagnostic(diag, rule, self);
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &**self.fallback_bundle
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let log = serde_json::to_string_pretty(&self.log())?;
+        writeln!(&mut self.dst, "{}", log)?;
+        self.dst.flush()
+    }
+}
+
+// The following data types are provided just for serialisation. Field names follow the SARIF
+// specification, which is why they are renamed to camel case.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    results: &'a [SarifResult],
+    /// Columns in regions count characters, not bytes or UTF-16 code units.
+    column_kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'static str>,
+    rules: &'a [Rule],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code (e.g. `E0308`) or lint name (e.g. `unused_variables`).
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    /// The long form explanation of an error code, in Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<Message>,
+    properties: RuleProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleProperties {
+    /// `"error"` for error codes and `"lint"` for lints.
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// One of "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<ResultProperties>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultProperties {
+    lint_level_source: LintLevelSource,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum LintLevelSource {
+    /// The lint is at its default level.
+    Default,
+    /// The level was set by a command line flag such as `-D warnings`.
+    CommandLine { flag: String },
+    /// The level was set by a lint attribute.
+    Attribute { location: PhysicalLocation },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, in characters.
+    start_column: usize,
+    end_line: usize,
+    /// 1-based, in characters, pointing one past the last character of the region.
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Message,
+}
+
+impl Rule {
+    fn new(code: &DiagnosticId, registry: Option<&Registry>) -> Rule {
+        match code {
+            DiagnosticId::Error(code) => {
+                let explanation = registry
+                    .and_then(|registry| registry.try_find_description(code).ok().flatten());
+                Rule {
+                    id: code.clone(),
+                    help_uri: Some(format!("https://doc.rust-lang.org/error-index.html#{}", code)),
+                    help: explanation.map(|text| Message { text: text.to_owned() }),
+                    properties: RuleProperties { kind: "error" },
+                }
+            }
+            DiagnosticId::Lint { name, .. } => Rule {
+                id: name.clone(),
+                help_uri: name.strip_prefix("clippy::").map(|lint| {
+                    format!("https://rust-lang.github.io/rust-clippy/master/index.html#{}", lint)
+                }),
+                help: None,
+                properties: RuleProperties { kind: "lint" },
+            },
+        }
+    }
+}
+
+impl SarifResult {
+    fn from_errors_diagnostic(
+        diag: &crate::Diagnostic,
+        rule: Option<(String, usize)>,
+        se: &SarifEmitter,
+    ) -> SarifResult {
+        let args = se.to_fluent_args(diag.args());
+        let mut text = se.translate_messages(&diag.message, &args).into_owned();
+
+        let (locations, mut related_locations) = Location::from_multispan(&diag.span, &args, se);
+        for child in &diag.children {
+            Self::add_sub_diagnostic(child, &args, se, &mut text, &mut related_locations);
+        }
+
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|suggestion| Fix::from_suggestion(suggestion, &args, se))
+            .collect();
+
+        let properties = diag.lint_level_origin.as_ref().map(|origin| ResultProperties {
+            lint_level_source: match origin {
+                LintLevelOrigin::Default => LintLevelSource::Default,
+                LintLevelOrigin::CommandLine(flag) => {
+                    LintLevelSource::CommandLine { flag: flag.clone() }
+                }
+                LintLevelOrigin::Attribute(span) => {
+                    LintLevelSource::Attribute { location: PhysicalLocation::from_span(*span, se) }
+                }
+            },
+        });
+
+        let (rule_id, rule_index) = match rule {
+            Some((id, index)) => (Some(id), Some(index)),
+            None => (None, None),
+        };
+        SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(diag.level),
+            message: Message { text },
+            locations,
+            related_locations,
+            fixes,
+            properties,
+        }
+    }
+
+    /// Sub-diagnostics with a span become related locations, the others are appended to the
+    /// message of the result, formatted like rustc's human readable output.
+    fn add_sub_diagnostic(
+        child: &SubDiagnostic,
+        args: &FluentArgs<'_>,
+        se: &SarifEmitter,
+        text: &mut String,
+        related_locations: &mut Vec<Location>,
+    ) {
+        let message = se.translate_messages(&child.message, args);
+        let message = format!("{}: {}", child.level.to_str(), message);
+        let span = child.render_span.as_ref().unwrap_or(&child.span);
+        let mut labels = span.span_labels().into_iter().peekable();
+        if labels.peek().is_none() {
+            text.push('\n');
+            text.push_str(&message);
+            return;
+        }
+        for label in labels {
+            let label_message = match label.label {
+                Some(label) => format!("{}: {}", message, se.translate_message(&label, args)),
+                None => message.clone(),
+            };
+            related_locations.push(Location {
+                physical_location: PhysicalLocation::from_span(label.span, se),
+                message: Some(Message { text: label_message }),
+            });
+        }
+    }
+}
+
+impl Location {
+    /// Splits the spans of `msp` into the primary locations and the labelled secondary spans.
+    fn from_multispan(
+        msp: &MultiSpan,
+        args: &FluentArgs<'_>,
+        se: &SarifEmitter,
+    ) -> (Vec<Location>, Vec<Location>) {
+        let mut primary = vec![];
+        let mut secondary = vec![];
+        for label in msp.span_labels() {
+            let location = Location {
+                physical_location: PhysicalLocation::from_span(label.span, se),
+                message: label
+                    .label
+                    .map(|label| Message { text: se.translate_message(&label, args).into_owned() }),
+            };
+            if label.is_primary {
+                primary.push(location)
+            } else {
+                secondary.push(location)
+            }
+        }
+        (primary, secondary)
+    }
+}
+
+impl PhysicalLocation {
+    fn from_span(span: Span, se: &SarifEmitter) -> PhysicalLocation {
+        let start = se.sm.lookup_char_pos(span.lo());
+        PhysicalLocation {
+            artifact_location: ArtifactLocation::from_file_name(&start.file.name, se),
+            region: Region::from_span(span, se),
+        }
+    }
+}
+
+impl ArtifactLocation {
+    fn from_file_name(file_name: &FileName, se: &SarifEmitter) -> ArtifactLocation {
+        let path = se.sm.filename_for_diagnostics(file_name).to_string().replace('\\', "/");
+        // SARIF requires URIs. Relative paths are valid relative references, absolute paths need
+        // to be turned into `file` URIs.
+        let uri = if path.starts_with('/') {
+            format!("file://{}", path)
+        } else if path.as_bytes().get(1) == Some(&b':') {
+            format!("file:///{}", path)
+        } else {
+            path
+        };
+        ArtifactLocation { uri }
+    }
+}
+
+impl Region {
+    fn from_span(span: Span, se: &SarifEmitter) -> Region {
+        let start = se.sm.lookup_char_pos(span.lo());
+        let end = se.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        Region {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+            byte_offset: byte_start,
+            byte_length: byte_end - byte_start,
+        }
+    }
+}
+
+impl Fix {
+    /// Each alternative substitution of a suggestion is a separate fix.
+    fn from_suggestion(
+        suggestion: &CodeSuggestion,
+        args: &FluentArgs<'_>,
+        se: &SarifEmitter,
+    ) -> Vec<Fix> {
+        let description = se.translate_message(&suggestion.msg, args);
+        suggestion
+            .substitutions
+            .iter()
+            .map(|substitution| {
+                let mut artifact_changes: Vec<ArtifactChange> = vec![];
+                for part in &substitution.parts {
+                    let file = se.sm.lookup_char_pos(part.span.lo()).file;
+                    let artifact_location = ArtifactLocation::from_file_name(&file.name, se);
+                    let replacement = Replacement {
+                        deleted_region: Region::from_span(part.span, se),
+                        inserted_content: Message { text: part.snippet.clone() },
+                    };
+                    match artifact_changes
+                        .iter_mut()
+                        .find(|change| change.artifact_location == artifact_location)
+                    {
+                        Some(change) => change.replacements.push(replacement),
+                        None => artifact_changes.push(ArtifactChange {
+                            artifact_location,
+                            replacements: vec![replacement],
+                        }),
+                    }
+                }
+                Fix {
+                    description: Message { text: description.to_string() },
+                    artifact_changes,
+                    properties: FixProperties { applicability: suggestion.applicability },
+                }
+            })
+            .collect()
+    }
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+        Level::Warning(_) => "warning",
+        Level::Note | Level::OnceNote | Level::Help => "note",
+        Level::FailureNote | Level::Allow | Level::Expect(_) => "none",
+    }
+}
@@ -0,0 +1,211 @@
+use super::*;
+
+use crate::Handler;
+use rustc_span::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+struct TestLog {
+    version: String,
+    runs: Vec<TestRun>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TestRun {
+    tool: serde_json::Value,
+    results: Vec<TestResult>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TestResult {
+    rule_id: Option<String>,
+    rule_index: Option<usize>,
+    level: String,
+    message: TestMessage,
+    locations: Vec<TestLocation>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TestMessage {
+    text: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TestLocation {
+    physical_location: TestPhysicalLocation,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TestPhysicalLocation {
+    artifact_location: TestMessageUri,
+    region: TestRegion,
+}
+
+#[derive(Deserialize, Debug)]
+struct TestMessageUri {
+    uri: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct TestRegion {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a handler writing SARIF and returns the log written once the handler finishes.
+fn with_sarif_log(code: &str, f: impl FnOnce(&Handler)) -> TestLog {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(rustc_error_messages::DEFAULT_LOCALE_RESOURCES, false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let se = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            None,
+            sm,
+            None,
+            fallback_bundle,
+        )
+        .ui_testing(true);
+
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        handler.finish();
+
+        let bytes = output.lock().unwrap();
+        let actual_output = str::from_utf8(&bytes).unwrap();
+        serde_json::from_str(actual_output).unwrap()
+    })
+}
+
+#[test]
+fn empty_log() {
+    let log = with_sarif_log("", |_| {});
+    assert_eq!(log.version, "2.1.0");
+    assert_eq!(log.runs.len(), 1);
+    assert_eq!(log.runs[0].tool["driver"]["name"], "rustc");
+    assert!(log.runs[0].results.is_empty());
+}
+
+#[test]
+fn error_location() {
+    let log = with_sarif_log("\nmod foo;\nmod bar;\n", |handler| {
+        let span = Span::with_root_ctxt(BytePos(5), BytePos(12));
+        handler.span_err(span, "foo");
+    });
+    let result = &log.runs[0].results[0];
+    assert_eq!(result.level, "error");
+    assert_eq!(result.message.text, "foo");
+    assert_eq!(result.rule_id, None);
+    assert_eq!(result.locations.len(), 1);
+
+    let location = &result.locations[0].physical_location;
+    assert_eq!(location.artifact_location.uri, "test.rs");
+    assert_eq!(
+        location.region,
+        TestRegion {
+            start_line: 2,
+            start_column: 5,
+            end_line: 3,
+            end_column: 3,
+            byte_offset: 5,
+            byte_length: 7,
+        }
+    );
+}
+
+#[test]
+fn rules_are_shared() {
+    let log = with_sarif_log("fn main() {}", |handler| {
+        let span = Span::with_root_ctxt(BytePos(3), BytePos(7));
+        let code = DiagnosticId::Lint {
+            name: "dead_code".to_owned(),
+            has_future_breakage: false,
+            is_force_warn: false,
+        };
+        handler.struct_span_warn_with_code(span, "first", code.clone()).emit();
+        handler
+            .struct_span_err_with_code(span, "second", DiagnosticId::Error("E0000".to_owned()))
+            .emit();
+        handler.struct_span_warn_with_code(span, "third", code).emit();
+    });
+    let run = &log.runs[0];
+    let rules = run.tool["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["id"], "dead_code");
+    assert_eq!(rules[1]["id"], "E0000");
+
+    let results: Vec<_> =
+        run.results.iter().map(|r| (r.rule_id.as_deref(), r.rule_index, &*r.level)).collect();
+    assert_eq!(
+        results,
+        [
+            (Some("dead_code"), Some(0), "warning"),
+            (Some("E0000"), Some(1), "error"),
+            (Some("dead_code"), Some(0), "warning"),
+        ]
+    );
+}
+
+#[test]
+fn notes_without_span_are_appended() {
+    let log = with_sarif_log("fn main() {}", |handler| {
+        let span = Span::with_root_ctxt(BytePos(3), BytePos(7));
+        handler.struct_span_err(span, "foo").note("bar").help("baz").emit();
+    });
+    assert_eq!(log.runs[0].results[0].message.text, "foo\nnote: bar\nhelp: baz");
+}
+
+struct BrokenPipe;
+
+impl Write for BrokenPipe {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_error_is_reported() {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(rustc_error_messages::DEFAULT_LOCALE_RESOURCES, false);
+        let se = SarifEmitter::new(Box::new(BrokenPipe), None, sm, None, fallback_bundle);
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        handler.finish();
+        assert!(handler.has_errors().is_some());
+    })
+}
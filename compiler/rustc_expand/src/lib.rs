@@ -30,6 +30,7 @@
 pub mod expand;
 pub mod module;
 pub mod proc_macro;
+pub mod proc_macro_sandbox;
 
 pub(crate) mod mbe;
 
@@ -1,4 +1,5 @@
 use crate::base::{self, *};
+use crate::proc_macro_sandbox::{DeniedAccess, SandboxError, SandboxedProcMacro};
 use crate::proc_macro_server;
 
 use rustc_ast as ast;
@@ -15,6 +16,8 @@
 
 pub struct BangProcMacro {
     pub client: pm::bridge::client::Client<pm::TokenStream, pm::TokenStream>,
+    /// Set with `-Z proc-macro-sandbox`, in which case `client` is only a placeholder.
+    pub sandbox: Option<SandboxedProcMacro>,
 }
 
 impl base::BangProcMacro for BangProcMacro {
@@ -31,7 +34,18 @@ fn expand<'cx>(
 
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
         let server = proc_macro_server::Rustc::new(ecx);
-        self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace).map_err(|e| {
+        let result = match &self.sandbox {
+            None => self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace),
+            Some(sandboxed) => {
+                let strategy = sandboxed.connect();
+                let result = self.client.run(&strategy, server, input, proc_macro_backtrace);
+                if let Some(error) = strategy.0.take_error() {
+                    return Err(report_sandbox_error(ecx, span, error));
+                }
+                result
+            }
+        };
+        result.map_err(|e| {
             let mut err = ecx.struct_span_err(span, "proc macro panicked");
             if let Some(s) = e.as_str() {
                 err.help(&format!("message: {}", s));
@@ -43,6 +57,8 @@ fn expand<'cx>(
 
 pub struct AttrProcMacro {
     pub client: pm::bridge::client::Client<(pm::TokenStream, pm::TokenStream), pm::TokenStream>,
+    /// Set with `-Z proc-macro-sandbox`, in which case `client` is only a placeholder.
+    pub sandbox: Option<SandboxedProcMacro>,
 }
 
 impl base::AttrProcMacro for AttrProcMacro {
@@ -60,20 +76,34 @@ fn expand<'cx>(
 
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
         let server = proc_macro_server::Rustc::new(ecx);
-        self.client
-            .run(&EXEC_STRATEGY, server, annotation, annotated, proc_macro_backtrace)
-            .map_err(|e| {
-                let mut err = ecx.struct_span_err(span, "custom attribute panicked");
-                if let Some(s) = e.as_str() {
-                    err.help(&format!("message: {}", s));
+        let result = match &self.sandbox {
+            None => {
+                self.client.run(&EXEC_STRATEGY, server, annotation, annotated, proc_macro_backtrace)
+            }
+            Some(sandboxed) => {
+                let strategy = sandboxed.connect();
+                let result =
+                    self.client.run(&strategy, server, annotation, annotated, proc_macro_backtrace);
+                if let Some(error) = strategy.0.take_error() {
+                    return Err(report_sandbox_error(ecx, span, error));
                 }
-                err.emit()
-            })
+                result
+            }
+        };
+        result.map_err(|e| {
+            let mut err = ecx.struct_span_err(span, "custom attribute panicked");
+            if let Some(s) = e.as_str() {
+                err.help(&format!("message: {}", s));
+            }
+            err.emit()
+        })
     }
 }
 
 pub struct DeriveProcMacro {
     pub client: pm::bridge::client::Client<pm::TokenStream, pm::TokenStream>,
+    /// Set with `-Z proc-macro-sandbox`, in which case `client` is only a placeholder.
+    pub sandbox: Option<SandboxedProcMacro>,
 }
 
 impl MultiItemModifier for DeriveProcMacro {
@@ -106,7 +136,19 @@ fn expand(
                 });
             let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
             let server = proc_macro_server::Rustc::new(ecx);
-            match self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace) {
+            let result = match &self.sandbox {
+                None => self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace),
+                Some(sandboxed) => {
+                    let strategy = sandboxed.connect();
+                    let result = self.client.run(&strategy, server, input, proc_macro_backtrace);
+                    if let Some(error) = strategy.0.take_error() {
+                        report_sandbox_error(ecx, span, error);
+                        return ExpandResult::Ready(vec![]);
+                    }
+                    result
+                }
+            };
+            match result {
                 Ok(stream) => stream,
                 Err(e) => {
                     let mut err = ecx.struct_span_err(span, "proc-macro derive panicked");
@@ -149,3 +191,25 @@ fn expand(
         ExpandResult::Ready(items)
     }
 }
+
+fn report_sandbox_error(ecx: &ExtCtxt<'_>, span: Span, error: SandboxError) -> ErrorGuaranteed {
+    match error {
+        SandboxError::Denied(ref access) => {
+            let mut err = ecx.struct_span_err(span, "proc macro attempted I/O that is not allowed");
+            err.note(&error.to_string());
+            err.note(match access {
+                DeniedAccess::Syscall(_) => {
+                    "`-Z proc-macro-sandbox` does not allow proc macros to use sockets"
+                }
+                DeniedAccess::Path(_) => {
+                    "`-Z proc-macro-sandbox` only allows proc macros to read the paths given with \
+                     `-Z proc-macro-sandbox-allow-path`"
+                }
+            });
+            err.emit()
+        }
+        SandboxError::Failed(message) => ecx
+            .struct_span_err(span, &format!("could not run the sandboxed proc macro: {}", message))
+            .emit(),
+    }
+}
@@ -0,0 +1,394 @@
+//! Implementation of `-Z proc-macro-sandbox`.
+//!
+//! Proc macros normally run in the compiler process, so they can do anything the compiler
+//! can do. With this option, the proc-macro dylibs are never loaded into the compiler. The
+//! macros of each proc-macro crate instead run in a separate process, which is the compiler
+//! executable started in a special mode (see `run_sandbox_process_if_requested`). That
+//! process restricts itself before loading the dylib, so that even the constructors of the
+//! dylib run in the sandbox:
+//!
+//! - Landlock denies all filesystem access, except for reading and executing the dylib and the
+//!   libraries of the sysroot, and reading the paths given with
+//!   `-Z proc-macro-sandbox-allow-path`. Once the dylib is loaded, the process stops when a
+//!   macro opens a path that Landlock denies, so that the compiler can report it even if the
+//!   macro handles the error.
+//! - A seccomp filter stops the process when it creates or connects a socket of any kind, or
+//!   sets up io_uring, which could be used to get around the filter. Landlock doesn't restrict
+//!   connecting to Unix domain sockets, and the process only needs the pipes it inherits.
+//!
+//! The `proc_macro` bridge already encodes every call from a macro to the compiler into a
+//! buffer, so the compiler only has to forward these buffers over the stdin and stdout of
+//! the process, see `proc_macro::bridge::server::OutOfProcess`. The process is started when
+//! the first macro of the crate is expanded and is then reused for all of its macros.
+
+use rustc_data_structures::sync::{Lock, Lrc};
+
+use pm::bridge::server::{ClientMessage, ClientProcess, OutOfProcess};
+
+use std::cell::RefCell;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{self, Child, ChildStdin, ChildStdout, Command, Stdio};
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod restrict;
+
+/// Set in the environment of the sandboxed processes.
+const SANDBOX_PROCESS_ENV: &str = "RUSTC_PROC_MACRO_SANDBOX_PROCESS";
+
+// Every message starts with one of these tags, followed by the length of the payload as a
+// little-endian `u64` and the payload.
+
+/// To the process: the NUL-separated path of the dylib, name of the proc-macro declarations
+/// symbol, library directory of the sysroot, and allowed paths.
+const MSG_SETUP: u8 = 0;
+/// To the process: the index of the macro as a little-endian `u32`, whether to force
+/// showing panics as a byte, and the encoded input.
+const MSG_RUN: u8 = 1;
+/// To the process: an encoded response to a request of the macro.
+const MSG_RESPONSE: u8 = 2;
+/// From the process: the sandbox is in place and the dylib was loaded.
+const MSG_READY: u8 = 3;
+/// From the process: an encoded request of the macro.
+const MSG_REQUEST: u8 = 4;
+/// From the process: the encoded output of the macro.
+const MSG_OUTPUT: u8 = 5;
+/// From the process: the name of a system call that the sandbox does not allow. The
+/// process exits right after sending this.
+const MSG_DENIED: u8 = 6;
+/// From the process: an error that prevented the process from running the macros.
+const MSG_ERROR: u8 = 7;
+/// From the process: a path that the sandbox does not allow the macro to open. The process
+/// exits right after sending this.
+const MSG_DENIED_PATH: u8 = 8;
+
+/// The macros of a proc-macro crate, which run in a sandboxed process.
+pub struct ProcMacroSandbox {
+    dylib: PathBuf,
+    decls_symbol: String,
+    /// The libraries of the sysroot, which the dylib may need to load.
+    sysroot_lib: PathBuf,
+    allowed_paths: Vec<PathBuf>,
+    process: Lock<Option<SandboxProcess>>,
+}
+
+impl ProcMacroSandbox {
+    pub fn new(
+        dylib: PathBuf,
+        decls_symbol: String,
+        sysroot_lib: PathBuf,
+        allowed_paths: Vec<PathBuf>,
+    ) -> Self {
+        ProcMacroSandbox {
+            dylib,
+            decls_symbol,
+            sysroot_lib,
+            allowed_paths,
+            process: Lock::new(None),
+        }
+    }
+}
+
+/// A macro of a `ProcMacroSandbox`.
+#[derive(Clone)]
+pub struct SandboxedProcMacro {
+    pub sandbox: Lrc<ProcMacroSandbox>,
+    /// The index of the macro in the proc-macro declarations of the dylib.
+    pub index: usize,
+}
+
+impl SandboxedProcMacro {
+    pub(crate) fn connect(&self) -> OutOfProcess<Connection<'_>> {
+        OutOfProcess(Connection { proc_macro: self, error: RefCell::new(None) })
+    }
+}
+
+pub(crate) enum SandboxError {
+    /// The macro attempted I/O that the sandbox does not allow.
+    Denied(DeniedAccess),
+    /// The sandboxed process could not be started, or stopped unexpectedly.
+    Failed(String),
+}
+
+pub(crate) enum DeniedAccess {
+    /// A system call that the sandbox never allows.
+    Syscall(String),
+    /// A path that the macro is not allowed to open.
+    Path(String),
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxError::Denied(DeniedAccess::Syscall(syscall)) => {
+                write!(f, "the proc macro called `{}`, which the sandbox does not allow", syscall)
+            }
+            SandboxError::Denied(DeniedAccess::Path(path)) => {
+                write!(f, "the proc macro opened `{}`, which the sandbox does not allow", path)
+            }
+            SandboxError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// The connection to the process of a `SandboxedProcMacro` during one expansion.
+pub(crate) struct Connection<'a> {
+    proc_macro: &'a SandboxedProcMacro,
+    error: RefCell<Option<SandboxError>>,
+}
+
+impl Connection<'_> {
+    /// Returns the error that stopped the expansion, if it was not a panic of the macro.
+    pub(crate) fn take_error(&self) -> Option<SandboxError> {
+        self.error.borrow_mut().take()
+    }
+
+    /// Runs `f` on the process, which is started first if necessary. If `f` fails, the
+    /// process is discarded and the error is recorded.
+    fn with_process<R>(
+        &self,
+        f: impl FnOnce(&mut SandboxProcess) -> Result<R, SandboxError>,
+    ) -> Result<R, String> {
+        let sandbox = &self.proc_macro.sandbox;
+        let mut process = sandbox.process.lock();
+        let current = match process.take() {
+            Some(current) => Ok(current),
+            None => SandboxProcess::spawn(sandbox),
+        };
+        let result = current.and_then(|mut current| {
+            let result = f(&mut current)?;
+            *process = Some(current);
+            Ok(result)
+        });
+        result.map_err(|error| {
+            let message = error.to_string();
+            *self.error.borrow_mut() = Some(error);
+            message
+        })
+    }
+}
+
+impl ClientProcess for Connection<'_> {
+    fn start(&self, input: &[u8], force_show_panics: bool) -> Result<(), String> {
+        let mut payload = Vec::with_capacity(5 + input.len());
+        payload.extend_from_slice(&(self.proc_macro.index as u32).to_le_bytes());
+        payload.push(force_show_panics as u8);
+        payload.extend_from_slice(input);
+        self.with_process(|process| process.send(MSG_RUN, &payload))
+    }
+
+    fn next_message(&self) -> Result<ClientMessage, String> {
+        self.with_process(|process| match process.recv()? {
+            (MSG_REQUEST, request) => Ok(ClientMessage::Request(request)),
+            (MSG_OUTPUT, output) => Ok(ClientMessage::Output(output)),
+            (tag, _) => Err(process.unexpected_message(tag)),
+        })
+    }
+
+    fn respond(&self, response: &[u8]) -> Result<(), String> {
+        self.with_process(|process| process.send(MSG_RESPONSE, response))
+    }
+}
+
+struct SandboxProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl SandboxProcess {
+    fn spawn(sandbox: &ProcMacroSandbox) -> Result<SandboxProcess, SandboxError> {
+        let failed = |err: io::Error| {
+            SandboxError::Failed(format!("could not start the sandboxed process: {}", err))
+        };
+        let mut child = Command::new(env::current_exe().map_err(failed)?)
+            .env(SANDBOX_PROCESS_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut process = SandboxProcess { child, stdin, stdout };
+
+        let mut setup = Vec::new();
+        let fields = [
+            sandbox.dylib.as_os_str(),
+            OsStr::new(&sandbox.decls_symbol),
+            sandbox.sysroot_lib.as_os_str(),
+        ]
+        .into_iter()
+        .chain(sandbox.allowed_paths.iter().map(|path| path.as_os_str()));
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                setup.push(0);
+            }
+            setup.extend_from_slice(field.to_string_lossy().as_bytes());
+        }
+        process.send(MSG_SETUP, &setup)?;
+        match process.recv()? {
+            (MSG_READY, _) => Ok(process),
+            (tag, _) => Err(process.unexpected_message(tag)),
+        }
+    }
+
+    fn send(&mut self, tag: u8, payload: &[u8]) -> Result<(), SandboxError> {
+        write_message(&mut self.stdin, tag, payload).map_err(|_| self.exited())
+    }
+
+    /// Receives the next message, turning the messages that report an error into errors.
+    fn recv(&mut self) -> Result<(u8, Vec<u8>), SandboxError> {
+        match read_message(&mut self.stdout) {
+            Ok((MSG_DENIED, syscall)) => Err(SandboxError::Denied(DeniedAccess::Syscall(
+                String::from_utf8_lossy(&syscall).into_owned(),
+            ))),
+            Ok((MSG_DENIED_PATH, path)) => Err(SandboxError::Denied(DeniedAccess::Path(
+                String::from_utf8_lossy(&path).into_owned(),
+            ))),
+            Ok((MSG_ERROR, message)) => Err(SandboxError::Failed(format!(
+                "the sandboxed process failed: {}",
+                String::from_utf8_lossy(&message)
+            ))),
+            Ok(message) => Ok(message),
+            Err(_) => Err(self.exited()),
+        }
+    }
+
+    fn exited(&mut self) -> SandboxError {
+        let status = match self.child.wait() {
+            Ok(status) => status.to_string(),
+            Err(err) => err.to_string(),
+        };
+        SandboxError::Failed(format!("the sandboxed process exited unexpectedly ({})", status))
+    }
+
+    fn unexpected_message(&mut self, tag: u8) -> SandboxError {
+        let _ = self.child.kill();
+        SandboxError::Failed(format!("unexpected message {} from the sandboxed process", tag))
+    }
+}
+
+impl Drop for SandboxProcess {
+    fn drop(&mut self) {
+        // The process is waiting for the next macro to run, so there is nothing to lose.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn write_message(w: &mut impl Write, tag: u8, payload: &[u8]) -> io::Result<()> {
+    let mut header = [0; 9];
+    header[0] = tag;
+    header[1..].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+    w.write_all(&header)?;
+    w.write_all(payload)?;
+    w.flush()
+}
+
+fn read_message(r: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0; 9];
+    r.read_exact(&mut header)?;
+    let len = u64::from_le_bytes(header[1..].try_into().unwrap());
+    let mut payload = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+/// If this process was started as a sandboxed process by `-Z proc-macro-sandbox`, runs the
+/// macros the compiler asks for and exits. Tools that use `rustc_driver` have to call this
+/// at the start of `main` to support the option.
+pub fn run_sandbox_process_if_requested() {
+    if env::var_os(SANDBOX_PROCESS_ENV).is_none() {
+        return;
+    }
+
+    #[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        use std::fs::File;
+        use std::os::unix::io::FromRawFd;
+
+        // Proc macros may print to stdout or read from stdin, which carry the messages from
+        // and to the compiler. Move these to other file descriptors first, so that whatever
+        // the macros print goes to stderr.
+        let (input, output) = unsafe { restrict::take_stdio() };
+        let mut input = BufReader::new(unsafe { File::from_raw_fd(input) });
+        let mut output = unsafe { File::from_raw_fd(output) };
+
+        if let Err(message) = serve(&mut input, &mut output) {
+            let _ = write_message(&mut output, MSG_ERROR, message.as_bytes());
+            process::exit(1);
+        }
+    }
+
+    process::exit(0);
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn serve(input: &mut impl Read, output: &mut std::fs::File) -> Result<(), String> {
+    use pm::bridge::client::ProcMacro;
+    use std::os::unix::io::AsRawFd;
+
+    let (tag, setup) = read_message(input).map_err(|err| err.to_string())?;
+    if tag != MSG_SETUP {
+        return Err(format!("unexpected message {} from the compiler", tag));
+    }
+    let setup = String::from_utf8(setup).map_err(|err| err.to_string())?;
+    let mut fields = setup.split('\0');
+    let dylib = PathBuf::from(fields.next().unwrap_or_default());
+    let decls_symbol = fields.next().unwrap_or_default();
+    let sysroot_lib = PathBuf::from(fields.next().unwrap_or_default());
+    let allowed_paths: Vec<PathBuf> = fields.map(PathBuf::from).collect();
+
+    // Loading the dylib runs its constructors, so the sandbox has to be in place first.
+    restrict::restrict_self(&allowed_paths, &[dylib.clone(), sysroot_lib], output.as_raw_fd())?;
+
+    // Like `CrateLoader::dlsym_proc_macros`, but the library is only ever loaded here.
+    let lib = unsafe { libloading::Library::new(&dylib) }.map_err(|err| err.to_string())?;
+    let decls = unsafe { lib.get::<*const &[ProcMacro]>(decls_symbol.as_bytes()) }
+        .map_err(|err| err.to_string())?;
+    let decls: &[ProcMacro] = unsafe { **decls.into_raw() };
+    std::mem::forget(lib);
+
+    restrict::report_path_denials();
+    write_message(output, MSG_READY, &[]).map_err(|err| err.to_string())?;
+
+    loop {
+        let (tag, mut run) = match read_message(input) {
+            Ok(message) => message,
+            // The compiler is done with this crate.
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err.to_string()),
+        };
+        if tag != MSG_RUN || run.len() < 5 {
+            return Err(format!("unexpected message {} from the compiler", tag));
+        }
+        let index = u32::from_le_bytes(run[..4].try_into().unwrap()) as usize;
+        let force_show_panics = run[4] != 0;
+        let macro_input = run.split_off(5);
+
+        // There is no way to report a failure to the client, but the compiler notices that
+        // this process exited.
+        let dispatch = |request: Vec<u8>| {
+            let response = write_message(output, MSG_REQUEST, &request)
+                .and_then(|()| read_message(input))
+                .unwrap_or_else(|_| process::exit(1));
+            match response {
+                (MSG_RESPONSE, response) => response,
+                _ => process::exit(1),
+            }
+        };
+        let macro_output = match decls.get(index) {
+            Some(ProcMacro::CustomDerive { client, .. } | ProcMacro::Bang { client, .. }) => {
+                client.run_out_of_process(macro_input, dispatch, force_show_panics)
+            }
+            Some(ProcMacro::Attr { client, .. }) => {
+                client.run_out_of_process(macro_input, dispatch, force_show_panics)
+            }
+            None => return Err(format!("no proc macro with index {}", index)),
+        };
+        write_message(output, MSG_OUTPUT, &macro_output).map_err(|err| err.to_string())?;
+    }
+}
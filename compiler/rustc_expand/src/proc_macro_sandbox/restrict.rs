@@ -0,0 +1,396 @@
+//! The restrictions of the sandboxed processes, using Landlock and seccomp.
+//!
+//! The `libc` crate does not have all of the definitions needed here, so they are copied
+//! from the Linux headers.
+
+use super::{MSG_DENIED, MSG_DENIED_PATH};
+
+use std::ffi::{CStr, CString};
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::process;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "x86_64")]
+const SYS_SOCKET: u32 = 41;
+#[cfg(target_arch = "x86_64")]
+const SYS_CONNECT: u32 = 42;
+#[cfg(target_arch = "x86_64")]
+const SYS_SOCKETPAIR: u32 = 53;
+#[cfg(target_arch = "x86_64")]
+const SYS_OPEN: u32 = 2;
+#[cfg(target_arch = "x86_64")]
+const SYS_OPENAT: u32 = 257;
+
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+#[cfg(target_arch = "aarch64")]
+const SYS_SOCKET: u32 = 198;
+#[cfg(target_arch = "aarch64")]
+const SYS_SOCKETPAIR: u32 = 199;
+#[cfg(target_arch = "aarch64")]
+const SYS_CONNECT: u32 = 203;
+#[cfg(target_arch = "aarch64")]
+const SYS_OPENAT: u32 = 56;
+
+// Newer system calls have the same number on all architectures.
+const SYS_IO_URING_SETUP: u32 = 425;
+const SYS_OPENAT2: libc::c_long = 437;
+const SYS_LANDLOCK_CREATE_RULESET: libc::c_long = 444;
+const SYS_LANDLOCK_ADD_RULE: libc::c_long = 445;
+const SYS_LANDLOCK_RESTRICT_SELF: libc::c_long = 446;
+
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+const LANDLOCK_ACCESS_FS_EXECUTE: u64 = 1 << 0;
+const LANDLOCK_ACCESS_FS_READ_FILE: u64 = 1 << 2;
+const LANDLOCK_ACCESS_FS_READ_DIR: u64 = 1 << 3;
+/// All the rights of the first version of Landlock.
+const LANDLOCK_ACCESS_FS_V1: u64 = (1 << 13) - 1;
+const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+// The fields of these structs are only read by the kernel.
+
+#[repr(C)]
+#[allow(dead_code)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+}
+
+#[repr(C, packed)]
+#[allow(dead_code)]
+struct LandlockPathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct OpenHow {
+    flags: u64,
+    mode: u64,
+    resolve: u64,
+}
+
+const SECCOMP_MODE_FILTER: libc::c_ulong = 2;
+const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+/// Offsets into `struct seccomp_data`.
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+/// Set in the numbers of the x32 system calls on x86_64, which the filter does not check.
+const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+const BPF_LD_W_ABS: u16 = 0x20;
+const BPF_JEQ_K: u16 = 0x15;
+const BPF_JGE_K: u16 = 0x35;
+const BPF_RET_K: u16 = 0x06;
+
+#[repr(C)]
+#[allow(dead_code)]
+struct SockFilter {
+    code: u16,
+    jt: u8,
+    jf: u8,
+    k: u32,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct SockFprog {
+    len: u16,
+    filter: *const SockFilter,
+}
+
+/// The start of the `siginfo_t` of a `SIGSYS`.
+#[repr(C)]
+#[allow(dead_code)]
+struct SigsysInfo {
+    signo: libc::c_int,
+    errno: libc::c_int,
+    code: libc::c_int,
+    call_addr: *mut libc::c_void,
+    syscall: libc::c_int,
+    arch: libc::c_uint,
+}
+
+/// Where to report the system calls denied by the seccomp filter.
+static DENIAL_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Whether the paths that Landlock does not allow opening are reported. This is only set once
+/// the dylib is loaded, since the dynamic loader tries paths that it may not open.
+static REPORT_PATH_DENIALS: AtomicBool = AtomicBool::new(false);
+
+/// Moves stdin and stdout to new file descriptors, which are returned, and replaces them
+/// with `/dev/null` and stderr.
+pub(super) unsafe fn take_stdio() -> (RawFd, RawFd) {
+    let input = libc::fcntl(libc::STDIN_FILENO, libc::F_DUPFD_CLOEXEC, 3);
+    let output = libc::fcntl(libc::STDOUT_FILENO, libc::F_DUPFD_CLOEXEC, 3);
+    let null = libc::open(b"/dev/null\0".as_ptr().cast(), libc::O_RDONLY);
+    if input < 0
+        || output < 0
+        || null < 0
+        || libc::dup2(null, libc::STDIN_FILENO) < 0
+        || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0
+    {
+        // The compiler notices that the process exited.
+        process::exit(1);
+    }
+    libc::close(null);
+    (input, output)
+}
+
+/// Restricts this process and the threads and processes it starts. Must be called while
+/// the process has a single thread. The `libraries` can also be executed, i.e. loaded.
+pub(super) fn restrict_self(
+    allowed_paths: &[PathBuf],
+    libraries: &[PathBuf],
+    output: RawFd,
+) -> Result<(), String> {
+    let ruleset = landlock_ruleset(allowed_paths, libraries)?;
+
+    DENIAL_FD.store(output, Ordering::Relaxed);
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = handle_sigsys as usize;
+    action.sa_flags = libc::SA_SIGINFO;
+    if unsafe { libc::sigaction(libc::SIGSYS, &action, ptr::null_mut()) } != 0 {
+        return Err(last_error("could not install the `SIGSYS` handler"));
+    }
+
+    // Required to restrict an unprivileged process.
+    let result = unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1 as libc::c_ulong, 0, 0, 0) };
+    if result != 0 {
+        return Err(last_error("could not set `PR_SET_NO_NEW_PRIVS`"));
+    }
+
+    let result = unsafe { libc::syscall(SYS_LANDLOCK_RESTRICT_SELF, ruleset, 0u32) };
+    unsafe { libc::close(ruleset) };
+    if result != 0 {
+        return Err(last_error("could not enforce the Landlock ruleset"));
+    }
+
+    install_seccomp_filter()
+}
+
+/// Makes the process report the paths that Landlock does not allow it to open to the compiler,
+/// and exit, instead of letting the open fail like for any missing permission.
+pub(super) fn report_path_denials() {
+    REPORT_PATH_DENIALS.store(true, Ordering::Relaxed);
+}
+
+/// Creates a ruleset that only allows reading the `allowed_paths` and the `libraries`, and
+/// executing the `libraries`.
+fn landlock_ruleset(allowed_paths: &[PathBuf], libraries: &[PathBuf]) -> Result<RawFd, String> {
+    let abi = unsafe {
+        libc::syscall(
+            SYS_LANDLOCK_CREATE_RULESET,
+            ptr::null::<LandlockRulesetAttr>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if abi < 1 {
+        return Err(last_error("Landlock is not supported by the kernel"));
+    }
+
+    // Rights that the kernel knows about but that are not handled are always allowed.
+    let mut handled_access_fs = LANDLOCK_ACCESS_FS_V1;
+    if abi >= 2 {
+        handled_access_fs |= LANDLOCK_ACCESS_FS_REFER;
+    }
+    if abi >= 3 {
+        handled_access_fs |= LANDLOCK_ACCESS_FS_TRUNCATE;
+    }
+    let attr = LandlockRulesetAttr { handled_access_fs };
+    let ruleset = unsafe {
+        libc::syscall(
+            SYS_LANDLOCK_CREATE_RULESET,
+            &attr as *const LandlockRulesetAttr,
+            mem::size_of::<LandlockRulesetAttr>(),
+            0u32,
+        )
+    };
+    if ruleset < 0 {
+        return Err(last_error("could not create the Landlock ruleset"));
+    }
+    let ruleset = ruleset as RawFd;
+
+    let rules = allowed_paths.iter().map(|path| (path, false));
+    for (path, executable) in rules.chain(libraries.iter().map(|path| (path, true))) {
+        let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|err| err.to_string())?;
+        let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+        if fd < 0 {
+            return Err(last_error(&format!("could not open `{}`", path.display())));
+        }
+        // Landlock rejects rules that grant directory rights on files.
+        let mut allowed_access = if path.is_dir() {
+            LANDLOCK_ACCESS_FS_READ_FILE | LANDLOCK_ACCESS_FS_READ_DIR
+        } else {
+            LANDLOCK_ACCESS_FS_READ_FILE
+        };
+        if executable {
+            allowed_access |= LANDLOCK_ACCESS_FS_EXECUTE;
+        }
+        let rule = LandlockPathBeneathAttr { allowed_access, parent_fd: fd };
+        let result = unsafe {
+            libc::syscall(
+                SYS_LANDLOCK_ADD_RULE,
+                ruleset,
+                LANDLOCK_RULE_PATH_BENEATH,
+                &rule as *const LandlockPathBeneathAttr,
+                0u32,
+            )
+        };
+        unsafe { libc::close(fd) };
+        if result != 0 {
+            return Err(last_error(&format!("could not allow `{}`", path.display())));
+        }
+    }
+
+    Ok(ruleset)
+}
+
+/// Installs a filter that traps the system calls that could access the network or other
+/// processes through sockets. The process only communicates with the compiler through the
+/// pipes it inherits, so it never needs a socket.
+///
+/// Opening files is trapped as well, so that `handle_sigsys` can tell the opens denied by
+/// Landlock from other errors. It opens the files with `openat2`, which is not trapped.
+fn install_seccomp_filter() -> Result<(), String> {
+    #[allow(unused_mut)]
+    let mut trapped = vec![SYS_IO_URING_SETUP, SYS_SOCKET, SYS_SOCKETPAIR, SYS_CONNECT, SYS_OPENAT];
+    #[cfg(target_arch = "x86_64")]
+    trapped.push(SYS_OPEN);
+
+    let stmt = |code, k| SockFilter { code, jt: 0, jf: 0, k };
+    let jump = |code, k, jt, jf| SockFilter { code, jt, jf, k };
+    // The jump offsets are relative to the next instruction. The last three instructions
+    // allow, trap and kill, in that order.
+    let n = trapped.len() as u8;
+    let mut filter = vec![
+        stmt(BPF_LD_W_ABS, SECCOMP_DATA_ARCH),
+        jump(BPF_JEQ_K, AUDIT_ARCH, 0, n + 4),
+        stmt(BPF_LD_W_ABS, SECCOMP_DATA_NR),
+        jump(BPF_JGE_K, X32_SYSCALL_BIT, n + 2, 0),
+    ];
+    for (i, &syscall) in trapped.iter().enumerate() {
+        filter.push(jump(BPF_JEQ_K, syscall, n - i as u8, 0));
+    }
+    filter.push(stmt(BPF_RET_K, SECCOMP_RET_ALLOW));
+    filter.push(stmt(BPF_RET_K, SECCOMP_RET_TRAP));
+    filter.push(stmt(BPF_RET_K, SECCOMP_RET_KILL_PROCESS));
+
+    let program = SockFprog { len: filter.len() as u16, filter: filter.as_ptr() };
+    let result = unsafe {
+        libc::prctl(libc::PR_SET_SECCOMP, SECCOMP_MODE_FILTER, &program as *const SockFprog)
+    };
+    if result != 0 {
+        return Err(last_error("could not install the seccomp filter"));
+    }
+    Ok(())
+}
+
+/// The `SIGSYS` handler, which runs when the seccomp filter traps a system call. Opens are
+/// performed here, the other system calls are reported to the compiler by name.
+extern "C" fn handle_sigsys(
+    _: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    let syscall = unsafe { (*(info as *const SigsysInfo)).syscall } as u32;
+    let context = context as *mut libc::ucontext_t;
+    let name: &[u8] = match syscall {
+        SYS_OPENAT => return unsafe { emulate_open(context, true) },
+        #[cfg(target_arch = "x86_64")]
+        SYS_OPEN => return unsafe { emulate_open(context, false) },
+        SYS_SOCKET => b"socket",
+        SYS_SOCKETPAIR => b"socketpair",
+        SYS_CONNECT => b"connect",
+        SYS_IO_URING_SETUP => b"io_uring_setup",
+        _ => b"unknown",
+    };
+    report_denial(MSG_DENIED, name)
+}
+
+/// Performs the trapped `open` or `openat` call described by `context` with `openat2` and
+/// stores its result in `context`, as if the call had not been trapped.
+unsafe fn emulate_open(context: *mut libc::ucontext_t, at: bool) {
+    let args = syscall_args(context);
+    let (dirfd, path, flags, mode) = if at {
+        (args[0] as libc::c_int, args[1], args[2], args[3])
+    } else {
+        (libc::AT_FDCWD, args[0], args[1], args[2])
+    };
+    let path = path as *const libc::c_char;
+    // Unlike `openat`, `openat2` rejects unknown bits and a mode if no file can be created.
+    let flags = flags as u32 as libc::c_int;
+    let creates = flags & libc::O_CREAT != 0 || flags & libc::O_TMPFILE == libc::O_TMPFILE;
+    let mode = if creates { mode & 0o7777 } else { 0 };
+    let how = OpenHow { flags: flags as u32 as u64, mode, resolve: 0 };
+
+    // The errno of the interrupted code must be preserved, the result is returned in
+    // the register instead.
+    let errno = libc::__errno_location();
+    let saved_errno = *errno;
+    let fd =
+        libc::syscall(SYS_OPENAT2, dirfd, path, &how as *const OpenHow, mem::size_of::<OpenHow>());
+    let result = if fd < 0 { -(*errno as i64) } else { fd as i64 };
+    *errno = saved_errno;
+
+    // Landlock denies opening a path with `EACCES`.
+    if result == -(libc::EACCES as i64) && REPORT_PATH_DENIALS.load(Ordering::Relaxed) {
+        report_denial(MSG_DENIED_PATH, CStr::from_ptr(path).to_bytes());
+    }
+    set_syscall_result(context, result);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn syscall_args(context: *mut libc::ucontext_t) -> [u64; 4] {
+    let regs = &(*context).uc_mcontext.gregs;
+    [libc::REG_RDI, libc::REG_RSI, libc::REG_RDX, libc::REG_R10]
+        .map(|reg| regs[reg as usize] as u64)
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn set_syscall_result(context: *mut libc::ucontext_t, result: i64) {
+    (*context).uc_mcontext.gregs[libc::REG_RAX as usize] = result;
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn syscall_args(context: *mut libc::ucontext_t) -> [u64; 4] {
+    let regs = &(*context).uc_mcontext.regs;
+    [regs[0], regs[1], regs[2], regs[3]]
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn set_syscall_result(context: *mut libc::ucontext_t, result: i64) {
+    (*context).uc_mcontext.regs[0] = result as u64;
+}
+
+/// Sends a message to the compiler and exits. Only async-signal-safe functions can be called
+/// from the `SIGSYS` handler, so the message is written without allocating.
+fn report_denial(tag: u8, payload: &[u8]) -> ! {
+    let mut header = [0u8; 9];
+    header[0] = tag;
+    header[1..].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+    let message = [
+        libc::iovec { iov_base: header.as_mut_ptr().cast(), iov_len: header.len() },
+        libc::iovec { iov_base: payload.as_ptr() as *mut libc::c_void, iov_len: payload.len() },
+    ];
+    unsafe {
+        libc::writev(DENIAL_FD.load(Ordering::Relaxed), message.as_ptr(), message.len() as _);
+        libc::_exit(1);
+    }
+}
+
+fn last_error(what: &str) -> String {
+    format!("{}: {}", what, io::Error::last_os_error())
+}
@@ -371,6 +371,7 @@ fn track_env_var(&mut self, var: &str, value: Option<&str>) {
             .env_depinfo
             .borrow_mut()
             .insert((Symbol::intern(var), value.map(Symbol::intern)));
+        self.sess().proc_macro_env_depinfo.borrow_mut().insert(Symbol::intern(var));
     }
 
     fn track_path(&mut self, path: &str) {
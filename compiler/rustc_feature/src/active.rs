@@ -502,6 +502,10 @@ pub fn set(&self, features: &mut Features, span: Span) {
     (active, strict_provenance, "1.61.0", Some(95228), None),
     /// Allows the use of `#[target_feature]` on safe functions.
     (active, target_feature_11, "1.45.0", Some(69098), None),
+    /// Allows `#[test_setup]` and `#[test_teardown]` functions that run before and after all tests.
+    (active, test_hooks, "1.64.0", None, None),
+    /// Allows `#[test_timeout]` to override the timeout of a test.
+    (active, test_timeout, "1.64.0", None, None),
     /// Allows using `#[thread_local]` on `static` items.
     (active, thread_local, "1.0.0", Some(29594), None),
     /// Allows defining `trait X = A + B;` alias items.
@@ -433,6 +433,10 @@ pub struct BuiltinAttribute {
         test_runner, CrateLevel, template!(List: "path"), ErrorFollowing, custom_test_frameworks,
         "custom test frameworks are an unstable feature",
     ),
+    gated!(
+        test_timeout, Normal, template!(NameValueStr: "seconds"), ErrorFollowing,
+        experimental!(test_timeout)
+    ),
     // RFC #1268
     gated!(
         marker, Normal, template!(Word), WarnFollowing, marker_trait_attr, experimental!(marker)
@@ -718,6 +722,12 @@ pub struct BuiltinAttribute {
         rustc_test_marker, Normal, template!(Word), WarnFollowing,
         "the `#[rustc_test_marker]` attribute is used internally to track tests",
     ),
+    rustc_attr!(
+        rustc_test_hook, Normal, template!(NameValueStr: "test_setup|test_teardown"),
+        ErrorFollowing,
+        "the `#[rustc_test_hook]` attribute is used internally to track test setup and teardown \
+         functions",
+    ),
     rustc_attr!(
         rustc_unsafe_specialization_marker, Normal, template!(Word), WarnFollowing,
         "the `#[rustc_unsafe_specialization_marker]` attribute is used to check specializations"
@@ -0,0 +1,137 @@
+//! Implementation of `-Z explain-recompilation`.
+//!
+//! While trying to mark the nodes of the previous dep-graph green, the dep-graph records which
+//! dependency prevented each failed node from being marked green. Following these records
+//! leads from every re-executed node back to an input that changed (or could no longer be
+//! recomputed). This module groups the re-executed nodes by that input, which tells which
+//! edits invalidated the most work, and prints the full chain for every codegen unit that
+//! could not be reused.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::definitions::DefPathHash;
+use rustc_middle::dep_graph::{DepKind, DepNode, FingerprintStyle, RecompilationChain};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::RecompilationReportFormat;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Report {
+    /// Sorted by the number of nodes each input caused to be re-executed.
+    inputs: Vec<Input>,
+    recompiled: Vec<Recompiled>,
+}
+
+#[derive(Serialize)]
+struct Input {
+    node: String,
+    /// `false` if the input could not be recomputed at all, e.g. because it was removed.
+    changed: bool,
+    reexecuted: usize,
+}
+
+#[derive(Serialize)]
+struct Recompiled {
+    node: String,
+    codegen_unit: bool,
+    /// From the changed input to `node`.
+    chain: Vec<String>,
+}
+
+/// Prints the recompilation report for the current session in the requested format.
+pub fn explain_recompilation(tcx: TyCtxt<'_>, format: RecompilationReportFormat) {
+    let chains = tcx.dep_graph.recompilation_chains();
+    let report = build_report(tcx, &chains);
+
+    match format {
+        RecompilationReportFormat::Text => print_text_report(tcx, &report),
+        RecompilationReportFormat::Json => {
+            println!("{}", serde_json::to_string(&report).unwrap());
+        }
+    }
+}
+
+/// Names `node` like its `Debug` impl does, except that the items which were removed since the
+/// previous session, and thus don't have a `DefId` anymore, are named by their hash.
+fn node_name(tcx: TyCtxt<'_>, node: &DepNode) -> String {
+    if node.kind.fingerprint_style(tcx) != FingerprintStyle::DefPathHash {
+        return format!("{:?}", node);
+    }
+    match tcx.try_def_path_hash_to_def_id(DefPathHash(node.hash.into())) {
+        Some(def_id) => format!("{:?}({})", node.kind, tcx.def_path_debug_str(def_id)),
+        None => format!("{:?}({})", node.kind, node.hash),
+    }
+}
+
+fn build_report(tcx: TyCtxt<'_>, chains: &[RecompilationChain]) -> Report {
+    // Printing a node may need to look up its def-path, so only do it once per node.
+    let mut names: FxHashMap<DepNode, String> = FxHashMap::default();
+    let mut name =
+        |node: &DepNode| names.entry(*node).or_insert_with(|| node_name(tcx, node)).clone();
+
+    let mut inputs: FxHashMap<DepNode, Input> = FxHashMap::default();
+    let mut recompiled = Vec::with_capacity(chains.len());
+    for chain in chains {
+        let (Some(input), Some(node)) = (chain.nodes.first(), chain.nodes.last()) else { continue };
+
+        inputs
+            .entry(*input)
+            .or_insert_with(|| Input {
+                node: name(input),
+                changed: chain.input_changed,
+                reexecuted: 0,
+            })
+            .reexecuted += 1;
+
+        recompiled.push(Recompiled {
+            node: name(node),
+            codegen_unit: node.kind == DepKind::CompileCodegenUnit,
+            chain: chain.nodes.iter().map(|node| name(node)).collect(),
+        });
+    }
+
+    let mut inputs: Vec<_> = inputs.into_values().collect();
+    inputs.sort_by(|a, b| b.reexecuted.cmp(&a.reexecuted).then_with(|| a.node.cmp(&b.node)));
+    recompiled
+        .sort_by(|a, b| b.codegen_unit.cmp(&a.codegen_unit).then_with(|| a.node.cmp(&b.node)));
+
+    Report { inputs, recompiled }
+}
+
+fn print_text_report(tcx: TyCtxt<'_>, report: &Report) {
+    println!(
+        "{} dep-nodes of the previous session were re-executed because of {} inputs",
+        report.recompiled.len(),
+        report.inputs.len()
+    );
+    if report.inputs.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{:<80} {:>12}", "input", "re-executed");
+    for input in &report.inputs {
+        let input_name = if input.changed {
+            format!("{} (changed)", input.node)
+        } else {
+            format!("{} (could not be recomputed)", input.node)
+        };
+        println!("{:<80} {:>12}", input_name, input.reexecuted);
+    }
+
+    // Explaining every query that was re-executed would drown out the codegen units, which
+    // are what usually matters for build times.
+    let verbose = tcx.sess.verbose();
+    for recompiled in &report.recompiled {
+        if !recompiled.codegen_unit && !verbose {
+            continue;
+        }
+
+        println!();
+        println!("{} was re-executed because:", recompiled.node);
+        let (input, rest) = recompiled.chain.split_first().unwrap();
+        println!("    {}", input);
+        for node in rest {
+            println!("    -> {}", node);
+        }
+    }
+}
@@ -13,11 +13,14 @@
 
 mod assert_dep_graph;
 pub mod assert_module_sources;
+mod explain_recompilation;
 mod persist;
 
 use assert_dep_graph::assert_dep_graph;
+pub use explain_recompilation::explain_recompilation;
 pub use persist::copy_cgu_workproduct_to_incr_comp_cache_dir;
 pub use persist::delete_workproduct_files;
+pub use persist::dump_incr_cache;
 pub use persist::finalize_session_directory;
 pub use persist::garbage_collect_session_directories;
 pub use persist::in_incr_comp_dir;
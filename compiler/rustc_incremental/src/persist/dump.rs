@@ -0,0 +1,273 @@
+//! Implementation of `-Z dump-incr-cache`, which prints what an incremental compilation session
+//! directory contains, and of `-Z dump-incr-cache-baseline`, which compares two of them to
+//! explain why a crate was rebuilt.
+//!
+//! The files are read the same way `load` reads them, but nothing is discarded when they turn
+//! out to be unusable, since the whole point is to look at caches that misbehave.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::memmap::Mmap;
+use rustc_middle::dep_graph::{DepKind, DepNode, SerializedDepGraph, SerializedDepNodeIndex};
+use rustc_middle::ty::OnDiskCache;
+use rustc_serialize::opaque::MemDecoder;
+use rustc_serialize::Decodable;
+use rustc_session::Session;
+use std::path::{Path, PathBuf};
+
+use super::data::*;
+use super::file_format;
+use super::fs::*;
+
+/// Everything that could be read from a session directory.
+struct SessionData {
+    dir: PathBuf,
+    commandline_args_hash: Option<u64>,
+    dep_graph: SerializedDepGraph,
+    /// Dep-nodes of `dep_graph` that have a cached query result.
+    cached_results: FxHashSet<SerializedDepNodeIndex>,
+    work_products: Vec<SerializedWorkProduct>,
+}
+
+#[derive(Default)]
+struct KindStats {
+    nodes: usize,
+    edges: usize,
+    cached_results: usize,
+}
+
+#[derive(Default)]
+struct KindChanges {
+    added: usize,
+    removed: usize,
+    changed: usize,
+}
+
+/// Prints the contents of the session directory given to `-Z dump-incr-cache`, or how it differs
+/// from the one given to `-Z dump-incr-cache-baseline`.
+pub fn dump_incr_cache<'a, C: OnDiskCache<'a>>(sess: &'a Session) {
+    let Some(path) = &sess.opts.debugging_opts.dump_incr_cache else { return };
+    let data = load_session_data::<C>(sess, path);
+
+    match &sess.opts.debugging_opts.dump_incr_cache_baseline {
+        Some(baseline) => {
+            let baseline = load_session_data::<C>(sess, baseline);
+            print_diff(sess, &baseline, &data);
+        }
+        None => print_session_data(sess, &data),
+    }
+}
+
+fn load_session_data<'a, C: OnDiskCache<'a>>(sess: &'a Session, path: &Path) -> SessionData {
+    let dir = session_directory_to_dump(path);
+
+    let mut commandline_args_hash = None;
+    let mut dep_graph = SerializedDepGraph::default();
+    if let Some((bytes, start_pos)) = read_file(sess, &dir, DEP_GRAPH_FILENAME) {
+        let mut decoder = MemDecoder::new(&bytes, start_pos);
+        commandline_args_hash = Some(u64::decode(&mut decoder));
+        dep_graph = SerializedDepGraph::decode(&mut decoder);
+    }
+
+    let cached_results = match read_file(sess, &dir, QUERY_CACHE_FILENAME) {
+        Some((bytes, start_pos)) => {
+            C::new(sess, bytes, start_pos).cached_query_results().into_iter().collect()
+        }
+        None => FxHashSet::default(),
+    };
+
+    let work_products = match read_file(sess, &dir, WORK_PRODUCTS_FILENAME) {
+        Some((bytes, start_pos)) => Decodable::decode(&mut MemDecoder::new(&bytes, start_pos)),
+        None => Vec::new(),
+    };
+
+    SessionData { dir, commandline_args_hash, dep_graph, cached_results, work_products }
+}
+
+fn read_file(sess: &Session, dir: &Path, file_name: &str) -> Option<(Mmap, usize)> {
+    let path = in_incr_comp_dir(dir, file_name);
+    match file_format::read_file(
+        sess.opts.debugging_opts.incremental_info,
+        &path,
+        sess.is_nightly_build(),
+    ) {
+        Ok(Some(data)) => Some(data),
+        Ok(None) => {
+            sess.warn(&format!(
+                "`{}` does not exist or was written by a different compiler version",
+                path.display()
+            ));
+            None
+        }
+        Err(err) => {
+            sess.err(&format!("could not read `{}`: {}", path.display(), err));
+            None
+        }
+    }
+}
+
+fn print_session_data(sess: &Session, data: &SessionData) {
+    let dep_graph = &data.dep_graph;
+
+    println!("incremental session directory `{}`", data.dir.display());
+    if let Some(hash) = data.commandline_args_hash {
+        println!("command line arguments hash: {:016x}", hash);
+    }
+    println!(
+        "{}: {} nodes, {} edges",
+        DEP_GRAPH_FILENAME,
+        dep_graph.node_count(),
+        dep_graph.edge_count()
+    );
+    println!("{}: {} cached query results", QUERY_CACHE_FILENAME, data.cached_results.len());
+    println!("{}: {} work products", WORK_PRODUCTS_FILENAME, data.work_products.len());
+    for swp in &data.work_products {
+        let exists = in_incr_comp_dir(&data.dir, &swp.work_product.saved_file).exists();
+        println!(
+            "    {} -> {}{}",
+            swp.work_product.cgu_name,
+            swp.work_product.saved_file,
+            if exists { "" } else { " (missing)" }
+        );
+    }
+
+    let mut stats: FxHashMap<DepKind, KindStats> = FxHashMap::default();
+    for index in dep_graph.indices() {
+        let stat = stats.entry(dep_graph.index_to_node(index).kind).or_default();
+        stat.nodes += 1;
+        stat.edges += dep_graph.edge_targets_from(index).len();
+        if data.cached_results.contains(&index) {
+            stat.cached_results += 1;
+        }
+    }
+    let mut stats: Vec<_> = stats.into_iter().collect();
+    stats.sort_by(|(a_kind, a), (b_kind, b)| {
+        b.nodes.cmp(&a.nodes).then_with(|| format!("{:?}", a_kind).cmp(&format!("{:?}", b_kind)))
+    });
+
+    println!();
+    println!("{:<40} {:>10} {:>10} {:>10}", "node kind", "nodes", "edges", "cached");
+    for (kind, stat) in stats {
+        println!(
+            "{:<40} {:>10} {:>10} {:>10}",
+            format!("{:?}", kind),
+            stat.nodes,
+            stat.edges,
+            stat.cached_results
+        );
+    }
+
+    if sess.verbose() {
+        println!();
+        for index in dep_graph.indices() {
+            let targets: Vec<_> =
+                dep_graph.edge_targets_from(index).iter().map(|target| target.as_usize()).collect();
+            println!(
+                "{}: {:?} {}{} -> {:?}",
+                index.as_usize(),
+                dep_graph.index_to_node(index),
+                dep_graph.fingerprint_by_index(index).to_hex(),
+                if data.cached_results.contains(&index) { " (cached)" } else { "" },
+                targets
+            );
+        }
+    }
+}
+
+/// Prints how `new` differs from `old`. A dep-node of `new` is dirty if it was added or its
+/// fingerprint changed. Dirty nodes none of whose dependencies are dirty are the changed inputs
+/// that made the compiler re-execute the other dirty nodes.
+fn print_diff(sess: &Session, old: &SessionData, new: &SessionData) {
+    let (old_graph, new_graph) = (&old.dep_graph, &new.dep_graph);
+
+    println!("comparing `{}` with `{}`", old.dir.display(), new.dir.display());
+    if old.commandline_args_hash != new.commandline_args_hash {
+        println!(
+            "the command line arguments differ, so no results of the old session could be reused"
+        );
+    }
+
+    let mut changes: FxHashMap<DepKind, KindChanges> = FxHashMap::default();
+    let mut dirty = FxHashSet::default();
+    for index in new_graph.indices() {
+        let node = new_graph.index_to_node(index);
+        match old_graph.fingerprint_of(&node) {
+            Some(fingerprint) if fingerprint == new_graph.fingerprint_by_index(index) => {}
+            Some(_) => {
+                changes.entry(node.kind).or_default().changed += 1;
+                dirty.insert(index);
+            }
+            None => {
+                changes.entry(node.kind).or_default().added += 1;
+                dirty.insert(index);
+            }
+        }
+    }
+    for index in old_graph.indices() {
+        let node = old_graph.index_to_node(index);
+        if new_graph.node_to_index_opt(&node).is_none() {
+            changes.entry(node.kind).or_default().removed += 1;
+        }
+    }
+
+    let mut changes: Vec<_> = changes.into_iter().collect();
+    changes.sort_by_key(|(kind, _)| format!("{:?}", kind));
+
+    println!();
+    println!("{:<40} {:>10} {:>10} {:>10}", "node kind", "added", "removed", "changed");
+    for (kind, change) in changes {
+        println!(
+            "{:<40} {:>10} {:>10} {:>10}",
+            format!("{:?}", kind),
+            change.added,
+            change.removed,
+            change.changed
+        );
+    }
+
+    let describe = |index: SerializedDepNodeIndex| {
+        let node: DepNode = new_graph.index_to_node(index);
+        let new_fingerprint = new_graph.fingerprint_by_index(index).to_hex();
+        match old_graph.fingerprint_of(&node) {
+            Some(old_fingerprint) => {
+                format!("{:?} {} -> {}", node, old_fingerprint.to_hex(), new_fingerprint)
+            }
+            None => format!("{:?} (added) {}", node, new_fingerprint),
+        }
+    };
+
+    let mut sorted_dirty: Vec<_> = dirty.iter().copied().collect();
+    sorted_dirty.sort();
+    let (inputs, consequences): (Vec<_>, Vec<_>) = sorted_dirty.into_iter().partition(|&index| {
+        new_graph.edge_targets_from(index).iter().all(|target| !dirty.contains(target))
+    });
+
+    println!();
+    println!("changed inputs:");
+    for &index in &inputs {
+        println!("    {}", describe(index));
+    }
+    println!("{} dep-nodes changed as a consequence", consequences.len());
+    if sess.verbose() {
+        for &index in &consequences {
+            println!("    {}", describe(index));
+        }
+    }
+
+    let old_products: FxHashMap<_, _> =
+        old.work_products.iter().map(|swp| (swp.id, &swp.work_product)).collect();
+    let new_products: FxHashMap<_, _> =
+        new.work_products.iter().map(|swp| (swp.id, &swp.work_product)).collect();
+
+    println!();
+    println!("work products:");
+    for swp in &new.work_products {
+        if !old_products.contains_key(&swp.id) {
+            println!("    + {}", swp.work_product.cgu_name);
+        }
+    }
+    for swp in &old.work_products {
+        if !new_products.contains_key(&swp.id) {
+            println!("    - {}", swp.work_product.cgu_name);
+        }
+    }
+}
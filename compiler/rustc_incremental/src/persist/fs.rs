@@ -122,10 +122,10 @@
 mod tests;
 
 const LOCK_FILE_EXT: &str = ".lock";
-const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
+pub(crate) const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
-const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
-const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+pub(crate) const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
+pub(crate) const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -575,6 +575,19 @@ fn find_source_directory(
     find_source_directory_in_iter(iter, source_directories_already_tried)
 }
 
+/// Returns the session directory `-Z dump-incr-cache` should inspect for `path`. This is `path`
+/// itself if it is a session directory, or the most recent published session directory if it is
+/// the incremental directory of a whole crate.
+pub(crate) fn session_directory_to_dump(path: &Path) -> PathBuf {
+    if path.join(DEP_GRAPH_FILENAME).exists() {
+        return path.to_owned();
+    }
+
+    let Ok(entries) = path.read_dir() else { return path.to_owned() };
+    let iter = entries.filter_map(|e| e.ok().map(|e| e.path()));
+    find_source_directory_in_iter(iter, &FxHashSet::default()).unwrap_or_else(|| path.to_owned())
+}
+
 fn find_source_directory_in_iter<I>(
     iter: I,
     source_directories_already_tried: &FxHashSet<PathBuf>,
@@ -4,12 +4,14 @@
 
 mod data;
 mod dirty_clean;
+mod dump;
 mod file_format;
 mod fs;
 mod load;
 mod save;
 mod work_product;
 
+pub use dump::dump_incr_cache;
 pub use fs::finalize_session_directory;
 pub use fs::garbage_collect_session_directories;
 pub use fs::in_incr_comp_dir;
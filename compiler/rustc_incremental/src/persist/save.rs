@@ -43,6 +43,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             tcx.dep_graph.print_incremental_info()
         }
 
+        if let Some(format) = sess.opts.debugging_opts.explain_recompilation {
+            crate::explain_recompilation(tcx, format);
+        }
+
         join(
             move || {
                 sess.time("incr_comp_persist_result_cache", || {
@@ -183,5 +187,6 @@ pub fn build_dep_graph(
         encoder,
         sess.opts.debugging_opts.query_dep_graph,
         sess.opts.debugging_opts.incremental_info,
+        sess.opts.debugging_opts.explain_recompilation.is_some(),
     ))
 }
@@ -28,7 +28,7 @@
 use rustc_plugin_impl as plugin;
 use rustc_query_impl::{OnDiskCache, Queries as TcxQueries};
 use rustc_resolve::{Resolver, ResolverArenas};
-use rustc_session::config::{CrateType, Input, OutputFilenames, OutputType};
+use rustc_session::config::{CrateType, DepInfoFormat, Input, OutputFilenames, OutputType};
 use rustc_session::cstore::{CrateStoreDyn, MetadataLoader, MetadataLoaderDyn};
 use rustc_session::output::{filename_for_input, filename_for_metadata};
 use rustc_session::search_paths::PathKind;
@@ -37,6 +37,7 @@
 use rustc_span::FileName;
 use rustc_trait_selection::traits;
 use rustc_typeck as typeck;
+use serde::Serialize;
 use tempfile::Builder as TempFileBuilder;
 use tracing::{info, warn};
 
@@ -595,6 +596,35 @@ fn escape_dep_env(symbol: Symbol) -> String {
     escaped
 }
 
+/// The dep-info written with `-Z dep-info-format=json`.
+#[derive(Serialize)]
+struct JsonDepInfo {
+    outputs: Vec<String>,
+    /// Source files of the crate, excluding `included_files`.
+    sources: Vec<String>,
+    /// Files read by `include!`, `include_str!` and `include_bytes!`.
+    included_files: Vec<String>,
+    /// Environment variables read during the build, with `null` values for unset variables.
+    env: Vec<JsonEnvDep>,
+    proc_macro: JsonProcMacroDeps,
+    /// Crates and the codegen backend, with `-Z binary-dep-depinfo`.
+    binary_deps: Vec<String>,
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct JsonEnvDep {
+    name: String,
+    value: Option<String>,
+}
+
+/// Dependencies declared with `proc_macro::tracked_env::var` and
+/// `proc_macro::tracked_path::path`.
+#[derive(Serialize)]
+struct JsonProcMacroDeps {
+    files: Vec<String>,
+    env: Vec<String>,
+}
+
 fn write_out_deps(
     sess: &Session,
     boxed_resolver: &RefCell<BoxedResolver>,
@@ -608,33 +638,35 @@ fn write_out_deps(
     let deps_filename = outputs.path(OutputType::DepInfo);
 
     let result = (|| -> io::Result<()> {
-        // Build a list of files used to compile the output and
-        // write Makefile-compatible dependency rules
-        let mut files: Vec<String> = sess
+        // Build a list of files used to compile the output
+        let source_files: Vec<String> = sess
             .source_map()
             .files()
             .iter()
             .filter(|fmap| fmap.is_real_file())
             .filter(|fmap| !fmap.is_imported())
-            .map(|fmap| escape_dep_filename(&fmap.name.prefer_local().to_string()))
+            .map(|fmap| fmap.name.prefer_local().to_string())
             .collect();
 
         // Account for explicitly marked-to-track files
         // (e.g. accessed in proc macros).
         let file_depinfo = sess.parse_sess.file_depinfo.borrow();
-        let extra_tracked_files = file_depinfo.iter().map(|path_sym| {
-            let path = PathBuf::from(path_sym.as_str());
-            let file = FileName::from(path);
-            escape_dep_filename(&file.prefer_local().to_string())
-        });
-        files.extend(extra_tracked_files);
+        let tracked_files: Vec<String> = file_depinfo
+            .iter()
+            .map(|path_sym| {
+                let path = PathBuf::from(path_sym.as_str());
+                let file = FileName::from(path);
+                file.prefer_local().to_string()
+            })
+            .collect();
 
+        let mut binary_deps = Vec::new();
         if sess.binary_dep_depinfo() {
             if let Some(ref backend) = sess.opts.debugging_opts.codegen_backend {
                 if backend.contains('.') {
                     // If the backend name contain a `.`, it is the path to an external dynamic
                     // library. If not, it is not a path.
-                    files.push(backend.to_string());
+                    binary_deps.push(backend.to_string());
                 }
             }
 
@@ -642,47 +674,41 @@ fn write_out_deps(
                 for cnum in resolver.cstore().crates_untracked() {
                     let source = resolver.cstore().crate_source_untracked(cnum);
                     if let Some((path, _)) = &source.dylib {
-                        files.push(escape_dep_filename(&path.display().to_string()));
+                        binary_deps.push(path.display().to_string());
                     }
                     if let Some((path, _)) = &source.rlib {
-                        files.push(escape_dep_filename(&path.display().to_string()));
+                        binary_deps.push(path.display().to_string());
                     }
                     if let Some((path, _)) = &source.rmeta {
-                        files.push(escape_dep_filename(&path.display().to_string()));
+                        binary_deps.push(path.display().to_string());
                     }
                 }
             });
         }
 
         let mut file = BufWriter::new(fs::File::create(&deps_filename)?);
-        for path in out_filenames {
-            writeln!(file, "{}: {}\n", path.display(), files.join(" "))?;
-        }
-
-        // Emit a fake target for each input file to the compilation. This
-        // prevents `make` from spitting out an error if a file is later
-        // deleted. For more info see #28735
-        for path in files {
-            writeln!(file, "{}:", path)?;
-        }
-
-        // Emit special comments with information about accessed environment variables.
-        let env_depinfo = sess.parse_sess.env_depinfo.borrow();
-        if !env_depinfo.is_empty() {
-            let mut envs: Vec<_> = env_depinfo
-                .iter()
-                .map(|(k, v)| (escape_dep_env(*k), v.map(escape_dep_env)))
-                .collect();
-            envs.sort_unstable();
-            writeln!(file)?;
-            for (k, v) in envs {
-                write!(file, "# env-dep:{}", k)?;
-                if let Some(v) = v {
-                    write!(file, "={}", v)?;
-                }
-                writeln!(file)?;
+        match sess.opts.debugging_opts.dep_info_format {
+            DepInfoFormat::Makefile => {
+                let files: Vec<String> = source_files
+                    .iter()
+                    .chain(&tracked_files)
+                    .chain(&binary_deps)
+                    .map(|path| escape_dep_filename(path))
+                    .collect();
+                write_makefile_deps(&mut file, sess, out_filenames, &files)?;
+            }
+            DepInfoFormat::Json => {
+                write_json_deps(
+                    &mut file,
+                    sess,
+                    out_filenames,
+                    source_files,
+                    tracked_files,
+                    binary_deps,
+                )?;
             }
         }
+        file.flush()?;
 
         Ok(())
     })();
@@ -703,6 +729,99 @@ fn write_out_deps(
     }
 }
 
+/// Writes Makefile-compatible dependency rules.
+fn write_makefile_deps(
+    file: &mut impl Write,
+    sess: &Session,
+    out_filenames: &[PathBuf],
+    files: &[String],
+) -> io::Result<()> {
+    for path in out_filenames {
+        writeln!(file, "{}: {}\n", path.display(), files.join(" "))?;
+    }
+
+    // Emit a fake target for each input file to the compilation. This
+    // prevents `make` from spitting out an error if a file is later
+    // deleted. For more info see #28735
+    for path in files {
+        writeln!(file, "{}:", path)?;
+    }
+
+    // Emit special comments with information about accessed environment variables.
+    let env_depinfo = sess.parse_sess.env_depinfo.borrow();
+    if !env_depinfo.is_empty() {
+        let mut envs: Vec<_> =
+            env_depinfo.iter().map(|(k, v)| (escape_dep_env(*k), v.map(escape_dep_env))).collect();
+        envs.sort_unstable();
+        writeln!(file)?;
+        for (k, v) in envs {
+            write!(file, "# env-dep:{}", k)?;
+            if let Some(v) = v {
+                write!(file, "={}", v)?;
+            }
+            writeln!(file)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_json_deps(
+    file: &mut impl Write,
+    sess: &Session,
+    out_filenames: &[PathBuf],
+    source_files: Vec<String>,
+    tracked_files: Vec<String>,
+    binary_deps: Vec<String>,
+) -> io::Result<()> {
+    let sorted = |mut paths: Vec<String>| {
+        paths.sort_unstable();
+        paths.dedup();
+        paths
+    };
+
+    let included_files: Vec<String> = sess
+        .parse_sess
+        .include_depinfo
+        .borrow()
+        .iter()
+        .map(|path_sym| FileName::from(PathBuf::from(path_sym.as_str())).prefer_local().to_string())
+        .collect();
+    let sources: Vec<String> =
+        source_files.into_iter().filter(|path| !included_files.contains(path)).collect();
+
+    let mut env: Vec<JsonEnvDep> = sess
+        .parse_sess
+        .env_depinfo
+        .borrow()
+        .iter()
+        .map(|(name, value)| JsonEnvDep {
+            name: name.to_string(),
+            value: value.map(|value| value.to_string()),
+        })
+        .collect();
+    env.sort_unstable();
+
+    let proc_macro_env: Vec<String> = sess
+        .parse_sess
+        .proc_macro_env_depinfo
+        .borrow()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let dep_info = JsonDepInfo {
+        outputs: sorted(out_filenames.iter().map(|path| path.display().to_string()).collect()),
+        sources: sorted(sources),
+        included_files: sorted(included_files),
+        env,
+        proc_macro: JsonProcMacroDeps { files: sorted(tracked_files), env: sorted(proc_macro_env) },
+        binary_deps: sorted(binary_deps),
+    };
+    serde_json::to_writer(&mut *file, &dep_info)?;
+    writeln!(file)
+}
+
 pub fn prepare_outputs(
     sess: &Session,
     compiler: &Compiler,
@@ -9,8 +9,8 @@
     rustc_optgroups, ErrorOutputType, ExternLocation, LocationDetail, Options, Passes,
 };
 use rustc_session::config::{
-    BranchProtection, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey, PacRet,
-    SymbolManglingVersion, WasiExecModel,
+    BranchProtection, DepInfoFormat, Externs, OomStrategy, OutputType, OutputTypes, PAuthKey,
+    PacRet, PrintTypeSizesFormat, RecompilationReportFormat, SymbolManglingVersion, WasiExecModel,
 };
 use rustc_session::config::{CFGuard, ExternEntry, LinkerPluginLto, LtoCli, SwitchWithOptPath};
 use rustc_session::lint::Level;
@@ -644,17 +644,21 @@ macro_rules! untracked {
     // Make sure that changing an [UNTRACKED] option leaves the hash unchanged.
     // This list is in alphabetical order.
     untracked!(assert_incr_state, Some(String::from("loaded")));
+    untracked!(build_manifest, SwitchWithOptPath::Enabled(Some(PathBuf::from("abc"))));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dep_tasks, true);
     untracked!(dlltool, Some(PathBuf::from("custom_dlltool.exe")));
     untracked!(dont_buffer_diagnostics, true);
     untracked!(dump_dep_graph, true);
+    untracked!(dump_incr_cache, Some(PathBuf::from("incr")));
+    untracked!(dump_incr_cache_baseline, Some(PathBuf::from("incr-old")));
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
     untracked!(dump_mir_dir, String::from("abc"));
     untracked!(dump_mir_exclude_pass_number, true);
     untracked!(dump_mir_graphviz, true);
     untracked!(emit_stack_sizes, true);
+    untracked!(explain_recompilation, Some(RecompilationReportFormat::Json));
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
@@ -680,8 +684,13 @@ macro_rules! untracked {
     untracked!(profile_closures, true);
     untracked!(print_llvm_passes, true);
     untracked!(print_mono_items, Some(String::from("abc")));
+    untracked!(print_mono_item_stats, true);
     untracked!(print_type_sizes, true);
+    untracked!(print_type_sizes_filter, Some(String::from("std::option")));
+    untracked!(print_type_sizes_format, PrintTypeSizesFormat::Json);
     untracked!(proc_macro_backtrace, true);
+    untracked!(proc_macro_sandbox, true);
+    untracked!(proc_macro_sandbox_allow_path, vec![String::from("/src")]);
     untracked!(query_dep_graph, true);
     untracked!(save_analysis, true);
     untracked!(self_profile, SwitchWithOptPath::Enabled(None));
@@ -730,6 +739,7 @@ macro_rules! tracked {
     tracked!(crate_attr, vec!["abc".to_string()]);
     tracked!(debug_info_for_profiling, true);
     tracked!(debug_macros, true);
+    tracked!(dep_info_format, DepInfoFormat::Json);
     tracked!(dep_info_omit_d_target, true);
     tracked!(drop_tracking, true);
     tracked!(dual_proc_macros, true);
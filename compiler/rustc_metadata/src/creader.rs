@@ -9,6 +9,7 @@
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::Lrc;
 use rustc_expand::base::SyntaxExtension;
+use rustc_expand::proc_macro_sandbox::ProcMacroSandbox;
 use rustc_hir::def_id::{CrateNum, LocalDefId, StableCrateId, LOCAL_CRATE};
 use rustc_hir::definitions::Definitions;
 use rustc_index::vec::IndexVec;
@@ -397,7 +398,7 @@ fn register_crate(
 
         let cnum_map = self.resolve_crate_deps(root, &crate_root, &metadata, cnum, dep_kind)?;
 
-        let raw_proc_macros = if crate_root.is_proc_macro_crate() {
+        let (raw_proc_macros, proc_macro_sandbox) = if crate_root.is_proc_macro_crate() {
             let temp_root;
             let (dlsym_source, dlsym_root) = match &host_lib {
                 Some(host_lib) => (&host_lib.source, {
@@ -407,9 +408,30 @@ fn register_crate(
                 None => (&source, &crate_root),
             };
             let dlsym_dylib = dlsym_source.dylib.as_ref().expect("no dylib for a proc-macro crate");
-            Some(self.dlsym_proc_macros(&dlsym_dylib.0, dlsym_root.stable_crate_id())?)
+            if self.sess.opts.debugging_opts.proc_macro_sandbox {
+                // The dylib is only loaded by the sandboxed process, since loading it would
+                // already run code from it.
+                let current_dir = env::current_dir().map_err(|err| {
+                    CrateError::DlOpen(format!("could not get the current directory: {}", err))
+                })?;
+                let sandbox = ProcMacroSandbox::new(
+                    current_dir.join(&dlsym_dylib.0),
+                    self.sess.generate_proc_macro_decls_symbol(dlsym_root.stable_crate_id()),
+                    self.sess.host_tlib_path.dir.clone(),
+                    self.sess
+                        .opts
+                        .debugging_opts
+                        .proc_macro_sandbox_allow_path
+                        .iter()
+                        .map(|path| current_dir.join(path))
+                        .collect(),
+                );
+                (None, Some(Lrc::new(sandbox)))
+            } else {
+                (Some(self.dlsym_proc_macros(&dlsym_dylib.0, dlsym_root.stable_crate_id())?), None)
+            }
         } else {
-            None
+            (None, None)
         };
 
         // Perform some verification *after* resolve_crate_deps() above is
@@ -426,6 +448,7 @@ fn register_crate(
             metadata,
             crate_root,
             raw_proc_macros,
+            proc_macro_sandbox,
             cnum,
             cnum_map,
             dep_kind,
@@ -10,8 +10,9 @@
 use rustc_data_structures::svh::Svh;
 use rustc_data_structures::sync::{Lock, LockGuard, Lrc, OnceCell};
 use rustc_data_structures::unhash::UnhashMap;
-use rustc_expand::base::{SyntaxExtension, SyntaxExtensionKind};
+use rustc_expand::base::{parse_macro_name_and_helper_attrs, SyntaxExtension, SyntaxExtensionKind};
 use rustc_expand::proc_macro::{AttrProcMacro, BangProcMacro, DeriveProcMacro};
+use rustc_expand::proc_macro_sandbox::{ProcMacroSandbox, SandboxedProcMacro};
 use rustc_hir::def::{CtorKind, CtorOf, DefKind, Res};
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex, CRATE_DEF_INDEX, LOCAL_CRATE};
 use rustc_hir::definitions::{DefKey, DefPath, DefPathData, DefPathHash};
@@ -99,6 +100,8 @@ pub(crate) struct CrateMetadata {
     incoherent_impls: FxHashMap<SimplifiedType, LazyArray<DefIndex>>,
     /// Proc macro descriptions for this crate, if it's a proc macro crate.
     raw_proc_macros: Option<&'static [ProcMacro]>,
+    /// Set instead of `raw_proc_macros` if the macros of the crate run in a sandbox.
+    proc_macro_sandbox: Option<Lrc<ProcMacroSandbox>>,
     /// Source maps for code from the crate.
     source_map_import_info: OnceCell<Vec<ImportedSourceFile>>,
     /// For every definition in this crate, maps its `DefPathHash` to its `DefIndex`.
@@ -750,19 +753,21 @@ pub(crate) fn decode_crate_deps<'a>(
 }
 
 impl<'a, 'tcx> CrateMetadataRef<'a> {
-    fn raw_proc_macro(self, id: DefIndex) -> &'a ProcMacro {
+    fn proc_macro_index(self, id: DefIndex) -> usize {
         // DefIndex's in root.proc_macro_data have a one-to-one correspondence
         // with items in 'raw_proc_macros'.
-        let pos = self
-            .root
+        self.root
             .proc_macro_data
             .as_ref()
             .unwrap()
             .macros
             .decode(self)
             .position(|i| i == id)
-            .unwrap();
-        &self.raw_proc_macros.unwrap()[pos]
+            .unwrap()
+    }
+
+    fn raw_proc_macro(self, id: DefIndex) -> &'a ProcMacro {
+        &self.raw_proc_macros.unwrap()[self.proc_macro_index(id)]
     }
 
     fn opt_item_name(self, item_index: DefIndex) -> Option<Symbol> {
@@ -825,36 +830,85 @@ fn get_span(self, index: DefIndex, sess: &Session) -> Span {
     }
 
     fn load_proc_macro(self, id: DefIndex, sess: &Session) -> SyntaxExtension {
-        let (name, kind, helper_attrs) = match *self.raw_proc_macro(id) {
-            ProcMacro::CustomDerive { trait_name, attributes, client } => {
-                let helper_attrs =
-                    attributes.iter().cloned().map(Symbol::intern).collect::<Vec<_>>();
-                (
-                    trait_name,
-                    SyntaxExtensionKind::Derive(Box::new(DeriveProcMacro { client })),
-                    helper_attrs,
-                )
-            }
-            ProcMacro::Attr { name, client } => {
-                (name, SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client })), Vec::new())
-            }
-            ProcMacro::Bang { name, client } => {
-                (name, SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client })), Vec::new())
-            }
+        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
+        let (name, kind, helper_attrs) = match &self.proc_macro_sandbox {
+            Some(sandbox) => self.load_sandboxed_proc_macro(id, sandbox, &attrs, sess),
+            None => match *self.raw_proc_macro(id) {
+                ProcMacro::CustomDerive { trait_name, attributes, client } => {
+                    let helper_attrs =
+                        attributes.iter().cloned().map(Symbol::intern).collect::<Vec<_>>();
+                    (
+                        Symbol::intern(trait_name),
+                        SyntaxExtensionKind::Derive(Box::new(DeriveProcMacro {
+                            client,
+                            sandbox: None,
+                        })),
+                        helper_attrs,
+                    )
+                }
+                ProcMacro::Attr { name, client } => (
+                    Symbol::intern(name),
+                    SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client, sandbox: None })),
+                    Vec::new(),
+                ),
+                ProcMacro::Bang { name, client } => (
+                    Symbol::intern(name),
+                    SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client, sandbox: None })),
+                    Vec::new(),
+                ),
+            },
         };
 
-        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
         SyntaxExtension::new(
             sess,
             kind,
             self.get_span(id, sess),
             helper_attrs,
             self.root.edition,
-            Symbol::intern(name),
+            name,
             &attrs,
         )
     }
 
+    /// Like the declarations in the dylib, which is not loaded with `-Z proc-macro-sandbox`,
+    /// the metadata has the name, kind and helper attributes of every proc macro.
+    fn load_sandboxed_proc_macro(
+        self,
+        id: DefIndex,
+        sandbox: &Lrc<ProcMacroSandbox>,
+        attrs: &[ast::Attribute],
+        sess: &Session,
+    ) -> (Symbol, SyntaxExtensionKind, Vec<Symbol>) {
+        let sandbox =
+            Some(SandboxedProcMacro { sandbox: sandbox.clone(), index: self.proc_macro_index(id) });
+        let client = proc_macro::bridge::client::Client::out_of_process();
+        let name = self.item_name(id);
+        match self.def_kind(id) {
+            DefKind::Macro(MacroKind::Derive) => {
+                let helper_attrs = sess
+                    .find_by_name(attrs, sym::proc_macro_derive)
+                    .and_then(|attr| {
+                        parse_macro_name_and_helper_attrs(sess.diagnostic(), attr, "derive")
+                    })
+                    .map_or_else(Vec::new, |(_, helper_attrs)| helper_attrs);
+                let kind =
+                    SyntaxExtensionKind::Derive(Box::new(DeriveProcMacro { client, sandbox }));
+                (name, kind, helper_attrs)
+            }
+            DefKind::Macro(MacroKind::Attr) => (
+                name,
+                SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client, sandbox })),
+                Vec::new(),
+            ),
+            DefKind::Macro(MacroKind::Bang) => (
+                name,
+                SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client, sandbox })),
+                Vec::new(),
+            ),
+            kind => bug!("unexpected kind {:?} of proc macro {:?}", kind, id),
+        }
+    }
+
     fn get_variant(self, kind: &EntryKind, index: DefIndex, parent_did: DefId) -> ty::VariantDef {
         let data = match kind {
             EntryKind::Variant(data) | EntryKind::Struct(data) | EntryKind::Union(data) => {
@@ -993,11 +1047,9 @@ fn for_each_module_child(
             // the view of this crate as a proc macro crate.
             if id == CRATE_DEF_INDEX {
                 for def_index in data.macros.decode(self) {
-                    let raw_macro = self.raw_proc_macro(def_index);
-                    let res = Res::Def(
-                        DefKind::Macro(macro_kind(raw_macro)),
-                        self.local_def_id(def_index),
-                    );
+                    // Unlike the declarations in the dylib, this is also available when the
+                    // macros run in a sandbox.
+                    let res = Res::Def(self.def_kind(def_index), self.local_def_id(def_index));
                     let ident = self.item_ident(def_index, sess);
                     callback(ModChild {
                         ident,
@@ -1409,6 +1461,10 @@ fn def_path_hash_to_def_index(self, hash: DefPathHash) -> DefIndex {
         self.def_path_hash_map.def_path_hash_to_def_index(&hash)
     }
 
+    fn try_def_path_hash_to_def_index(self, hash: DefPathHash) -> Option<DefIndex> {
+        self.def_path_hash_map.try_def_path_hash_to_def_index(&hash)
+    }
+
     fn expn_hash_to_expn_id(self, sess: &Session, index_guess: u32, hash: ExpnHash) -> ExpnId {
         debug_assert_eq!(ExpnId::from_hash(hash), None);
         let index_guess = ExpnIndex::from_u32(index_guess);
@@ -1675,6 +1731,7 @@ pub(crate) fn new(
         blob: MetadataBlob,
         root: CrateRoot,
         raw_proc_macros: Option<&'static [ProcMacro]>,
+        proc_macro_sandbox: Option<Lrc<ProcMacroSandbox>>,
         cnum: CrateNum,
         cnum_map: CrateNumMap,
         dep_kind: CrateDepKind,
@@ -1701,6 +1758,7 @@ pub(crate) fn new(
             trait_impls,
             incoherent_impls: Default::default(),
             raw_proc_macros,
+            proc_macro_sandbox,
             source_map_import_info: OnceCell::new(),
             def_path_hash_map,
             expn_hash_map: Default::default(),
@@ -1822,13 +1880,3 @@ fn reverse_translate_def_id(&self, did: DefId) -> Option<DefId> {
         None
     }
 }
-
-// Cannot be implemented on 'ProcMacro', as libproc_macro
-// does not depend on librustc_ast
-fn macro_kind(raw: &ProcMacro) -> MacroKind {
-    match raw {
-        ProcMacro::CustomDerive { .. } => MacroKind::Derive,
-        ProcMacro::Attr { .. } => MacroKind::Attr,
-        ProcMacro::Bang { .. } => MacroKind::Bang,
-    }
-}
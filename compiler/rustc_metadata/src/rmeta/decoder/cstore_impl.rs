@@ -652,6 +652,12 @@ fn def_path_hash_to_def_id(&self, cnum: CrateNum, hash: DefPathHash) -> DefId {
         DefId { krate: cnum, index: def_index }
     }
 
+    fn try_def_path_hash_to_def_id(&self, hash: DefPathHash) -> Option<DefId> {
+        let cnum = *self.stable_crate_ids.get(&hash.stable_crate_id())?;
+        let def_index = self.get_crate_data(cnum).try_def_path_hash_to_def_index(hash)?;
+        Some(DefId { krate: cnum, index: def_index })
+    }
+
     fn expn_hash_to_expn_id(
         &self,
         sess: &Session,
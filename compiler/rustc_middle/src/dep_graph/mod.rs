@@ -7,7 +7,7 @@
 mod dep_node;
 
 pub use rustc_query_system::dep_graph::{
-    debug::DepNodeFilter, hash_result, DepContext, DepNodeColor, DepNodeIndex,
+    debug::DepNodeFilter, hash_result, DepContext, DepNodeColor, DepNodeIndex, FingerprintStyle,
     SerializedDepNodeIndex, WorkProduct, WorkProductId,
 };
 
@@ -20,6 +20,7 @@
 pub type DepGraphQuery = rustc_query_system::dep_graph::DepGraphQuery<DepKind>;
 pub type SerializedDepGraph = rustc_query_system::dep_graph::SerializedDepGraph<DepKind>;
 pub type EdgeFilter = rustc_query_system::dep_graph::debug::EdgeFilter<DepKind>;
+pub type RecompilationChain = rustc_query_system::dep_graph::RecompilationChain<DepKind>;
 
 impl rustc_query_system::dep_graph::DepKind for DepKind {
     const NULL: Self = DepKind::Null;
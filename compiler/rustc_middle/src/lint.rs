@@ -3,7 +3,8 @@
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
 use rustc_errors::{
-    Diagnostic, DiagnosticBuilder, DiagnosticId, EmissionGuarantee, ErrorGuaranteed, MultiSpan,
+    Diagnostic, DiagnosticBuilder, DiagnosticId, EmissionGuarantee, ErrorGuaranteed,
+    LintLevelOrigin, MultiSpan,
 };
 use rustc_hir::HirId;
 use rustc_index::vec::IndexVec;
@@ -258,6 +259,7 @@ pub fn explain_lint_level_source(
     let name = lint.name_lower();
     match src {
         LintLevelSource::Default => {
+            err.set_lint_level_origin(LintLevelOrigin::Default);
             err.note_once(&format!("`#[{}({})]` on by default", level.as_str(), name));
         }
         LintLevelSource::CommandLine(lint_flag_val, orig_level) => {
@@ -272,13 +274,17 @@ pub fn explain_lint_level_source(
                 }
             };
             let hyphen_case_lint_name = name.replace('_', "-");
+            let hyphen_case_flag_val = lint_flag_val.as_str().replace('_', "-");
+            err.set_lint_level_origin(LintLevelOrigin::CommandLine(format!(
+                "{} {}",
+                flag, hyphen_case_flag_val
+            )));
             if lint_flag_val.as_str() == name {
                 err.note_once(&format!(
                     "requested on the command line with `{} {}`",
                     flag, hyphen_case_lint_name
                 ));
             } else {
-                let hyphen_case_flag_val = lint_flag_val.as_str().replace('_', "-");
                 err.note_once(&format!(
                     "`{} {}` implied by `{} {}`",
                     flag, hyphen_case_lint_name, flag, hyphen_case_flag_val
@@ -289,6 +295,7 @@ pub fn explain_lint_level_source(
             if let Some(rationale) = reason {
                 err.note(rationale.as_str());
             }
+            err.set_lint_level_origin(LintLevelOrigin::Attribute(src));
             err.span_note_once(src, "the lint level is defined here");
             if lint_attr_name.as_str() != name {
                 let level_str = level.as_str();
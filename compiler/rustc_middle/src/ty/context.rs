@@ -1,7 +1,7 @@
 //! Type context book-keeping.
 
 use crate::arena::Arena;
-use crate::dep_graph::{DepGraph, DepKind, DepKindStruct};
+use crate::dep_graph::{DepGraph, DepKind, DepKindStruct, SerializedDepNodeIndex};
 use crate::hir::place::Place as HirPlace;
 use crate::infer::canonical::{Canonical, CanonicalVarInfo, CanonicalVarInfos};
 use crate::lint::{struct_lint_level, LintDiagnosticBuilder, LintLevelSource};
@@ -89,6 +89,10 @@ fn new_empty(source_map: &'tcx SourceMap) -> Self
     fn drop_serialized_data(&self, tcx: TyCtxt<'tcx>);
 
     fn serialize(&self, tcx: TyCtxt<'tcx>, encoder: FileEncoder) -> FileEncodeResult;
+
+    /// Returns the dep-nodes of the previous session whose query results are stored in this
+    /// cache. Used by `-Z dump-incr-cache`.
+    fn cached_query_results(&self) -> Vec<SerializedDepNodeIndex>;
 }
 
 #[allow(rustc::usage_of_ty_tykind)]
@@ -1438,6 +1442,18 @@ pub fn def_path_hash_to_def_id(self, hash: DefPathHash, err: &mut dyn FnMut() ->
         }
     }
 
+    /// Like `def_path_hash_to_def_id`, but returns `None` for the hashes of items which don't
+    /// exist in this session, e.g. because they were removed since the previous incremental
+    /// session.
+    pub fn try_def_path_hash_to_def_id(self, hash: DefPathHash) -> Option<DefId> {
+        if hash.stable_crate_id() == self.sess.local_stable_crate_id() {
+            let index = self.definitions.def_path_hash_to_def_index_map().get(&hash)?;
+            Some(DefId { krate: LOCAL_CRATE, index })
+        } else {
+            self.cstore.try_def_path_hash_to_def_id(hash)
+        }
+    }
+
     pub fn def_path_debug_str(self, def_id: DefId) -> String {
         // We are explicitly not going through queries here in order to get
         // crate name and stable crate id since this code is called from debug!()
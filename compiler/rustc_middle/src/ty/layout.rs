@@ -1,16 +1,16 @@
 use crate::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use crate::mir::{GeneratorLayout, GeneratorSavedLocal};
 use crate::ty::normalize_erasing_regions::NormalizationError;
-use crate::ty::subst::Subst;
+use crate::ty::subst::{GenericArgKind, Subst};
 use crate::ty::{self, subst::SubstsRef, EarlyBinder, ReprOptions, Ty, TyCtxt, TypeFoldable};
 use rustc_ast as ast;
 use rustc_attr as attr;
 use rustc_hir as hir;
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_hir::lang_items::LangItem;
 use rustc_index::bit_set::BitSet;
 use rustc_index::vec::{Idx, IndexVec};
-use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use rustc_span::symbol::Symbol;
 use rustc_span::{Span, DUMMY_SP};
 use rustc_target::abi::call::{
@@ -1914,13 +1914,39 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
         // (delay format until we actually need it)
         let record = |kind, packed, opt_discr_size, variants| {
             let type_desc = format!("{:?}", layout.ty);
+            // Types of the local crate are printed without the crate name, add it so that
+            // `-Z print-type-sizes-filter` names them like the types of other crates.
+            let type_path = match *layout.ty.kind() {
+                ty::Adt(adt_def, _) if adt_def.did().is_local() => {
+                    format!("{}::{}", self.tcx.crate_name(LOCAL_CRATE), type_desc)
+                }
+                _ => type_desc.clone(),
+            };
+            let generic_args = match *layout.ty.kind() {
+                ty::Adt(_, substs) => substs
+                    .iter()
+                    .filter(|arg| !matches!(arg.unpack(), GenericArgKind::Lifetime(_)))
+                    .map(|arg| format!("{:?}", arg))
+                    .collect(),
+                _ => vec![],
+            };
+            let niche = layout.largest_niche.map(|niche| NicheInfo {
+                offset: niche.offset.bytes(),
+                size: niche.value.size(self).bytes(),
+                valid_range_start: niche.valid_range.start,
+                valid_range_end: niche.valid_range.end,
+                available: niche.available(self),
+            });
             self.tcx.sess.code_stats.record_type_size(
                 kind,
                 type_desc,
+                type_path,
+                generic_args,
                 layout.align.abi,
                 layout.size,
                 packed,
                 opt_discr_size,
+                niche,
                 variants,
             );
         };
@@ -321,6 +321,21 @@ pub fn iter_accesses<F>(&self, mut f: F)
             f(accessor, &self.targets[range.clone()])
         }
     }
+
+    // Internally iterate over all accesses, in the order in which the
+    // collector found them.
+    fn iter_accesses_in_collection_order<F>(&self, mut f: F)
+    where
+        F: FnMut(MonoItem<'tcx>, MonoItem<'tcx>),
+    {
+        let mut ranges: Vec<_> = self.index.iter().collect();
+        ranges.sort_by_key(|(_, range)| range.start);
+        for (&accessor, range) in ranges {
+            for &target in &self.targets[range.clone()] {
+                f(accessor, target);
+            }
+        }
+    }
 }
 
 #[instrument(skip(tcx, mode), level = "debug")]
@@ -361,6 +376,81 @@ pub fn collect_crate_mono_items(
     (visited.into_inner(), inlining_map.into_inner())
 }
 
+/// The instantiations of one generic definition, for `-Z print-mono-item-stats`.
+struct DefinitionStats<'tcx> {
+    name: String,
+    copies: usize,
+    /// The sum of the estimated sizes of all copies, in MIR statements.
+    size_estimate: usize,
+    /// The first mono item the collector found to reference one of the copies.
+    first_caller: Option<MonoItem<'tcx>>,
+}
+
+/// Prints, for every generic definition, how many copies of it the collector
+/// instantiated and how large they are estimated to be, heaviest first. This
+/// is meant to find the generic code that bloats a binary; it only looks at
+/// the MIR, so it cannot see how much LLVM inlines or optimizes away.
+pub fn print_mono_item_stats<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    inlining_map: &InliningMap<'tcx>,
+) {
+    let mut stats: FxHashMap<DefId, DefinitionStats<'tcx>> = FxHashMap::default();
+    for item in items {
+        let MonoItem::Fn(instance) = *item else { continue };
+        if !item.is_generic_fn() {
+            continue;
+        }
+        let def_id = instance.def_id();
+        let stat = stats.entry(def_id).or_insert_with(|| DefinitionStats {
+            name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+            copies: 0,
+            size_estimate: 0,
+            first_caller: None,
+        });
+        stat.copies += 1;
+        stat.size_estimate += item.size_estimate(tcx);
+    }
+
+    inlining_map.iter_accesses_in_collection_order(|caller, target| {
+        if let MonoItem::Fn(instance) = target {
+            if let Some(stat) = stats.get_mut(&instance.def_id()) {
+                stat.first_caller.get_or_insert(caller);
+            }
+        }
+    });
+
+    let mut stats: Vec<_> = stats.into_values().collect();
+    stats.sort_by(|a, b| b.size_estimate.cmp(&a.size_estimate).then_with(|| a.name.cmp(&b.name)));
+
+    println!(
+        "print-mono-item-stats: {} instantiations of {} generic definitions, estimated size: {}",
+        stats.iter().map(|stat| stat.copies).sum::<usize>(),
+        stats.len(),
+        stats.iter().map(|stat| stat.size_estimate).sum::<usize>()
+    );
+    println!(
+        "print-mono-item-stats: {:>10} {:>8} {:>10}  definition",
+        "total size", "copies", "size/copy"
+    );
+    for stat in stats {
+        let first_caller = match stat.first_caller {
+            Some(caller) => {
+                format!(" (first instantiated by `{}`)", with_no_trimmed_paths!(caller.to_string()))
+            }
+            None => String::new(),
+        };
+        println!(
+            "print-mono-item-stats: {:>10} {:>8} {:>10}  `{}`{}",
+            stat.size_estimate,
+            stat.copies,
+            stat.size_estimate / stat.copies,
+            stat.name,
+            first_caller
+        );
+    }
+}
+
 // Find all non-generic items by walking the HIR. These items serve as roots to
 // start monomorphizing from.
 #[instrument(skip(tcx, mode), level = "debug")]
@@ -0,0 +1,195 @@
+//! The `affinity` partitioning strategy.
+//!
+//! LLVM can only inline a function into its callers if they end up in the same
+//! codegen unit. The default strategy places every instantiation of a generic
+//! function next to its definition, which is usually far away from the code
+//! that uses it. This strategy instead moves each generic instantiation into
+//! the codegen unit that calls it most, and, when merging codegen units,
+//! merges each one into the codegen unit it shares the most calls with rather
+//! than into the smallest one.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::mir::mono::{CodegenUnit, MonoItem};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_span::symbol::Symbol;
+
+use super::PartitioningCx;
+use crate::collector::InliningMap;
+use crate::partitioning::default::DefaultPartitioning;
+use crate::partitioning::merging;
+use crate::partitioning::{Partitioner, PostInliningPartitioning, PreInliningPartitioning};
+
+pub struct AffinityPartitioning;
+
+impl<'tcx> Partitioner<'tcx> for AffinityPartitioning {
+    fn place_root_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        mono_items: &mut dyn Iterator<Item = MonoItem<'tcx>>,
+    ) -> PreInliningPartitioning<'tcx> {
+        // Start out with the default placement, which also computes the
+        // linkage and visibility of every item, and then move the generic
+        // instantiations around.
+        let mut partitioning = DefaultPartitioning.place_root_mono_items(cx, mono_items);
+        let codegen_units = &mut partitioning.codegen_units;
+
+        // Sort the codegen units by name so that ties below are broken
+        // deterministically.
+        codegen_units.sort_by(|a, b| a.name().as_str().cmp(b.name().as_str()));
+
+        let mut placements: FxHashMap<MonoItem<'tcx>, usize> = FxHashMap::default();
+        let mut unplaced = Vec::new();
+        for (index, cgu) in codegen_units.iter().enumerate() {
+            for &mono_item in cgu.items().keys() {
+                if mono_item.is_generic_fn() {
+                    unplaced.push((mono_item, index));
+                } else {
+                    placements.insert(mono_item, index);
+                }
+            }
+        }
+        unplaced.sort_by_cached_key(|(mono_item, _)| with_no_trimmed_paths!(mono_item.to_string()));
+
+        let accessors = accessor_map(cx.inlining_map);
+
+        // Generic instantiations may be called by other generic instantiations,
+        // so keep going until no instantiation is called by an item that has
+        // already been placed.
+        loop {
+            let mut made_progress = false;
+            unplaced.retain(|&(mono_item, old_index)| {
+                let mut calls_per_cgu: FxHashMap<usize, usize> = FxHashMap::default();
+                for accessor in accessors.get(&mono_item).into_iter().flatten() {
+                    if let Some(&index) = placements.get(accessor) {
+                        *calls_per_cgu.entry(index).or_default() += 1;
+                    }
+                }
+
+                // Prefer the codegen unit with the most calls, and the first
+                // one by name among those.
+                let Some((new_index, _)) = calls_per_cgu
+                    .into_iter()
+                    .max_by_key(|&(index, calls)| (calls, std::cmp::Reverse(index)))
+                else {
+                    return true;
+                };
+
+                if new_index != old_index {
+                    let linkage = codegen_units[old_index].items_mut().remove(&mono_item).unwrap();
+                    codegen_units[new_index].items_mut().insert(mono_item, linkage);
+                }
+                placements.insert(mono_item, new_index);
+                made_progress = true;
+                false
+            });
+
+            if !made_progress {
+                break;
+            }
+        }
+
+        // Instantiations that are not called by anything else, or only by
+        // items that are not roots, stay where the default strategy put them.
+        // Drop the codegen units that were emptied above. If there is more
+        // than one codegen unit, at least one of them contains an item.
+        if codegen_units.len() > 1 {
+            codegen_units.retain(|cgu| !cgu.items().is_empty());
+        }
+
+        partitioning
+    }
+
+    fn merge_codegen_units(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+    ) {
+        let neighbors = neighbor_map(cx.inlining_map);
+        let mut placements: FxHashMap<MonoItem<'tcx>, Symbol> = initial_partitioning
+            .codegen_units
+            .iter()
+            .flat_map(|cgu| cgu.items().keys().map(|&mono_item| (mono_item, cgu.name())))
+            .collect();
+
+        merging::merge_codegen_units_into(
+            cx,
+            initial_partitioning,
+            |codegen_units: &[CodegenUnit<'tcx>], smallest: &CodegenUnit<'tcx>| {
+                let mut calls_per_cgu: FxHashMap<Symbol, usize> = FxHashMap::default();
+                for mono_item in smallest.items().keys() {
+                    for neighbor in neighbors.get(mono_item).into_iter().flatten() {
+                        match placements.get(neighbor) {
+                            Some(&name) if name != smallest.name() => {
+                                *calls_per_cgu.entry(name).or_default() += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                // Merge into the codegen unit with the most calls from or to
+                // the smallest one, preferring smaller codegen units on ties.
+                // Without any calls, fall back to the second smallest one like
+                // the default strategy.
+                let target_index = codegen_units
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, cgu)| {
+                        calls_per_cgu.get(&cgu.name()).map(|&calls| (calls, index))
+                    })
+                    .max()
+                    .map_or(codegen_units.len() - 1, |(_, index)| index);
+
+                let target_name = codegen_units[target_index].name();
+                for mono_item in smallest.items().keys() {
+                    placements.insert(*mono_item, target_name);
+                }
+                target_index
+            },
+        );
+    }
+
+    fn place_inlined_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        initial_partitioning: PreInliningPartitioning<'tcx>,
+    ) -> PostInliningPartitioning<'tcx> {
+        DefaultPartitioning.place_inlined_mono_items(cx, initial_partitioning)
+    }
+
+    fn internalize_symbols(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        partitioning: &mut PostInliningPartitioning<'tcx>,
+    ) {
+        DefaultPartitioning.internalize_symbols(cx, partitioning)
+    }
+}
+
+/// Maps every mono item to all the mono items that reference it.
+fn accessor_map<'tcx>(
+    inlining_map: &InliningMap<'tcx>,
+) -> FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>> {
+    let mut accessor_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>> = Default::default();
+    inlining_map.iter_accesses(|accessor, accessees| {
+        for accessee in accessees {
+            accessor_map.entry(*accessee).or_default().push(accessor);
+        }
+    });
+    accessor_map
+}
+
+/// Maps every mono item to all the mono items it references or that reference
+/// it.
+fn neighbor_map<'tcx>(
+    inlining_map: &InliningMap<'tcx>,
+) -> FxHashMap<MonoItem<'tcx>, FxHashSet<MonoItem<'tcx>>> {
+    let mut neighbor_map: FxHashMap<MonoItem<'tcx>, FxHashSet<MonoItem<'tcx>>> = Default::default();
+    inlining_map.iter_accesses(|accessor, accessees| {
+        for accessee in accessees {
+            neighbor_map.entry(accessor).or_default().insert(*accessee);
+            neighbor_map.entry(*accessee).or_default().insert(accessor);
+        }
+    });
+    neighbor_map
+}
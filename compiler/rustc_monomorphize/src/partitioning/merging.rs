@@ -11,6 +11,20 @@
 pub fn merge_codegen_units<'tcx>(
     cx: &PartitioningCx<'_, 'tcx>,
     initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+) {
+    // Merge the smallest codegen unit into the second smallest one.
+    merge_codegen_units_into(cx, initial_partitioning, |codegen_units, _| codegen_units.len() - 1)
+}
+
+/// Merges the smallest codegen unit into another one until the target number
+/// of codegen units is reached. `merge_target` is given the remaining codegen
+/// units, sorted from largest to smallest, and the smallest one, which has
+/// been removed from them, and returns the index of the codegen unit to merge
+/// it into.
+pub fn merge_codegen_units_into<'tcx>(
+    cx: &PartitioningCx<'_, 'tcx>,
+    initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+    mut merge_target: impl FnMut(&[CodegenUnit<'tcx>], &CodegenUnit<'tcx>) -> usize,
 ) {
     assert!(cx.target_cgu_count >= 1);
     let codegen_units = &mut initial_partitioning.codegen_units;
@@ -30,29 +44,27 @@ pub fn merge_codegen_units<'tcx>(
     let mut cgu_contents: FxHashMap<Symbol, Vec<Symbol>> =
         codegen_units.iter().map(|cgu| (cgu.name(), vec![cgu.name()])).collect();
 
-    // Merge the two smallest codegen units until the target size is reached.
+    // Merge the smallest codegen unit into another one until the target size
+    // is reached.
     while codegen_units.len() > cx.target_cgu_count {
         // Sort small cgus to the back
         codegen_units.sort_by_cached_key(|cgu| cmp::Reverse(cgu.size_estimate()));
         let mut smallest = codegen_units.pop().unwrap();
-        let second_smallest = codegen_units.last_mut().unwrap();
+        let target_index = merge_target(codegen_units, &smallest);
+        let target = &mut codegen_units[target_index];
 
-        // Move the mono-items from `smallest` to `second_smallest`
-        second_smallest.modify_size_estimate(smallest.size_estimate());
+        // Move the mono-items from `smallest` to `target`
+        target.modify_size_estimate(smallest.size_estimate());
         for (k, v) in smallest.items_mut().drain() {
-            second_smallest.items_mut().insert(k, v);
+            target.items_mut().insert(k, v);
         }
 
-        // Record that `second_smallest` now contains all the stuff that was in
+        // Record that `target` now contains all the stuff that was in
         // `smallest` before.
         let mut consumed_cgu_names = cgu_contents.remove(&smallest.name()).unwrap();
-        cgu_contents.get_mut(&second_smallest.name()).unwrap().append(&mut consumed_cgu_names);
+        cgu_contents.get_mut(&target.name()).unwrap().append(&mut consumed_cgu_names);
 
-        debug!(
-            "CodegenUnit {} merged into CodegenUnit {}",
-            smallest.name(),
-            second_smallest.name()
-        );
+        debug!("CodegenUnit {} merged into CodegenUnit {}", smallest.name(), target.name());
     }
 
     let cgu_name_builder = &mut CodegenUnitNameBuilder::new(cx.tcx);
@@ -91,9 +91,24 @@
 //! Note though that as a side-effect of creating a codegen units per
 //! source-level module, functions from the same module will be available for
 //! inlining, even when they are not marked `#[inline]`.
+//!
+//! Partitioning Strategies
+//! -----------------------
+//! The scheme described above is the `default` strategy. Two alternatives can
+//! be selected with `-Z cgu-partitioning-strategy`:
+//!
+//! - `affinity` moves every generic instantiation into the codegen unit that
+//!   calls it most, and merges codegen units along the calls between them, so
+//!   that LLVM can inline more.
+//!
+//! - `stable` distributes all items over a fixed number of codegen units by
+//!   hashing their names, so that edits move as few items as possible between
+//!   codegen units.
 
+mod affinity;
 mod default;
 mod merging;
+mod stable;
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync;
@@ -148,6 +163,8 @@ fn get_partitioner<'tcx>(tcx: TyCtxt<'tcx>) -> Box<dyn Partitioner<'tcx>> {
 
     match strategy {
         "default" => Box::new(default::DefaultPartitioning),
+        "affinity" => Box::new(affinity::AffinityPartitioning),
+        "stable" => Box::new(stable::StablePartitioning),
         _ => tcx.sess.fatal("unknown partitioning strategy"),
     }
 }
@@ -377,6 +394,10 @@ fn collect_and_partition_mono_items<'tcx>(
 
     tcx.sess.abort_if_errors();
 
+    if tcx.sess.opts.debugging_opts.print_mono_item_stats {
+        collector::print_mono_item_stats(tcx, &items, &inlining_map);
+    }
+
     let (codegen_units, _) = tcx.sess.time("partition_and_assert_distinct_symbols", || {
         sync::join(
             || {
@@ -0,0 +1,95 @@
+//! The `stable` partitioning strategy.
+//!
+//! With incremental compilation, a codegen unit can only be reused if none of
+//! the items in it changed. The default strategy derives codegen units from
+//! modules and then merges the smallest ones, so an edit that changes the size
+//! of a module can reshuffle the contents of many codegen units. This strategy
+//! instead puts every item into one of a fixed number of buckets, chosen by
+//! hashing the item's name, so an item only moves to another codegen unit if it
+//! is renamed or the number of codegen units changes.
+
+use std::hash::Hash;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::mir::mono::{CodegenUnit, CodegenUnitNameBuilder, MonoItem};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_span::symbol::Symbol;
+
+use super::PartitioningCx;
+use crate::partitioning::default::DefaultPartitioning;
+use crate::partitioning::{Partitioner, PostInliningPartitioning, PreInliningPartitioning};
+
+pub struct StablePartitioning;
+
+impl<'tcx> Partitioner<'tcx> for StablePartitioning {
+    fn place_root_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        mono_items: &mut dyn Iterator<Item = MonoItem<'tcx>>,
+    ) -> PreInliningPartitioning<'tcx> {
+        // The default placement computes the linkage and visibility of every
+        // item, only the codegen units it chose are thrown away.
+        let mut partitioning = DefaultPartitioning.place_root_mono_items(cx, mono_items);
+
+        let cgu_name_builder = &mut CodegenUnitNameBuilder::new(cx.tcx);
+        let mut buckets: FxHashMap<u64, CodegenUnit<'tcx>> = FxHashMap::default();
+        for mut cgu in partitioning.codegen_units.drain(..) {
+            for (mono_item, linkage) in cgu.items_mut().drain() {
+                let bucket = bucket_of_mono_item(cx, mono_item);
+                buckets
+                    .entry(bucket)
+                    .or_insert_with(|| CodegenUnit::new(bucket_cgu_name(cgu_name_builder, bucket)))
+                    .items_mut()
+                    .insert(mono_item, linkage);
+            }
+        }
+
+        // Always ensure we have at least one CGU; otherwise, if we have a
+        // crate with just types (for example), we could wind up with no CGU.
+        if buckets.is_empty() {
+            buckets.insert(0, CodegenUnit::new(bucket_cgu_name(cgu_name_builder, 0)));
+        }
+
+        partitioning.codegen_units = buckets.into_values().collect();
+        partitioning
+    }
+
+    fn merge_codegen_units(
+        &mut self,
+        _cx: &PartitioningCx<'_, 'tcx>,
+        _initial_partitioning: &mut PreInliningPartitioning<'tcx>,
+    ) {
+        // There are never more buckets than the target number of codegen
+        // units, and merging them would defeat the purpose of this strategy.
+    }
+
+    fn place_inlined_mono_items(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        initial_partitioning: PreInliningPartitioning<'tcx>,
+    ) -> PostInliningPartitioning<'tcx> {
+        DefaultPartitioning.place_inlined_mono_items(cx, initial_partitioning)
+    }
+
+    fn internalize_symbols(
+        &mut self,
+        cx: &PartitioningCx<'_, 'tcx>,
+        partitioning: &mut PostInliningPartitioning<'tcx>,
+    ) {
+        DefaultPartitioning.internalize_symbols(cx, partitioning)
+    }
+}
+
+fn bucket_of_mono_item<'tcx>(cx: &PartitioningCx<'_, 'tcx>, mono_item: MonoItem<'tcx>) -> u64 {
+    // The name of an item does not depend on anything else in the crate,
+    // unlike, for example, its symbol name, which includes the crate's hash.
+    let mut hasher = StableHasher::new();
+    with_no_trimmed_paths!(mono_item.to_string()).hash(&mut hasher);
+    hasher.finish::<u64>() % cx.target_cgu_count as u64
+}
+
+fn bucket_cgu_name(name_builder: &mut CodegenUnitNameBuilder<'_>, bucket: u64) -> Symbol {
+    name_builder.build_cgu_name(LOCAL_CRATE, &["bucket"], Some(bucket))
+}
@@ -2374,6 +2374,8 @@ fn check_invalid_crate_level_attr(tcx: TyCtxt<'_>, attrs: &[Attribute]) {
         sym::derive,
         sym::test,
         sym::test_case,
+        sym::test_setup,
+        sym::test_teardown,
         sym::global_allocator,
         sym::bench,
     ];
@@ -155,7 +155,9 @@ fn new(tcx: TyCtxt<'_>, file: &SourceFile) -> EncodedSourceFileId {
 impl<'sess> rustc_middle::ty::OnDiskCache<'sess> for OnDiskCache<'sess> {
     /// Creates a new `OnDiskCache` instance from the serialized data in `data`.
     fn new(sess: &'sess Session, data: Mmap, start_pos: usize) -> Self {
-        debug_assert!(sess.opts.incremental.is_some());
+        debug_assert!(
+            sess.opts.incremental.is_some() || sess.opts.debugging_opts.dump_incr_cache.is_some()
+        );
 
         // Wrap in a scope so we can borrow `data`.
         let footer: Footer = {
@@ -352,6 +354,10 @@ fn serialize<'tcx>(&self, tcx: TyCtxt<'tcx>, encoder: FileEncoder) -> FileEncode
             encoder.finish()
         })
     }
+
+    fn cached_query_results(&self) -> Vec<SerializedDepNodeIndex> {
+        self.query_result_index.keys().copied().collect()
+    }
 }
 
 impl<'sess> OnDiskCache<'sess> {
@@ -76,14 +76,9 @@ pub fn construct<Ctxt, Key>(tcx: Ctxt, kind: K, arg: &Key) -> DepNode<K>
         let hash = arg.to_fingerprint(tcx);
         let dep_node = DepNode { kind, hash: hash.into() };
 
-        #[cfg(debug_assertions)]
-        {
-            if !tcx.fingerprint_style(kind).reconstructible()
-                && (tcx.sess().opts.debugging_opts.incremental_info
-                    || tcx.sess().opts.debugging_opts.query_dep_graph)
-            {
-                tcx.dep_graph().register_dep_node_debug_str(dep_node, || arg.to_debug_str(tcx));
-            }
+        // Nodes such as codegen units can only be named through the string registered here.
+        if tcx.dep_graph().records_debug_strs() && !tcx.fingerprint_style(kind).reconstructible() {
+            tcx.dep_graph().register_dep_node_debug_str(dep_node, || arg.to_debug_str(tcx));
         }
 
         dep_node
@@ -90,10 +90,18 @@ struct DepGraphData<K: DepKind> {
 
     dep_node_debug: Lock<FxHashMap<DepNode<K>, String>>,
 
+    /// Whether `DepNode::construct` fills `dep_node_debug`. This is computed once from the
+    /// session options because constructing dep-nodes is a hot path.
+    record_debug_strs: bool,
+
     /// Used by incremental compilation tests to assert that
     /// a particular query result was decoded from disk
     /// (not just marked green)
     debug_loaded_from_disk: Lock<FxHashSet<DepNode<K>>>,
+
+    /// For `-Z explain-recompilation`: maps each node of the previous graph that
+    /// could not be marked green to the dependency that prevented it.
+    recompilation_causes: Option<Lock<FxHashMap<SerializedDepNodeIndex, SerializedDepNodeIndex>>>,
 }
 
 pub fn hash_result<R>(hcx: &mut StableHashingContext<'_>, result: &R) -> Fingerprint
@@ -113,6 +121,7 @@ pub fn new(
         encoder: FileEncoder,
         record_graph: bool,
         record_stats: bool,
+        record_recompilation_causes: bool,
     ) -> DepGraph<K> {
         let prev_graph_node_count = prev_graph.node_count();
 
@@ -133,15 +142,21 @@ pub fn new(
         );
         debug_assert_eq!(_green_node_index, DepNodeIndex::SINGLETON_DEPENDENCYLESS_ANON_NODE);
 
+        // `-Z explain-recompilation` needs to name nodes in release builds too.
+        let record_debug_strs =
+            cfg!(debug_assertions) && (record_graph || record_stats) || record_recompilation_causes;
+
         DepGraph {
             data: Some(Lrc::new(DepGraphData {
                 previous_work_products: prev_work_products,
                 dep_node_debug: Default::default(),
+                record_debug_strs,
                 current,
                 processed_side_effects: Default::default(),
                 previous: prev_graph,
                 colors: DepNodeColorMap::new(prev_graph_node_count),
                 debug_loaded_from_disk: Default::default(),
+                recompilation_causes: record_recompilation_causes.then(Default::default),
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
         }
@@ -521,6 +536,13 @@ pub fn debug_was_loaded_from_disk(&self, dep_node: DepNode<K>) -> bool {
         self.data.as_ref().unwrap().debug_loaded_from_disk.lock().contains(&dep_node)
     }
 
+    /// Returns `true` if debug strings should be registered for dep-nodes whose key can't be
+    /// reconstructed from their hash.
+    #[inline]
+    pub fn records_debug_strs(&self) -> bool {
+        self.data.as_ref().map_or(false, |data| data.record_debug_strs)
+    }
+
     #[inline(always)]
     pub fn register_dep_node_debug_str<F>(&self, dep_node: DepNode<K>, debug_str_gen: F)
     where
@@ -719,7 +741,12 @@ fn try_mark_previous_green<Ctxt: QueryContext<DepKind = K>>(
         let prev_deps = data.previous.edge_targets_from(prev_dep_node_index);
 
         for &dep_dep_node_index in prev_deps {
-            self.try_mark_parent_green(tcx, data, dep_dep_node_index, dep_node)?
+            if self.try_mark_parent_green(tcx, data, dep_dep_node_index, dep_node).is_none() {
+                if let Some(causes) = &data.recompilation_causes {
+                    causes.lock().insert(prev_dep_node_index, dep_dep_node_index);
+                }
+                return None;
+            }
         }
 
         // If we got here without hitting a `return` that means that all
@@ -829,6 +856,32 @@ pub fn exec_cache_promotions<Ctxt: DepContext<DepKind = K>>(&self, tcx: Ctxt) {
         }
     }
 
+    /// Returns a chain for every node of the previous session that could not be marked green,
+    /// leading from the changed node that was ultimately responsible to the node itself. Only
+    /// available with `-Z explain-recompilation`.
+    pub fn recompilation_chains(&self) -> Vec<RecompilationChain<K>> {
+        let Some(data) = &self.data else { return Vec::new() };
+        let Some(causes) = &data.recompilation_causes else { return Vec::new() };
+        let causes = causes.lock();
+
+        let mut recompiled: Vec<_> = causes.keys().copied().collect();
+        recompiled.sort_unstable();
+        recompiled
+            .into_iter()
+            .map(|prev_index| {
+                let mut nodes = vec![data.previous.index_to_node(prev_index)];
+                let mut input = prev_index;
+                while let Some(&cause) = causes.get(&input) {
+                    nodes.push(data.previous.index_to_node(cause));
+                    input = cause;
+                }
+                nodes.reverse();
+                let input_changed = data.colors.get(input) == Some(DepNodeColor::Red);
+                RecompilationChain { nodes, input_changed }
+            })
+            .collect()
+    }
+
     pub fn print_incremental_info(&self) {
         if let Some(data) = &self.data {
             data.current.encoder.borrow().print_incremental_info(
@@ -852,6 +905,19 @@ pub(crate) fn next_virtual_depnode_index(&self) -> DepNodeIndex {
     }
 }
 
+/// Explains why a node of the previous session could not be marked green and had to be
+/// re-executed. See [`DepGraph::recompilation_chains`].
+#[derive(Clone, Debug)]
+pub struct RecompilationChain<K: DepKind> {
+    /// Starts with the input that changed, followed by the nodes that depend on it, and ends with
+    /// the node that was re-executed. Each node could not be marked green because of the one
+    /// before it.
+    pub nodes: Vec<DepNode<K>>,
+    /// Whether the input was recomputed and found to have changed. Otherwise it could not be
+    /// recomputed at all, for example because the item it refers to no longer exists.
+    pub input_changed: bool,
+}
+
 /// A "work product" is an intermediate result that we save into the
 /// incremental directory for later re-use. The primary example are
 /// the object files that we save for each partition at code
@@ -94,6 +94,14 @@ pub fn fingerprint_by_index(&self, dep_node_index: SerializedDepNodeIndex) -> Fi
     pub fn node_count(&self) -> usize {
         self.index.len()
     }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_list_data.len()
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = SerializedDepNodeIndex> {
+        self.nodes.indices()
+    }
 }
 
 impl<'a, K: DepKind + Decodable<MemDecoder<'a>>> Decodable<MemDecoder<'a>>
@@ -1,9 +1,11 @@
+use crate::config::PrintTypeSizesFormat;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::Lock;
 use rustc_target::abi::{Align, Size};
+use serde::Serialize;
 use std::cmp::{self, Ordering};
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
 pub struct VariantInfo {
     pub name: Option<String>,
     pub kind: SizeKind,
@@ -12,13 +14,14 @@ pub struct VariantInfo {
     pub fields: Vec<FieldInfo>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SizeKind {
     Exact,
     Min,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
 pub struct FieldInfo {
     pub name: String,
     pub offset: u64,
@@ -26,7 +29,8 @@ pub struct FieldInfo {
     pub align: u64,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DataTypeKind {
     Struct,
     Union,
@@ -34,17 +38,65 @@ pub enum DataTypeKind {
     Closure,
 }
 
+/// The largest niche of a type: a scalar whose invalid values can be used to encode the
+/// discriminant of an enum containing the type.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// The valid values of the scalar, a range that may wrap around.
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+    /// The number of invalid values.
+    pub available: u128,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TypeSizeInfo {
     pub kind: DataTypeKind,
     pub type_description: String,
+    /// The description qualified with the crate name, also for types of the local crate.
+    pub type_path: String,
+    /// The generic arguments the type was instantiated with, lifetimes excluded.
+    pub generic_args: Vec<String>,
     pub align: u64,
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
+/// The JSON form of a [`TypeSizeInfo`], with the padding computed by the text output spelled
+/// out as well.
+#[derive(Serialize)]
+struct TypeLayout<'a> {
+    #[serde(rename = "type")]
+    ty: &'a str,
+    kind: DataTypeKind,
+    generic_args: &'a [String],
+    size: u64,
+    align: u64,
+    packed: bool,
+    discriminant_size: Option<u64>,
+    niche: Option<&'a NicheInfo>,
+    variants: Vec<VariantLayout<'a>>,
+    /// Padding after the largest variant.
+    end_padding: u64,
+}
+
+#[derive(Serialize)]
+struct VariantLayout<'a> {
+    name: Option<&'a str>,
+    size_kind: SizeKind,
+    size: u64,
+    align: u64,
+    /// Padding between the discriminant and the fields, and between the fields.
+    padding: u64,
+    /// In declaration order.
+    fields: &'a [FieldInfo],
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
@@ -55,10 +107,13 @@ pub fn record_type_size<S: ToString>(
         &self,
         kind: DataTypeKind,
         type_desc: S,
+        type_path: String,
+        generic_args: Vec<String>,
         align: Align,
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
+        niche: Option<NicheInfo>,
         mut variants: Vec<VariantInfo>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
@@ -68,18 +123,34 @@ pub fn record_type_size<S: ToString>(
         let info = TypeSizeInfo {
             kind,
             type_description: type_desc.to_string(),
+            type_path,
+            generic_args,
             align: align.bytes(),
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
     }
 
-    pub fn print_type_sizes(&self) {
+    /// Prints the recorded layouts of all types whose crate-qualified path starts with `filter`.
+    pub fn print_type_sizes(&self, format: PrintTypeSizesFormat, filter: Option<&str>) {
         let type_sizes = self.type_sizes.borrow();
-        let mut sorted: Vec<_> = type_sizes.iter().collect();
+        let mut sorted: Vec<_> = type_sizes
+            .iter()
+            .filter(|info| filter.map_or(true, |prefix| info.type_path.starts_with(prefix)))
+            .collect();
+
+        if format == PrintTypeSizesFormat::Json {
+            // Sort by description only, so that the output of two compilations can be diffed.
+            sorted.sort_by(|info1, info2| info1.type_description.cmp(&info2.type_description));
+            for info in sorted {
+                println!("{}", serde_json::to_string(&info.to_layout()).unwrap());
+            }
+            return;
+        }
 
         // Primary sort: large-to-small.
         // Secondary sort: description (dictionary order)
@@ -179,3 +250,52 @@ pub fn print_type_sizes(&self) {
         }
     }
 }
+
+impl TypeSizeInfo {
+    fn to_layout(&self) -> TypeLayout<'_> {
+        let discr_size = self.opt_discr_size.unwrap_or(0);
+        let mut max_variant_size = discr_size;
+        let variants = self
+            .variants
+            .iter()
+            .map(|variant| {
+                max_variant_size = cmp::max(max_variant_size, variant.size);
+
+                // Same as the text output: walk the fields by increasing offset and add up the
+                // gaps between them.
+                let mut fields: Vec<_> = variant.fields.iter().collect();
+                fields.sort_by_key(|f| (f.offset, f.size));
+                let mut min_offset = discr_size;
+                let mut padding = 0;
+                for field in fields {
+                    if field.offset > min_offset {
+                        padding += field.offset - min_offset;
+                    }
+                    min_offset = cmp::max(min_offset, field.offset + field.size);
+                }
+
+                VariantLayout {
+                    name: variant.name.as_deref(),
+                    size_kind: variant.kind,
+                    size: variant.size,
+                    align: variant.align,
+                    padding,
+                    fields: &variant.fields,
+                }
+            })
+            .collect();
+
+        TypeLayout {
+            ty: &self.type_description,
+            kind: self.kind,
+            generic_args: &self.generic_args,
+            size: self.overall_size,
+            align: self.align,
+            packed: self.packed,
+            discriminant_size: self.opt_discr_size,
+            niche: self.niche.as_ref(),
+            variants,
+            end_padding: self.overall_size.saturating_sub(max_variant_size),
+        }
+    }
+}
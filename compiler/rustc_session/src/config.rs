@@ -140,6 +140,33 @@ pub enum MirSpanview {
     Block,
 }
 
+/// The format of the report printed by `-Z explain-recompilation`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum RecompilationReportFormat {
+    /// Default `-Z explain-recompilation` or `-Z explain-recompilation=text`
+    Text,
+    /// `-Z explain-recompilation=json`
+    Json,
+}
+
+/// The format of the layouts printed by `-Z print-type-sizes`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum PrintTypeSizesFormat {
+    /// Default `-Z print-type-sizes-format=text`
+    Text,
+    /// `-Z print-type-sizes-format=json`
+    Json,
+}
+
+/// The format of the file written by `--emit=dep-info`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum DepInfoFormat {
+    /// Default `-Z dep-info-format=makefile`
+    Makefile,
+    /// `-Z dep-info-format=json`
+    Json,
+}
+
 /// The different settings that the `-C instrument-coverage` flag can have.
 ///
 /// Coverage instrumentation now supports combining `-C instrument-coverage`
@@ -374,6 +401,9 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF log describing all diagnostics, written at the end of the session. Meant
+    /// for static analysis tooling such as code scanning services.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -1458,7 +1488,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "",
             "error-format",
             "How errors and other messages are produced",
-            "human|json|short",
+            "human|json|sarif|short",
         ),
         opt::multi_s("", "json", "Configure the JSON output of the compiler", "CONFIG"),
         opt::opt_s(
@@ -1633,11 +1663,12 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
                 &format!(
-                    "argument for `--error-format` must be `human`, `json` or \
+                    "argument for `--error-format` must be `human`, `json`, `sarif` or \
                      `short` (instead was `{arg}`)"
                 ),
             ),
@@ -1715,6 +1746,12 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
     }
 }
 
@@ -2765,10 +2802,10 @@ pub fn needs_analysis(&self) -> bool {
 /// how the hash should be calculated when adding a new command-line argument.
 pub(crate) mod dep_tracking {
     use super::{
-        BranchProtection, CFGuard, CFProtection, CrateType, DebugInfo, ErrorOutputType,
-        InstrumentCoverage, LdImpl, LinkerPluginLto, LocationDetail, LtoCli, OomStrategy, OptLevel,
-        OutputType, OutputTypes, Passes, SourceFileHashAlgorithm, SwitchWithOptPath,
-        SymbolManglingVersion, TrimmedDefPaths,
+        BranchProtection, CFGuard, CFProtection, CrateType, DebugInfo, DepInfoFormat,
+        ErrorOutputType, InstrumentCoverage, LdImpl, LinkerPluginLto, LocationDetail, LtoCli,
+        OomStrategy, OptLevel, OutputType, OutputTypes, Passes, SourceFileHashAlgorithm,
+        SwitchWithOptPath, SymbolManglingVersion, TrimmedDefPaths,
     };
     use crate::lint;
     use crate::options::WasiExecModel;
@@ -2859,6 +2896,7 @@ fn hash(
         SwitchWithOptPath,
         SymbolManglingVersion,
         SourceFileHashAlgorithm,
+        DepInfoFormat,
         TrimmedDefPaths,
         Option<LdImpl>,
         OutputType,
@@ -200,6 +200,9 @@ pub trait CrateStore: std::fmt::Debug {
 
     /// Fetch a DefId from a DefPathHash for a foreign crate.
     fn def_path_hash_to_def_id(&self, cnum: CrateNum, hash: DefPathHash) -> DefId;
+    /// Like `def_path_hash_to_def_id`, but returns `None` if no foreign crate of this session
+    /// defines an item with this hash.
+    fn try_def_path_hash_to_def_id(&self, hash: DefPathHash) -> Option<DefId>;
     fn expn_hash_to_expn_id(
         &self,
         sess: &Session,
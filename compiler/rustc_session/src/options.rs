@@ -385,6 +385,9 @@ mod desc {
     pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavor::one_of();
     pub const parse_optimization_fuel: &str = "crate=integer";
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
+    pub const parse_recompilation_report_format: &str = "`text` (default) or `json`";
+    pub const parse_print_type_sizes_format: &str = "`text` (default) or `json`";
+    pub const parse_dep_info_format: &str = "`makefile` (default) or `json`";
     pub const parse_instrument_coverage: &str =
         "`all` (default), `except-unused-generics`, `except-unused-functions`, or `off`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
@@ -822,6 +825,39 @@ pub(crate) fn parse_mir_spanview(slot: &mut Option<MirSpanview>, v: Option<&str>
         true
     }
 
+    pub(crate) fn parse_recompilation_report_format(
+        slot: &mut Option<RecompilationReportFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = Some(match v {
+            None | Some("text") => RecompilationReportFormat::Text,
+            Some("json") => RecompilationReportFormat::Json,
+            _ => return false,
+        });
+        true
+    }
+
+    pub(crate) fn parse_print_type_sizes_format(
+        slot: &mut PrintTypeSizesFormat,
+        v: Option<&str>,
+    ) -> bool {
+        *slot = match v {
+            Some("text") => PrintTypeSizesFormat::Text,
+            Some("json") => PrintTypeSizesFormat::Json,
+            _ => return false,
+        };
+        true
+    }
+
+    pub(crate) fn parse_dep_info_format(slot: &mut DepInfoFormat, v: Option<&str>) -> bool {
+        *slot = match v {
+            Some("makefile") => DepInfoFormat::Makefile,
+            Some("json") => DepInfoFormat::Json,
+            _ => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut Option<InstrumentCoverage>,
         v: Option<&str>,
@@ -1209,10 +1245,15 @@ pub(crate) fn parse_branch_protection(
         (default: no)"),
     branch_protection: Option<BranchProtection> = (None, parse_branch_protection, [TRACKED],
         "set options for branch target identification and pointer authentication on AArch64"),
+    build_manifest: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "write the content hashes of all outputs, and the inputs that affect them, to a file \
+        (default: `<crate name>.manifest` in the output directory)"),
     cf_protection: CFProtection = (CFProtection::None, parse_cfprotection, [TRACKED],
         "instrument control-flow architecture protection"),
     cgu_partitioning_strategy: Option<String> = (None, parse_opt_string, [TRACKED],
-        "the codegen unit partitioning strategy to use"),
+        "the codegen unit partitioning strategy to use: `default`, `affinity` (place generic \
+        instantiations with their callers) or `stable` (fixed hash-based buckets)"),
     chalk: bool = (false, parse_bool, [TRACKED],
         "enable the experimental Chalk-based trait solving engine"),
     codegen_backend: Option<String> = (None, parse_opt_string, [TRACKED],
@@ -1230,6 +1271,10 @@ pub(crate) fn parse_branch_protection(
     dep_info_omit_d_target: bool = (false, parse_bool, [TRACKED],
         "in dep-info output, omit targets for tracking dependencies of the dep-info files \
         themselves (default: no)"),
+    dep_info_format: DepInfoFormat = (DepInfoFormat::Makefile, parse_dep_info_format, [TRACKED],
+        "the format of the dep-info file: `makefile` rules, or `json` that also lists the files \
+        read by `include!` macros, the values of the environment variables read by `env!` and \
+        `option_env!`, and the dependencies declared by proc macros (default: makefile)"),
     dep_tasks: bool = (false, parse_bool, [UNTRACKED],
         "print tasks that execute and the color their dep node gets (requires debug build) \
         (default: no)"),
@@ -1245,6 +1290,14 @@ pub(crate) fn parse_branch_protection(
     dump_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv) \
         (default: no)"),
+    dump_incr_cache: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "print the dep-graph, cached query results and work products stored in an incremental \
+        session directory, then exit. `val` is a session directory or the incremental directory \
+        of a crate, in which case its most recent session is used (`-Z verbose` also lists every \
+        node)"),
+    dump_incr_cache_baseline: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "compare the session directory of `-Z dump-incr-cache` with this older one and print \
+        which dep-nodes were added, removed or changed, starting with the changed inputs"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump MIR state to file.
         `val` is used to select which passes and functions to dump. For example:
@@ -1271,6 +1324,10 @@ pub(crate) fn parse_branch_protection(
         an additional `.html` file showing the computed coverage spans."),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emit a section containing stack size metadata (default: no)"),
+    explain_recompilation: Option<RecompilationReportFormat> = (None, parse_recompilation_report_format, [UNTRACKED],
+        "print which changed inputs caused dep-nodes and codegen units of an incremental build to \
+        be re-executed, along with the chain of dependencies in between, as `text` (default) or \
+        `json`"),
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "reduce memory use by retaining fewer names within compilation artifacts (LLVM-IR) \
         (default: no)"),
@@ -1420,10 +1477,25 @@ pub(crate) fn parse_branch_protection(
         "print the LLVM optimization passes being run (default: no)"),
     print_mono_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "print the result of the monomorphization collection pass"),
+    print_mono_item_stats: bool = (false, parse_bool, [UNTRACKED],
+        "print how many instantiations of each generic function the monomorphization collector \
+        found, and their estimated size (default: no)"),
     print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print layout information for each type encountered (default: no)"),
+    print_type_sizes_filter: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "only print the layouts of types whose path, including the crate name, starts with this \
+        prefix with `-Z print-type-sizes`"),
+    print_type_sizes_format: PrintTypeSizesFormat = (PrintTypeSizesFormat::Text, parse_print_type_sizes_format, [UNTRACKED],
+        "the format of the layouts printed by `-Z print-type-sizes`: `text` (default) or `json` \
+        (one object per type, with field offsets, padding and niches)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
          "show backtraces for panics during proc-macro execution (default: no)"),
+    proc_macro_sandbox: bool = (false, parse_bool, [UNTRACKED],
+        "run proc macros in a separate process that cannot use sockets and can only \
+        read the paths given with `-Z proc-macro-sandbox-allow-path` (Linux only) (default: no)"),
+    proc_macro_sandbox_allow_path: Vec<String> = (Vec::new(), parse_string_push, [UNTRACKED],
+        "allow proc macros run with `-Z proc-macro-sandbox` to read this file, or the files \
+        under this directory (may be given multiple times)"),
     profile: bool = (false, parse_bool, [TRACKED],
         "insert profiling code (default: no)"),
     profile_closures: bool = (false, parse_no_flag, [UNTRACKED],
@@ -162,6 +162,11 @@ pub struct ParseSess {
     pub env_depinfo: Lock<FxHashSet<(Symbol, Option<Symbol>)>>,
     /// File paths accessed during the build.
     pub file_depinfo: Lock<FxHashSet<Symbol>>,
+    /// Environment variables declared as dependencies by proc macros. Their values are also
+    /// recorded in `env_depinfo`.
+    pub proc_macro_env_depinfo: Lock<FxHashSet<Symbol>>,
+    /// Files read by `include!`, `include_str!` and `include_bytes!`.
+    pub include_depinfo: Lock<FxHashSet<Symbol>>,
     /// All the type ascriptions expressions that have had a suggestion for likely path typo.
     pub type_ascription_path_suggestions: Lock<FxHashSet<Span>>,
     /// Whether cfg(version) should treat the current release as incomplete
@@ -204,6 +209,8 @@ pub fn with_span_handler(handler: Handler, source_map: Lrc<SourceMap>) -> Self {
             reached_eof: Lock::new(false),
             env_depinfo: Default::default(),
             file_depinfo: Default::default(),
+            proc_macro_env_depinfo: Default::default(),
+            include_depinfo: Default::default(),
             type_ascription_path_suggestions: Default::default(),
             assume_incomplete_release: false,
             proc_macro_quoted_spans: Default::default(),
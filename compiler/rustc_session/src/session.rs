@@ -1,6 +1,6 @@
 use crate::cgu_reuse_tracker::CguReuseTracker;
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+pub use crate::code_stats::{DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use crate::config::{self, CrateType, OutputType, SwitchWithOptPath};
 use crate::parse::ParseSess;
 use crate::search_paths::{PathKind, SearchPath};
@@ -19,6 +19,7 @@
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     fallback_fluent_bundle, DiagnosticBuilder, DiagnosticId, DiagnosticMessage, EmissionGuarantee,
     ErrorGuaranteed, FluentBundle, LazyFallbackBundle, MultiSpan,
@@ -254,6 +255,7 @@ pub fn finish_diagnostics(&self, registry: &Registry) {
         self.check_miri_unleashed_features();
         self.diagnostic().print_error_count(registry);
         self.emit_future_breakage();
+        self.diagnostic().finish();
     }
 
     fn emit_future_breakage(&self) {
@@ -1207,6 +1209,14 @@ fn default_emitter(
             )
             .ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => Box::new(
+            SarifEmitter::stderr(Some(registry), source_map, bundle, fallback_bundle)
+                .ui_testing(sopts.debugging_opts.ui_testing),
+        ),
+        (config::ErrorOutputType::Sarif, Some(dst)) => Box::new(
+            SarifEmitter::new(dst, Some(registry), source_map, bundle, fallback_bundle)
+                .ui_testing(sopts.debugging_opts.ui_testing),
+        ),
     }
 }
 
@@ -1489,6 +1499,15 @@ fn validate_commandline_args_with_session_available(sess: &Session) {
             ))
         }
     }
+
+    // The sandbox relies on Landlock and seccomp. Unlike most options, this depends on the
+    // host rather than on the target.
+    if sess.opts.debugging_opts.proc_macro_sandbox
+        && !(cfg!(target_os = "linux")
+            && (cfg!(target_arch = "x86_64") || cfg!(target_arch = "aarch64")))
+    {
+        sess.err("`-Z proc-macro-sandbox` is only supported on x86_64 and AArch64 Linux hosts");
+    }
 }
 
 /// Holds data on the current incremental compilation session, if there is one.
@@ -1528,18 +1547,27 @@ fn early_error_handler(output: config::ErrorOutputType) -> rustc_errors::Handler
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, fallback_bundle, None, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic(None, fallback_bundle)),
     };
     rustc_errors::Handler::with_emitter(true, None, emitter)
 }
 
 pub fn early_error_no_abort(output: config::ErrorOutputType, msg: &str) -> ErrorGuaranteed {
-    early_error_handler(output).struct_err(msg).emit()
+    let handler = early_error_handler(output);
+    let reported = handler.struct_err(msg).emit();
+    handler.finish();
+    reported
 }
 
 pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
-    early_error_handler(output).struct_fatal(msg).emit()
+    let handler = early_error_handler(output);
+    let fatal = handler.fatal(msg);
+    handler.finish();
+    fatal.raise()
 }
 
 pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
-    early_error_handler(output).struct_warn(msg).emit()
+    let handler = early_error_handler(output);
+    handler.struct_warn(msg).emit();
+    handler.finish();
 }
@@ -13,5 +13,10 @@
 #![cfg_attr(not(feature = "default"), feature(rustc_private))]
 
 pub mod mir;
+pub mod rustc_internal;
+pub mod stable_mir;
+
+// Make this module private for now since external users should not call these directly.
+mod rustc_smir;
 
 pub mod very_unstable;
@@ -0,0 +1,96 @@
+//! Module that implements the bridge between Stable MIR and internal compiler MIR.
+//!
+//! For that, we define APIs that will temporarily be public to 3P that exposes rustc internal APIs
+//! until stable MIR is complete.
+
+use crate::rustc_smir::Tables;
+use crate::stable_mir::{self, with};
+use crate::very_unstable::driver::{Callbacks, Compilation, RunCompiler};
+use crate::very_unstable::interface::{interface, Queries};
+use crate::very_unstable::middle::ty::TyCtxt;
+use crate::very_unstable::span::def_id::{CrateNum, DefId};
+
+pub fn item_def_id(item: &stable_mir::CrateItem) -> DefId {
+    with_tables(|t| t.item_def_id(item))
+}
+
+pub fn crate_item(did: DefId) -> stable_mir::CrateItem {
+    with_tables(|t| t.crate_item(did))
+}
+
+pub fn crate_num(item: &stable_mir::Crate) -> CrateNum {
+    item.id.into()
+}
+
+fn with_tables<R>(mut f: impl FnMut(&mut Tables<'_>) -> R) -> R {
+    let mut ret = None;
+    with(|tables| tables.rustc_tables(&mut |t| ret = Some(f(t))));
+    ret.unwrap()
+}
+
+/// Install a stable MIR context for `tcx` and run `f` in it. All stable MIR APIs can only be
+/// used while `f` is executing.
+pub fn run(tcx: TyCtxt<'_>, f: impl FnOnce()) {
+    stable_mir::run(Tables::new(tcx), f);
+}
+
+/// A compiler driver for tools that only want to look at the stable MIR of a crate.
+///
+/// The tool is handed control once the crate has been analyzed successfully, with a stable MIR
+/// context already installed, and never sees a `TyCtxt`:
+///
+/// ```ignore (needs-a-compiler-session)
+/// let args: Vec<String> = std::env::args().collect();
+/// StableMir::new(args, || {
+///     for item in stable_mir::all_local_items() {
+///         println!("{}: {} blocks", item.name(), item.body().blocks.len());
+///     }
+/// })
+/// .run()
+/// .unwrap();
+/// ```
+pub struct StableMir<F: FnOnce() + Send> {
+    args: Vec<String>,
+    callback: Option<F>,
+    continue_compilation: bool,
+}
+
+impl<F: FnOnce() + Send> StableMir<F> {
+    /// Creates a new driver that runs the compiler with the given command line `args` and
+    /// invokes `callback` after analysis.
+    pub fn new(args: Vec<String>, callback: F) -> Self {
+        StableMir { args, callback: Some(callback), continue_compilation: false }
+    }
+
+    /// By default the compiler stops after the callback returns. Set this to continue with
+    /// code generation, e.g. for tools that are used as a `RUSTC_WRAPPER`.
+    pub fn continue_compilation(&mut self, continue_compilation: bool) -> &mut Self {
+        self.continue_compilation = continue_compilation;
+        self
+    }
+
+    /// Runs the compiler. Returns an error if compilation failed before the callback could be
+    /// invoked.
+    pub fn run(&mut self) -> interface::Result<()> {
+        let args = self.args.clone();
+        RunCompiler::new(&args, self).run()
+    }
+}
+
+impl<F: FnOnce() + Send> Callbacks for StableMir<F> {
+    fn after_analysis<'tcx>(
+        &mut self,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().peek_mut().enter(|tcx| {
+            let callback = self.callback.take().expect("stable MIR callback invoked twice");
+            run(tcx, callback);
+        });
+        if self.continue_compilation {
+            Compilation::Continue
+        } else {
+            Compilation::Stop
+        }
+    }
+}
@@ -0,0 +1,791 @@
+//! Module that implements what will become the rustc side of Stable MIR.
+//!
+//! This module is responsible for building Stable MIR components from internal components.
+//!
+//! This module is not intended to be invoked directly by users. It will eventually
+//! become the public API of rustc that will be invoked by the `stable_mir` crate.
+//!
+//! For now, we are developing everything inside `rustc`, thus, we keep this module private.
+
+use std::collections::HashMap;
+
+use crate::stable_mir::mir::mono::{Instance, InstanceKind};
+use crate::stable_mir::ty::{
+    AdtDef, AliasDef, AliasKind, BoundRegion, BoundTy, ClosureDef, Const, ConstKind, DynTy,
+    FloatTy, FnDef, FnSig, ForeignDef, GeneratorDef, GenericArgKind, GenericArgs, IntTy,
+    ParamConst, ParamRegion, ParamTy, Region, RigidTy, TraitDef, TyKind, UintTy,
+};
+use crate::stable_mir::{self, Context, ItemKind};
+use crate::very_unstable::hir::{self, def::DefKind};
+use crate::very_unstable::middle::bug;
+use crate::very_unstable::middle::mir;
+use crate::very_unstable::middle::ty::adjustment::PointerCast;
+use crate::very_unstable::middle::ty::{self, Ty, TyCtxt};
+use crate::very_unstable::span::def_id::{CrateNum, DefId, LOCAL_CRATE};
+use crate::very_unstable::span::{Span, Symbol};
+
+impl<'tcx> Context for Tables<'tcx> {
+    fn local_crate(&self) -> stable_mir::Crate {
+        smir_crate(self.tcx, LOCAL_CRATE)
+    }
+
+    fn external_crates(&self) -> Vec<stable_mir::Crate> {
+        self.tcx.crates(()).iter().map(|crate_num| smir_crate(self.tcx, *crate_num)).collect()
+    }
+
+    fn find_crate(&self, name: &str) -> Option<stable_mir::Crate> {
+        [LOCAL_CRATE].iter().chain(self.tcx.crates(()).iter()).find_map(|crate_num| {
+            let crate_name = self.tcx.crate_name(*crate_num).to_string();
+            (name == crate_name).then(|| smir_crate(self.tcx, *crate_num))
+        })
+    }
+
+    fn all_local_items(&mut self) -> stable_mir::CrateItems {
+        self.tcx.mir_keys(()).iter().map(|item| self.crate_item(item.to_def_id())).collect()
+    }
+
+    fn entry_fn(&mut self) -> Option<stable_mir::CrateItem> {
+        Some(self.crate_item(self.tcx.entry_fn(())?.0))
+    }
+
+    fn mir_body(&mut self, item: &stable_mir::CrateItem) -> stable_mir::mir::Body {
+        let def_id = self.item_def_id(item);
+        let body =
+            self.tcx.instance_mir(ty::InstanceDef::Item(ty::WithOptConstParam::unknown(def_id)));
+        self.body(body)
+    }
+
+    fn item_name(&mut self, def_id: stable_mir::DefId) -> stable_mir::Symbol {
+        self.tcx.def_path_str(self.def_ids[def_id.0])
+    }
+
+    fn item_kind(&mut self, item: &stable_mir::CrateItem) -> ItemKind {
+        let def_id = self.item_def_id(item);
+        match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn => ItemKind::Fn,
+            DefKind::Closure => ItemKind::Closure,
+            DefKind::Generator => ItemKind::Generator,
+            DefKind::Const | DefKind::AssocConst | DefKind::AnonConst | DefKind::InlineConst => {
+                ItemKind::Const
+            }
+            DefKind::Static(_) => ItemKind::Static,
+            DefKind::Ctor(..) => ItemKind::Ctor,
+            kind => bug!("unexpected def kind {:?} for an item with MIR", kind),
+        }
+    }
+
+    fn item_span(&mut self, item: &stable_mir::CrateItem) -> stable_mir::mir::Span {
+        let def_id = self.item_def_id(item);
+        self.span(self.tcx.def_span(def_id))
+    }
+
+    fn ty_kind(&mut self, ty: stable_mir::ty::Ty) -> TyKind {
+        let ty = self.types[ty.0];
+        self.rustc_ty_to_ty(ty)
+    }
+
+    fn resolve_instance(&mut self, def: stable_mir::DefId, args: &GenericArgs) -> Option<Instance> {
+        let def_id = self.def_ids[def.0];
+        let substs = self.rustc_substs(args)?;
+        let instance =
+            ty::Instance::resolve(self.tcx, ty::ParamEnv::reveal_all(), def_id, substs).ok()??;
+        Some(self.instance(instance))
+    }
+
+    fn instance_body(&mut self, instance: &Instance) -> Option<stable_mir::mir::Body> {
+        let def_id = self.def_ids[instance.def.0];
+        let def = match instance.kind {
+            InstanceKind::Item => ty::InstanceDef::Item(ty::WithOptConstParam::unknown(def_id)),
+            // Shims are not exposed with enough information to rebuild them, and intrinsics and
+            // virtual calls have no body at all.
+            InstanceKind::Intrinsic | InstanceKind::Virtual(_) | InstanceKind::Shim(_) => {
+                return None;
+            }
+        };
+        if !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        let substs = self.rustc_substs(&instance.args)?;
+        let instance = ty::Instance { def, substs };
+        let body = instance.subst_mir_and_normalize_erasing_regions(
+            self.tcx,
+            ty::ParamEnv::reveal_all(),
+            self.tcx.instance_mir(def).clone(),
+        );
+        Some(self.body(&body))
+    }
+
+    fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>)) {
+        f(self)
+    }
+}
+
+pub struct Tables<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub def_ids: Vec<DefId>,
+    pub types: Vec<Ty<'tcx>>,
+    /// Reverse lookup tables so interning an already known entity doesn't need a linear search.
+    pub(crate) def_id_map: HashMap<DefId, stable_mir::DefId>,
+    pub(crate) type_map: HashMap<Ty<'tcx>, stable_mir::ty::Ty>,
+}
+
+impl<'tcx> Tables<'tcx> {
+    pub(crate) fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Tables {
+            tcx,
+            def_ids: Vec::new(),
+            types: Vec::new(),
+            def_id_map: HashMap::new(),
+            type_map: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn create_def_id(&mut self, did: DefId) -> stable_mir::DefId {
+        let def_ids = &mut self.def_ids;
+        *self.def_id_map.entry(did).or_insert_with(|| {
+            def_ids.push(did);
+            stable_mir::DefId(def_ids.len() - 1)
+        })
+    }
+
+    fn intern_ty(&mut self, ty: Ty<'tcx>) -> stable_mir::ty::Ty {
+        let types = &mut self.types;
+        *self.type_map.entry(ty).or_insert_with(|| {
+            types.push(ty);
+            stable_mir::ty::Ty(types.len() - 1)
+        })
+    }
+
+    fn span(&self, span: Span) -> stable_mir::mir::Span {
+        let source_map = self.tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        stable_mir::mir::Span {
+            file: source_map.filename_for_diagnostics(&lo.file.name).to_string(),
+            start_line: lo.line,
+            start_col: lo.col.0 + 1,
+            end_line: hi.line,
+            end_col: hi.col.0 + 1,
+        }
+    }
+
+    fn body(&mut self, body: &mir::Body<'tcx>) -> stable_mir::mir::Body {
+        stable_mir::mir::Body {
+            blocks: body
+                .basic_blocks()
+                .iter()
+                .map(|block| stable_mir::mir::BasicBlock {
+                    terminator: self.terminator(block.terminator()),
+                    statements: block
+                        .statements
+                        .iter()
+                        .map(|statement| self.statement(statement))
+                        .collect(),
+                    is_cleanup: block.is_cleanup,
+                })
+                .collect(),
+            locals: body
+                .local_decls
+                .iter()
+                .map(|decl| stable_mir::mir::LocalDecl {
+                    ty: self.intern_ty(decl.ty),
+                    mutability: decl.mutability.stable(),
+                    span: self.span(decl.source_info.span),
+                })
+                .collect(),
+            arg_count: body.arg_count,
+            span: self.span(body.span),
+        }
+    }
+
+    fn statement(&mut self, s: &mir::Statement<'tcx>) -> stable_mir::mir::Statement {
+        use mir::StatementKind::*;
+        use stable_mir::mir::StatementKind as SK;
+        let kind = match &s.kind {
+            Assign(assign) => SK::Assign(self.place(&assign.0), self.rvalue(&assign.1)),
+            FakeRead(fake_read) => SK::FakeRead(fake_read.0.stable(), self.place(&fake_read.1)),
+            SetDiscriminant { place, variant_index } => SK::SetDiscriminant {
+                place: self.place(place),
+                variant_index: variant_index.as_usize(),
+            },
+            Deinit(place) => SK::Deinit(self.place(place)),
+            StorageLive(local) => SK::StorageLive(local.as_usize()),
+            StorageDead(local) => SK::StorageDead(local.as_usize()),
+            Retag(kind, place) => SK::Retag(kind.stable(), self.place(place)),
+            AscribeUserType(ascription, variance) => {
+                SK::AscribeUserType(self.place(&ascription.0), variance.stable())
+            }
+            Coverage(_) => SK::Coverage,
+            CopyNonOverlapping(copy) => {
+                SK::CopyNonOverlapping(stable_mir::mir::CopyNonOverlapping {
+                    src: self.operand(&copy.src),
+                    dst: self.operand(&copy.dst),
+                    count: self.operand(&copy.count),
+                })
+            }
+            Nop => SK::Nop,
+        };
+        stable_mir::mir::Statement { kind, span: self.span(s.source_info.span) }
+    }
+
+    fn rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>) -> stable_mir::mir::Rvalue {
+        use mir::Rvalue::*;
+        use stable_mir::mir::Rvalue as RV;
+        match rvalue {
+            Use(op) => RV::Use(self.operand(op)),
+            Repeat(op, len) => RV::Repeat(self.operand(op), self.ty_const(*len)),
+            Ref(region, kind, place) => RV::Ref(region.stable(), kind.stable(), self.place(place)),
+            ThreadLocalRef(def_id) => RV::ThreadLocalRef(self.crate_item(*def_id)),
+            AddressOf(mutability, place) => RV::AddressOf(mutability.stable(), self.place(place)),
+            Len(place) => RV::Len(self.place(place)),
+            Cast(cast_kind, op, ty) => {
+                RV::Cast(cast_kind.stable(), self.operand(op), self.intern_ty(*ty))
+            }
+            BinaryOp(bin_op, ops) => {
+                RV::BinaryOp(bin_op.stable(), self.operand(&ops.0), self.operand(&ops.1))
+            }
+            CheckedBinaryOp(bin_op, ops) => {
+                RV::CheckedBinaryOp(bin_op.stable(), self.operand(&ops.0), self.operand(&ops.1))
+            }
+            NullaryOp(null_op, ty) => RV::NullaryOp(null_op.stable(), self.intern_ty(*ty)),
+            UnaryOp(un_op, op) => RV::UnaryOp(un_op.stable(), self.operand(op)),
+            Discriminant(place) => RV::Discriminant(self.place(place)),
+            Aggregate(agg_kind, operands) => RV::Aggregate(
+                self.aggregate_kind(agg_kind),
+                operands.iter().map(|op| self.operand(op)).collect(),
+            ),
+            ShallowInitBox(op, ty) => RV::ShallowInitBox(self.operand(op), self.intern_ty(*ty)),
+        }
+    }
+
+    fn aggregate_kind(
+        &mut self,
+        kind: &mir::AggregateKind<'tcx>,
+    ) -> stable_mir::mir::AggregateKind {
+        use stable_mir::mir::AggregateKind as AK;
+        match kind {
+            mir::AggregateKind::Array(ty) => AK::Array(self.intern_ty(*ty)),
+            mir::AggregateKind::Tuple => AK::Tuple,
+            mir::AggregateKind::Adt(def_id, variant, substs, user_ty, active_field) => AK::Adt(
+                AdtDef(self.create_def_id(*def_id)),
+                variant.as_usize(),
+                self.substs(substs),
+                user_ty.map(|index| index.as_usize()),
+                *active_field,
+            ),
+            mir::AggregateKind::Closure(def_id, substs) => {
+                AK::Closure(ClosureDef(self.create_def_id(*def_id)), self.substs(substs))
+            }
+            mir::AggregateKind::Generator(def_id, substs, movability) => AK::Generator(
+                GeneratorDef(self.create_def_id(*def_id)),
+                self.substs(substs),
+                movability.stable(),
+            ),
+        }
+    }
+
+    fn operand(&mut self, op: &mir::Operand<'tcx>) -> stable_mir::mir::Operand {
+        use mir::Operand::*;
+        match op {
+            Copy(place) => stable_mir::mir::Operand::Copy(self.place(place)),
+            Move(place) => stable_mir::mir::Operand::Move(self.place(place)),
+            Constant(c) => stable_mir::mir::Operand::Constant(stable_mir::mir::Constant {
+                span: self.span(c.span),
+                literal: self.mir_const(c.literal),
+            }),
+        }
+    }
+
+    fn place(&mut self, place: &mir::Place<'tcx>) -> stable_mir::mir::Place {
+        stable_mir::mir::Place {
+            local: place.local.as_usize(),
+            projection: place.projection.iter().map(|elem| self.projection_elem(elem)).collect(),
+        }
+    }
+
+    fn projection_elem(&mut self, elem: mir::PlaceElem<'tcx>) -> stable_mir::mir::ProjectionElem {
+        use mir::ProjectionElem::*;
+        use stable_mir::mir::ProjectionElem as PE;
+        match elem {
+            Deref => PE::Deref,
+            Field(field, ty) => PE::Field(field.as_usize(), self.intern_ty(ty)),
+            Index(local) => PE::Index(local.as_usize()),
+            ConstantIndex { offset, min_length, from_end } => {
+                PE::ConstantIndex { offset, min_length, from_end }
+            }
+            Subslice { from, to, from_end } => PE::Subslice { from, to, from_end },
+            Downcast(name, variant) => {
+                PE::Downcast(name.map(|name| name.to_string()), variant.as_usize())
+            }
+        }
+    }
+
+    fn terminator(&mut self, terminator: &mir::Terminator<'tcx>) -> stable_mir::mir::Terminator {
+        use mir::TerminatorKind::*;
+        use stable_mir::mir::TerminatorKind as TK;
+        let kind = match &terminator.kind {
+            Goto { target } => TK::Goto { target: target.as_usize() },
+            SwitchInt { discr, switch_ty, targets } => TK::SwitchInt {
+                discr: self.operand(discr),
+                switch_ty: self.intern_ty(*switch_ty),
+                targets: targets
+                    .iter()
+                    .map(|(value, target)| stable_mir::mir::SwitchTarget {
+                        value,
+                        target: target.as_usize(),
+                    })
+                    .collect(),
+                otherwise: targets.otherwise().as_usize(),
+            },
+            Resume => TK::Resume,
+            Abort => TK::Abort,
+            Return => TK::Return,
+            Unreachable => TK::Unreachable,
+            Drop { place, target, unwind } => TK::Drop {
+                place: self.place(place),
+                target: target.as_usize(),
+                unwind: unwind.map(|block| block.as_usize()),
+            },
+            DropAndReplace { place, value, target, unwind } => TK::DropAndReplace {
+                place: self.place(place),
+                value: self.operand(value),
+                target: target.as_usize(),
+                unwind: unwind.map(|block| block.as_usize()),
+            },
+            Call { func, args, destination, target, cleanup, from_hir_call: _, fn_span: _ } => {
+                TK::Call {
+                    func: self.operand(func),
+                    args: args.iter().map(|arg| self.operand(arg)).collect(),
+                    destination: self.place(destination),
+                    target: target.map(|block| block.as_usize()),
+                    cleanup: cleanup.map(|block| block.as_usize()),
+                }
+            }
+            Assert { cond, expected, msg, target, cleanup } => TK::Assert {
+                cond: self.operand(cond),
+                expected: *expected,
+                msg: self.assert_message(msg),
+                target: target.as_usize(),
+                cleanup: cleanup.map(|block| block.as_usize()),
+            },
+            Yield { value, resume, resume_arg, drop } => TK::Yield {
+                value: self.operand(value),
+                resume: resume.as_usize(),
+                resume_arg: self.place(resume_arg),
+                drop: drop.map(|block| block.as_usize()),
+            },
+            GeneratorDrop => TK::GeneratorDrop,
+            // False edges only matter to borrowck and are gone from the MIR we hand out, but
+            // handle them anyway in case a tool asks for the body of a const during CTFE.
+            FalseEdge { real_target, imaginary_target: _ } => {
+                TK::Goto { target: real_target.as_usize() }
+            }
+            FalseUnwind { real_target, unwind: _ } => TK::Goto { target: real_target.as_usize() },
+            InlineAsm { template, operands, options, line_spans, destination, cleanup } => {
+                TK::InlineAsm {
+                    template: format!("{:?}", template),
+                    operands: operands
+                        .iter()
+                        .map(|operand| self.inline_asm_operand(operand))
+                        .collect(),
+                    options: format!("{:?}", options),
+                    line_spans: format!("{:?}", line_spans),
+                    destination: destination.map(|block| block.as_usize()),
+                    cleanup: cleanup.map(|block| block.as_usize()),
+                }
+            }
+        };
+        stable_mir::mir::Terminator { kind, span: self.span(terminator.source_info.span) }
+    }
+
+    fn inline_asm_operand(
+        &mut self,
+        operand: &mir::InlineAsmOperand<'tcx>,
+    ) -> stable_mir::mir::InlineAsmOperand {
+        use mir::InlineAsmOperand;
+
+        let (in_value, out_place) = match operand {
+            InlineAsmOperand::In { value, .. } => (Some(self.operand(value)), None),
+            InlineAsmOperand::Out { place, .. } => (None, place.map(|place| self.place(&place))),
+            InlineAsmOperand::InOut { in_value, out_place, .. } => {
+                (Some(self.operand(in_value)), out_place.map(|place| self.place(&place)))
+            }
+            InlineAsmOperand::Const { .. }
+            | InlineAsmOperand::SymFn { .. }
+            | InlineAsmOperand::SymStatic { .. } => (None, None),
+        };
+
+        stable_mir::mir::InlineAsmOperand { in_value, out_place, raw_rpr: format!("{:?}", operand) }
+    }
+
+    fn assert_message(&mut self, msg: &mir::AssertMessage<'tcx>) -> stable_mir::mir::AssertMessage {
+        use mir::AssertKind;
+        use stable_mir::mir::AssertMessage as AM;
+        match msg {
+            AssertKind::BoundsCheck { len, index } => {
+                AM::BoundsCheck { len: self.operand(len), index: self.operand(index) }
+            }
+            AssertKind::Overflow(bin_op, op1, op2) => {
+                AM::Overflow(bin_op.stable(), self.operand(op1), self.operand(op2))
+            }
+            AssertKind::OverflowNeg(op) => AM::OverflowNeg(self.operand(op)),
+            AssertKind::DivisionByZero(op) => AM::DivisionByZero(self.operand(op)),
+            AssertKind::RemainderByZero(op) => AM::RemainderByZero(self.operand(op)),
+            AssertKind::ResumedAfterReturn(generator) => AM::ResumedAfterReturn(generator.stable()),
+            AssertKind::ResumedAfterPanic(generator) => AM::ResumedAfterPanic(generator.stable()),
+        }
+    }
+
+    fn instance(&mut self, instance: ty::Instance<'tcx>) -> Instance {
+        let kind = match instance.def {
+            ty::InstanceDef::Item(_) => InstanceKind::Item,
+            ty::InstanceDef::Intrinsic(_) => InstanceKind::Intrinsic,
+            ty::InstanceDef::Virtual(_, index) => InstanceKind::Virtual(index),
+            ty::InstanceDef::FnPtrShim(_, ty) | ty::InstanceDef::CloneShim(_, ty) => {
+                InstanceKind::Shim(Some(self.intern_ty(ty)))
+            }
+            ty::InstanceDef::DropGlue(_, ty) => InstanceKind::Shim(ty.map(|ty| self.intern_ty(ty))),
+            ty::InstanceDef::VtableShim(_)
+            | ty::InstanceDef::ReifyShim(_)
+            | ty::InstanceDef::ClosureOnceShim { .. } => InstanceKind::Shim(None),
+        };
+        Instance {
+            kind,
+            def: self.create_def_id(instance.def_id()),
+            args: self.substs(instance.substs),
+        }
+    }
+
+    fn substs(&mut self, substs: ty::SubstsRef<'tcx>) -> GenericArgs {
+        GenericArgs(
+            substs
+                .iter()
+                .map(|arg| match arg.unpack() {
+                    ty::GenericArgKind::Lifetime(region) => {
+                        GenericArgKind::Lifetime(region.stable())
+                    }
+                    ty::GenericArgKind::Type(ty) => GenericArgKind::Type(self.intern_ty(ty)),
+                    ty::GenericArgKind::Const(c) => GenericArgKind::Const(self.ty_const(c)),
+                })
+                .collect(),
+        )
+    }
+
+    /// Translates stable generic arguments back into the compiler's representation. Returns
+    /// `None` if one of the arguments is a constant whose value is not exposed by stable MIR.
+    fn rustc_substs(&mut self, args: &GenericArgs) -> Option<ty::SubstsRef<'tcx>> {
+        let tcx = self.tcx;
+        let args = args
+            .0
+            .iter()
+            .map(|arg| match arg {
+                GenericArgKind::Lifetime(Region::Static) => Some(tcx.lifetimes.re_static.into()),
+                GenericArgKind::Lifetime(_) => Some(tcx.lifetimes.re_erased.into()),
+                GenericArgKind::Type(ty) => Some(self.types[ty.0].into()),
+                GenericArgKind::Const(c) => {
+                    let ty = self.types[c.ty.0];
+                    let c = match &c.kind {
+                        ConstKind::Scalar(bits) => {
+                            ty::Const::from_bits(tcx, *bits, ty::ParamEnv::empty().and(ty))
+                        }
+                        ConstKind::Param(param) => tcx.mk_const(ty::ConstS {
+                            ty,
+                            kind: ty::ConstKind::Param(ty::ParamConst {
+                                index: param.index,
+                                name: Symbol::intern(&param.name),
+                            }),
+                        }),
+                        ConstKind::Opaque(_) => return None,
+                    };
+                    Some(c.into())
+                }
+            })
+            .collect::<Option<Vec<ty::GenericArg<'tcx>>>>()?;
+        Some(tcx.intern_substs(&args))
+    }
+
+    fn ty_const(&mut self, c: ty::Const<'tcx>) -> Const {
+        let kind = match c.kind() {
+            ty::ConstKind::Param(param) => {
+                ConstKind::Param(ParamConst { index: param.index, name: param.name.to_string() })
+            }
+            kind => match kind.try_to_scalar_int() {
+                Some(scalar) => ConstKind::Scalar(scalar.assert_bits(scalar.size())),
+                None => ConstKind::Opaque(c.to_string()),
+            },
+        };
+        Const { ty: self.intern_ty(c.ty()), kind }
+    }
+
+    fn mir_const(&mut self, literal: mir::ConstantKind<'tcx>) -> Const {
+        match literal {
+            mir::ConstantKind::Ty(c) => self.ty_const(c),
+            mir::ConstantKind::Val(..) => {
+                let kind = match literal.try_to_scalar_int() {
+                    Some(scalar) => ConstKind::Scalar(scalar.assert_bits(scalar.size())),
+                    None => ConstKind::Opaque(format!("{:?}", literal)),
+                };
+                Const { ty: self.intern_ty(literal.ty()), kind }
+            }
+        }
+    }
+
+    fn rustc_ty_to_ty(&mut self, ty: Ty<'tcx>) -> TyKind {
+        match ty.kind() {
+            ty::Bool => TyKind::RigidTy(RigidTy::Bool),
+            ty::Char => TyKind::RigidTy(RigidTy::Char),
+            ty::Int(int_ty) => TyKind::RigidTy(RigidTy::Int(match int_ty {
+                ty::IntTy::Isize => IntTy::Isize,
+                ty::IntTy::I8 => IntTy::I8,
+                ty::IntTy::I16 => IntTy::I16,
+                ty::IntTy::I32 => IntTy::I32,
+                ty::IntTy::I64 => IntTy::I64,
+                ty::IntTy::I128 => IntTy::I128,
+            })),
+            ty::Uint(uint_ty) => TyKind::RigidTy(RigidTy::Uint(match uint_ty {
+                ty::UintTy::Usize => UintTy::Usize,
+                ty::UintTy::U8 => UintTy::U8,
+                ty::UintTy::U16 => UintTy::U16,
+                ty::UintTy::U32 => UintTy::U32,
+                ty::UintTy::U64 => UintTy::U64,
+                ty::UintTy::U128 => UintTy::U128,
+            })),
+            ty::Float(float_ty) => TyKind::RigidTy(RigidTy::Float(match float_ty {
+                ty::FloatTy::F32 => FloatTy::F32,
+                ty::FloatTy::F64 => FloatTy::F64,
+            })),
+            ty::Adt(adt_def, substs) => TyKind::RigidTy(RigidTy::Adt(
+                AdtDef(self.create_def_id(adt_def.did())),
+                self.substs(substs),
+            )),
+            ty::Foreign(def_id) => {
+                TyKind::RigidTy(RigidTy::Foreign(ForeignDef(self.create_def_id(*def_id))))
+            }
+            ty::Str => TyKind::RigidTy(RigidTy::Str),
+            ty::Array(ty, len) => {
+                TyKind::RigidTy(RigidTy::Array(self.intern_ty(*ty), self.ty_const(*len)))
+            }
+            ty::Slice(ty) => TyKind::RigidTy(RigidTy::Slice(self.intern_ty(*ty))),
+            ty::RawPtr(ty::TypeAndMut { ty, mutbl }) => {
+                TyKind::RigidTy(RigidTy::RawPtr(self.intern_ty(*ty), mutbl.stable()))
+            }
+            ty::Ref(region, ty, mutbl) => {
+                TyKind::RigidTy(RigidTy::Ref(region.stable(), self.intern_ty(*ty), mutbl.stable()))
+            }
+            ty::FnDef(def_id, substs) => TyKind::RigidTy(RigidTy::FnDef(
+                FnDef(self.create_def_id(*def_id)),
+                self.substs(substs),
+            )),
+            ty::FnPtr(poly_sig) => {
+                let sig = poly_sig.skip_binder();
+                TyKind::RigidTy(RigidTy::FnPtr(FnSig {
+                    inputs_and_output: sig
+                        .inputs_and_output
+                        .iter()
+                        .map(|ty| self.intern_ty(ty))
+                        .collect(),
+                    c_variadic: sig.c_variadic,
+                    unsafety: sig.unsafety.stable(),
+                    abi: sig.abi.name().to_string(),
+                }))
+            }
+            ty::Dynamic(predicates, region) => TyKind::RigidTy(RigidTy::Dynamic(
+                DynTy {
+                    principal: predicates
+                        .principal_def_id()
+                        .map(|def_id| TraitDef(self.create_def_id(def_id))),
+                    auto_traits: predicates
+                        .auto_traits()
+                        .map(|def_id| TraitDef(self.create_def_id(def_id)))
+                        .collect(),
+                },
+                region.stable(),
+            )),
+            ty::Closure(def_id, substs) => TyKind::RigidTy(RigidTy::Closure(
+                ClosureDef(self.create_def_id(*def_id)),
+                self.substs(substs),
+            )),
+            ty::Generator(def_id, substs, movability) => TyKind::RigidTy(RigidTy::Generator(
+                GeneratorDef(self.create_def_id(*def_id)),
+                self.substs(substs),
+                movability.stable(),
+            )),
+            ty::GeneratorWitness(witness) => TyKind::RigidTy(RigidTy::GeneratorWitness(
+                witness.skip_binder().iter().map(|ty| self.intern_ty(ty)).collect(),
+            )),
+            ty::Never => TyKind::RigidTy(RigidTy::Never),
+            ty::Tuple(fields) => TyKind::RigidTy(RigidTy::Tuple(
+                fields.iter().map(|ty| self.intern_ty(ty)).collect(),
+            )),
+            ty::Projection(projection) => TyKind::Alias(
+                AliasKind::Projection,
+                AliasDef(self.create_def_id(projection.item_def_id)),
+                self.substs(projection.substs),
+            ),
+            ty::Opaque(def_id, substs) => TyKind::Alias(
+                AliasKind::Opaque,
+                AliasDef(self.create_def_id(*def_id)),
+                self.substs(substs),
+            ),
+            ty::Param(param) => {
+                TyKind::Param(ParamTy { index: param.index, name: param.name.to_string() })
+            }
+            ty::Bound(debruijn, bound_ty) => {
+                TyKind::Bound(debruijn.as_usize(), BoundTy { var: bound_ty.var.as_usize() })
+            }
+            ty::Placeholder(..) | ty::Infer(_) | ty::Error(_) => {
+                bug!("unexpected type `{:?}` in stable MIR", ty)
+            }
+        }
+    }
+
+    pub(crate) fn crate_item(&mut self, did: DefId) -> stable_mir::CrateItem {
+        stable_mir::CrateItem(self.create_def_id(did))
+    }
+
+    pub(crate) fn item_def_id(&self, item: &stable_mir::CrateItem) -> DefId {
+        self.def_ids[item.0 .0]
+    }
+}
+
+/// Build a stable mir crate from a given crate number.
+fn smir_crate(tcx: TyCtxt<'_>, crate_num: CrateNum) -> stable_mir::Crate {
+    let crate_name = tcx.crate_name(crate_num).to_string();
+    let is_local = crate_num == LOCAL_CRATE;
+    stable_mir::Crate { id: crate_num.into(), name: crate_name, is_local }
+}
+
+/// Trait used to convert between an internal MIR type to a Stable MIR type.
+pub(crate) trait Stable {
+    /// The stable representation of the type implementing Stable.
+    type T;
+    /// Converts an object to the equivalent Stable MIR representation.
+    fn stable(&self) -> Self::T;
+}
+
+macro_rules! stable_enum {
+    ($($rustc:path => $smir:path { $($variant:ident),* $(,)? }),* $(,)?) => {
+        $(
+            impl Stable for $rustc {
+                type T = $smir;
+                fn stable(&self) -> Self::T {
+                    match self {
+                        $(<$rustc>::$variant => <$smir>::$variant,)*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+stable_enum! {
+    mir::Mutability => stable_mir::mir::Mutability { Not, Mut },
+    hir::Unsafety => stable_mir::mir::Safety { Unsafe, Normal },
+    hir::Movability => stable_mir::mir::Movability { Static, Movable },
+    mir::RetagKind => stable_mir::mir::RetagKind { FnEntry, TwoPhase, Raw, Default },
+    ty::Variance => stable_mir::mir::Variance { Covariant, Invariant, Contravariant, Bivariant },
+    mir::NullOp => stable_mir::mir::NullOp { SizeOf, AlignOf },
+    mir::UnOp => stable_mir::mir::UnOp { Not, Neg },
+    hir::AsyncGeneratorKind => stable_mir::mir::AsyncGeneratorKind { Block, Closure, Fn },
+    mir::BinOp => stable_mir::mir::BinOp {
+        Add, Sub, Mul, Div, Rem, BitXor, BitAnd, BitOr, Shl, Shr, Eq, Lt, Le, Ne, Ge, Gt, Offset,
+    },
+}
+
+impl Stable for mir::FakeReadCause {
+    type T = stable_mir::mir::FakeReadCause;
+    fn stable(&self) -> Self::T {
+        use mir::FakeReadCause::*;
+        match self {
+            ForMatchGuard => stable_mir::mir::FakeReadCause::ForMatchGuard,
+            ForMatchedPlace(_) => stable_mir::mir::FakeReadCause::ForMatchedPlace,
+            ForGuardBinding => stable_mir::mir::FakeReadCause::ForGuardBinding,
+            ForLet(_) => stable_mir::mir::FakeReadCause::ForLet,
+            ForIndex => stable_mir::mir::FakeReadCause::ForIndex,
+        }
+    }
+}
+
+impl Stable for mir::BorrowKind {
+    type T = stable_mir::mir::BorrowKind;
+    fn stable(&self) -> Self::T {
+        use mir::BorrowKind::*;
+        match *self {
+            Shared => stable_mir::mir::BorrowKind::Shared,
+            Shallow => stable_mir::mir::BorrowKind::Shallow,
+            Unique => stable_mir::mir::BorrowKind::Unique,
+            Mut { allow_two_phase_borrow } => {
+                stable_mir::mir::BorrowKind::Mut { allow_two_phase_borrow }
+            }
+        }
+    }
+}
+
+impl Stable for mir::CastKind {
+    type T = stable_mir::mir::CastKind;
+    fn stable(&self) -> Self::T {
+        use mir::CastKind::*;
+        match self {
+            PointerExposeAddress => stable_mir::mir::CastKind::PointerExposeAddress,
+            PointerFromExposedAddress => stable_mir::mir::CastKind::PointerFromExposedAddress,
+            Pointer(cast) => stable_mir::mir::CastKind::Pointer(cast.stable()),
+            Misc => stable_mir::mir::CastKind::Misc,
+        }
+    }
+}
+
+impl Stable for PointerCast {
+    type T = stable_mir::mir::PointerCast;
+    fn stable(&self) -> Self::T {
+        use stable_mir::mir::PointerCast as PC;
+        match self {
+            PointerCast::ReifyFnPointer => PC::ReifyFnPointer,
+            PointerCast::UnsafeFnPointer => PC::UnsafeFnPointer,
+            PointerCast::ClosureFnPointer(unsafety) => PC::ClosureFnPointer(unsafety.stable()),
+            PointerCast::MutToConstPointer => PC::MutToConstPointer,
+            PointerCast::ArrayToPointer => PC::ArrayToPointer,
+            PointerCast::Unsize => PC::Unsize,
+        }
+    }
+}
+
+impl Stable for hir::GeneratorKind {
+    type T = stable_mir::mir::GeneratorKind;
+    fn stable(&self) -> Self::T {
+        match self {
+            hir::GeneratorKind::Async(kind) => stable_mir::mir::GeneratorKind::Async(kind.stable()),
+            hir::GeneratorKind::Gen => stable_mir::mir::GeneratorKind::Gen,
+        }
+    }
+}
+
+impl<'tcx> Stable for ty::Region<'tcx> {
+    type T = Region;
+    fn stable(&self) -> Self::T {
+        match self.kind() {
+            ty::ReStatic => Region::Static,
+            ty::ReErased => Region::Erased,
+            ty::ReEarlyBound(region) => {
+                Region::Param(ParamRegion { index: region.index, name: region.name.to_string() })
+            }
+            ty::ReLateBound(debruijn, region) => Region::Bound(
+                debruijn.as_usize(),
+                BoundRegion { var: region.var.as_usize(), name: region.kind.stable() },
+            ),
+            ty::ReFree(region) => Region::Free(region.bound_region.stable()),
+            ty::ReVar(_) | ty::RePlaceholder(_) | ty::ReEmpty(_) => {
+                bug!("unexpected region `{:?}` in stable MIR", self)
+            }
+        }
+    }
+}
+
+impl Stable for ty::BoundRegionKind {
+    type T = Option<stable_mir::Symbol>;
+    fn stable(&self) -> Self::T {
+        match self {
+            ty::BrNamed(_, name) => Some(name.to_string()),
+            ty::BrAnon(_) | ty::BrEnv => None,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+mod body;
+pub mod mono;
+
+pub use body::*;
@@ -0,0 +1,44 @@
+use crate::stable_mir::mir::Body;
+use crate::stable_mir::ty::{FnDef, GenericArgs, Ty};
+use crate::stable_mir::{with, DefId, Symbol};
+
+/// A function definition instantiated with concrete generic arguments, as it would be called at
+/// runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instance {
+    pub kind: InstanceKind,
+    pub def: DefId,
+    pub args: GenericArgs,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstanceKind {
+    /// A user defined item, such as a function or closure.
+    Item,
+    /// A compiler intrinsic, which has no MIR body.
+    Intrinsic,
+    /// A call to a trait method through a vtable, with the index of the method in the vtable.
+    Virtual(usize),
+    /// A compiler generated shim, e.g. drop glue or a `Clone` implementation for a builtin type.
+    Shim(Option<Ty>),
+}
+
+impl Instance {
+    /// Resolve the instance called through the function definition `def` with the given generic arguments.
+    ///
+    /// Returns `None` if the call cannot be resolved yet, e.g. because it depends on a generic
+    /// parameter of the caller.
+    pub fn resolve(def: &FnDef, args: &GenericArgs) -> Option<Instance> {
+        with(|cx| cx.resolve_instance(def.0, args))
+    }
+
+    /// The MIR body of this instance with its generic arguments substituted, or `None` for
+    /// instances without a body such as intrinsics and virtual calls.
+    pub fn body(&self) -> Option<Body> {
+        with(|cx| cx.instance_body(self))
+    }
+
+    pub fn name(&self) -> Symbol {
+        with(|cx| cx.item_name(self.def))
+    }
+}
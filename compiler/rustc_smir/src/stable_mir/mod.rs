@@ -0,0 +1,175 @@
+//! Module that implements the public interface to the Stable MIR.
+//!
+//! This module shall contain all type definitions and APIs that we expect 3P tools to invoke to
+//! interact with the compiler.
+//!
+//! None of the types in this module borrow from the compiler: every value is owned and can be
+//! freely cloned, stored and compared by a tool. Handles such as [`DefId`] and [`ty::Ty`] are only
+//! meaningful while the closure passed to [`crate::rustc_internal::run`] is executing, since they
+//! index into tables owned by that run.
+//!
+//! The goal is to eventually move this module to its own crate which shall be published on
+//! [crates.io](https://crates.io).
+
+use std::cell::Cell;
+
+use crate::rustc_smir::Tables;
+
+use self::mir::mono::Instance;
+use self::ty::{GenericArgs, Ty, TyKind};
+
+pub mod mir;
+pub mod ty;
+
+/// The version of the stable MIR data model implemented by this crate.
+///
+/// This is bumped whenever a type in this module changes in a way that tools may observe, so that
+/// they can detect incompatible compiler versions up front instead of misinterpreting data.
+pub const VERSION: u32 = 1;
+
+/// Use String for now but we should replace it.
+pub type Symbol = String;
+
+/// The number that identifies a crate.
+pub type CrateNum = usize;
+
+/// A unique identification number for each item accessible for the current compilation unit.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DefId(pub(crate) usize);
+
+/// A list of crate items.
+pub type CrateItems = Vec<CrateItem>;
+
+/// Holds information about a crate.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Crate {
+    pub id: CrateNum,
+    pub name: Symbol,
+    pub is_local: bool,
+}
+
+/// Holds information about an item in the crate.
+/// For now, it only stores the item DefId. Use functions inside `rustc_internal` module to
+/// use this item.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CrateItem(pub(crate) DefId);
+
+impl CrateItem {
+    /// Retrieve the MIR body of this item.
+    ///
+    /// Functions and closures return their optimized MIR, constants and statics return the MIR
+    /// used for const evaluation.
+    pub fn body(&self) -> mir::Body {
+        with(|cx| cx.mir_body(self))
+    }
+
+    /// The fully qualified path of this item, e.g. `my_crate::module::function`.
+    pub fn name(&self) -> Symbol {
+        with(|cx| cx.item_name(self.0))
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        with(|cx| cx.item_kind(self))
+    }
+
+    pub fn span(&self) -> mir::Span {
+        with(|cx| cx.item_span(self))
+    }
+}
+
+/// The kind of an item that has a MIR body.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemKind {
+    Fn,
+    Closure,
+    Generator,
+    Const,
+    Static,
+    Ctor,
+}
+
+/// Return the function where execution starts if the current
+/// crate defines that. This is usually `main`, but could be
+/// `start` if the crate is a no-std crate.
+pub fn entry_fn() -> Option<CrateItem> {
+    with(|cx| cx.entry_fn())
+}
+
+/// Access to the local crate.
+pub fn local_crate() -> Crate {
+    with(|cx| cx.local_crate())
+}
+
+/// Try to find a crate with the given name.
+pub fn find_crate(name: &str) -> Option<Crate> {
+    with(|cx| cx.find_crate(name))
+}
+
+/// Retrieve all crates the local crate depends on, directly or indirectly.
+pub fn external_crates() -> Vec<Crate> {
+    with(|cx| cx.external_crates())
+}
+
+/// Retrieve all items in the local crate that have a MIR associated with them.
+pub fn all_local_items() -> CrateItems {
+    with(|cx| cx.all_local_items())
+}
+
+/// The interface the compiler side of stable MIR implements. Every query made by a tool is
+/// forwarded through this trait to the context installed by [`run`].
+pub trait Context {
+    fn entry_fn(&mut self) -> Option<CrateItem>;
+    /// Retrieve all items of the local crate that have a MIR associated with them.
+    fn all_local_items(&mut self) -> CrateItems;
+    fn mir_body(&mut self, item: &CrateItem) -> mir::Body;
+    fn item_name(&mut self, def_id: DefId) -> Symbol;
+    fn item_kind(&mut self, item: &CrateItem) -> ItemKind;
+    fn item_span(&mut self, item: &CrateItem) -> mir::Span;
+    /// Get information about the local crate.
+    fn local_crate(&self) -> Crate;
+    /// Retrieve a list of all external crates.
+    fn external_crates(&self) -> Vec<Crate>;
+
+    /// Find a crate with the given name.
+    fn find_crate(&self, name: &str) -> Option<Crate>;
+
+    /// Obtain the representation of a type.
+    fn ty_kind(&mut self, ty: Ty) -> TyKind;
+
+    /// Resolve a function definition and its generic arguments to the instance that would be
+    /// called at runtime, if that can be determined without further monomorphization.
+    fn resolve_instance(&mut self, def: DefId, args: &GenericArgs) -> Option<Instance>;
+
+    /// Retrieve the MIR body of a resolved instance, with its generic arguments substituted.
+    fn instance_body(&mut self, instance: &Instance) -> Option<mir::Body>;
+
+    /// HACK: Until we have fully stable consumers, we need an escape hatch
+    /// to get `DefId`s out of `CrateItem`s.
+    fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>));
+}
+
+// A thread local variable that stores a pointer to the tables mapping between TyCtxt
+// datastructures and stable MIR datastructures
+scoped_tls::scoped_thread_local! (static TLV: Cell<*mut ()>);
+
+pub fn run(mut context: impl Context, f: impl FnOnce()) {
+    assert!(!TLV.is_set());
+    fn g<'a>(mut context: &mut (dyn Context + 'a), f: impl FnOnce()) {
+        let ptr: *mut () = &mut context as *mut &mut _ as _;
+        TLV.set(&Cell::new(ptr), || {
+            f();
+        });
+    }
+    g(&mut context, f);
+}
+
+/// Loads the current context and calls a function with it.
+/// Do not nest these, as that will ICE.
+pub(crate) fn with<R>(f: impl FnOnce(&mut dyn Context) -> R) -> R {
+    assert!(TLV.is_set());
+    TLV.with(|tlv| {
+        let ptr = tlv.get();
+        assert!(!ptr.is_null());
+        f(unsafe { *(ptr as *mut &mut dyn Context) })
+    })
+}
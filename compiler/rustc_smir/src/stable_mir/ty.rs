@@ -0,0 +1,207 @@
+use super::mir::{Movability, Mutability, Safety};
+use super::{with, DefId, Symbol};
+
+/// A type. Types are interned by the compiler, this is a handle that can be used to retrieve
+/// its [`TyKind`] while the stable MIR context is active.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Ty(pub(crate) usize);
+
+impl Ty {
+    pub fn kind(&self) -> TyKind {
+        with(|context| context.ty_kind(*self))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TyKind {
+    RigidTy(RigidTy),
+    Alias(AliasKind, AliasDef, GenericArgs),
+    Param(ParamTy),
+    Bound(usize, BoundTy),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RigidTy {
+    Bool,
+    Char,
+    Int(IntTy),
+    Uint(UintTy),
+    Float(FloatTy),
+    Adt(AdtDef, GenericArgs),
+    Foreign(ForeignDef),
+    Str,
+    Array(Ty, Const),
+    Slice(Ty),
+    RawPtr(Ty, Mutability),
+    Ref(Region, Ty, Mutability),
+    FnDef(FnDef, GenericArgs),
+    FnPtr(FnSig),
+    Dynamic(DynTy, Region),
+    Closure(ClosureDef, GenericArgs),
+    Generator(GeneratorDef, GenericArgs, Movability),
+    GeneratorWitness(Vec<Ty>),
+    Never,
+    Tuple(Vec<Ty>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntTy {
+    Isize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UintTy {
+    Usize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AliasKind {
+    Projection,
+    Opaque,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ForeignDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FnDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ClosureDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GeneratorDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AdtDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AliasDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraitDef(pub(crate) DefId);
+
+macro_rules! def_names {
+    ($($def:ident),*) => {
+        $(
+            impl $def {
+                pub fn name(&self) -> Symbol {
+                    with(|cx| cx.item_name(self.0))
+                }
+            }
+        )*
+    };
+}
+
+def_names!(ForeignDef, FnDef, ClosureDef, GeneratorDef, AdtDef, AliasDef, TraitDef);
+
+/// The generic arguments a definition is instantiated with, in declaration order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenericArgs(pub Vec<GenericArgKind>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenericArgKind {
+    Lifetime(Region),
+    Type(Ty),
+    Const(Const),
+}
+
+/// Regions are erased in optimized MIR, so the other kinds only show up in types taken from item
+/// signatures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Region {
+    Static,
+    Erased,
+    /// A lifetime parameter of the enclosing item, like `'a` in `fn foo<'a>(x: &'a u8)`.
+    Param(ParamRegion),
+    /// A region introduced by a binder such as `for<'a>`, with the number of binders between its
+    /// use and the one introducing it.
+    Bound(usize, BoundRegion),
+    /// A late-bound region of a function signature, as seen from inside the function body.
+    Free(Option<Symbol>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamRegion {
+    pub index: u32,
+    pub name: Symbol,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundRegion {
+    pub var: usize,
+    /// The name of the region, or `None` for anonymous regions like the ones elided in `&u8`.
+    pub name: Option<Symbol>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Const {
+    pub ty: Ty,
+    pub kind: ConstKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstKind {
+    /// A fully evaluated scalar, represented by its raw bits.
+    Scalar(u128),
+    Param(ParamConst),
+    /// A constant whose value is not exposed by this API yet, in its pretty-printed form.
+    Opaque(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamTy {
+    pub index: u32,
+    pub name: Symbol,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamConst {
+    pub index: u32,
+    pub name: Symbol,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundTy {
+    pub var: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FnSig {
+    pub inputs_and_output: Vec<Ty>,
+    pub c_variadic: bool,
+    pub unsafety: Safety,
+    pub abi: Symbol,
+}
+
+impl FnSig {
+    pub fn output(&self) -> Ty {
+        self.inputs_and_output[self.inputs_and_output.len() - 1]
+    }
+
+    pub fn inputs(&self) -> &[Ty] {
+        &self.inputs_and_output[..self.inputs_and_output.len() - 1]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynTy {
+    pub principal: Option<TraitDef>,
+    pub auto_traits: Vec<TraitDef>,
+}
@@ -1251,6 +1251,7 @@
         rustc_std_internal_symbol,
         rustc_strict_coherence,
         rustc_symbol_name,
+        rustc_test_hook,
         rustc_test_marker,
         rustc_then_this_would_need,
         rustc_trivial_field_reads,
@@ -1422,8 +1423,12 @@
         test_2018_feature,
         test_accepted_feature,
         test_case,
+        test_hooks,
         test_removed_feature,
         test_runner,
+        test_setup,
+        test_teardown,
+        test_timeout,
         test_unstable_lint,
         then_with,
         thread,
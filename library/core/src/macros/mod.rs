@@ -1502,6 +1502,30 @@ macro_rules! trace_macros {
         /* compiler built-in */
     }
 
+    /// Attribute macro applied to a function to run it once before all the tests.
+    #[unstable(
+        feature = "test_hooks",
+        issue = "none",
+        reason = "test hooks are an unstable feature"
+    )]
+    #[allow_internal_unstable(rustc_attrs)]
+    #[rustc_builtin_macro]
+    pub macro test_setup($item:item) {
+        /* compiler built-in */
+    }
+
+    /// Attribute macro applied to a function to run it once after all the tests.
+    #[unstable(
+        feature = "test_hooks",
+        issue = "none",
+        reason = "test hooks are an unstable feature"
+    )]
+    #[allow_internal_unstable(rustc_attrs)]
+    #[rustc_builtin_macro]
+    pub macro test_teardown($item:item) {
+        /* compiler built-in */
+    }
+
     /// Attribute macro applied to a static to register it as a global allocator.
     ///
     /// See also [`std::alloc::GlobalAlloc`](../../../std/alloc/trait.GlobalAlloc.html).
@@ -91,3 +91,6 @@
     reason = "`cfg_eval` is a recently implemented feature"
 )]
 pub use crate::macros::builtin::cfg_eval;
+
+#[unstable(feature = "test_hooks", issue = "none", reason = "test hooks are an unstable feature")]
+pub use crate::macros::builtin::{test_setup, test_teardown};
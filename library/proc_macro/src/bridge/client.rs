@@ -375,6 +375,18 @@ fn clone(&self) -> Self {
     }
 }
 
+impl<I, O> Client<I, O> {
+    /// A client that can only be run with `server::OutOfProcess`, which ignores it and
+    /// runs the actual client in another process.
+    pub const fn out_of_process() -> Self {
+        extern "C" fn run(_: BridgeConfig<'_>) -> Buffer {
+            panic!("a client that runs out of process cannot be run in process")
+        }
+
+        Client { get_handle_counters: HandleCounters::get, run, _marker: PhantomData }
+    }
+}
+
 fn maybe_install_panic_hook(force_show_panics: bool) {
     // Hide the default panic output within `proc_macro` expansions.
     // NB. the server can't do this because it may use a different libstd.
@@ -254,6 +254,83 @@ enum State<T> {
     }
 }
 
+/// The connection to a client running in another process, see `OutOfProcess`.
+pub trait ClientProcess {
+    /// Starts running the client with the encoded `input`.
+    fn start(&self, input: &[u8], force_show_panics: bool) -> Result<(), String>;
+
+    /// Waits for the next request of the client, or for its output once it is done.
+    fn next_message(&self) -> Result<ClientMessage, String>;
+
+    /// Sends the encoded response to the last request of the client.
+    fn respond(&self, response: &[u8]) -> Result<(), String>;
+}
+
+pub enum ClientMessage {
+    /// An encoded request, to be answered with `ClientProcess::respond`.
+    Request(Vec<u8>),
+    /// The encoded output of the client, which has finished running.
+    Output(Vec<u8>),
+}
+
+/// Runs the client in another process, which in turn calls `Client::run_out_of_process`.
+/// The client passed to `run` is ignored, so it can be `Client::out_of_process()`.
+///
+/// If the connection to the process fails, the client is treated as if it panicked, with
+/// the error as the panic message.
+pub struct OutOfProcess<P>(pub P);
+
+impl<P: ClientProcess> ExecutionStrategy for OutOfProcess<P> {
+    fn run_bridge_and_client(
+        &self,
+        dispatcher: &mut impl DispatcherTrait,
+        input: Buffer,
+        _run_client: extern "C" fn(BridgeConfig<'_>) -> Buffer,
+        force_show_panics: bool,
+    ) -> Buffer {
+        let process = &self.0;
+        let mut run = || -> Result<Buffer, String> {
+            process.start(&input, force_show_panics)?;
+            loop {
+                match process.next_message()? {
+                    ClientMessage::Request(request) => {
+                        let response = dispatcher.dispatch(Buffer::from(request));
+                        process.respond(&response)?;
+                    }
+                    ClientMessage::Output(output) => return Ok(Buffer::from(output)),
+                }
+            }
+        };
+
+        run().unwrap_or_else(|message| {
+            let mut buf = Buffer::new();
+            Err::<(), _>(PanicMessage::String(message)).encode(&mut buf, &mut ());
+            buf
+        })
+    }
+}
+
+impl<I, O> client::Client<I, O> {
+    /// Runs the client with an `input` encoded by the server of another process, which uses
+    /// `OutOfProcess`, and returns its encoded output. The requests of the client are passed
+    /// to `dispatch`, which has to forward them to that server.
+    pub fn run_out_of_process(
+        &self,
+        input: Vec<u8>,
+        mut dispatch: impl FnMut(Vec<u8>) -> Vec<u8>,
+        force_show_panics: bool,
+    ) -> Vec<u8> {
+        let mut dispatch = |request: Buffer| Buffer::from(dispatch(request.to_vec()));
+        let output = (self.run)(BridgeConfig {
+            input: Buffer::from(input),
+            dispatch: (&mut dispatch).into(),
+            force_show_panics,
+            _marker: marker::PhantomData,
+        });
+        output.to_vec()
+    }
+}
+
 fn run_server<
     S: Server,
     I: Encode<HandleStore<MarkedTypes<S>>>,
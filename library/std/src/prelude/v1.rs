@@ -78,6 +78,10 @@
 )]
 pub use core::prelude::v1::cfg_eval;
 
+// Do not `doc(no_inline)` either.
+#[unstable(feature = "test_hooks", issue = "none", reason = "test hooks are an unstable feature")]
+pub use core::prelude::v1::{test_setup, test_teardown};
+
 // The file so far is equivalent to src/libcore/prelude/v1.rs,
 // and below to src/liballoc/prelude.rs.
 // Those files are duplicated rather than using glob imports
@@ -119,6 +119,9 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
     start.elapsed().as_nanos() as u64
 }
 
+/// The number of samples that summarize a benchmark.
+pub(crate) const SAMPLE_COUNT: usize = 50;
+
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
 where
     F: FnMut() -> T,
@@ -139,7 +142,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     n = cmp::max(1, n);
 
     let mut total_run = Duration::new(0, 0);
-    let samples: &mut [f64] = &mut [0.0_f64; 50];
+    let samples: &mut [f64] = &mut [0.0_f64; SAMPLE_COUNT];
     loop {
         let loop_start = Instant::now();
 
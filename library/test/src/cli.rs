@@ -1,10 +1,16 @@
 //! Module converting command-line arguments into test configuration.
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use super::helpers::baseline::{baseline_path, read_baseline, DEFAULT_REGRESSION_THRESHOLD_PCT};
+use super::helpers::filter::{Glob, NamePattern, Regex};
 use super::helpers::isatty;
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::helpers::shard::read_test_durations;
+use super::options::{
+    BenchBaseline, ColorConfig, Options, OutputFormat, RunIgnored, Shard, SourceLocation,
+};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -12,6 +18,8 @@ pub struct TestOpts {
     pub list: bool,
     pub filters: Vec<String>,
     pub filter_exact: bool,
+    pub filter_patterns: Vec<NamePattern>,
+    pub filter_locations: Vec<SourceLocation>,
     pub force_run_in_process: bool,
     pub exclude_should_panic: bool,
     pub run_ignored: RunIgnored,
@@ -23,9 +31,13 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    pub shard: Option<Shard>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    pub test_timeout: Option<Duration>,
+    pub retries: usize,
+    pub bench_baseline: Option<BenchBaseline>,
     pub options: Options,
 }
 
@@ -82,6 +94,28 @@ fn optgroups() -> getopts::Options {
              Alias to --format=terse",
         )
         .optflag("", "exact", "Exactly match filters rather than by substring")
+        .optmulti(
+            "",
+            "filter-glob",
+            "Run tests whose whole names match GLOB, where `*` matches
+            any part of a path segment and `**` matches anything (this
+            flag can be used multiple times)",
+            "GLOB",
+        )
+        .optmulti(
+            "",
+            "filter-regex",
+            "Run tests whose names contain a match of REGEX (this flag can
+            be used multiple times)",
+            "REGEX",
+        )
+        .optmulti(
+            "",
+            "at",
+            "Run the tests that contain the given line of the given file
+            (this flag can be used multiple times)",
+            "FILE:LINE",
+        )
         .optopt(
             "",
             "color",
@@ -102,6 +136,13 @@ fn optgroups() -> getopts::Options {
             "pretty|terse|json|junit",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
+        .optflag(
+            "",
+            "report-details",
+            "Include the captured output of every test, when it started,
+            how long it ran and on which thread, and whether it should
+            panic in the output of --format=json and --format=junit",
+        )
         .optopt(
             "Z",
             "",
@@ -138,12 +179,67 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECONDS, unless they set
+            another timeout with `#[test_timeout]`. Tests that run in a
+            subprocess, which is the case with panic=abort, are killed once
+            they time out. Other tests cannot be stopped and only fail once
+            they finish.",
+            "SECONDS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests up to N more times. Tests that pass on a
+            later attempt are reported as flaky and do not fail the test
+            run. Tests that are not plain functions, such as doctests,
+            are not retried.",
+            "N",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of shard K (counting from 0) out of the shards given with
+            --shard-count",
+            "K",
+        )
+        .optopt("", "shard-count", "Split the tests into N shards", "N")
+        .optopt(
+            "",
+            "partition-by-time",
+            "Balance the shards by the execution times of the tests in FILE,
+            the output of an earlier run with --format=json and --report-time",
+            "FILE",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the results of the benchmarks as the baseline NAME",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of the benchmarks with the baseline NAME,
+            and fail if any of them regressed",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "regression-threshold",
+            "Only report a benchmark as regressed if it is slower than in
+            the baseline by more than PERCENT, with 95% confidence
+            (default: 5)",
+            "PERCENT",
         );
     opts
 }
@@ -155,7 +251,11 @@ fn usage(binary: &str, options: &getopts::Options) {
 
 The FILTER string is tested against the name of all tests, and only those
 tests whose names contain the filter are run. Multiple filter strings may
-be passed, which will run all tests matching any of the filters.
+be passed, which will run all tests matching any of the filters. Tests can
+also be selected by the globs given with --filter-glob, such as
+`parser::*::parse_*`, by the regular expressions given with --filter-regex, or
+by a line of their source with --at=src/lexer.rs:120. A test is run if it
+matches any of these.
 
 By default, all tests are run in parallel. This can be altered with the
 --test-threads flag or the RUST_TEST_THREADS environment variable when running
@@ -167,6 +267,18 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+The tests can be split across several machines with --shard-count=N and
+--shard-index=K. Every test is assigned to the same shard on every machine,
+based on its name. With --partition-by-time, the shards are instead chosen
+such that they all take about the same time, based on an earlier run; all
+machines must then use the same file.
+
+The results of the benchmarks can be saved with --save-baseline=NAME and
+compared with those of a later run with --baseline=NAME, which reports the
+change of every benchmark and fails if any of them regressed. Baselines are
+stored in the directory given by RUST_TEST_BASELINE_DIR, by default in the
+bench-baselines directory of the target directory of the test binary.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -245,6 +357,21 @@ macro_rules! unstable_optopt {
     }};
 }
 
+// Gets the option values and checks if unstable features are enabled.
+macro_rules! unstable_optmulti {
+    ($matches:ident, $allow_unstable:ident, $option_name:literal) => {{
+        let opt = $matches.opt_strs($option_name);
+        if !$allow_unstable && !opt.is_empty() {
+            return Err(format!(
+                "The \"{}\" option is only accepted on the nightly compiler with -Z unstable-options",
+                $option_name
+            ));
+        }
+
+        opt
+    }};
+}
+
 // Implementation of `parse_opts` that doesn't care about help message
 // and returns a `Result`.
 fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
@@ -254,8 +381,14 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let bench_baseline = get_bench_baseline(&matches, allow_unstable)?;
+    let filter_patterns = get_filter_patterns(&matches, allow_unstable)?;
+    let filter_locations = get_filter_locations(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -274,12 +407,16 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let color = get_color_config(&matches)?;
     let format = get_format(&matches, quiet, allow_unstable)?;
 
-    let options = Options::new().display_output(matches.opt_present("show-output"));
+    let options = Options::new()
+        .display_output(matches.opt_present("show-output"))
+        .report_details(unstable_optflag!(matches, allow_unstable, "report-details"));
 
     let test_opts = TestOpts {
         list,
         filters,
         filter_exact: exact,
+        filter_patterns,
+        filter_locations,
         force_run_in_process,
         exclude_should_panic,
         run_ignored,
@@ -291,9 +428,13 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
+        bench_baseline,
         options,
     };
 
@@ -329,6 +470,93 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs_str) => match secs_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number of seconds \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_filter_patterns(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Vec<NamePattern>> {
+    let mut patterns = Vec::new();
+    for glob in unstable_optmulti!(matches, allow_unstable, "filter-glob") {
+        patterns.push(NamePattern::Glob(Glob::new(&glob)));
+    }
+    for regex in unstable_optmulti!(matches, allow_unstable, "filter-regex") {
+        match Regex::new(&regex) {
+            Ok(regex) => patterns.push(NamePattern::Regex(regex)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --filter-regex must be a valid regular expression \
+                     (was {regex}: {e})"
+                ));
+            }
+        }
+    }
+
+    Ok(patterns)
+}
+
+fn get_filter_locations(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Vec<SourceLocation>> {
+    let mut locations = Vec::new();
+    for location in unstable_optmulti!(matches, allow_unstable, "at") {
+        let parsed = location.rsplit_once(':').and_then(|(file, line)| {
+            let line = line.parse::<usize>().ok().filter(|&line| line > 0)?;
+            (!file.is_empty()).then(|| SourceLocation { file: file.to_string(), line })
+        });
+        match parsed {
+            Some(location) => locations.push(location),
+            None => {
+                return Err(format!(
+                    "argument for --at must be a file and a line number, \
+                     such as src/lib.rs:42 (was {location})"
+                ));
+            }
+        }
+    }
+
+    Ok(locations)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -369,6 +597,104 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Shard>> {
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let partition_by_time = unstable_optopt!(matches, allow_unstable, "partition-by-time");
+
+    let (index, count) = match (index, count) {
+        (Some(index), Some(count)) => (index, count),
+        (None, None) if partition_by_time.is_none() => return Ok(None),
+        (None, None) => {
+            return Err("--partition-by-time requires --shard-index and --shard-count".into());
+        }
+        _ => {
+            return Err("the options --shard-index and --shard-count must be used together".into());
+        }
+    };
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-count must be a number > 0 \
+                 (error: {})",
+                e
+            ));
+        }
+    };
+    let index = match index.parse::<usize>() {
+        Ok(n) if n < count => n,
+        Ok(_) => {
+            return Err(format!("argument for --shard-index must be less than {}", count));
+        }
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-index must be a number \
+                 (error: {})",
+                e
+            ));
+        }
+    };
+    let durations = match partition_by_time {
+        Some(path) => Some(read_test_durations(Path::new(&path)).map_err(|e| {
+            format!("could not read the test times for --partition-by-time from {path}: {e}")
+        })?),
+        None => None,
+    };
+
+    Ok(Some(Shard { index, count, durations }))
+}
+
+fn get_bench_baseline(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<BenchBaseline>> {
+    let save = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let compare = unstable_optopt!(matches, allow_unstable, "baseline");
+    let threshold = unstable_optopt!(matches, allow_unstable, "regression-threshold");
+
+    if save.is_none() && compare.is_none() {
+        if threshold.is_some() {
+            return Err("--regression-threshold requires --baseline".into());
+        }
+        return Ok(None);
+    }
+    let threshold_pct = match threshold {
+        Some(threshold) => match threshold.parse::<f64>() {
+            Ok(pct) if pct.is_finite() && pct >= 0.0 => pct,
+            _ => {
+                return Err(format!(
+                    "argument for --regression-threshold must be a non-negative \
+                     percentage, found {threshold}"
+                ));
+            }
+        },
+        None => DEFAULT_REGRESSION_THRESHOLD_PCT,
+    };
+    let compare = match compare {
+        Some(name) => {
+            let path = baseline_path(&name)
+                .map_err(|e| format!("could not find the directory of baselines: {e}"))?;
+            let benchmarks = read_baseline(&path).map_err(|e| {
+                format!("could not read the baseline `{name}` from {}: {e}", path.display())
+            })?;
+            Some((name, benchmarks))
+        }
+        None => None,
+    };
+
+    let save = match save {
+        Some(name) => Some(
+            baseline_path(&name)
+                .map_err(|e| format!("could not find the directory of baselines: {e}"))?,
+        ),
+        None => None,
+    };
+
+    Ok(Some(BenchBaseline { save, compare, threshold_pct }))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
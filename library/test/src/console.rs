@@ -1,5 +1,6 @@
 //! Module providing interface for running tests in the console.
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io;
 use std::io::prelude::Write;
@@ -8,15 +9,23 @@
 use super::{
     bench::fmt_bench_samples,
     cli::TestOpts,
-    event::{CompletedTest, TestEvent},
+    event::{CompletedTest, FailedHook, TestEvent},
     filter_tests,
     formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
-    helpers::{concurrency::get_concurrency, metrics::MetricMap},
-    options::{Options, OutputFormat},
-    run_tests, term,
+    helpers::{
+        baseline::{
+            compare, fmt_bench_change, save_baseline, BaselineComparison, BenchStats, Verdict,
+        },
+        concurrency::get_concurrency,
+        metrics::MetricMap,
+    },
+    options::{BenchBaseline, Options, OutputFormat},
+    run_tests_with_hooks,
+    stats::Summary,
+    term,
     test_result::TestResult,
     time::{TestExecTime, TestSuiteExecTime},
-    types::{NamePadding, TestDesc, TestDescAndFn},
+    types::{NamePadding, TestDesc, TestDescAndFn, TestHooks},
 };
 
 /// Generic wrapper over stdout.
@@ -47,6 +56,9 @@ pub struct ConsoleTestState {
     pub passed: usize,
     pub failed: usize,
     pub ignored: usize,
+    /// The tests that passed after failing, see `--retries`. They are also
+    /// counted in `passed`.
+    pub flaky: usize,
     pub filtered_out: usize,
     pub measured: usize,
     pub exec_time: Option<TestSuiteExecTime>,
@@ -54,6 +66,12 @@ pub struct ConsoleTestState {
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
+    /// The setup and teardown hooks that panicked. They fail the test run.
+    pub failed_hooks: Vec<FailedHook>,
+    /// The results of the benchmarks, to save them as a baseline.
+    pub bench_stats: BTreeMap<String, BenchStats>,
+    pub bench_baseline: Option<BenchBaseline>,
     pub options: Options,
 }
 
@@ -70,6 +88,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             passed: 0,
             failed: 0,
             ignored: 0,
+            flaky: 0,
             filtered_out: 0,
             measured: 0,
             exec_time: None,
@@ -77,10 +96,19 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             failures: Vec::new(),
             not_failures: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
+            failed_hooks: Vec::new(),
+            bench_stats: BTreeMap::new(),
+            bench_baseline: opts.bench_baseline.clone(),
             options: opts.options,
         })
     }
 
+    /// Whether all tests and hooks succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.failed == 0 && self.failed_hooks.is_empty()
+    }
+
     pub fn write_log<F, S>(&mut self, msg: F) -> io::Result<()>
     where
         S: AsRef<str>,
@@ -119,6 +147,8 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut => "failed (timed out)".to_owned(),
+                    TestResult::TrFlaky(attempts) => format!("flaky ({attempts} failed attempts)"),
                 },
                 name,
             )
@@ -129,6 +159,25 @@ pub fn write_log_result(
         self.write_log(|| "\n")
     }
 
+    /// Compares the results of a benchmark with the baseline given with
+    /// `--baseline`, if any.
+    pub fn compare_with_baseline(
+        &self,
+        test: &TestDesc,
+        summary: &Summary,
+    ) -> Option<BaselineComparison> {
+        let baseline = self.bench_baseline.as_ref()?;
+        let (_, benchmarks) = baseline.compare.as_ref()?;
+        let comparison = match benchmarks.get(test.name.as_slice()) {
+            Some(old) => match compare(old, &BenchStats::new(summary), baseline.threshold_pct) {
+                Some(change) => BaselineComparison::Changed(change),
+                None => BaselineComparison::Missing,
+            },
+            None => BaselineComparison::Missing,
+        };
+        Some(comparison)
+    }
+
     fn current_test_count(&self) -> usize {
         self.passed + self.failed + self.ignored + self.measured
     }
@@ -201,7 +250,25 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
                 bs.ns_iter_summ.median,
                 bs.ns_iter_summ.max - bs.ns_iter_summ.min,
             );
-            st.measured += 1
+            st.bench_stats.insert(test.name.to_string(), BenchStats::new(&bs.ns_iter_summ));
+            match st.compare_with_baseline(&test, &bs.ns_iter_summ) {
+                Some(BaselineComparison::Changed(change))
+                    if change.verdict == Verdict::Regressed =>
+                {
+                    let (name, _) = st.bench_baseline.as_ref().unwrap().compare.as_ref().unwrap();
+                    let mut stdout = stdout;
+                    stdout.extend_from_slice(
+                        format!(
+                            "note: compared to the baseline `{name}`: {}\n",
+                            fmt_bench_change(&change)
+                        )
+                        .as_bytes(),
+                    );
+                    st.failed += 1;
+                    st.failures.push((test, stdout));
+                }
+                _ => st.measured += 1,
+            }
         }
         TestResult::TrFailed => {
             st.failed += 1;
@@ -217,6 +284,15 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut => {
+            st.failed += 1;
+            st.failures.push((test, stdout));
+        }
+        TestResult::TrFlaky(_) => {
+            st.passed += 1;
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -241,12 +317,18 @@ fn on_test_event(
             let test = &completed_test.desc;
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
+            let details = &completed_test.details;
             let stdout = &completed_test.stdout;
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), &*stdout, st)?;
+            out.write_result(test, result, exec_time.as_ref(), details.as_ref(), &*stdout, st)?;
             handle_test_result(st, completed_test);
         }
+        TestEvent::TeHookFailed(hook) => {
+            st.write_log(|| format!("{} {} failed\n", hook.kind.as_str(), hook.name))?;
+            out.write_hook_failure(&hook)?;
+            st.failed_hooks.push(hook);
+        }
     }
 
     Ok(())
@@ -255,6 +337,14 @@ fn on_test_event(
 /// A simple console test runner.
 /// Runs provided tests reporting process and results to the stdout.
 pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<bool> {
+    run_tests_console_with_hooks(opts, tests, TestHooks::default())
+}
+
+pub(crate) fn run_tests_console_with_hooks(
+    opts: &TestOpts,
+    tests: Vec<TestDescAndFn>,
+    hooks: TestHooks<'_>,
+) -> io::Result<bool> {
     let output = match term::stdout() {
         None => OutputLocation::Raw(io::stdout()),
         Some(t) => OutputLocation::Pretty(t),
@@ -290,10 +380,17 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
     let is_instant_supported = !cfg!(target_family = "wasm") && !cfg!(miri);
 
     let start_time = is_instant_supported.then(Instant::now);
-    run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
+    run_tests_with_hooks(opts, tests, hooks, |x| on_test_event(&x, &mut st, &mut *out))?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
-    assert!(st.current_test_count() == st.total);
+    if let Some(path) = opts.bench_baseline.as_ref().and_then(|baseline| baseline.save.as_ref()) {
+        if !st.bench_stats.is_empty() {
+            save_baseline(path, &st.bench_stats)?;
+        }
+    }
+
+    // The tests do not run if a setup hook failed.
+    assert!(st.current_test_count() == st.total || !st.failed_hooks.is_empty());
 
     out.write_run_finish(&st)
 }
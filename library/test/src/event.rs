@@ -3,7 +3,18 @@
 
 use super::test_result::TestResult;
 use super::time::TestExecTime;
-use super::types::{TestDesc, TestId};
+use super::types::{TestDesc, TestHookKind, TestId};
+use std::time::SystemTime;
+
+/// When and where a test ran, only recorded with `--report-details`.
+#[derive(Debug, Clone)]
+pub struct TestRunDetails {
+    pub start_time: SystemTime,
+    /// The thread that ran the test, unless it ran in a subprocess.
+    pub thread_name: Option<String>,
+    /// The JSON of the `TestFailureReport` the test panicked with, if any.
+    pub failure: Option<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct CompletedTest {
@@ -11,6 +22,7 @@ pub struct CompletedTest {
     pub desc: TestDesc,
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
+    pub details: Option<TestRunDetails>,
     pub stdout: Vec<u8>,
 }
 
@@ -22,10 +34,18 @@ pub fn new(
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
     ) -> Self {
-        Self { id, desc, result, exec_time, stdout }
+        Self { id, desc, result, exec_time, details: None, stdout }
     }
 }
 
+/// A setup or teardown hook that panicked, with its captured output.
+#[derive(Debug, Clone)]
+pub struct FailedHook {
+    pub kind: TestHookKind,
+    pub name: String,
+    pub stdout: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub enum TestEvent {
     TeFiltered(Vec<TestDesc>, Option<u64>),
@@ -33,4 +53,5 @@ pub enum TestEvent {
     TeResult(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
+    TeHookFailed(FailedHook),
 }
@@ -1,8 +1,11 @@
-use std::{borrow::Cow, io, io::prelude::Write};
+use std::{borrow::Cow, fmt::Write as _, io, io::prelude::Write, time::UNIX_EPOCH};
 
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    event::{FailedHook, TestRunDetails},
+    helpers::baseline::BaselineComparison,
+    options::ShouldPanic,
     test_result::TestResult,
     time,
     types::TestDesc,
@@ -36,6 +39,7 @@ fn write_event(
         name: &str,
         evt: &str,
         exec_time: Option<&time::TestExecTime>,
+        details: &str,
         stdout: Option<Cow<'_, str>>,
         extra: Option<&str>,
     ) -> io::Result<()> {
@@ -49,6 +53,7 @@ fn write_event(
         if let Some(exec_time) = exec_time {
             self.write_message(&*format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64()))?;
         }
+        self.write_message(details)?;
         if let Some(stdout) = stdout {
             self.write_message(&*format!(r#", "stdout": "{}""#, EscapedString(stdout)))?;
         }
@@ -84,38 +89,77 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        details: Option<&TestRunDetails>,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()> {
-        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
+        let display_stdout = state.options.display_output
+            || state.options.report_details
+            || *result != TestResult::TrOk;
+        let details =
+            if state.options.report_details { fmt_details(desc, details) } else { String::new() };
         let stdout = if display_stdout && !stdout.is_empty() {
             Some(String::from_utf8_lossy(stdout))
         } else {
             None
         };
         match *result {
-            TestResult::TrOk => {
-                self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
-            }
+            TestResult::TrOk => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "ok",
+                exec_time,
+                &details,
+                stdout,
+                None,
+            ),
 
-            TestResult::TrFailed => {
-                self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
-            }
+            TestResult::TrFailed => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                &details,
+                stdout,
+                None,
+            ),
 
             TestResult::TrTimedFail => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
+                &details,
                 stdout,
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                &details,
+                stdout,
+                Some(r#""reason": "timed out""#),
+            ),
+
+            TestResult::TrFlaky(attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                &details,
+                stdout,
+                Some(&*format!(r#""failed_attempts": {}"#, attempts)),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
+                &details,
                 stdout,
                 Some(&*format!(r#""message": "{}""#, EscapedString(m))),
             ),
@@ -125,6 +169,7 @@ fn write_result(
                 desc.name.as_slice(),
                 "ignored",
                 exec_time,
+                &details,
                 stdout,
                 desc.ignore_message
                     .map(|msg| format!(r#""message": "{}""#, EscapedString(msg)))
@@ -141,15 +186,32 @@ fn write_result(
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
 
+                let change = match state.compare_with_baseline(desc, &bs.ns_iter_summ) {
+                    Some(BaselineComparison::Changed(change)) => format!(
+                        ", \"change\": {{ \
+                         \"percent\": {}, \
+                         \"low\": {}, \
+                         \"high\": {}, \
+                         \"verdict\": \"{}\" }}",
+                        change.change_pct,
+                        change.low_pct,
+                        change.high_pct,
+                        change.verdict.as_str()
+                    ),
+                    Some(BaselineComparison::Missing) => r#", "change": null"#.to_string(),
+                    None => String::new(),
+                };
+
                 let line = format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{}\", \
                      \"median\": {}, \
-                     \"deviation\": {}{} }}",
+                     \"deviation\": {}{}{} }}",
                     EscapedString(desc.name.as_slice()),
                     median,
                     deviation,
-                    mbps
+                    mbps,
+                    change
                 );
 
                 self.writeln_message(&*line)
@@ -164,6 +226,11 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_hook_failure(&mut self, hook: &FailedHook) -> io::Result<()> {
+        let stdout = String::from_utf8_lossy(&hook.stdout);
+        self.write_event(hook.kind.as_str(), &hook.name, "failed", None, "", Some(stdout), None)
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message(&*format!(
             "{{ \"type\": \"suite\", \
@@ -172,15 +239,21 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
              \"failed\": {}, \
              \"ignored\": {}, \
              \"measured\": {}, \
-             \"filtered_out\": {}",
-            if state.failed == 0 { "ok" } else { "failed" },
+             \"filtered_out\": {}, \
+             \"flaky\": {}",
+            if state.succeeded() { "ok" } else { "failed" },
             state.passed,
             state.failed,
             state.ignored,
             state.measured,
             state.filtered_out,
+            state.flaky,
         ))?;
 
+        if !state.failed_hooks.is_empty() {
+            self.write_message(&*format!(", \"failed_hooks\": {}", state.failed_hooks.len()))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!(", \"exec_time\": {}", exec_time.0.as_secs_f64());
             self.write_message(&time_str)?;
@@ -188,13 +261,53 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.writeln_message(" }")?;
 
-        Ok(state.failed == 0)
+        Ok(state.succeeded())
+    }
+}
+
+/// The fields added to the result of a test with `--report-details`.
+fn fmt_details(desc: &TestDesc, details: Option<&TestRunDetails>) -> String {
+    let mut fields = String::new();
+    if let Some(details) = details {
+        if let Ok(start_time) = details.start_time.duration_since(UNIX_EPOCH) {
+            write!(fields, r#", "start_time": {}"#, start_time.as_secs_f64()).unwrap();
+        }
+        if let Some(thread_name) = &details.thread_name {
+            write!(fields, r#", "thread": "{}""#, EscapedString(thread_name)).unwrap();
+        }
+    }
+    match desc.should_panic {
+        ShouldPanic::No => fields.push_str(r#", "should_panic": false"#),
+        ShouldPanic::Yes => fields.push_str(r#", "should_panic": true"#),
+        ShouldPanic::YesWithMessage(msg) => write!(
+            fields,
+            r#", "should_panic": true, "should_panic_message": "{}""#,
+            EscapedString(msg)
+        )
+        .unwrap(),
+    }
+    if !desc.source_file.is_empty() {
+        write!(
+            fields,
+            r#", "source_file": "{}", "start_line": {}, "end_line": {}"#,
+            EscapedString(desc.source_file),
+            desc.start_line,
+            desc.end_line
+        )
+        .unwrap();
+    }
+    if let Some(item_path) = desc.item_path {
+        write!(fields, r#", "item_path": "{}""#, EscapedString(item_path)).unwrap();
+    }
+    if let Some(failure) = details.and_then(|details| details.failure.as_deref()) {
+        write!(fields, r#", "failure": {}"#, failure).unwrap();
     }
+    fields
 }
 
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
-struct EscapedString<S: AsRef<str>>(S);
+pub(crate) struct EscapedString<S: AsRef<str>>(pub S);
 
 impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -1,9 +1,12 @@
+use std::fmt::Write as _;
 use std::io::{self, prelude::Write};
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 use super::OutputFormatter;
 use crate::{
     console::{ConsoleTestState, OutputLocation},
+    event::{FailedHook, TestRunDetails},
+    helpers::baseline::{fmt_bench_change, BaselineComparison, Verdict},
     test_result::TestResult,
     time,
     types::{TestDesc, TestType},
@@ -11,7 +14,9 @@
 
 pub struct JunitFormatter<T> {
     out: OutputLocation<T>,
-    results: Vec<(TestDesc, TestResult, Duration)>,
+    /// The results, with the captured output and the run details if they
+    /// should be included, which is the case with `--report-details`.
+    results: Vec<(TestDesc, TestResult, Duration, Option<Vec<u8>>, Option<TestRunDetails>)>,
 }
 
 impl<T: Write> JunitFormatter<T> {
@@ -19,11 +24,47 @@ pub fn new(out: OutputLocation<T>) -> Self {
         Self { out, results: Vec::new() }
     }
 
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
     fn write_message(&mut self, s: &str) -> io::Result<()> {
         assert!(!s.contains('\n'));
 
         self.out.write_all(s.as_ref())
     }
+
+    fn write_properties(&mut self, details: Option<&TestRunDetails>) -> io::Result<()> {
+        let details = match details {
+            Some(details) => details,
+            None => return Ok(()),
+        };
+        self.write_message("<properties>")?;
+        if let Ok(start_time) = details.start_time.duration_since(UNIX_EPOCH) {
+            self.write_message(&*format!(
+                "<property name=\"start_time\" value=\"{}\"/>",
+                start_time.as_secs_f64()
+            ))?;
+        }
+        if let Some(thread_name) = &details.thread_name {
+            self.write_message(&*format!(
+                "<property name=\"thread\" value=\"{}\"/>",
+                EscapedXml(thread_name)
+            ))?;
+        }
+        self.write_message("</properties>")
+    }
+
+    fn write_system_out(&mut self, stdout: Option<&[u8]>) -> io::Result<()> {
+        match stdout {
+            Some(stdout) if !stdout.is_empty() => self.write_message(&*format!(
+                "<system-out>{}</system-out>",
+                EscapedXml(&String::from_utf8_lossy(stdout))
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl<T: Write> OutputFormatter for JunitFormatter<T> {
@@ -51,32 +92,72 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
-        _stdout: &[u8],
-        _state: &ConsoleTestState,
+        details: Option<&TestRunDetails>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         // Because the testsuite node holds some of the information as attributes, we can't write it
         // until all of the tests have finished. Instead of writing every result as they come in, we add
         // them to a Vec and write them all at once when run is complete.
         let duration = exec_time.map(|t| t.0).unwrap_or_default();
-        self.results.push((desc.clone(), result.clone(), duration));
+        let report_details = state.options.report_details;
+        let stdout = report_details.then(|| stdout.to_vec());
+        let details = details.filter(|_| report_details).cloned();
+        self.results.push((desc.clone(), result.clone(), duration, stdout, details));
         Ok(())
     }
+    fn write_hook_failure(&mut self, _: &FailedHook) -> io::Result<()> {
+        // The failed hooks are written with the results, once the run is complete.
+        Ok(())
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         self.write_message("<testsuites>")?;
 
         self.write_message(&*format!(
             "<testsuite name=\"test\" package=\"test\" id=\"0\" \
-             errors=\"0\" \
+             errors=\"{}\" \
              failures=\"{}\" \
              tests=\"{}\" \
              skipped=\"{}\" \
              >",
-            state.failed, state.total, state.ignored
+            state.failed_hooks.len(),
+            state.failed,
+            state.total,
+            state.ignored
         ))?;
-        for (desc, result, duration) in std::mem::replace(&mut self.results, Vec::new()) {
+        for hook in &state.failed_hooks {
+            self.write_message(&*format!(
+                "<testcase classname=\"{}\" name=\"{}\" time=\"0\">",
+                hook.kind.as_str(),
+                EscapedXml(&hook.name)
+            ))?;
+            self.write_message(&*format!("<error type=\"{}\"/>", hook.kind.as_str()))?;
+            self.write_system_out(Some(&hook.stdout))?;
+            self.write_message("</testcase>")?;
+        }
+        let results = std::mem::replace(&mut self.results, Vec::new());
+        for (desc, result, duration, stdout, details) in results {
             let (class_name, test_name) = parse_class_name(&desc);
             match result {
-                TestResult::TrIgnored => { /* no-op */ }
+                // Ignored tests are only listed with `--report-details`.
+                TestResult::TrIgnored if !state.options.report_details => {}
+                TestResult::TrIgnored => {
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"0\">",
+                        class_name, test_name,
+                    ))?;
+                    self.write_properties(details.as_ref())?;
+                    match desc.ignore_message {
+                        Some(msg) => self.write_message(&*format!(
+                            "<skipped message=\"{}\"/>",
+                            EscapedXml(msg)
+                        ))?,
+                        None => self.write_message("<skipped/>")?,
+                    }
+                    self.write_message("</testcase>")?;
+                }
                 TestResult::TrFailed => {
                     self.write_message(&*format!(
                         "<testcase classname=\"{}\" \
@@ -85,7 +166,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(details.as_ref())?;
                     self.write_message("<failure type=\"assert\"/>")?;
+                    self.write_system_out(stdout.as_deref())?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -97,11 +180,16 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    self.write_message(&*format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
+                    self.write_properties(details.as_ref())?;
+                    self.write_message(&*format!(
+                        "<failure message=\"{}\" type=\"assert\"/>",
+                        EscapedXml(&m)
+                    ))?;
+                    self.write_system_out(stdout.as_deref())?;
                     self.write_message("</testcase>")?;
                 }
 
-                TestResult::TrTimedFail => {
+                TestResult::TrTimedFail | TestResult::TrTimedOut => {
                     self.write_message(&*format!(
                         "<testcase classname=\"{}\" \
                          name=\"{}\" time=\"{}\">",
@@ -109,16 +197,65 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(details.as_ref())?;
                     self.write_message("<failure type=\"timeout\"/>")?;
+                    self.write_system_out(stdout.as_deref())?;
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrFlaky(attempts) => {
+                    self.write_message(&*format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_properties(details.as_ref())?;
+                    for _ in 0..attempts {
+                        self.write_message("<flakyFailure type=\"assert\"/>")?;
+                    }
+                    self.write_system_out(stdout.as_deref())?;
                     self.write_message("</testcase>")?;
                 }
 
                 TestResult::TrBench(ref b) => {
+                    match state.compare_with_baseline(&desc, &b.ns_iter_summ) {
+                        Some(BaselineComparison::Changed(change))
+                            if change.verdict == Verdict::Regressed =>
+                        {
+                            self.write_message(&*format!(
+                                "<testcase classname=\"benchmark::{}\" \
+                                 name=\"{}\" time=\"{}\">",
+                                class_name, test_name, b.ns_iter_summ.sum
+                            ))?;
+                            self.write_message(&*format!(
+                                "<failure message=\"{}\" type=\"regression\"/>",
+                                fmt_bench_change(&change)
+                            ))?;
+                            self.write_message("</testcase>")?;
+                        }
+                        _ => {
+                            self.write_message(&*format!(
+                                "<testcase classname=\"benchmark::{}\" \
+                                 name=\"{}\" time=\"{}\" />",
+                                class_name, test_name, b.ns_iter_summ.sum
+                            ))?;
+                        }
+                    }
+                }
+
+                TestResult::TrOk if stdout.is_some() || details.is_some() => {
                     self.write_message(&*format!(
-                        "<testcase classname=\"benchmark::{}\" \
-                         name=\"{}\" time=\"{}\" />",
-                        class_name, test_name, b.ns_iter_summ.sum
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
                     ))?;
+                    self.write_properties(details.as_ref())?;
+                    self.write_system_out(stdout.as_deref())?;
+                    self.write_message("</testcase>")?;
                 }
 
                 TestResult::TrOk => {
@@ -139,7 +276,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.out.write_all(b"\n")?;
 
-        Ok(state.failed == 0)
+        Ok(state.succeeded())
     }
 }
 
@@ -178,3 +315,30 @@ fn parse_class_name_doc(desc: &TestDesc) -> (String, String) {
 fn parse_class_name_integration(desc: &TestDesc) -> (String, String) {
     (String::from("integration"), String::from(desc.name.as_slice()))
 }
+
+/// Escapes text for XML attributes and elements. Line breaks are escaped as
+/// well to keep the whole document on one line, and characters that XML does
+/// not allow are replaced.
+struct EscapedXml<'a>(&'a str);
+
+impl std::fmt::Display for EscapedXml<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                '\n' => f.write_str("&#10;")?,
+                '\r' => f.write_str("&#13;")?,
+                '\t' => f.write_str("&#9;")?,
+                '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {
+                    f.write_char(char::REPLACEMENT_CHARACTER)?
+                }
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,9 +2,10 @@
 
 use crate::{
     console::ConsoleTestState,
+    event::{FailedHook, TestRunDetails},
     test_result::TestResult,
     time,
-    types::{TestDesc, TestName},
+    types::{TestDesc, TestHookKind, TestName},
 };
 
 mod json;
@@ -12,7 +13,7 @@
 mod pretty;
 mod terse;
 
-pub(crate) use self::json::JsonFormatter;
+pub(crate) use self::json::{EscapedString, JsonFormatter};
 pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::pretty::PrettyFormatter;
 pub(crate) use self::terse::TerseFormatter;
@@ -26,12 +27,40 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        details: Option<&TestRunDetails>,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()>;
+    fn write_hook_failure(&mut self, hook: &FailedHook) -> io::Result<()>;
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool>;
 }
 
+/// The output of the failed setup and teardown hooks, followed by their names.
+pub(crate) fn fmt_failed_hooks(failed_hooks: &[FailedHook]) -> String {
+    let mut out = String::from("\nsetup and teardown failures:\n");
+    for hook in failed_hooks {
+        out.push_str(&format!("\n---- {} {} stdout ----\n", hook.kind.as_str(), hook.name));
+        out.push_str(&String::from_utf8_lossy(&hook.stdout));
+    }
+    out.push_str("\nsetup and teardown failures:\n");
+    for hook in failed_hooks {
+        out.push_str(&format!("    {} {}\n", hook.kind.as_str(), hook.name));
+    }
+    out
+}
+
+/// The number of failed hooks of each kind, for the summary of the test run.
+pub(crate) fn fmt_failed_hooks_count(failed_hooks: &[FailedHook]) -> String {
+    let mut out = String::new();
+    for kind in [TestHookKind::Setup, TestHookKind::Teardown] {
+        let count = failed_hooks.iter().filter(|hook| hook.kind == kind).count();
+        if count > 0 {
+            out.push_str(&format!("; {count} {} failed", kind.as_str()));
+        }
+    }
+    out
+}
+
 pub(crate) fn write_stderr_delimiter(test_output: &mut Vec<u8>, test_name: &TestName) {
     match test_output.last() {
         Some(b'\n') => (),
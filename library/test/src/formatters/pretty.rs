@@ -1,9 +1,11 @@
 use std::{io, io::prelude::Write};
 
-use super::OutputFormatter;
+use super::{fmt_failed_hooks, fmt_failed_hooks_count, OutputFormatter};
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestState, OutputLocation},
+    event::{FailedHook, TestRunDetails},
+    helpers::baseline::fmt_baseline_comparison,
     term,
     test_result::TestResult,
     time,
@@ -57,6 +59,14 @@ pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (timed out)", term::color::RED)
+    }
+
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("ok (flaky)", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -168,6 +178,10 @@ pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flaky_tests, "flaky tests")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -208,8 +222,9 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        _: Option<&TestRunDetails>,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
@@ -222,8 +237,13 @@ fn write_result(
             TestResult::TrBench(ref bs) => {
                 self.write_bench()?;
                 self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state.compare_with_baseline(desc, &bs.ns_iter_summ) {
+                    self.write_plain(&format!(" {}", fmt_baseline_comparison(&comparison)))?;
+                }
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut => self.write_timed_out()?,
+            TestResult::TrFlaky(_) => self.write_flaky()?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -238,11 +258,23 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_hook_failure(&mut self, hook: &FailedHook) -> io::Result<()> {
+        self.write_plain(&format!("{} {} ... ", hook.kind.as_str(), hook.name))?;
+        self.write_failed()?;
+        self.write_plain("\n")
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
-        let success = state.failed == 0;
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+        if !state.failed_hooks.is_empty() {
+            self.write_plain(&fmt_failed_hooks(&state.failed_hooks))?;
+        }
+        let success = state.succeeded();
         if !success {
             if !state.failures.is_empty() {
                 self.write_failures(state)?;
@@ -269,6 +301,12 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(&s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(&format!("; {} flaky", state.flaky))?;
+        }
+
+        self.write_plain(&fmt_failed_hooks_count(&state.failed_hooks))?;
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(&time_str)?;
@@ -1,9 +1,11 @@
 use std::{io, io::prelude::Write};
 
-use super::OutputFormatter;
+use super::{fmt_failed_hooks, fmt_failed_hooks_count, OutputFormatter};
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestState, OutputLocation},
+    event::{FailedHook, TestRunDetails},
+    helpers::baseline::fmt_baseline_comparison,
     term,
     test_result::TestResult,
     time,
@@ -55,6 +57,10 @@ pub fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("i", term::color::YELLOW)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("f", term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -154,6 +160,17 @@ pub fn write_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
         Ok(())
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky tests:\n")?;
+        let mut flaky_tests: Vec<_> =
+            state.flaky_tests.iter().map(|(f, _)| f.name.to_string()).collect();
+        flaky_tests.sort();
+        for name in &flaky_tests {
+            self.write_plain(&format!("    {name}\n"))?;
+        }
+        Ok(())
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -195,21 +212,28 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         _: Option<&time::TestExecTime>,
+        _: Option<&TestRunDetails>,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
-                self.write_failed()
-            }
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrTimedOut => self.write_failed(),
             TestResult::TrIgnored => self.write_ignored(),
+            TestResult::TrFlaky(_) => self.write_flaky(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
                     self.write_test_name(desc)?;
                 }
                 self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state.compare_with_baseline(desc, &bs.ns_iter_summ) {
+                    self.write_plain(&format!(" {}", fmt_baseline_comparison(&comparison)))?;
+                }
+                self.write_plain("\n")
             }
         }
     }
@@ -222,12 +246,23 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_hook_failure(&mut self, _: &FailedHook) -> io::Result<()> {
+        // The failure is only shown at the end, to not break the lines of results.
+        Ok(())
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_outputs(state)?;
         }
-        let success = state.failed == 0;
-        if !success {
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+        if !state.failed_hooks.is_empty() {
+            self.write_plain(&fmt_failed_hooks(&state.failed_hooks))?;
+        }
+        let success = state.succeeded();
+        if state.failed > 0 {
             self.write_failures(state)?;
         }
 
@@ -247,6 +282,12 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(&s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(&format!("; {} flaky", state.flaky))?;
+        }
+
+        self.write_plain(&fmt_failed_hooks_count(&state.failed_hooks))?;
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(&time_str)?;
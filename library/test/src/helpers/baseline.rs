@@ -0,0 +1,202 @@
+//! Saving the results of benchmarks and comparing them with the results of an
+//! earlier run, see `--save-baseline` and `--baseline`.
+
+use super::json::{self, JsonValue};
+use crate::bench::SAMPLE_COUNT;
+use crate::formatters::EscapedString;
+use crate::stats::Summary;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How much slower, in percent, a benchmark must be to be reported as a
+/// regression, unless `--regression-threshold` is given.
+pub const DEFAULT_REGRESSION_THRESHOLD_PCT: f64 = 5.0;
+
+/// What a baseline records about a benchmark. The times are in nanoseconds
+/// per iteration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchStats {
+    pub median: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub samples: usize,
+}
+
+impl BenchStats {
+    pub fn new(summary: &Summary) -> BenchStats {
+        BenchStats {
+            median: summary.median,
+            mean: summary.mean,
+            std_dev: summary.std_dev,
+            samples: SAMPLE_COUNT,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Improved,
+    Unchanged,
+    Regressed,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Improved => "improved",
+            Verdict::Unchanged => "unchanged",
+            Verdict::Regressed => "regressed",
+        }
+    }
+}
+
+/// The change of the mean time of a benchmark relative to the baseline, with
+/// its 95% confidence interval, all in percent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchChange {
+    pub change_pct: f64,
+    pub low_pct: f64,
+    pub high_pct: f64,
+    pub verdict: Verdict,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BaselineComparison {
+    /// The baseline has no results for the benchmark.
+    Missing,
+    Changed(BenchChange),
+}
+
+/// Compares the results of a benchmark with those of the baseline. It only
+/// counts as a regression or an improvement if the whole confidence interval
+/// is beyond the threshold, so noise alone rarely fails a run.
+pub fn compare(old: &BenchStats, new: &BenchStats, threshold_pct: f64) -> Option<BenchChange> {
+    if old.mean <= 0.0 || old.samples < 2 || new.samples < 2 {
+        return None;
+    }
+    // Welch's interval for the difference of the means. With this many
+    // samples, the t-distribution is close enough to the normal distribution.
+    let std_err = (old.std_dev.powi(2) / old.samples as f64
+        + new.std_dev.powi(2) / new.samples as f64)
+        .sqrt();
+    let diff = new.mean - old.mean;
+    let margin = 1.96 * std_err;
+    let pct = |ns: f64| ns / old.mean * 100.0;
+
+    let (change_pct, low_pct, high_pct) = (pct(diff), pct(diff - margin), pct(diff + margin));
+    let verdict = if low_pct > threshold_pct {
+        Verdict::Regressed
+    } else if high_pct < -threshold_pct {
+        Verdict::Improved
+    } else {
+        Verdict::Unchanged
+    };
+    Some(BenchChange { change_pct, low_pct, high_pct, verdict })
+}
+
+pub fn fmt_bench_change(change: &BenchChange) -> String {
+    format!(
+        "{:+.2}% [{:+.2}%, {:+.2}%], {}",
+        change.change_pct,
+        change.low_pct,
+        change.high_pct,
+        change.verdict.as_str()
+    )
+}
+
+pub fn fmt_baseline_comparison(comparison: &BaselineComparison) -> String {
+    match comparison {
+        BaselineComparison::Missing => "(not in baseline)".to_string(),
+        BaselineComparison::Changed(change) => format!("(change: {})", fmt_bench_change(change)),
+    }
+}
+
+/// The file of the baseline with the given name, in the directory given by
+/// `RUST_TEST_BASELINE_DIR`. By default, baselines are stored in the target
+/// directory of the test binary, e.g. `target/release/bench-baselines` for a
+/// binary in `target/release/deps`, so that they don't depend on the working
+/// directory.
+pub fn baseline_path(name: &str) -> io::Result<PathBuf> {
+    let dir = match env::var_os("RUST_TEST_BASELINE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let exe = env::current_exe()?;
+            let mut dir = exe.parent().unwrap_or(Path::new(""));
+            if dir.file_name().map_or(false, |name| name == "deps") {
+                dir = dir.parent().unwrap_or(dir);
+            }
+            dir.join("bench-baselines")
+        }
+    };
+    Ok(dir.join(format!("{name}.json")))
+}
+
+pub fn read_baseline(path: &Path) -> Result<BTreeMap<String, BenchStats>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let benchmarks = match json::parse(&contents)?.get("benchmarks") {
+        Some(JsonValue::Object(benchmarks)) => benchmarks.clone(),
+        _ => return Err("expected an object with the field `benchmarks`".to_string()),
+    };
+
+    let mut baseline = BTreeMap::new();
+    for (name, stats) in benchmarks {
+        let field = |field| {
+            stats
+                .get(field)
+                .and_then(|value| value.as_f64())
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| format!("invalid or missing `{field}` of benchmark `{name}`"))
+        };
+        let stats = BenchStats {
+            median: field("median")?,
+            mean: field("mean")?,
+            std_dev: field("std_dev")?,
+            samples: field("samples")? as usize,
+        };
+        baseline.insert(name, stats);
+    }
+    Ok(baseline)
+}
+
+/// Adds the results to the baseline, replacing earlier results of the same
+/// benchmarks. The results of other benchmarks, such as those of other
+/// benchmark executables of the same crate, are kept.
+pub fn save_baseline(path: &Path, results: &BTreeMap<String, BenchStats>) -> io::Result<()> {
+    let mut baseline = if path.exists() {
+        read_baseline(path).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("could not read the baseline {}: {e}", path.display()),
+            )
+        })?
+    } else {
+        BTreeMap::new()
+    };
+    baseline.extend(results.iter().map(|(name, stats)| (name.clone(), *stats)));
+
+    let mut contents = String::from("{\n  \"benchmarks\": {");
+    for (i, (name, stats)) in baseline.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        write!(
+            contents,
+            "{}\n    \"{}\": {{ \"median\": {}, \"mean\": {}, \"std_dev\": {}, \"samples\": {} }}",
+            separator,
+            EscapedString(name),
+            stats.median,
+            stats.mean,
+            stats.std_dev,
+            stats.samples
+        )
+        .unwrap();
+    }
+    contents.push_str("\n  }\n}\n");
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+}
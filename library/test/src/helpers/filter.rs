@@ -0,0 +1,470 @@
+//! Selecting tests by the patterns given with `--filter-glob` and
+//! `--filter-regex`, and by the locations given with `--at`.
+
+use crate::options::SourceLocation;
+use crate::types::TestDesc;
+
+/// A pattern that selects the tests whose names it matches.
+#[derive(Debug)]
+pub enum NamePattern {
+    Glob(Glob),
+    Regex(Regex),
+}
+
+impl NamePattern {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NamePattern::Glob(glob) => glob.matches(name),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// Returns whether the test contains the location, comparing the paths by
+/// their trailing components, since the source file of a test is relative to
+/// wherever the compiler was invoked.
+pub fn test_contains_location(desc: &TestDesc, location: &SourceLocation) -> bool {
+    if desc.source_file.is_empty() || !(desc.start_line..=desc.end_line).contains(&location.line) {
+        return false;
+    }
+    let normalize = |path: &str| path.replace('\\', "/").trim_start_matches("./").to_string();
+    let (test_file, file) = (normalize(desc.source_file), normalize(&location.file));
+    let ends_with = |path: &str, suffix: &str| {
+        path.strip_suffix(suffix).map_or(false, |prefix| prefix.is_empty() || prefix.ends_with('/'))
+    };
+    ends_with(&test_file, &file) || ends_with(&file, &test_file)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GlobToken {
+    Char(char),
+    /// `?`, any character of a path segment
+    AnyChar,
+    /// `*`, any part of a path segment
+    Segment,
+    /// `**`, anything at all
+    Anything,
+}
+
+/// A glob matched against the whole name of a test, like `mod::*::parse_*`.
+/// `*` and `?` do not match the `:` of path separators, `**` matches
+/// anything.
+#[derive(Debug)]
+pub struct Glob {
+    tokens: Vec<GlobToken>,
+}
+
+impl Glob {
+    pub fn new(glob: &str) -> Glob {
+        let mut tokens = Vec::new();
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '*' if chars.next_if_eq(&'*').is_some() => GlobToken::Anything,
+                '*' => GlobToken::Segment,
+                '?' => GlobToken::AnyChar,
+                c => GlobToken::Char(c),
+            };
+            tokens.push(token);
+        }
+        Glob { tokens }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        // `matched[j]` is whether the tokens so far match the first `j`
+        // characters of the name.
+        let mut matched = vec![false; name.len() + 1];
+        matched[0] = true;
+        for token in &self.tokens {
+            let mut next = vec![false; name.len() + 1];
+            for j in 0..=name.len() {
+                next[j] = match token {
+                    GlobToken::Char(c) => j > 0 && matched[j - 1] && name[j - 1] == *c,
+                    GlobToken::AnyChar => j > 0 && matched[j - 1] && name[j - 1] != ':',
+                    GlobToken::Segment => {
+                        matched[j] || (j > 0 && next[j - 1] && name[j - 1] != ':')
+                    }
+                    GlobToken::Anything => matched[j] || (j > 0 && next[j - 1]),
+                };
+            }
+            matched = next;
+        }
+        matched[name.len()]
+    }
+}
+
+/// A regular expression that matches a test if it matches any part of its
+/// name. It supports the common subset of the usual syntaxes: `.`, `^`, `$`,
+/// character classes like `[a-z]`, `\d`, `\w` and `\s`, groups, alternatives
+/// and the quantifiers `*`, `+`, `?` and `{n,m}`.
+///
+/// The expression is compiled to a program for a nondeterministic automaton,
+/// which runs all of its threads in lockstep, so matching takes linear time
+/// even for expressions like `(a*)*b`.
+#[derive(Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+#[derive(Debug)]
+struct Node {
+    atom: Atom,
+    min: usize,
+    max: Option<usize>,
+}
+
+#[derive(Debug)]
+enum Atom {
+    Char(char),
+    AnyChar,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(char),
+    AnyChar,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    /// Continues at both of the instructions.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// More instructions than any sensible filter needs, which bounds the size of
+/// repetitions like `(a{100}){100}`.
+const MAX_PROGRAM_LEN: usize = 10_000;
+
+impl Regex {
+    pub fn new(regex: &str) -> Result<Regex, String> {
+        let mut parser = RegexParser { chars: regex.chars().collect(), pos: 0 };
+        let alternatives = parser.parse_alternatives()?;
+        if parser.peek().is_some() {
+            return Err("unmatched `)`".to_string());
+        }
+        let mut program = Vec::new();
+        compile_alternatives(&alternatives, &mut program)?;
+        program.push(Inst::Match);
+        Ok(Regex { program })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        // `added[pc]` is one more than the last position at which a thread
+        // reached the instruction, so every instruction runs once per position.
+        let mut added = vec![0; self.program.len()];
+        let mut threads = Vec::new();
+        for pos in 0..=text.len() {
+            // A match may start anywhere.
+            if self.add_thread(&mut threads, &mut added, 0, &text, pos) {
+                return true;
+            }
+            let mut next = Vec::new();
+            for pc in threads {
+                let advances = match (&self.program[pc], text.get(pos)) {
+                    (Inst::Char(expected), Some(c)) => c == expected,
+                    (Inst::AnyChar, Some(_)) => true,
+                    (Inst::Class { ranges, negated }, Some(c)) => {
+                        ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(c)) != *negated
+                    }
+                    _ => false,
+                };
+                if advances && self.add_thread(&mut next, &mut added, pc + 1, &text, pos + 1) {
+                    return true;
+                }
+            }
+            threads = next;
+        }
+        false
+    }
+
+    /// Adds the thread at `pc` to `threads` after following all jumps and
+    /// assertions, and returns whether it reached the end of the program.
+    fn add_thread(
+        &self,
+        threads: &mut Vec<usize>,
+        added: &mut [usize],
+        pc: usize,
+        text: &[char],
+        pos: usize,
+    ) -> bool {
+        if added[pc] == pos + 1 {
+            return false;
+        }
+        added[pc] = pos + 1;
+        match self.program[pc] {
+            Inst::Split(first, second) => {
+                self.add_thread(threads, added, first, text, pos)
+                    || self.add_thread(threads, added, second, text, pos)
+            }
+            Inst::Jump(target) => self.add_thread(threads, added, target, text, pos),
+            Inst::Start => pos == 0 && self.add_thread(threads, added, pc + 1, text, pos),
+            Inst::End => pos == text.len() && self.add_thread(threads, added, pc + 1, text, pos),
+            Inst::Match => true,
+            Inst::Char(_) | Inst::AnyChar | Inst::Class { .. } => {
+                threads.push(pc);
+                false
+            }
+        }
+    }
+}
+
+fn push_inst(program: &mut Vec<Inst>, inst: Inst) -> Result<usize, String> {
+    if program.len() >= MAX_PROGRAM_LEN {
+        return Err("the regular expression is too large".to_string());
+    }
+    program.push(inst);
+    Ok(program.len() - 1)
+}
+
+fn compile_alternatives(alternatives: &[Vec<Node>], program: &mut Vec<Inst>) -> Result<(), String> {
+    let mut jumps_to_end = Vec::new();
+    for (i, nodes) in alternatives.iter().enumerate() {
+        if i + 1 == alternatives.len() {
+            compile_nodes(nodes, program)?;
+        } else {
+            let split = push_inst(program, Inst::Split(0, 0))?;
+            compile_nodes(nodes, program)?;
+            jumps_to_end.push(push_inst(program, Inst::Jump(0))?);
+            program[split] = Inst::Split(split + 1, program.len());
+        }
+    }
+    let end = program.len();
+    for jump in jumps_to_end {
+        program[jump] = Inst::Jump(end);
+    }
+    Ok(())
+}
+
+fn compile_nodes(nodes: &[Node], program: &mut Vec<Inst>) -> Result<(), String> {
+    for node in nodes {
+        for _ in 0..node.min {
+            compile_atom(&node.atom, program)?;
+        }
+        match node.max {
+            None => {
+                let split = push_inst(program, Inst::Split(0, 0))?;
+                compile_atom(&node.atom, program)?;
+                push_inst(program, Inst::Jump(split))?;
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            Some(max) => {
+                // Each optional repetition may skip all of the remaining ones.
+                let mut splits = Vec::new();
+                for _ in node.min..max {
+                    splits.push(push_inst(program, Inst::Split(0, 0))?);
+                    compile_atom(&node.atom, program)?;
+                }
+                let end = program.len();
+                for split in splits {
+                    program[split] = Inst::Split(split + 1, end);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn compile_atom(atom: &Atom, program: &mut Vec<Inst>) -> Result<(), String> {
+    let inst = match atom {
+        Atom::Char(c) => Inst::Char(*c),
+        Atom::AnyChar => Inst::AnyChar,
+        Atom::Class { ranges, negated } => {
+            Inst::Class { ranges: ranges.clone(), negated: *negated }
+        }
+        Atom::Start => Inst::Start,
+        Atom::End => Inst::End,
+        Atom::Group(alternatives) => return compile_alternatives(alternatives, program),
+    };
+    push_inst(program, inst)?;
+    Ok(())
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+/// What follows a `\`: a single character, or one of the classes `\d`, `\w`
+/// and `\s`, or their negations.
+enum Escape {
+    Char(char),
+    Class(&'static [(char, char)], bool),
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn parse_alternatives(&mut self) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![self.parse_nodes()?];
+        while self.eat('|') {
+            alternatives.push(self.parse_nodes()?);
+        }
+        Ok(alternatives)
+    }
+
+    fn parse_nodes(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            self.pos += 1;
+            let atom = match c {
+                '.' => Atom::AnyChar,
+                '^' => Atom::Start,
+                '$' => Atom::End,
+                '(' => {
+                    // Whether the group captures makes no difference here.
+                    if self.eat('?') && !self.eat(':') {
+                        return Err("only non-capturing groups `(?:...)` are supported".to_string());
+                    }
+                    let alternatives = self.parse_alternatives()?;
+                    if !self.eat(')') {
+                        return Err("unclosed group".to_string());
+                    }
+                    Atom::Group(alternatives)
+                }
+                '[' => self.parse_class()?,
+                '\\' => match self.parse_escape()? {
+                    Escape::Char(c) => Atom::Char(c),
+                    Escape::Class(ranges, negated) => {
+                        Atom::Class { ranges: ranges.to_vec(), negated }
+                    }
+                },
+                '*' | '+' | '?' | '{' => {
+                    return Err(format!("`{c}` does not follow anything to repeat"));
+                }
+                c => Atom::Char(c),
+            };
+            let (min, max) = self.parse_quantifier()?;
+            nodes.push(Node { atom, min, max });
+        }
+        Ok(nodes)
+    }
+
+    fn parse_quantifier(&mut self) -> Result<(usize, Option<usize>), String> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let end = match self.chars[self.pos..].iter().position(|&c| c == '}') {
+                    Some(end) => self.pos + end,
+                    None => return Err("unclosed repetition `{`".to_string()),
+                };
+                let range: String = self.chars[self.pos + 1..end].iter().collect();
+                let parse = |n: &str| {
+                    n.trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid repetition `{{{range}}}`"))
+                };
+                let (min, max) = match range.split_once(',') {
+                    None => (parse(&range)?, Some(parse(&range)?)),
+                    Some((min, max)) if max.trim().is_empty() => (parse(min)?, None),
+                    Some((min, max)) => (parse(min)?, Some(parse(max)?)),
+                };
+                if max.map_or(false, |max| max < min) {
+                    return Err(format!("invalid repetition `{{{range}}}`"));
+                }
+                self.pos = end;
+                (min, max)
+            }
+            _ => return Ok((1, Some(1))),
+        };
+        self.pos += 1;
+        // Lazy quantifiers match the same names.
+        self.eat('?');
+        Ok((min, max))
+    }
+
+    fn parse_escape(&mut self) -> Result<Escape, String> {
+        let c = self.next().ok_or_else(|| "trailing `\\`".to_string())?;
+        Ok(match c {
+            'd' | 'D' => Escape::Class(DIGIT, c == 'D'),
+            'w' | 'W' => Escape::Class(WORD, c == 'W'),
+            's' | 'S' => Escape::Class(SPACE, c == 'S'),
+            'n' => Escape::Char('\n'),
+            't' => Escape::Char('\t'),
+            c if c.is_ascii_alphanumeric() => {
+                return Err(format!("unsupported escape sequence `\\{c}`"));
+            }
+            c => Escape::Char(c),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Atom, String> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = match self.next() {
+                Some(']') if !first => break,
+                Some('\\') => match self.parse_escape()? {
+                    Escape::Char(c) => c,
+                    Escape::Class(class, false) => {
+                        ranges.extend_from_slice(class);
+                        first = false;
+                        continue;
+                    }
+                    Escape::Class(_, true) => {
+                        return Err("negated classes are not supported in `[...]`".to_string());
+                    }
+                },
+                Some(c) => c,
+                None => return Err("unclosed character class `[`".to_string()),
+            };
+            first = false;
+            let is_range = self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']');
+            if is_range && self.chars.get(self.pos + 1).is_some() {
+                self.pos += 1;
+                let hi = match self.next() {
+                    Some('\\') => match self.parse_escape()? {
+                        Escape::Char(hi) => hi,
+                        Escape::Class(..) => {
+                            return Err("invalid range in character class".to_string())
+                        }
+                    },
+                    Some(hi) => hi,
+                    None => unreachable!(),
+                };
+                if hi < c {
+                    return Err(format!("invalid range `{c}-{hi}` in character class"));
+                }
+                ranges.push((c, hi));
+            } else {
+                ranges.push((c, c));
+            }
+        }
+        Ok(Atom::Class { ranges, negated })
+    }
+}
@@ -0,0 +1,105 @@
+//! Splitting the tests into shards, see `--shard-index` and `--shard-count`.
+
+use super::json;
+use crate::options::Shard;
+use crate::types::TestDescAndFn;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Only keeps the tests of the given shard. Every process that is given the
+/// same tests computes the same shards, no matter on which machine it runs.
+pub fn shard_tests(shard: &Shard, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
+    let shards = match &shard.durations {
+        Some(durations) => partition_by_time(shard.count, &tests, durations),
+        None => tests.iter().map(|test| shard_by_name(shard.count, test)).collect(),
+    };
+    tests
+        .into_iter()
+        .zip(shards)
+        .filter_map(|(test, index)| (index == shard.index).then_some(test))
+        .collect()
+}
+
+/// Assigns the test to a shard based on its name alone, so that adding or
+/// removing other tests does not move it to another shard.
+fn shard_by_name(count: usize, test: &TestDescAndFn) -> usize {
+    // FNV-1a, which unlike `DefaultHasher` is guaranteed not to change
+    // between releases.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in test.desc.name.as_slice().bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % count as u64) as usize
+}
+
+/// Assigns the tests to shards such that all shards take about the same time,
+/// by giving each test, from the slowest to the fastest, to the shard with
+/// the least total time so far. Tests without a known duration are assumed to
+/// take the average time of the tests with one.
+fn partition_by_time(
+    count: usize,
+    tests: &[TestDescAndFn],
+    durations: &HashMap<String, Duration>,
+) -> Vec<usize> {
+    let known: Vec<Duration> =
+        tests.iter().filter_map(|test| durations.get(test.desc.name.as_slice())).copied().collect();
+    let average = if known.is_empty() {
+        Duration::ZERO
+    } else {
+        known.iter().sum::<Duration>() / known.len() as u32
+    };
+
+    let mut order: Vec<(Duration, usize)> = tests
+        .iter()
+        .enumerate()
+        .map(|(i, test)| {
+            let duration = if test.desc.ignore {
+                Duration::ZERO
+            } else {
+                durations.get(test.desc.name.as_slice()).copied().unwrap_or(average)
+            };
+            (duration, i)
+        })
+        .collect();
+    // The tests are sorted by name, so ties are broken deterministically.
+    order.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    // The total time and the number of tests of every shard. The latter
+    // spreads out tests that take no time at all.
+    let mut loads = vec![(Duration::ZERO, 0usize); count];
+    let mut shards = vec![0; tests.len()];
+    for (duration, i) in order {
+        let (index, load) = loads.iter_mut().enumerate().min_by_key(|(_, load)| **load).unwrap();
+        load.0 += duration;
+        load.1 += 1;
+        shards[i] = index;
+    }
+    shards
+}
+
+/// Reads the execution times of the tests from the output of an earlier run
+/// with `--format=json --report-time`. All other lines are ignored.
+pub fn read_test_durations(path: &Path) -> Result<HashMap<String, Duration>, String> {
+    let report = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut durations = HashMap::new();
+    for line in report.lines() {
+        let event = match json::parse(line) {
+            Ok(event) => event,
+            Err(_) => continue,
+        };
+        if event.get("type").and_then(|ty| ty.as_str()) != Some("test") {
+            continue;
+        }
+        let name = event.get("name").and_then(|name| name.as_str());
+        let exec_time = event.get("exec_time").and_then(|time| time.as_f64());
+        if let (Some(name), Some(exec_time)) = (name, exec_time) {
+            if exec_time.is_finite() && exec_time >= 0.0 && exec_time < u64::MAX as f64 {
+                durations.insert(name.to_string(), Duration::from_secs_f64(exec_time));
+            }
+        }
+    }
+    Ok(durations)
+}
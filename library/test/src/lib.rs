@@ -24,7 +24,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard, ShouldPanic};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -39,12 +39,12 @@ pub mod test {
         filter_tests,
         helpers::metrics::{Metric, MetricMap},
         options::{Concurrent, Options, RunIgnored, RunStrategy, ShouldPanic},
-        run_test, test_main, test_main_static,
+        run_test, test_main, test_main_static, test_main_static_with_hooks,
         test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk},
         time::{TestExecTime, TestTimeOptions},
         types::{
             DynTestFn, DynTestName, StaticBenchFn, StaticTestFn, StaticTestName, TestDesc,
-            TestDescAndFn, TestId, TestName, TestType,
+            TestDescAndFn, TestFailureReport, TestHook, TestId, TestName, TestType,
         },
     };
 }
@@ -55,10 +55,10 @@ pub mod test {
     io::prelude::Write,
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
-    sync::mpsc::{channel, Sender},
+    sync::mpsc::{self, channel, Sender},
     sync::{Arc, Mutex},
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 pub mod bench;
@@ -77,9 +77,11 @@ pub mod test {
 #[cfg(test)]
 mod tests;
 
-use event::{CompletedTest, TestEvent};
+use event::{CompletedTest, FailedHook, TestEvent, TestRunDetails};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::filter::test_contains_location;
+use helpers::shard::shard_tests;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::{Concurrent, RunStrategy};
 use test_result::*;
@@ -93,6 +95,15 @@ pub mod test {
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
 pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Options>) {
+    test_main_with_hooks(args, tests, options, TestHooks::default())
+}
+
+fn test_main_with_hooks(
+    args: &[String],
+    tests: Vec<TestDescAndFn>,
+    options: Option<Options>,
+    hooks: TestHooks<'_>,
+) {
     let mut opts = match cli::parse_opts(args) {
         Some(Ok(o)) => o,
         Some(Err(msg)) => {
@@ -110,7 +121,7 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
             process::exit(ERROR_EXIT_CODE);
         }
     } else {
-        match console::run_tests_console(&opts, tests) {
+        match console::run_tests_console_with_hooks(&opts, tests, hooks) {
             Ok(true) => {}
             Ok(false) => process::exit(ERROR_EXIT_CODE),
             Err(e) => {
@@ -127,9 +138,23 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=unwind.
 pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    test_main_static_with_hooks(tests, &[], &[])
+}
+
+/// Like `test_main_static`, but also runs the functions marked with
+/// `#[test_setup]` before and those marked with `#[test_teardown]` after all
+/// of the tests.
+///
+/// This is the entry point for the main function generated by `rustc --test`
+/// when panic=unwind and the crate has such functions.
+pub fn test_main_static_with_hooks(
+    tests: &[&TestDescAndFn],
+    setup: &[TestHook],
+    teardown: &[TestHook],
+) {
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, None)
+    test_main_with_hooks(&args, owned_tests, None, TestHooks { setup, teardown })
 }
 
 /// A variant optimized for invocation with a static test vector.
@@ -141,6 +166,22 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
+    test_main_static_abort_with_hooks(tests, &[], &[])
+}
+
+/// Like `test_main_static_abort`, but also runs the functions marked with
+/// `#[test_setup]` before and those marked with `#[test_teardown]` after all
+/// of the tests. The hooks only run in the main process, so the tests, which
+/// run in subprocesses, only see their effects outside of the process, such as
+/// files and environment variables. A hook that panics aborts the test run.
+///
+/// This is the entry point for the main function generated by `rustc --test`
+/// when panic=abort and the crate has such functions.
+pub fn test_main_static_abort_with_hooks(
+    tests: &[&TestDescAndFn],
+    setup: &[TestHook],
+    teardown: &[TestHook],
+) {
     // If we're being run in SpawnedSecondary mode, run the test here. run_test
     // will then exit the process.
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
@@ -161,7 +202,8 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
 
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
+    let options = Some(Options::new().panic_abort(true));
+    test_main_with_hooks(&args, owned_tests, options, TestHooks { setup, teardown })
 }
 
 /// Clones static values for putting into a dynamic vector, which test_main()
@@ -192,6 +234,18 @@ pub fn assert_test_result<T: Termination>(result: T) {
 pub fn run_tests<F>(
     opts: &TestOpts,
     tests: Vec<TestDescAndFn>,
+    notify_about_test_event: F,
+) -> io::Result<()>
+where
+    F: FnMut(TestEvent) -> io::Result<()>,
+{
+    run_tests_with_hooks(opts, tests, TestHooks::default(), notify_about_test_event)
+}
+
+pub(crate) fn run_tests_with_hooks<F>(
+    opts: &TestOpts,
+    tests: Vec<TestDescAndFn>,
+    hooks: TestHooks<'_>,
     mut notify_about_test_event: F,
 ) -> io::Result<()>
 where
@@ -242,7 +296,7 @@ struct TimeoutEntry {
     let event = TestEvent::TeFiltered(filtered_descs, shuffle_seed);
     notify_about_test_event(event)?;
 
-    let (filtered_tests, filtered_benchs): (Vec<_>, _) = filtered_tests
+    let (filtered_tests, mut filtered_benchs): (Vec<_>, Vec<_>) = filtered_tests
         .into_iter()
         .enumerate()
         .map(|(i, e)| (TestId(i), e))
@@ -258,6 +312,15 @@ struct TimeoutEntry {
     }
     let mut pending = 0;
 
+    // The hooks only run if there are any tests to run.
+    let run_hooks = !remaining.is_empty() || (opts.bench_benchmarks && !filtered_benchs.is_empty());
+    if run_hooks
+        && !run_test_hooks(opts, TestHookKind::Setup, hooks.setup, &mut notify_about_test_event)?
+    {
+        remaining.clear();
+        filtered_benchs.clear();
+    }
+
     let (tx, rx) = channel::<CompletedTest>();
     let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
         RunStrategy::SpawnPrimary
@@ -268,6 +331,24 @@ struct TimeoutEntry {
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
 
+    // The tests that are run again if they fail, see `--retries`. Only tests
+    // given as function pointers can be run more than once, the output of the
+    // others notes that they were not retried.
+    let retryable: HashMap<TestId, (TestDesc, fn())> = if opts.retries > 0 {
+        remaining
+            .iter()
+            .filter_map(|(id, test)| match test.testfn {
+                StaticTestFn(f) => Some((*id, (test.desc.clone(), f))),
+                _ => None,
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    // How many times each of them failed so far, and the output of the first
+    // failure.
+    let mut failed_attempts: HashMap<TestId, (usize, Vec<u8>)> = HashMap::new();
+
     fn get_timed_out_tests(
         running_tests: &TestMap,
         timeout_queue: &mut VecDeque<TimeoutEntry>,
@@ -293,15 +374,75 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         })
     }
 
+    // Returns the test to run again if it failed and has retries left.
+    // Otherwise, a test that passed after failing is marked as flaky.
+    fn retry_test(
+        retries: usize,
+        retryable: &HashMap<TestId, (TestDesc, fn())>,
+        failed_attempts: &mut HashMap<TestId, (usize, Vec<u8>)>,
+        completed_test: &mut CompletedTest,
+    ) -> Option<TestDescAndFn> {
+        let id = completed_test.id;
+        match completed_test.result {
+            TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut => {
+                let (desc, f) = match retryable.get(&id) {
+                    Some(retryable) => retryable,
+                    None => {
+                        if retries > 0 {
+                            writeln!(
+                                &mut completed_test.stdout,
+                                "note: not retried, `--retries` only applies to tests that are \
+                                 plain functions"
+                            )
+                            .unwrap();
+                        }
+                        return None;
+                    }
+                };
+                let (attempts, _) =
+                    failed_attempts.entry(id).or_insert_with(|| (0, completed_test.stdout.clone()));
+                *attempts += 1;
+                (*attempts <= retries)
+                    .then(|| TestDescAndFn { desc: desc.clone(), testfn: StaticTestFn(*f) })
+            }
+            TrOk => {
+                if let Some((attempts, stdout)) = failed_attempts.remove(&id) {
+                    completed_test.result = TrFlaky(attempts);
+                    completed_test.stdout = stdout;
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
     if concurrency == 1 {
         while !remaining.is_empty() {
-            let (id, test) = remaining.pop().unwrap();
+            let (id, mut test) = remaining.pop().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
-            let join_handle =
-                run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone(), Concurrent::No);
-            assert!(join_handle.is_none());
-            let completed_test = rx.recv().unwrap();
+            let completed_test = loop {
+                let join_handle = run_test(
+                    opts,
+                    !opts.run_tests,
+                    id,
+                    test,
+                    run_strategy,
+                    tx.clone(),
+                    Concurrent::No,
+                );
+                assert!(join_handle.is_none());
+                let mut completed_test = rx.recv().unwrap();
+                match retry_test(
+                    opts.retries,
+                    &retryable,
+                    &mut failed_attempts,
+                    &mut completed_test,
+                ) {
+                    Some(retry) => test = retry,
+                    None => break completed_test,
+                }
+            };
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
@@ -313,8 +454,11 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
 
-                let event = TestEvent::TeWait(desc.clone());
-                notify_about_test_event(event)?; //here no pad
+                // A test that is run again was already announced.
+                if !failed_attempts.contains_key(&id) {
+                    let event = TestEvent::TeWait(desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
                 let join_handle = run_test(
                     opts,
                     !opts.run_tests,
@@ -364,9 +508,17 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
+            pending -= 1;
+            if let Some(retry) =
+                retry_test(opts.retries, &retryable, &mut failed_attempts, &mut completed_test)
+            {
+                timeout_queue.retain(|entry| entry.id != completed_test.id);
+                remaining.push((completed_test.id, retry));
+                continue;
+            }
+
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
-            pending -= 1;
         }
     }
 
@@ -382,9 +534,43 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             notify_about_test_event(event)?;
         }
     }
+
+    if run_hooks {
+        run_test_hooks(opts, TestHookKind::Teardown, hooks.teardown, &mut notify_about_test_event)?;
+    }
     Ok(())
 }
 
+/// Runs the hooks in order, reports those that panic, and returns whether all
+/// of them succeeded. Like tests, the hooks have their output captured.
+fn run_test_hooks<F>(
+    opts: &TestOpts,
+    kind: TestHookKind,
+    hooks: &[TestHook],
+    notify_about_test_event: &mut F,
+) -> io::Result<bool>
+where
+    F: FnMut(TestEvent) -> io::Result<()>,
+{
+    let mut succeeded = true;
+    for hook in hooks {
+        let data = Arc::new(Mutex::new(Vec::new()));
+        if !opts.nocapture {
+            io::set_output_capture(Some(data.clone()));
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| __rust_begin_short_backtrace(hook.hook_fn)));
+        io::set_output_capture(None);
+
+        if result.is_err() {
+            succeeded = false;
+            let stdout = data.lock().unwrap_or_else(|e| e.into_inner()).to_vec();
+            let hook = FailedHook { kind, name: hook.name.to_string(), stdout };
+            notify_about_test_event(TestEvent::TeHookFailed(hook))?;
+        }
+    }
+    Ok(succeeded)
+}
+
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
     let matches_filter = |test: &TestDescAndFn, filter: &str| {
@@ -396,9 +582,17 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         }
     };
 
-    // Remove tests that don't match the test filter
-    if !opts.filters.is_empty() {
-        filtered.retain(|test| opts.filters.iter().any(|filter| matches_filter(test, filter)));
+    // Remove tests that don't match any of the test filters, patterns or locations
+    if !opts.filters.is_empty()
+        || !opts.filter_patterns.is_empty()
+        || !opts.filter_locations.is_empty()
+    {
+        filtered.retain(|test| {
+            let name = test.desc.name.as_slice();
+            opts.filters.iter().any(|filter| matches_filter(test, filter))
+                || opts.filter_patterns.iter().any(|pattern| pattern.matches(name))
+                || opts.filter_locations.iter().any(|at| test_contains_location(&test.desc, at))
+        });
     }
 
     // Skip tests that match any of the skip filters
@@ -424,6 +618,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
+    // Only keep the tests of this shard
+    if let Some(shard) = &opts.shard {
+        filtered = shard_tests(shard, filtered);
+    }
+
     filtered
 }
 
@@ -473,6 +672,8 @@ struct TestRunOpts {
         pub nocapture: bool,
         pub concurrency: Concurrent,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
+        pub details: bool,
     }
 
     fn run_test_inner(
@@ -490,18 +691,22 @@ fn run_test_inner(
                 id,
                 desc,
                 opts.nocapture,
-                opts.time.is_some(),
+                opts.time.is_some() || opts.details,
                 testfn,
                 monitor_ch,
                 opts.time,
+                opts.timeout,
+                opts.details,
             ),
             RunStrategy::SpawnPrimary => spawn_test_subprocess(
                 id,
                 desc,
                 opts.nocapture,
-                opts.time.is_some(),
+                opts.time.is_some() || opts.details,
                 monitor_ch,
                 opts.time,
+                opts.timeout,
+                opts.details,
             ),
         };
 
@@ -529,8 +734,14 @@ fn run_test_inner(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, concurrency, time: opts.time_options };
+    let test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        concurrency,
+        time: opts.time_options,
+        timeout: time::get_test_timeout(&desc, opts.test_timeout),
+        details: opts.options.report_details,
+    };
 
     match testfn {
         DynBenchFn(benchfn) => {
@@ -583,6 +794,8 @@ fn run_test_in_process(
     testfn: Box<dyn FnOnce() + Send>,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
+    report_details: bool,
 ) {
     // Buffer for capturing standard I/O
     let data = Arc::new(Mutex::new(Vec::new()));
@@ -591,21 +804,33 @@ fn run_test_in_process(
         io::set_output_capture(Some(data.clone()));
     }
 
-    let start = report_time.then(Instant::now);
-    let result = catch_unwind(AssertUnwindSafe(testfn));
-    let exec_time = start.map(|start| {
-        let duration = start.elapsed();
-        TestExecTime(duration)
+    let mut details = report_details.then(|| TestRunDetails {
+        start_time: SystemTime::now(),
+        thread_name: thread::current().name().map(str::to_owned),
+        failure: None,
     });
+    let start = (report_time || timeout.is_some()).then(Instant::now);
+    let result = catch_unwind(AssertUnwindSafe(testfn));
+    let duration = start.map(|start| start.elapsed());
+    let exec_time = if report_time { duration.map(TestExecTime) } else { None };
 
     io::set_output_capture(None);
 
+    if let (Some(details), Err(e)) = (&mut details, &result) {
+        details.failure = e.downcast_ref::<TestFailureReport>().map(|report| report.json.clone());
+    }
+
+    // The test cannot be stopped when it runs in this process, so it only
+    // fails once it is done.
+    let timed_out = timeout.zip(duration).map_or(false, |(timeout, duration)| duration > timeout);
     let test_result = match result {
+        _ if timed_out => TrTimedOut,
         Ok(()) => calc_result(&desc, Ok(()), &time_opts, &exec_time),
         Err(e) => calc_result(&desc, Err(e.as_ref()), &time_opts, &exec_time),
     };
     let stdout = data.lock().unwrap_or_else(|e| e.into_inner()).to_vec();
-    let message = CompletedTest::new(id, desc, test_result, exec_time, stdout);
+    let mut message = CompletedTest::new(id, desc, test_result, exec_time, stdout);
+    message.details = details;
     monitor_ch.send(message).unwrap();
 }
 
@@ -616,7 +841,14 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
+    report_details: bool,
 ) {
+    let details = report_details.then(|| TestRunDetails {
+        start_time: SystemTime::now(),
+        thread_name: None,
+        failure: None,
+    });
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
@@ -626,10 +858,17 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -646,6 +885,13 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if timed_out {
+            let timeout = timeout.unwrap();
+            writeln!(&mut test_output, "note: test timed out after {timeout:?} and was killed")
+                .unwrap();
+            return (TrTimedOut, test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -660,10 +906,56 @@ fn spawn_test_subprocess(
         (result, test_output, exec_time)
     })();
 
-    let message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    let mut message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    message.details = details;
     monitor_ch.send(message).unwrap();
 }
 
+/// Like `Command::output`, but kills the process if it does not exit within
+/// `timeout`, which is indicated by the returned `bool`.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    // Reads the output while the process runs, so that it does not block on a
+    // full pipe.
+    fn read_pipe<R: io::Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let mut data = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut data);
+            }
+            let _ = tx.send(data);
+        });
+        rx
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            child.kill()?;
+            break (child.wait()?, true);
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(10)));
+    };
+
+    // Processes started by the test may keep the pipes open after it exited,
+    // so only wait for the rest of the output for a moment.
+    let wait = Duration::from_secs(1);
+    let stdout = stdout.recv_timeout(wait).unwrap_or_default();
+    let stderr = stderr.recv_timeout(wait).unwrap_or_default();
+    Ok((process::Output { status, stdout, stderr }, timed_out))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Send>) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
@@ -1,5 +1,11 @@
 //! Enums denoting options for test execution.
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::helpers::baseline::BenchStats;
+
 /// Whether to execute tests concurrently or not
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Concurrent {
@@ -43,6 +49,35 @@ pub enum OutputFormat {
     Junit,
 }
 
+/// Which of several shards of the tests to run
+#[derive(Clone, Debug)]
+pub struct Shard {
+    /// The shard to run, starting at 0
+    pub index: usize,
+    /// The number of shards
+    pub count: usize,
+    /// The execution times of a previous run, to balance the shards by time
+    pub durations: Option<HashMap<String, Duration>>,
+}
+
+/// A line of a source file, to run the tests that contain it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+/// Saving the results of the benchmarks and comparing them with earlier ones
+#[derive(Clone, Debug)]
+pub struct BenchBaseline {
+    /// Where to save the results of this run
+    pub save: Option<PathBuf>,
+    /// The name and the results of the baseline to compare with
+    pub compare: Option<(String, BTreeMap<String, BenchStats>)>,
+    /// How much slower, in percent, a benchmark must be to be a regression
+    pub threshold_pct: f64,
+}
+
 /// Whether ignored test should be run or not
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RunIgnored {
@@ -70,11 +105,12 @@ pub enum RunStrategy {
 pub struct Options {
     pub display_output: bool,
     pub panic_abort: bool,
+    pub report_details: bool,
 }
 
 impl Options {
     pub fn new() -> Options {
-        Options { display_output: false, panic_abort: false }
+        Options { display_output: false, panic_abort: false, report_details: false }
     }
 
     pub fn display_output(mut self, display_output: bool) -> Options {
@@ -86,4 +122,9 @@ pub fn panic_abort(mut self, panic_abort: bool) -> Options {
         self.panic_abort = panic_abort;
         self
     }
+
+    pub fn report_details(mut self, report_details: bool) -> Options {
+        self.report_details = report_details;
+        self
+    }
 }
@@ -1,10 +1,14 @@
 use super::*;
 
+use crate::helpers::baseline::{
+    baseline_path, compare, read_baseline, save_baseline, BenchStats, Verdict,
+};
+use crate::helpers::filter::Regex;
 use crate::{
     bench::Bencher,
     console::OutputLocation,
-    formatters::PrettyFormatter,
-    options::OutputFormat,
+    formatters::{JunitFormatter, OutputFormatter, PrettyFormatter},
+    options::{OutputFormat, SourceLocation},
     test::{
         filter_tests,
         parse_opts,
@@ -27,8 +31,10 @@
     },
     time::{TestTimeOptions, TimeThreshold},
 };
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 impl TestOpts {
     fn new() -> TestOpts {
@@ -36,6 +42,8 @@ fn new() -> TestOpts {
             list: false,
             filters: vec![],
             filter_exact: false,
+            filter_patterns: vec![],
+            filter_locations: vec![],
             force_run_in_process: false,
             exclude_should_panic: false,
             run_ignored: RunIgnored::No,
@@ -47,9 +55,13 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
+            bench_baseline: None,
             options: Options::new(),
         }
     }
@@ -62,6 +74,11 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 name: StaticTestName("1"),
                 ignore: true,
                 ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                end_line: 0,
+                item_path: None,
+                timeout_secs: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -74,6 +91,11 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 name: StaticTestName("2"),
                 ignore: false,
                 ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                end_line: 0,
+                item_path: None,
+                timeout_secs: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -94,6 +116,11 @@ fn f() {
             name: StaticTestName("whatever"),
             ignore: true,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -115,6 +142,11 @@ fn f() {}
             name: StaticTestName("whatever"),
             ignore: true,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -140,6 +172,11 @@ fn f() {
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::Yes,
             compile_fail: false,
             no_run: false,
@@ -165,6 +202,11 @@ fn f() {
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::YesWithMessage("error message"),
             compile_fail: false,
             no_run: false,
@@ -195,6 +237,11 @@ fn f() {
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::YesWithMessage(expected),
             compile_fail: false,
             no_run: false,
@@ -229,6 +276,11 @@ fn f() {
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::YesWithMessage(expected),
             compile_fail: false,
             no_run: false,
@@ -255,6 +307,11 @@ fn f() {}
                 name: StaticTestName("whatever"),
                 ignore: false,
                 ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                end_line: 0,
+                item_path: None,
+                timeout_secs: None,
                 should_panic,
                 compile_fail: false,
                 no_run: false,
@@ -282,6 +339,40 @@ fn f() {}
     }
 }
 
+// FIXME: Re-enable emscripten once it can catch panics again (introduced by #65251)
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_failure_report() {
+    fn f() {
+        let report = TestFailureReport { json: r#"{"kind":"compile_error"}"#.to_string() };
+        panic::resume_unwind(Box::new(report));
+    }
+    let desc = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::DocTest,
+        },
+        testfn: DynTestFn(Box::new(f)),
+    };
+    let test_opts = TestOpts { options: Options::new().report_details(true), ..TestOpts::new() };
+    let (tx, rx) = channel();
+    run_test(&test_opts, false, TestId(0), desc, RunStrategy::InProcess, tx, Concurrent::No);
+    let completed_test = rx.recv().unwrap();
+    assert_eq!(completed_test.result, TrFailed);
+    let failure = completed_test.details.unwrap().failure;
+    assert_eq!(failure.as_deref(), Some(r#"{"kind":"compile_error"}"#));
+}
+
 fn report_time_test_template(report_time: bool) -> Option<TestExecTime> {
     fn f() {}
     let desc = TestDescAndFn {
@@ -289,6 +380,11 @@ fn f() {}
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -324,6 +420,11 @@ fn f() {}
             name: StaticTestName("whatever"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -358,11 +459,280 @@ fn test_error_on_exceed() {
     assert_eq!(result, TestResult::TrOk);
 }
 
+fn timeout_test_template(timeout_secs: Option<u64>) -> TestResult {
+    fn f() {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let desc = TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName("whatever"),
+            ignore: false,
+            ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: DynTestFn(Box::new(f)),
+    };
+
+    let test_opts = TestOpts { test_timeout: Some(Duration::from_millis(10)), ..TestOpts::new() };
+    let (tx, rx) = channel();
+    run_test(&test_opts, false, TestId(0), desc, RunStrategy::InProcess, tx, Concurrent::No);
+    rx.recv().unwrap().result
+}
+
+#[test]
+fn test_timed_out() {
+    assert_eq!(timeout_test_template(None), TestResult::TrTimedOut);
+}
+
+#[test]
+fn test_timeout_attribute_overrides_default() {
+    assert_eq!(timeout_test_template(Some(60)), TestResult::TrOk);
+}
+
+#[test]
+fn test_timeout_attribute_without_default() {
+    let desc = |timeout_secs| TestDesc {
+        name: StaticTestName("whatever"),
+        ignore: false,
+        ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs,
+        should_panic: ShouldPanic::No,
+        compile_fail: false,
+        no_run: false,
+        test_type: TestType::Unknown,
+    };
+
+    assert_eq!(time::get_test_timeout(&desc(Some(5)), None), Some(Duration::from_secs(5)));
+    assert_eq!(time::get_test_timeout(&desc(None), None), None);
+    let default = Some(Duration::from_secs(1));
+    assert_eq!(time::get_test_timeout(&desc(None), default), default);
+}
+
+#[test]
+fn parse_test_timeout_option() {
+    let args = |timeout: &str| {
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            format!("--test-timeout={timeout}"),
+        ];
+        parse_opts(&args).unwrap()
+    };
+
+    assert_eq!(args("30").unwrap().test_timeout, Some(Duration::from_secs(30)));
+    assert!(args("0").is_err());
+    assert!(args("soon").is_err());
+}
+
+// Runs the tests with `--retries=2` and returns their results by name.
+fn run_tests_with_retries(
+    tests: Vec<TestDescAndFn>,
+    test_threads: usize,
+) -> Vec<(String, TestResult)> {
+    let opts = TestOpts {
+        run_tests: true,
+        retries: 2,
+        test_threads: Some(test_threads),
+        ..TestOpts::new()
+    };
+    let mut results = Vec::new();
+    run_tests(&opts, tests, |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            results.push((completed_test.desc.name.to_string(), completed_test.result));
+        }
+        Ok(())
+    })
+    .unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+fn retried_tests(flaky: fn(), failing: fn()) -> Vec<TestDescAndFn> {
+    let desc = |name| TestDesc { name: StaticTestName(name), ..typed_test_desc(TestType::Unknown) };
+    vec![
+        TestDescAndFn { desc: desc("failing"), testfn: StaticTestFn(failing) },
+        TestDescAndFn { desc: desc("flaky"), testfn: StaticTestFn(flaky) },
+        TestDescAndFn { desc: desc("passing"), testfn: StaticTestFn(|| {}) },
+    ]
+}
+
+#[test]
+fn retry_failing_tests() {
+    static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+    static FAILING_RUNS: AtomicUsize = AtomicUsize::new(0);
+    fn flaky() {
+        assert!(FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) > 0);
+    }
+    fn failing() {
+        FAILING_RUNS.fetch_add(1, Ordering::SeqCst);
+        panic!();
+    }
+
+    let results = run_tests_with_retries(retried_tests(flaky, failing), 1);
+    assert_eq!(
+        results,
+        vec![
+            ("failing".to_string(), TestResult::TrFailed),
+            ("flaky".to_string(), TestResult::TrFlaky(1)),
+            ("passing".to_string(), TestResult::TrOk),
+        ]
+    );
+    assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 2);
+    assert_eq!(FAILING_RUNS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn retry_failing_tests_concurrently() {
+    static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+    static FAILING_RUNS: AtomicUsize = AtomicUsize::new(0);
+    fn flaky() {
+        assert!(FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) > 1);
+    }
+    fn failing() {
+        FAILING_RUNS.fetch_add(1, Ordering::SeqCst);
+        panic!();
+    }
+
+    let results = run_tests_with_retries(retried_tests(flaky, failing), 4);
+    assert_eq!(
+        results,
+        vec![
+            ("failing".to_string(), TestResult::TrFailed),
+            ("flaky".to_string(), TestResult::TrFlaky(2)),
+            ("passing".to_string(), TestResult::TrOk),
+        ]
+    );
+    assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 3);
+    assert_eq!(FAILING_RUNS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn dyn_tests_are_not_retried() {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let test = TestDescAndFn {
+        desc: TestDesc { name: StaticTestName("dyn"), ..typed_test_desc(TestType::Unknown) },
+        testfn: DynTestFn(Box::new(|| {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            panic!();
+        })),
+    };
+    let opts = TestOpts { run_tests: true, retries: 2, ..TestOpts::new() };
+    let mut completed = None;
+    run_tests(&opts, vec![test], |event| {
+        if let TestEvent::TeResult(completed_test) = event {
+            completed = Some(completed_test);
+        }
+        Ok(())
+    })
+    .unwrap();
+    let completed_test = completed.unwrap();
+    assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+    assert_eq!(completed_test.result, TestResult::TrFailed);
+    assert!(String::from_utf8_lossy(&completed_test.stdout).contains("note: not retried"));
+}
+
+#[test]
+fn parse_retries_option() {
+    let args = |retries: &str| {
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            format!("--retries={retries}"),
+        ];
+        parse_opts(&args).unwrap()
+    };
+
+    assert_eq!(args("3").unwrap().retries, 3);
+    assert!(args("many").is_err());
+}
+
+// Runs the tests with the given hooks and returns the failed hooks and the names of the tests
+// that were run.
+fn run_tests_with_test_hooks(
+    tests: Vec<TestDescAndFn>,
+    hooks: TestHooks<'_>,
+) -> (Vec<(TestHookKind, String)>, Vec<String>) {
+    let opts = TestOpts { run_tests: true, test_threads: Some(1), ..TestOpts::new() };
+    let mut failed_hooks = Vec::new();
+    let mut results = Vec::new();
+    run_tests_with_hooks(&opts, tests, hooks, |event| {
+        match event {
+            TestEvent::TeHookFailed(hook) => failed_hooks.push((hook.kind, hook.name)),
+            TestEvent::TeResult(completed_test) => {
+                results.push(completed_test.desc.name.to_string())
+            }
+            _ => {}
+        }
+        Ok(())
+    })
+    .unwrap();
+    (failed_hooks, results)
+}
+
+#[test]
+fn setup_failure_skips_tests() {
+    static TEARDOWNS: AtomicUsize = AtomicUsize::new(0);
+    fn teardown() {
+        TEARDOWNS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let tests = vec![TestDescAndFn {
+        desc: TestDesc { name: StaticTestName("a"), ..typed_test_desc(TestType::Unknown) },
+        testfn: StaticTestFn(|| panic!("tests should not run after a failed setup")),
+    }];
+    let hooks = TestHooks {
+        setup: &[
+            TestHook { name: "setup_ok", hook_fn: || {} },
+            TestHook { name: "setup_fail", hook_fn: || panic!() },
+        ],
+        teardown: &[TestHook { name: "teardown", hook_fn: teardown }],
+    };
+    let (failed_hooks, results) = run_tests_with_test_hooks(tests, hooks);
+    assert_eq!(failed_hooks, vec![(TestHookKind::Setup, "setup_fail".to_string())]);
+    assert!(results.is_empty());
+    assert_eq!(TEARDOWNS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn teardown_failure_is_reported() {
+    let tests = vec![TestDescAndFn {
+        desc: TestDesc { name: StaticTestName("a"), ..typed_test_desc(TestType::Unknown) },
+        testfn: StaticTestFn(|| {}),
+    }];
+    let hooks = TestHooks {
+        setup: &[],
+        teardown: &[
+            TestHook { name: "teardown_fail", hook_fn: || panic!() },
+            TestHook { name: "teardown_ok", hook_fn: || {} },
+        ],
+    };
+    let (failed_hooks, results) = run_tests_with_test_hooks(tests, hooks);
+    assert_eq!(failed_hooks, vec![(TestHookKind::Teardown, "teardown_fail".to_string())]);
+    assert_eq!(results, vec!["a".to_string()]);
+}
+
 fn typed_test_desc(test_type: TestType) -> TestDesc {
     TestDesc {
         name: StaticTestName("whatever"),
         ignore: false,
         ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -475,6 +845,11 @@ pub fn exclude_should_panic_option() {
             name: StaticTestName("3"),
             ignore: false,
             ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            end_line: 0,
+            item_path: None,
+            timeout_secs: None,
             should_panic: ShouldPanic::Yes,
             compile_fail: false,
             no_run: false,
@@ -499,6 +874,11 @@ fn tests() -> Vec<TestDescAndFn> {
                     name: StaticTestName(name),
                     ignore: false,
                     ignore_message: None,
+                    source_file: "",
+                    start_line: 0,
+                    end_line: 0,
+                    item_path: None,
+                    timeout_secs: None,
                     should_panic: ShouldPanic::No,
                     compile_fail: false,
                     no_run: false,
@@ -566,6 +946,110 @@ fn tests() -> Vec<TestDescAndFn> {
     assert_eq!(exact.len(), 2);
 }
 
+#[test]
+pub fn pattern_filter_match() {
+    let args = |args: &[&str]| {
+        let mut all_args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        all_args.extend(args.iter().map(|arg| arg.to_string()));
+        parse_opts(&all_args).unwrap()
+    };
+    let names = |filters: &[&str]| -> Vec<String> {
+        filter_tests(&args(filters).unwrap(), sample_tests())
+            .into_iter()
+            .map(|test| test.desc.name.as_slice().to_string())
+            .collect()
+    };
+
+    assert_eq!(names(&["--filter-glob=isize::*"]), ["isize::test_pow", "isize::test_to_str"]);
+    assert_eq!(names(&["--filter-glob=*::test"]), ["sha1::test"]);
+    assert_eq!(names(&["--filter-glob=*test"]), Vec::<String>::new());
+    assert_eq!(names(&["--filter-glob=**_ignored_*"]).len(), 5);
+    assert_eq!(names(&["--filter-glob=test::parse_*_flag"]).len(), 2);
+    assert_eq!(names(&["--filter-glob=isize::test_p?w"]), ["isize::test_pow"]);
+    assert_eq!(
+        names(&["--filter-regex=^isize::test_(pow|to_str)$"]),
+        ["isize::test_pow", "isize::test_to_str"]
+    );
+    assert_eq!(names(&["--filter-regex=[0-9]"]), ["sha1::test"]);
+    assert_eq!(names(&["--filter-regex=::s\\w{2}t_"]), ["test::sort_tests"]);
+    assert_eq!(names(&["--filter-regex=x{2,}"]), Vec::<String>::new());
+    // A test is run if it matches any of the filters
+    assert_eq!(
+        names(&["sha1", "--filter-glob=*::test_pow", "--filter-regex=sort"]),
+        ["isize::test_pow", "sha1::test", "test::sort_tests"]
+    );
+
+    assert!(args(&["--filter-regex=(a"]).is_err());
+    assert!(args(&["--filter-regex=a)"]).is_err());
+    assert!(args(&["--filter-regex=[a"]).is_err());
+    assert!(args(&["--filter-regex=*a"]).is_err());
+    assert!(parse_opts(&["progname".to_string(), "--filter-glob=a".to_string()]).unwrap().is_err());
+}
+
+#[test]
+fn regex_filter_takes_linear_time() {
+    let regex = Regex::new("^(a*)*b$").unwrap();
+    let name = "a".repeat(10_000);
+    assert!(!regex.is_match(&name));
+    assert!(regex.is_match(&format!("{name}b")));
+    assert!(Regex::new("(a{100}){1000}").is_err());
+}
+
+#[test]
+pub fn location_filter_match() {
+    let test = |name, source_file, start_line, end_line| TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName(name),
+            source_file,
+            start_line,
+            end_line,
+            ..typed_test_desc(TestType::UnitTest)
+        },
+        testfn: DynTestFn(Box::new(move || {})),
+    };
+    let tests = || {
+        vec![
+            test("lexer::a", "src/lexer.rs", 10, 14),
+            test("lexer::b", "src/lexer.rs", 16, 20),
+            test("parser::a", "src/parser/lexer.rs", 10, 14),
+            test("doctest", "", 0, 0),
+        ]
+    };
+    let names = |at: &[&str]| -> Vec<String> {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(at.iter().map(|at| format!("--at={at}")));
+        let opts = parse_opts(&args).unwrap().unwrap();
+        filter_tests(&opts, tests())
+            .into_iter()
+            .map(|test| test.desc.name.as_slice().to_string())
+            .collect()
+    };
+
+    assert_eq!(names(&["src/lexer.rs:10"]), ["lexer::a"]);
+    assert_eq!(names(&["src/lexer.rs:20"]), ["lexer::b"]);
+    assert_eq!(names(&["src/lexer.rs:15"]), Vec::<String>::new());
+    assert_eq!(names(&["lexer.rs:12"]), ["lexer::a", "parser::a"]);
+    assert_eq!(names(&["/home/me/project/src/lexer.rs:12"]), ["lexer::a"]);
+    assert_eq!(names(&["./src/parser/lexer.rs:12"]), ["parser::a"]);
+    assert_eq!(names(&["src\\lexer.rs:17", "src/parser/lexer.rs:11"]), ["lexer::b", "parser::a"]);
+    assert_eq!(names(&["er.rs:12"]), Vec::<String>::new());
+
+    let parse = |at: &str| {
+        parse_opts(&[
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            format!("--at={at}"),
+        ])
+        .unwrap()
+    };
+    assert!(parse("src/lib.rs").is_err());
+    assert!(parse("src/lib.rs:0").is_err());
+    assert!(parse(":12").is_err());
+    assert!(parse("C:\\src\\lib.rs:x").is_err());
+    let opts = parse("C:\\src\\lib.rs:7").unwrap();
+    assert_eq!(opts.filter_locations, [SourceLocation { file: "C:\\src\\lib.rs".into(), line: 7 }]);
+}
+
 fn sample_tests() -> Vec<TestDescAndFn> {
     let names = vec![
         "sha1::test".to_string(),
@@ -588,6 +1072,11 @@ fn testfn() {}
                 name: DynTestName((*name).clone()),
                 ignore: false,
                 ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                end_line: 0,
+                item_path: None,
+                timeout_secs: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -692,6 +1181,99 @@ pub fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+fn sharded_names(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<String> {
+    filter_tests(opts, tests).into_iter().map(|test| test.desc.name.to_string()).collect()
+}
+
+#[test]
+pub fn shards_cover_all_tests_once() {
+    let mut all_names = Vec::new();
+    for index in 0..3 {
+        let shard = Shard { index, count: 3, durations: None };
+        let opts = TestOpts { shard: Some(shard), ..TestOpts::new() };
+        all_names.extend(sharded_names(&opts, sample_tests()));
+    }
+    all_names.sort();
+
+    assert_eq!(all_names, sharded_names(&TestOpts::new(), sample_tests()));
+}
+
+#[test]
+pub fn shard_does_not_depend_on_other_tests() {
+    let shard = Shard { index: 1, count: 3, durations: None };
+    let opts = TestOpts { shard: Some(shard), ..TestOpts::new() };
+
+    let mut fewer_tests = sample_tests();
+    let removed = fewer_tests.remove(0).desc.name.to_string();
+
+    let mut expected = sharded_names(&opts, sample_tests());
+    expected.retain(|name| *name != removed);
+    assert_eq!(sharded_names(&opts, fewer_tests), expected);
+}
+
+#[test]
+pub fn partition_by_time_balances_shards() {
+    // One slow test, and ten fast ones which take as long together.
+    let durations: HashMap<String, Duration> = sample_tests()
+        .iter()
+        .map(|test| {
+            let name = test.desc.name.to_string();
+            let time = if name == "sha1::test" { 10 } else { 1 };
+            (name, Duration::from_secs(time))
+        })
+        .collect();
+
+    let shard = |index| Shard { index, count: 2, durations: Some(durations.clone()) };
+    let first =
+        sharded_names(&TestOpts { shard: Some(shard(0)), ..TestOpts::new() }, sample_tests());
+    let second =
+        sharded_names(&TestOpts { shard: Some(shard(1)), ..TestOpts::new() }, sample_tests());
+
+    assert_eq!(first, vec!["sha1::test".to_string()]);
+    assert_eq!(second.len(), 10);
+}
+
+#[test]
+pub fn read_test_durations_from_json_report() {
+    let path = env::temp_dir().join(format!("libtest-report-{}.json", process::id()));
+    let report = concat!(
+        r#"{ "type": "suite", "event": "started", "test_count": 2 }"#,
+        "\n",
+        r#"{ "type": "test", "event": "started", "name": "a::\"quoted\"" }"#,
+        "\n",
+        r#"{ "type": "test", "name": "a::\"quoted\"", "event": "ok", "exec_time": 1.5 }"#,
+        "\n",
+        "output that is not JSON\n",
+        r#"{ "type": "test", "name": "b", "event": "ignored" }"#,
+        "\n",
+    );
+    std::fs::write(&path, report).unwrap();
+    let durations = helpers::shard::read_test_durations(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(durations.len(), 1);
+    assert_eq!(durations["a::\"quoted\""], Duration::from_millis(1500));
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = |args: &[&str]| {
+        let mut all_args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        all_args.extend(args.iter().map(|arg| arg.to_string()));
+        parse_opts(&all_args).unwrap()
+    };
+
+    let opts = args(&["--shard-index=1", "--shard-count=4"]).unwrap();
+    let shard = opts.shard.unwrap();
+    assert_eq!((shard.index, shard.count), (1, 4));
+    assert!(shard.durations.is_none());
+
+    assert!(args(&["--shard-index=4", "--shard-count=4"]).is_err());
+    assert!(args(&["--shard-index=0", "--shard-count=0"]).is_err());
+    assert!(args(&["--shard-index=0"]).is_err());
+    assert!(args(&["--partition-by-time=report.json"]).is_err());
+}
+
 #[test]
 pub fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -739,6 +1321,11 @@ fn f(_: &mut Bencher) {}
         name: StaticTestName("f"),
         ignore: false,
         ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -761,6 +1348,11 @@ fn f(b: &mut Bencher) {
         name: StaticTestName("f"),
         ignore: false,
         ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -771,12 +1363,96 @@ fn f(b: &mut Bencher) {
     rx.recv().unwrap();
 }
 
+fn bench_stats(mean: f64, std_dev: f64) -> BenchStats {
+    BenchStats { median: mean, mean, std_dev, samples: 50 }
+}
+
+#[test]
+pub fn compare_benchmarks_with_baseline() {
+    let old = bench_stats(100.0, 1.0);
+    let verdict = |new| compare(&old, &new, 5.0).unwrap().verdict;
+
+    assert_eq!(verdict(bench_stats(120.0, 1.0)), Verdict::Regressed);
+    assert_eq!(verdict(bench_stats(80.0, 1.0)), Verdict::Improved);
+    // Slower, but not by more than the threshold.
+    assert_eq!(verdict(bench_stats(104.0, 1.0)), Verdict::Unchanged);
+    // Slower by more than the threshold, but too noisy to be sure.
+    assert_eq!(verdict(bench_stats(110.0, 30.0)), Verdict::Unchanged);
+
+    let change = compare(&old, &bench_stats(110.0, 0.0), 5.0).unwrap();
+    assert!((change.change_pct - 10.0).abs() < 1e-9);
+    assert!(change.low_pct < 10.0 && change.high_pct > 10.0);
+
+    assert_eq!(compare(&bench_stats(0.0, 0.0), &bench_stats(10.0, 0.0), 5.0), None);
+}
+
+#[test]
+pub fn save_and_read_baseline() {
+    let path =
+        env::temp_dir().join(format!("libtest-baselines-{}", process::id())).join("main.json");
+    let mut results = BTreeMap::new();
+    results.insert("a::\"quoted\"".to_string(), bench_stats(100.0, 1.5));
+    results.insert("b".to_string(), bench_stats(200.0, 2.0));
+    save_baseline(&path, &results).unwrap();
+
+    // Saving other results keeps those of the other benchmarks.
+    let mut more_results = BTreeMap::new();
+    more_results.insert("b".to_string(), bench_stats(250.0, 2.5));
+    more_results.insert("c".to_string(), bench_stats(0.5, 0.0));
+    save_baseline(&path, &more_results).unwrap();
+
+    let baseline = read_baseline(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(baseline.len(), 3);
+    assert_eq!(baseline["a::\"quoted\""], bench_stats(100.0, 1.5));
+    assert_eq!(baseline["b"], bench_stats(250.0, 2.5));
+    assert_eq!(baseline["c"], bench_stats(0.5, 0.0));
+}
+
+#[test]
+fn baselines_are_stored_in_the_target_directory() {
+    if env::var_os("RUST_TEST_BASELINE_DIR").is_some() {
+        return;
+    }
+    let path = baseline_path("main").unwrap();
+    let exe = env::current_exe().unwrap();
+    assert_eq!(path.file_name().unwrap(), "main.json");
+    assert_eq!(path.parent().unwrap().file_name().unwrap(), "bench-baselines");
+    assert!(exe.starts_with(path.parent().unwrap().parent().unwrap()));
+}
+
+#[test]
+fn parse_bench_baseline_options() {
+    let args = |args: &[&str]| {
+        let mut all_args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        all_args.extend(args.iter().map(|arg| arg.to_string()));
+        parse_opts(&all_args).unwrap()
+    };
+
+    let opts = args(&["--save-baseline=main"]).unwrap();
+    let bench_baseline = opts.bench_baseline.unwrap();
+    assert_eq!(bench_baseline.save, Some(baseline_path("main").unwrap()));
+    assert!(bench_baseline.compare.is_none());
+    assert_eq!(bench_baseline.threshold_pct, 5.0);
+
+    assert!(args(&[]).unwrap().bench_baseline.is_none());
+    assert!(args(&["--baseline=libtest-no-such-baseline"]).is_err());
+    assert!(args(&["--save-baseline=main", "--regression-threshold=-1"]).is_err());
+    assert!(args(&["--regression-threshold=10"]).is_err());
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     let test_a = TestDesc {
         name: StaticTestName("a"),
         ignore: false,
         ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -787,6 +1463,11 @@ fn should_sort_failures_before_printing_them() {
         name: StaticTestName("b"),
         ignore: false,
         ignore_message: None,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -801,6 +1482,7 @@ fn should_sort_failures_before_printing_them() {
         passed: 0,
         failed: 0,
         ignored: 0,
+        flaky: 0,
         filtered_out: 0,
         measured: 0,
         exec_time: None,
@@ -809,6 +1491,10 @@ fn should_sort_failures_before_printing_them() {
         options: Options::new(),
         not_failures: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        failed_hooks: Vec::new(),
+        bench_stats: BTreeMap::new(),
+        bench_baseline: None,
     };
 
     out.write_failures(&st).unwrap();
@@ -821,3 +1507,84 @@ fn should_sort_failures_before_printing_them() {
     let bpos = s.find("b").unwrap();
     assert!(apos < bpos);
 }
+
+fn junit_output(report_details: bool) -> String {
+    let desc = |name, ignore_message: Option<&'static str>| TestDesc {
+        name: StaticTestName(name),
+        ignore: ignore_message.is_some(),
+        ignore_message,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
+        should_panic: ShouldPanic::No,
+        compile_fail: false,
+        no_run: false,
+        test_type: TestType::UnitTest,
+    };
+    let details = TestRunDetails {
+        start_time: UNIX_EPOCH + Duration::from_millis(1500),
+        thread_name: Some("main".to_string()),
+        failure: None,
+    };
+
+    let opts =
+        TestOpts { options: Options::new().report_details(report_details), ..TestOpts::new() };
+    let state = console::ConsoleTestState::new(&opts).unwrap();
+    let mut out = JunitFormatter::new(OutputLocation::Raw(Vec::new()));
+    out.write_run_start(3, None).unwrap();
+    let ignored = desc("ignored", Some("needs <network> & \"root\""));
+    out.write_result(&ignored, &TrIgnored, None, None, b"", &state).unwrap();
+    let failed = TestResult::TrFailedMsg("left != right\n".to_string());
+    out.write_result(&desc("fails", None), &failed, None, Some(&details), b"oops\n", &state)
+        .unwrap();
+    let stdout = "hello <world>\t\u{1}\n".as_bytes();
+    out.write_result(&desc("passes", None), &TrOk, None, Some(&details), stdout, &state).unwrap();
+    out.write_run_finish(&state).unwrap();
+    match out.output_location() {
+        OutputLocation::Raw(m) => String::from_utf8(m.clone()).unwrap(),
+        OutputLocation::Pretty(_) => unreachable!(),
+    }
+}
+
+#[test]
+fn junit_output_is_unchanged_without_report_details() {
+    assert_eq!(
+        junit_output(false),
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><testsuites>"#,
+            r#"<testsuite name="test" package="test" id="0" errors="0" failures="0" tests="0" "#,
+            r#"skipped="0" >"#,
+            r#"<testcase classname="crate" name="fails" time="0">"#,
+            r#"<failure message="left != right&#10;" type="assert"/></testcase>"#,
+            r#"<testcase classname="crate" name="passes" time="0"/>"#,
+            r#"<system-out/><system-err/></testsuite></testsuites>"#,
+            "\n",
+        )
+    );
+}
+
+#[test]
+fn junit_output_with_report_details() {
+    assert_eq!(
+        junit_output(true),
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><testsuites>"#,
+            r#"<testsuite name="test" package="test" id="0" errors="0" failures="0" tests="0" "#,
+            r#"skipped="0" >"#,
+            r#"<testcase classname="crate" name="ignored" time="0">"#,
+            r#"<skipped message="needs &lt;network&gt; &amp; &quot;root&quot;"/></testcase>"#,
+            r#"<testcase classname="crate" name="fails" time="0"><properties>"#,
+            r#"<property name="start_time" value="1.5"/><property name="thread" value="main"/>"#,
+            r#"</properties><failure message="left != right&#10;" type="assert"/>"#,
+            r#"<system-out>oops&#10;</system-out></testcase>"#,
+            r#"<testcase classname="crate" name="passes" time="0"><properties>"#,
+            r#"<property name="start_time" value="1.5"/><property name="thread" value="main"/>"#,
+            "</properties><system-out>hello &lt;world&gt;&#9;\u{fffd}&#10;</system-out>",
+            r#"</testcase>"#,
+            r#"<system-out/><system-err/></testsuite></testsuites>"#,
+            "\n",
+        )
+    );
+}
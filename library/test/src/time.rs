@@ -61,6 +61,12 @@ pub fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the time after which the test fails: the timeout set with
+/// `#[test_timeout]` if there is one, otherwise the one given with `--test-timeout`.
+pub fn get_test_timeout(desc: &TestDesc, test_timeout: Option<Duration>) -> Option<Duration> {
+    desc.timeout_secs.map(Duration::from_secs).or(test_timeout)
+}
+
 /// The measured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
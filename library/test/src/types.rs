@@ -118,6 +118,17 @@ pub struct TestDesc {
     pub name: TestName,
     pub ignore: bool,
     pub ignore_message: Option<&'static str>,
+    /// The file that defines the test, as it was passed to rustc, or an empty
+    /// string if the test has no such location.
+    pub source_file: &'static str,
+    /// The first and the last line of the test in `source_file`, counting from 1.
+    pub start_line: usize,
+    pub end_line: usize,
+    /// For doctests, the path of the item whose documentation contains the
+    /// test, or the headers of the Markdown sections around it.
+    pub item_path: Option<&'static str>,
+    /// The timeout set with `#[test_timeout]`, which takes precedence over `--test-timeout`.
+    pub timeout_secs: Option<u64>,
     pub should_panic: options::ShouldPanic,
     pub compile_fail: bool,
     pub no_run: bool,
@@ -165,3 +176,46 @@ pub struct TestDescAndFn {
     pub desc: TestDesc,
     pub testfn: TestFn,
 }
+
+/// Structured details about why a test failed, for test runners such as
+/// rustdoc that know more about a failure than a panic message would say.
+///
+/// A test that panics with this as the payload, see
+/// [`std::panic::resume_unwind`], fails like any other test. With
+/// `--report-details`, the JSON formatter adds `json`, which must be a JSON
+/// value, to the result of the test as its `"failure"`.
+#[derive(Debug, Clone)]
+pub struct TestFailureReport {
+    pub json: String,
+}
+
+/// A function marked with `#[test_setup]` or `#[test_teardown]`, which the
+/// test harness runs once before or once after all of the tests.
+#[derive(Clone, Copy, Debug)]
+pub struct TestHook {
+    pub name: &'static str,
+    pub hook_fn: fn(),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestHookKind {
+    Setup,
+    Teardown,
+}
+
+impl TestHookKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestHookKind::Setup => "setup",
+            TestHookKind::Teardown => "teardown",
+        }
+    }
+}
+
+/// The hooks of a test run. The tests only run if all of the setup hooks
+/// succeed, while the teardown hooks always run.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct TestHooks<'a> {
+    pub setup: &'a [TestHook],
+    pub teardown: &'a [TestHook],
+}
@@ -664,6 +664,7 @@ macro_rules! describe {
                 test::Clippy,
                 test::RustDemangler,
                 test::CompiletestTest,
+                test::RustdocJsonDiff,
                 test::RustdocJSStd,
                 test::RustdocJSNotStd,
                 test::RustdocGUI,
@@ -22,7 +22,9 @@ def get_local_item(item_id):
 # local IDs have to be in `index`, external ones can sometimes be in `index` but otherwise have
 # to be in `paths`
 def valid_id(item_id):
-    return item_id in crate["index"] or item_id[0] != "0" and item_id in crate["paths"]
+    return item_id in crate["index"] or (
+        item_id in crate["paths"] and crate["paths"][item_id]["crate_id"] != 0
+    )
 
 
 def check_generics(generics):
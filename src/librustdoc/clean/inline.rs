@@ -11,16 +11,19 @@
 use rustc_hir::def_id::DefId;
 use rustc_hir::Mutability;
 use rustc_metadata::creader::{CStore, LoadedMacro};
-use rustc_middle::ty::{self, TyCtxt};
+use rustc_middle::ty::{self, DefIdTree, TyCtxt};
 use rustc_span::hygiene::MacroKind;
 use rustc_span::symbol::{kw, sym, Symbol};
 
 use crate::clean::{
     self, clean_fn_decl_from_did_and_sig, clean_ty_generics, utils, Attributes, AttributesExt,
-    Clean, ImplKind, ItemId, Type, Visibility,
+    Clean, ImplKind, ItemId, ItemIdSet, Type, Visibility,
 };
 use crate::core::DocContext;
+use crate::fold::DocFolder;
 use crate::formats::item_type::ItemType;
+use crate::formats::Impl;
+use crate::passes::Stripper;
 
 type Attrs<'hir> = &'hir [ast::Attribute];
 
@@ -134,6 +137,267 @@ pub(crate) fn try_inline(
     Some(ret)
 }
 
+/// Builds the items of the external items referenced by the public API of the crate that weren't
+/// inlined, so the JSON backend can output them with `--json-external-items`.
+///
+/// This includes the external items referenced in turn by the public API of those items, such as
+/// the types of their public fields or the signatures of their public methods.
+pub(crate) fn build_external_items(cx: &mut DocContext<'_>, krate: &clean::Crate) {
+    let mut referenced = Vec::new();
+    collect_referenced_items(&krate.module, &mut referenced);
+
+    let mut items = Vec::new();
+    let mut visited = FxHashSet::default();
+    while let Some(did) = referenced.pop() {
+        if did.is_local()
+            || cx.inlined.contains(&ItemId::from(did))
+            || (!cx.render_options.document_hidden && cx.tcx.is_doc_hidden(did))
+        {
+            continue;
+        }
+        let name = match cx.cache.external_paths.get(&did) {
+            Some((fqn, kind)) if !matches!(kind, ItemType::Module | ItemType::Primitive) => {
+                match fqn.last() {
+                    Some(&name) => name,
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        let parent_module = cx.tcx.parent(did);
+        let res = Res::Def(cx.tcx.def_kind(did), did);
+        let Some(built) = try_inline(cx, parent_module, None, res, name, None, &mut visited) else {
+            continue;
+        };
+
+        // The passes already ran, so private fields have to be stripped here, before looking for
+        // the items they refer to.
+        for item in built {
+            let item = if cx.render_options.document_private {
+                item
+            } else {
+                let mut retained = ItemIdSet::default();
+                let mut stripper = Stripper {
+                    retained: &mut retained,
+                    access_levels: &cx.cache.access_levels,
+                    update_retained: false,
+                };
+                match stripper.fold_item(item) {
+                    Some(item) => item,
+                    None => continue,
+                }
+            };
+            collect_referenced_items(&item, &mut referenced);
+            items.push(item);
+        }
+    }
+
+    for item in items {
+        if let clean::ImplItem(ref impl_) = *item.kind {
+            if let Some(did) = impl_.for_.def_id(&cx.cache) {
+                cx.cache.impls.entry(did).or_default().push(Impl { impl_item: item });
+            }
+        } else {
+            cx.cache.external_items.push(item);
+        }
+    }
+}
+
+/// Collects the items referred to by the public API of `item`, and of the items it contains.
+fn collect_referenced_items(item: &clean::Item, referenced: &mut Vec<DefId>) {
+    match *item.kind {
+        clean::StrippedItem(_) => return,
+        clean::ImportItem(ref import) => referenced.extend(import.source.did),
+        clean::StructItem(clean::Struct { ref generics, .. })
+        | clean::UnionItem(clean::Union { ref generics, .. })
+        | clean::EnumItem(clean::Enum { ref generics, .. }) => {
+            collect_generics(generics, referenced)
+        }
+        clean::FunctionItem(ref function)
+        | clean::TyMethodItem(ref function)
+        | clean::MethodItem(ref function, _)
+        | clean::ForeignFunctionItem(ref function) => {
+            collect_generics(&function.generics, referenced);
+            collect_fn_decl(&function.decl, referenced);
+        }
+        clean::TypedefItem(ref typedef) => {
+            collect_generics(&typedef.generics, referenced);
+            collect_type(&typedef.type_, referenced);
+        }
+        clean::AssocTypeItem(ref typedef, ref bounds) => {
+            collect_generics(&typedef.generics, referenced);
+            collect_type(&typedef.type_, referenced);
+            collect_bounds(bounds, referenced);
+        }
+        clean::TyAssocTypeItem(ref generics, ref bounds) => {
+            collect_generics(generics, referenced);
+            collect_bounds(bounds, referenced);
+        }
+        clean::OpaqueTyItem(clean::OpaqueTy { ref generics, ref bounds })
+        | clean::TraitAliasItem(clean::TraitAlias { ref generics, ref bounds })
+        | clean::TraitItem(clean::Trait { ref generics, ref bounds, .. }) => {
+            collect_generics(generics, referenced);
+            collect_bounds(bounds, referenced);
+        }
+        clean::ImplItem(ref impl_) => {
+            collect_generics(&impl_.generics, referenced);
+            collect_type(&impl_.for_, referenced);
+            if let Some(ref trait_) = impl_.trait_ {
+                collect_path(trait_, referenced);
+            }
+        }
+        clean::StaticItem(clean::Static { ref type_, .. })
+        | clean::ForeignStaticItem(clean::Static { ref type_, .. })
+        | clean::ConstantItem(clean::Constant { ref type_, .. })
+        | clean::StructFieldItem(ref type_)
+        | clean::TyAssocConstItem(ref type_)
+        | clean::AssocConstItem(ref type_, _) => collect_type(type_, referenced),
+        clean::ExternCrateItem { .. }
+        | clean::ModuleItem(_)
+        | clean::VariantItem(_)
+        | clean::ForeignTypeItem
+        | clean::MacroItem(_)
+        | clean::ProcMacroItem(_)
+        | clean::PrimitiveItem(_)
+        | clean::KeywordItem(_) => {}
+    }
+    for item in item.kind.inner_items() {
+        collect_referenced_items(item, referenced);
+    }
+}
+
+fn collect_generics(generics: &clean::Generics, referenced: &mut Vec<DefId>) {
+    for param in &generics.params {
+        collect_generic_param(param, referenced);
+    }
+    for predicate in &generics.where_predicates {
+        match predicate {
+            clean::WherePredicate::BoundPredicate { ty, bounds, .. } => {
+                collect_type(ty, referenced);
+                collect_bounds(bounds, referenced);
+            }
+            clean::WherePredicate::RegionPredicate { .. } => {}
+            clean::WherePredicate::EqPredicate { lhs, rhs } => {
+                collect_type(lhs, referenced);
+                collect_term(rhs, referenced);
+            }
+        }
+    }
+}
+
+fn collect_generic_param(param: &clean::GenericParamDef, referenced: &mut Vec<DefId>) {
+    match &param.kind {
+        clean::GenericParamDefKind::Lifetime { .. } => {}
+        clean::GenericParamDefKind::Type { bounds, default, .. } => {
+            collect_bounds(bounds, referenced);
+            if let Some(default) = default {
+                collect_type(default, referenced);
+            }
+        }
+        clean::GenericParamDefKind::Const { ty, .. } => collect_type(ty, referenced),
+    }
+}
+
+fn collect_bounds(bounds: &[clean::GenericBound], referenced: &mut Vec<DefId>) {
+    for bound in bounds {
+        if let clean::GenericBound::TraitBound(poly_trait, _) = bound {
+            for param in &poly_trait.generic_params {
+                collect_generic_param(param, referenced);
+            }
+            collect_path(&poly_trait.trait_, referenced);
+        }
+    }
+}
+
+fn collect_fn_decl(decl: &clean::FnDecl, referenced: &mut Vec<DefId>) {
+    for argument in &decl.inputs.values {
+        collect_type(&argument.type_, referenced);
+    }
+    if let clean::FnRetTy::Return(ref output) = decl.output {
+        collect_type(output, referenced);
+    }
+}
+
+fn collect_term(term: &clean::Term, referenced: &mut Vec<DefId>) {
+    match term {
+        clean::Term::Type(ty) => collect_type(ty, referenced),
+        clean::Term::Constant(constant) => collect_type(&constant.type_, referenced),
+    }
+}
+
+fn collect_path(path: &clean::Path, referenced: &mut Vec<DefId>) {
+    if let Res::Def(_, did) = path.res {
+        referenced.push(did);
+    }
+    for segment in &path.segments {
+        collect_generic_args(&segment.args, referenced);
+    }
+}
+
+fn collect_generic_args(args: &clean::GenericArgs, referenced: &mut Vec<DefId>) {
+    match args {
+        clean::GenericArgs::AngleBracketed { args, bindings } => {
+            for arg in args.iter() {
+                match arg {
+                    clean::GenericArg::Type(ty) => collect_type(ty, referenced),
+                    clean::GenericArg::Const(constant) => collect_type(&constant.type_, referenced),
+                    clean::GenericArg::Lifetime(_) | clean::GenericArg::Infer => {}
+                }
+            }
+            for binding in bindings.iter() {
+                collect_generic_args(&binding.assoc.args, referenced);
+                match &binding.kind {
+                    clean::TypeBindingKind::Equality { term } => collect_term(term, referenced),
+                    clean::TypeBindingKind::Constraint { bounds } => {
+                        collect_bounds(bounds, referenced)
+                    }
+                }
+            }
+        }
+        clean::GenericArgs::Parenthesized { inputs, output } => {
+            for input in inputs.iter() {
+                collect_type(input, referenced);
+            }
+            if let Some(output) = output {
+                collect_type(output, referenced);
+            }
+        }
+    }
+}
+
+fn collect_type(ty: &Type, referenced: &mut Vec<DefId>) {
+    match ty {
+        Type::Path { path } => collect_path(path, referenced),
+        Type::DynTrait(bounds, _) => {
+            for poly_trait in bounds {
+                collect_path(&poly_trait.trait_, referenced);
+            }
+        }
+        Type::BareFunction(function) => {
+            for param in &function.generic_params {
+                collect_generic_param(param, referenced);
+            }
+            collect_fn_decl(&function.decl, referenced);
+        }
+        Type::Tuple(types) => {
+            for ty in types {
+                collect_type(ty, referenced);
+            }
+        }
+        Type::Slice(ty)
+        | Type::Array(ty, _)
+        | Type::RawPointer(_, ty)
+        | Type::BorrowedRef { type_: ty, .. } => collect_type(ty, referenced),
+        Type::QPath { assoc, self_type, trait_, .. } => {
+            collect_generic_args(&assoc.args, referenced);
+            collect_type(self_type, referenced);
+            collect_path(trait_, referenced);
+        }
+        Type::ImplTrait(bounds) => collect_bounds(bounds, referenced),
+        Type::Generic(_) | Type::Primitive(_) | Type::Infer => {}
+    }
+}
+
 pub(crate) fn try_inline_glob(
     cx: &mut DocContext<'_>,
     res: Res,
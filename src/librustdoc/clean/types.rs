@@ -1663,14 +1663,6 @@ pub(crate) fn generics(&self) -> Option<Vec<&Type>> {
         }
     }
 
-    pub(crate) fn is_full_generic(&self) -> bool {
-        matches!(self, Type::Generic(_))
-    }
-
-    pub(crate) fn is_impl_trait(&self) -> bool {
-        matches!(self, Type::ImplTrait(_))
-    }
-
     pub(crate) fn projection(&self) -> Option<(&Type, DefId, PathSegment)> {
         if let QPath { self_type, trait_, assoc, .. } = self {
             Some((self_type, trait_.def_id(), *assoc.clone()))
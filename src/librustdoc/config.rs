@@ -6,6 +6,7 @@
 use std::str::FromStr;
 
 use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::OnDrop;
 use rustc_driver::print_flag_list;
 use rustc_session::config::{
     self, parse_crate_types_from_list, parse_externs, parse_target_triple, CrateType,
@@ -35,6 +36,7 @@
 pub(crate) enum OutputFormat {
     Json,
     Html,
+    Markdown,
 }
 
 impl Default for OutputFormat {
@@ -56,6 +58,7 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
             _ => Err(format!("unknown output format `{}`", value)),
         }
     }
@@ -117,6 +120,15 @@ pub(crate) struct Options {
     /// Optional path to persist the doctest executables to, defaults to a
     /// temporary directory if not set.
     pub(crate) persist_doctests: Option<PathBuf>,
+    /// Whether to compile the doctests that allow it together, into a single executable per
+    /// edition.
+    pub(crate) merge_doctests: bool,
+    /// Optional path to write the coverage profiles of the doctests to, along with their
+    /// executables, which are compiled with `-C instrument-coverage`.
+    pub(crate) doctest_coverage: Option<PathBuf>,
+    /// Whether the doctest results are printed as JSON with `--format json`, in which case the
+    /// compiler diagnostics of a doctest that failed to compile are part of its result.
+    pub(crate) json_test_results: bool,
     /// Runtool to run doctests with
     pub(crate) runtool: Option<String>,
     /// Arguments to pass to the runtool
@@ -190,6 +202,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("test_args", &self.test_args)
             .field("test_run_directory", &self.test_run_directory)
             .field("persist_doctests", &self.persist_doctests)
+            .field("merge_doctests", &self.merge_doctests)
+            .field("doctest_coverage", &self.doctest_coverage)
+            .field("json_test_results", &self.json_test_results)
             .field("show_coverage", &self.show_coverage)
             .field("crate_version", &self.crate_version)
             .field("render_options", &self.render_options)
@@ -272,6 +287,14 @@ pub(crate) struct RenderOptions {
     pub(crate) emit: Vec<EmitType>,
     /// If `true`, HTML source pages will generate links for items to their definition.
     pub(crate) generate_link_to_definition: bool,
+    /// If `true`, the JSON output also contains the full items of the external items referenced
+    /// by the crate's public API, rather than only their paths.
+    pub(crate) json_external_items: bool,
+    /// If `true`, the markdown output also contains a man page for each page, in `man/man3`.
+    pub(crate) man_pages: bool,
+    /// The JSON documentation of a previous version of the crate, against which the items added
+    /// or changed since then are marked.
+    pub(crate) compare_with: Option<rustdoc_json_types::Crate>,
     /// Set of function-call locations to include as examples
     pub(crate) call_locations: AllCallLocations,
     /// If `true`, Context::init will not emit shared files.
@@ -339,6 +362,8 @@ pub(crate) fn from_matches(
         let debugging_opts = DebuggingOptions::build(matches, error_format);
 
         let diag = new_handler(error_format, None, &debugging_opts);
+        // Write buffered diagnostics, like the SARIF log, on every return path.
+        let _finish_diag = OnDrop(|| diag.finish());
 
         // check for deprecated options
         check_deprecated_options(matches, &diag);
@@ -510,7 +535,7 @@ fn println_condition(condition: Condition) {
             .collect();
 
         let test_args = matches.opt_strs("test-args");
-        let test_args: Vec<String> =
+        let mut test_args: Vec<String> =
             test_args.iter().flat_map(|s| s.split_whitespace()).map(|s| s.to_string()).collect();
 
         let should_test = matches.opt_present("test");
@@ -521,6 +546,29 @@ fn println_condition(condition: Condition) {
             return Err(1);
         }
 
+        let json_test_results = match matches.opt_str("format").as_deref() {
+            None => false,
+            Some(_) if !should_test => {
+                diag.err("the `--test` flag must be passed to enable `--format`");
+                return Err(1);
+            }
+            Some("json") => true,
+            Some(format) => {
+                diag.struct_err(&format!("unsupported doctest format `{format}`"))
+                    .help("the only supported format is `json`")
+                    .emit();
+                return Err(1);
+            }
+        };
+        if json_test_results {
+            // The location of each doctest is part of the details that libtest reports.
+            test_args.push("--format=json".to_string());
+            test_args.push("--report-details".to_string());
+            if !test_args_enable_unstable_options(&test_args) {
+                test_args.push("-Zunstable-options".to_string());
+            }
+        }
+
         let out_dir = matches.opt_str("out-dir").map(|s| PathBuf::from(&s));
         let output = matches.opt_str("output").map(|s| PathBuf::from(&s));
         let output = match (out_dir, output) {
@@ -634,9 +682,10 @@ fn println_condition(condition: Condition) {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
                     if !out_fmt.is_json() && show_coverage {
-                        diag.struct_err(
-                            "html output format isn't supported for the --show-coverage option",
-                        )
+                        diag.struct_err(&format!(
+                            "{} output format isn't supported for the --show-coverage option",
+                            s
+                        ))
                         .emit();
                         return Err(1);
                     }
@@ -668,6 +717,8 @@ fn println_condition(condition: Condition) {
         let static_root_path = matches.opt_str("static-root-path");
         let test_run_directory = matches.opt_str("test-run-directory").map(PathBuf::from);
         let persist_doctests = matches.opt_str("persist-doctests").map(PathBuf::from);
+        let merge_doctests = matches.opt_present("merge-doctests");
+        let doctest_coverage = matches.opt_str("doctest-coverage").map(PathBuf::from);
         let test_builder = matches.opt_str("test-builder").map(PathBuf::from);
         let codegen_options_strs = matches.opt_strs("C");
         let debugging_opts_strs = matches.opt_strs("Z");
@@ -683,6 +734,8 @@ fn println_condition(condition: Condition) {
         let show_type_layout = matches.opt_present("show-type-layout");
         let nocapture = matches.opt_present("nocapture");
         let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
+        let json_external_items = matches.opt_present("json-external-items");
+        let man_pages = matches.opt_present("man-pages");
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
 
@@ -694,6 +747,30 @@ fn println_condition(condition: Condition) {
             return Err(1);
         }
 
+        if json_external_items && (show_coverage || output_format != OutputFormat::Json) {
+            diag.struct_err(
+                "--json-external-items option can only be used with JSON output format",
+            )
+            .emit();
+            return Err(1);
+        }
+
+        if man_pages && (show_coverage || output_format != OutputFormat::Markdown) {
+            diag.struct_err("--man-pages option can only be used with markdown output format")
+                .emit();
+            return Err(1);
+        }
+
+        let compare_with = matches.opt_str("compare-with");
+        if compare_with.is_some() && (show_coverage || output_format != OutputFormat::Html) {
+            diag.struct_err("--compare-with option can only be used with HTML output format")
+                .emit();
+            return Err(1);
+        }
+        let compare_with = compare_with
+            .map(|path| html::render::load_previous_version(&path, &diag))
+            .transpose()?;
+
         let scrape_examples_options = ScrapeExamplesOptions::new(matches, &diag)?;
         let with_examples = matches.opt_strs("with-examples");
         let call_locations = crate::scrape_examples::load_call_locations(with_examples, &diag)?;
@@ -726,6 +803,9 @@ fn println_condition(condition: Condition) {
             crate_version,
             test_run_directory,
             persist_doctests,
+            merge_doctests,
+            doctest_coverage,
+            json_test_results,
             runtool,
             runtool_args,
             enable_per_target_ignores,
@@ -761,6 +841,9 @@ fn println_condition(condition: Condition) {
                 ),
                 emit,
                 generate_link_to_definition,
+                json_external_items,
+                man_pages,
+                compare_with,
                 call_locations,
                 no_emit_shared: false,
             },
@@ -827,3 +910,24 @@ fn parse_extern_html_roots(
     }
     Ok(externs)
 }
+
+/// Whether the arguments for the test harness already contain `-Z unstable-options`, which the
+/// harness does not accept twice. Like the harness, this accepts the value of `-Z` in the same
+/// argument or in the next one, and stops at `--`.
+fn test_args_enable_unstable_options(test_args: &[String]) -> bool {
+    let mut args = test_args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        let value = match arg.strip_prefix("-Z") {
+            Some("") => args.next().map(String::as_str),
+            Some(value) => Some(value),
+            None => None,
+        };
+        if value == Some("unstable-options") {
+            return true;
+        }
+    }
+    false
+}
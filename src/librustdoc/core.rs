@@ -3,6 +3,7 @@
 use rustc_data_structures::sync::{self, Lrc};
 use rustc_errors::emitter::{Emitter, EmitterWriter};
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::{Namespace, Res};
 use rustc_hir::def_id::{DefId, DefIdMap, LocalDefId};
@@ -25,7 +26,7 @@
 use std::rc::Rc;
 use std::sync::LazyLock;
 
-use crate::clean::inline::build_external_trait;
+use crate::clean::inline::{build_external_items, build_external_trait};
 use crate::clean::{self, ItemId, TraitWithExtraInfo};
 use crate::config::{Options as RustdocOptions, OutputFormat, RenderOptions};
 use crate::formats::cache::Cache;
@@ -193,6 +194,15 @@ pub(crate) fn new_handler(
                 .ui_testing(debugging_opts.ui_testing),
             )
         }
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(|| {
+                Lrc::new(source_map::SourceMap::new(source_map::FilePathMapping::empty()))
+            });
+            Box::new(
+                SarifEmitter::stderr(None, source_map, None, fallback_bundle)
+                    .ui_testing(debugging_opts.ui_testing),
+            )
+        }
     };
 
     rustc_errors::Handler::with_emitter_and_flags(
@@ -481,6 +491,10 @@ fn report_deprecated_attr(name: &str, diag: &rustc_errors::Handler, sp: Span) {
 
     krate = tcx.sess.time("create_format_cache", || Cache::populate(&mut ctxt, krate));
 
+    if ctxt.render_options.json_external_items {
+        tcx.sess.time("build_external_items", || build_external_items(&mut ctxt, &krate));
+    }
+
     (krate, ctxt.render_options, ctxt.cache)
 }
 
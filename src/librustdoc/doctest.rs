@@ -26,7 +26,7 @@
 use std::env;
 use std::io::{self, Write};
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -268,6 +268,35 @@ enum TestFailure {
     UnexpectedRunPass,
 }
 
+impl TestFailure {
+    /// Renders the failure for the results of `--format json`, with the diagnostics of the
+    /// compiler.
+    fn to_json(&self, diagnostics: Vec<serde_json::Value>) -> String {
+        #[derive(serde::Serialize)]
+        struct FailureReport<'a> {
+            kind: &'static str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            missing_error_codes: Option<&'a [String]>,
+            diagnostics: Vec<serde_json::Value>,
+        }
+
+        let kind = match self {
+            TestFailure::CompileError => "compile_error",
+            TestFailure::UnexpectedCompilePass => "unexpected_compile_pass",
+            TestFailure::MissingErrorCodes(_) => "missing_error_codes",
+            TestFailure::ExecutionError(_) => "execution_error",
+            TestFailure::ExecutionFailure(_) => "execution_failure",
+            TestFailure::UnexpectedRunPass => "unexpected_run_pass",
+        };
+        let missing_error_codes = match self {
+            TestFailure::MissingErrorCodes(codes) => Some(&codes[..]),
+            _ => None,
+        };
+        serde_json::to_string(&FailureReport { kind, missing_error_codes, diagnostics })
+            .expect("failed to serialize a doctest failure")
+    }
+}
+
 enum DirState {
     Temp(tempfile::TempDir),
     Perm(PathBuf),
@@ -310,31 +339,14 @@ fn run_test(
     path: PathBuf,
     test_id: &str,
     report_unused_externs: impl Fn(UnusedExterns),
+    diagnostics: &mut Vec<serde_json::Value>,
 ) -> Result<(), TestFailure> {
     let (test, line_offset, supports_color) =
         make_test(test, Some(crate_name), lang_string.test_harness, opts, edition, Some(test_id));
 
     let output_file = outdir.path().join("rust_out");
 
-    let rustc_binary = rustdoc_options
-        .test_builder
-        .as_deref()
-        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
-    let mut compiler = Command::new(&rustc_binary);
-    compiler.arg("--crate-type").arg("bin");
-    for cfg in &rustdoc_options.cfgs {
-        compiler.arg("--cfg").arg(&cfg);
-    }
-    if !rustdoc_options.check_cfgs.is_empty() {
-        compiler.arg("-Z").arg("unstable-options");
-        for check_cfg in &rustdoc_options.check_cfgs {
-            compiler.arg("--check-cfg").arg(&check_cfg);
-        }
-    }
-    if let Some(sysroot) = rustdoc_options.maybe_sysroot {
-        compiler.arg("--sysroot").arg(sysroot);
-    }
-    compiler.arg("--edition").arg(&edition.to_string());
+    let mut compiler = doctest_compiler(&rustdoc_options, edition, &target, supports_color);
     compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
     compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
     compiler.arg("-o").arg(&output_file);
@@ -342,52 +354,16 @@ fn run_test(
         compiler.arg("--test");
     }
     if rustdoc_options.json_unused_externs.is_enabled() && !lang_string.compile_fail {
-        compiler.arg("--error-format=json");
+        if !rustdoc_options.json_test_results {
+            compiler.arg("--error-format=json");
+        }
         compiler.arg("--json").arg("unused-externs");
         compiler.arg("-Z").arg("unstable-options");
         compiler.arg("-W").arg("unused_crate_dependencies");
     }
-    for lib_str in &rustdoc_options.lib_strs {
-        compiler.arg("-L").arg(&lib_str);
-    }
-    for extern_str in &rustdoc_options.extern_strs {
-        compiler.arg("--extern").arg(&extern_str);
-    }
-    compiler.arg("-Ccodegen-units=1");
-    for codegen_options_str in &rustdoc_options.codegen_options_strs {
-        compiler.arg("-C").arg(&codegen_options_str);
-    }
-    for debugging_option_str in &rustdoc_options.debugging_opts_strs {
-        compiler.arg("-Z").arg(&debugging_option_str);
-    }
     if no_run && !lang_string.compile_fail && rustdoc_options.persist_doctests.is_none() {
         compiler.arg("--emit=metadata");
     }
-    compiler.arg("--target").arg(match target {
-        TargetTriple::TargetTriple(s) => s,
-        TargetTriple::TargetJson { path_for_rustdoc, .. } => {
-            path_for_rustdoc.to_str().expect("target path must be valid unicode").to_string()
-        }
-    });
-    if let ErrorOutputType::HumanReadable(kind) = rustdoc_options.error_format {
-        let (short, color_config) = kind.unzip();
-
-        if short {
-            compiler.arg("--error-format").arg("short");
-        }
-
-        match color_config {
-            ColorConfig::Never => {
-                compiler.arg("--color").arg("never");
-            }
-            ColorConfig::Always => {
-                compiler.arg("--color").arg("always");
-            }
-            ColorConfig::Auto => {
-                compiler.arg("--color").arg(if supports_color { "always" } else { "never" });
-            }
-        }
-    }
 
     compiler.arg("-");
     compiler.stdin(Stdio::piped());
@@ -409,15 +385,24 @@ fn drop(&mut self) {
     let mut out = str::from_utf8(&output.stderr)
         .unwrap()
         .lines()
-        .filter(|l| {
+        .filter_map(|l| {
             if let Ok(uext) = serde_json::from_str::<UnusedExterns>(l) {
                 report_unused_externs(uext);
-                false
-            } else {
-                true
+                return None;
             }
+            // With `--format json`, the compiler emits JSON diagnostics, which are printed as
+            // they would have been rendered and also reported with the result of the test.
+            if rustdoc_options.json_test_results {
+                if let Ok(diagnostic @ serde_json::Value::Object(_)) = serde_json::from_str(l) {
+                    let rendered = diagnostic["rendered"].as_str().unwrap_or_default();
+                    let rendered = rendered.trim_end_matches('\n').to_owned();
+                    diagnostics.push(diagnostic);
+                    return Some(rendered);
+                }
+            }
+            Some(l.to_owned())
         })
-        .intersperse_with(|| "\n")
+        .intersperse_with(|| "\n".to_owned())
         .collect::<String>();
 
     // Add a \n to the end to properly terminate the last line,
@@ -454,18 +439,122 @@ fn drop(&mut self) {
     }
 
     // Run the code!
+    run_doctest_binary(
+        &output_file,
+        &[],
+        test_id,
+        lang_string.should_panic,
+        &rustdoc_options,
+        runtool,
+        runtool_args,
+    )
+}
+
+/// Creates the command that compiles a doctest, with the options that do not depend on the
+/// doctest itself.
+fn doctest_compiler(
+    rustdoc_options: &RustdocOptions,
+    edition: Edition,
+    target: &TargetTriple,
+    supports_color: bool,
+) -> Command {
+    let rustc_binary = rustdoc_options
+        .test_builder
+        .as_deref()
+        .unwrap_or_else(|| rustc_interface::util::rustc_path().expect("found rustc"));
+    let mut compiler = Command::new(&rustc_binary);
+    compiler.arg("--crate-type").arg("bin");
+    for cfg in &rustdoc_options.cfgs {
+        compiler.arg("--cfg").arg(&cfg);
+    }
+    if !rustdoc_options.check_cfgs.is_empty() {
+        compiler.arg("-Z").arg("unstable-options");
+        for check_cfg in &rustdoc_options.check_cfgs {
+            compiler.arg("--check-cfg").arg(&check_cfg);
+        }
+    }
+    if let Some(sysroot) = &rustdoc_options.maybe_sysroot {
+        compiler.arg("--sysroot").arg(sysroot);
+    }
+    compiler.arg("--edition").arg(&edition.to_string());
+    for lib_str in &rustdoc_options.lib_strs {
+        compiler.arg("-L").arg(&lib_str);
+    }
+    for extern_str in &rustdoc_options.extern_strs {
+        compiler.arg("--extern").arg(&extern_str);
+    }
+    compiler.arg("-Ccodegen-units=1");
+    for codegen_options_str in &rustdoc_options.codegen_options_strs {
+        compiler.arg("-C").arg(&codegen_options_str);
+    }
+    for debugging_option_str in &rustdoc_options.debugging_opts_strs {
+        compiler.arg("-Z").arg(&debugging_option_str);
+    }
+    compiler.arg("--target").arg(match target {
+        TargetTriple::TargetTriple(s) => s.as_str(),
+        TargetTriple::TargetJson { path_for_rustdoc, .. } => {
+            path_for_rustdoc.to_str().expect("target path must be valid unicode")
+        }
+    });
+    if rustdoc_options.doctest_coverage.is_some() {
+        compiler.arg("-C").arg("instrument-coverage");
+    }
+    if rustdoc_options.json_test_results {
+        // The diagnostics are reported with the result of the doctest, see `run_test`.
+        compiler.arg("--error-format=json");
+    } else if let ErrorOutputType::HumanReadable(kind) = rustdoc_options.error_format {
+        let (short, color_config) = kind.unzip();
+
+        if short {
+            compiler.arg("--error-format").arg("short");
+        }
+
+        match color_config {
+            ColorConfig::Never => {
+                compiler.arg("--color").arg("never");
+            }
+            ColorConfig::Always => {
+                compiler.arg("--color").arg("always");
+            }
+            ColorConfig::Auto => {
+                compiler.arg("--color").arg(if supports_color { "always" } else { "never" });
+            }
+        }
+    }
+    compiler
+}
+
+/// Runs a compiled doctest with the given arguments, and checks that it succeeds, or that it
+/// panics if it is marked `should_panic`.
+fn run_doctest_binary(
+    binary: &Path,
+    args: &[&str],
+    test_id: &str,
+    should_panic: bool,
+    rustdoc_options: &RustdocOptions,
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+) -> Result<(), TestFailure> {
     let mut cmd;
 
     if let Some(tool) = runtool {
         cmd = Command::new(tool);
         cmd.args(runtool_args);
-        cmd.arg(output_file);
+        cmd.arg(binary);
     } else {
-        cmd = Command::new(output_file);
+        cmd = Command::new(binary);
     }
-    if let Some(run_directory) = rustdoc_options.test_run_directory {
+    cmd.args(args);
+    if let Some(run_directory) = &rustdoc_options.test_run_directory {
         cmd.current_dir(run_directory);
     }
+    if let Some(coverage_dir) = &rustdoc_options.doctest_coverage {
+        // Each doctest writes its own profile, and `%p` keeps the profiles apart if it runs more
+        // than once. The path is absolute, as the doctest may run in another directory.
+        let profile = coverage_dir.join(format!("{test_id}-%p.profraw"));
+        let profile = env::current_dir().map(|dir| dir.join(&profile)).unwrap_or(profile);
+        cmd.env("LLVM_PROFILE_FILE", profile);
+    }
 
     let result = if rustdoc_options.nocapture {
         cmd.status().map(|status| process::Output {
@@ -479,9 +568,9 @@ fn drop(&mut self) {
     match result {
         Err(e) => return Err(TestFailure::ExecutionError(e)),
         Ok(out) => {
-            if lang_string.should_panic && out.status.success() {
+            if should_panic && out.status.success() {
                 return Err(TestFailure::UnexpectedRunPass);
-            } else if !lang_string.should_panic && !out.status.success() {
+            } else if !should_panic && !out.status.success() {
                 return Err(TestFailure::ExecutionFailure(out));
             }
         }
@@ -500,11 +589,8 @@ pub(crate) fn make_test(
     edition: Edition,
     test_id: Option<&str>,
 ) -> (String, usize, bool) {
-    let (crate_attrs, everything_else, crates) = partition_source(s, edition);
-    let everything_else = everything_else.trim();
     let mut line_offset = 0;
     let mut prog = String::new();
-    let mut supports_color = false;
 
     if opts.attrs.is_empty() {
         // If there aren't any attributes supplied by #![doc(test(attr(...)))], then allow some
@@ -521,10 +607,100 @@ pub(crate) fn make_test(
         line_offset += 1;
     }
 
+    let Some(parsed) = parse_doctest(s, crate_name, edition)
+    else {
+        // If the parser panicked due to a fatal error, pass the test code through unchanged.
+        // The error will be reported during compilation.
+        return (s.to_owned(), 0, false);
+    };
+
     // Now push any outer attributes from the example, assuming they
     // are intended to be crate attributes.
-    prog.push_str(&crate_attrs);
-    prog.push_str(&crates);
+    prog.push_str(&parsed.crate_attrs);
+    prog.push_str(&parsed.crates);
+
+    if let Some(crate_name) = crate_to_inject(s, crate_name, opts, &parsed) {
+        prog.push_str(&format!("extern crate r#{crate_name};\n"));
+        line_offset += 1;
+    }
+
+    // FIXME: This code cannot yet handle no_std test cases yet
+    if dont_insert_main || parsed.already_has_main || prog.contains("![no_std]") {
+        prog.push_str(&parsed.everything_else);
+    } else {
+        let (main_pre, main_post) = wrap_in_main(&parsed.everything_else, test_id);
+        // Note on newlines: We insert a line/newline *before*, and *after*
+        // the doctest and adjust the `line_offset` accordingly.
+        // In the case of `-C instrument-coverage`, this means that the generated
+        // inner `main` function spans from the doctest opening codeblock to the
+        // closing one. For example
+        // /// ``` <- start of the inner main
+        // /// <- code under doctest
+        // /// ``` <- end of the inner main
+        line_offset += 1;
+
+        prog.extend([&main_pre, &parsed.everything_else, &main_post].iter().cloned());
+    }
+
+    debug!("final doctest:\n{prog}");
+
+    (prog, line_offset, parsed.supports_color)
+}
+
+/// Transforms a test into the file of a module of the crate built by `--merge-doctests`, which
+/// has a `main` function running the test. The code is preceded by blank lines so that it is on
+/// the same lines as when the test is compiled on its own, where `line` is passed to rustc as
+/// `UNSTABLE_RUSTDOC_TEST_LINE`. Returns the file, and whether the crate must have an
+/// `extern crate` for the documented crate, or `None` if the test cannot be merged because
+/// it has a `main` function or crate attributes of its own.
+fn make_merged_test(
+    s: &str,
+    crate_name: Option<&str>,
+    opts: &GlobalTestOptions,
+    edition: Edition,
+    test_id: &str,
+    line: usize,
+) -> Option<(String, bool)> {
+    let parsed = parse_doctest(s, crate_name, edition)?;
+    if parsed.already_has_main
+        || parsed.crate_attrs.contains("#![")
+        || parsed.crates.contains("#[macro_use]")
+    {
+        return None;
+    }
+
+    let inject_crate = crate_to_inject(s, crate_name, opts, &parsed).is_some();
+    let (main_pre, main_post) = wrap_in_main(&parsed.everything_else, Some(test_id));
+    // The crate attributes are only blank lines and comments here, and are left out, while the
+    // line of `main_pre` comes before the code.
+    let padding = "\n".repeat((line + parsed.crate_attrs.lines().count()).saturating_sub(1));
+    let module = format!(
+        "{padding}{crates}pub(crate) {main_pre}{everything_else}{main_post}\n",
+        crates = parsed.crates,
+        everything_else = parsed.everything_else,
+    );
+    Some((module, inject_crate))
+}
+
+/// The parts of a doctest, see `parse_doctest`.
+struct ParsedDoctest {
+    /// The crate attributes at the start of the test, with the lines around them.
+    crate_attrs: String,
+    /// The `extern crate` items following the crate attributes.
+    crates: String,
+    everything_else: String,
+    already_has_main: bool,
+    already_has_extern_crate: bool,
+    supports_color: bool,
+}
+
+/// Splits a doctest into its parts, and parses it to find out whether it has a `main` function
+/// and an `extern crate` for the documented crate. Returns `None` if the parser panicked due to a
+/// fatal error.
+fn parse_doctest(s: &str, crate_name: Option<&str>, edition: Edition) -> Option<ParsedDoctest> {
+    let (crate_attrs, everything_else, crates) = partition_source(s, edition);
+    let everything_else = everything_else.trim();
+    let mut supports_color = false;
 
     // Uses librustc_ast to parse the doctest and find if there's a main fn and the extern
     // crate already is included.
@@ -536,7 +712,7 @@ pub(crate) fn make_test(
             use rustc_span::source_map::FilePathMapping;
 
             let filename = FileName::anon_source_code(s);
-            let source = crates + everything_else;
+            let source = crates.clone() + everything_else;
 
             // Any errors in parsing should also appear when the doctest is compiled for real, so just
             // send all the errors that librustc_ast emits directly into a `Sink` instead of stderr.
@@ -640,9 +816,7 @@ pub(crate) fn make_test(
     });
     let Ok((already_has_main, already_has_extern_crate, found_macro)) = result
     else {
-        // If the parser panicked due to a fatal error, pass the test code through unchanged.
-        // The error will be reported during compilation.
-        return (s.to_owned(), 0, false);
+        return None;
     };
 
     // If a doctest's `fn main` is being masked by a wrapper macro, the parsing loop above won't
@@ -660,65 +834,61 @@ pub(crate) fn make_test(
         already_has_main
     };
 
+    Some(ParsedDoctest {
+        crate_attrs,
+        crates,
+        everything_else: everything_else.to_owned(),
+        already_has_main,
+        already_has_extern_crate,
+        supports_color,
+    })
+}
+
+/// Returns the name of the documented crate if an `extern crate` has to be added for it.
+fn crate_to_inject<'a>(
+    s: &str,
+    crate_name: Option<&'a str>,
+    opts: &GlobalTestOptions,
+    parsed: &ParsedDoctest,
+) -> Option<&'a str> {
     // Don't inject `extern crate std` because it's already injected by the
     // compiler.
-    if !already_has_extern_crate && !opts.no_crate_inject && crate_name != Some("std") {
-        if let Some(crate_name) = crate_name {
-            // Don't inject `extern crate` if the crate is never used.
-            // NOTE: this is terribly inaccurate because it doesn't actually
-            // parse the source, but only has false positives, not false
-            // negatives.
-            if s.contains(crate_name) {
-                prog.push_str(&format!("extern crate r#{crate_name};\n"));
-                line_offset += 1;
-            }
-        }
+    if parsed.already_has_extern_crate || opts.no_crate_inject || crate_name == Some("std") {
+        return None;
     }
+    // Don't inject `extern crate` if the crate is never used.
+    // NOTE: this is terribly inaccurate because it doesn't actually
+    // parse the source, but only has false positives, not false
+    // negatives.
+    crate_name.filter(|crate_name| s.contains(crate_name))
+}
 
-    // FIXME: This code cannot yet handle no_std test cases yet
-    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
-        prog.push_str(everything_else);
+/// Returns the code to put before and after the test to wrap it in a `main` function.
+fn wrap_in_main(everything_else: &str, test_id: Option<&str>) -> (String, String) {
+    let returns_result = everything_else.trim_end().ends_with("(())");
+    // Give each doctest main function a unique name.
+    // This is for example needed for the tooling around `-C instrument-coverage`.
+    let inner_fn_name = if let Some(test_id) = test_id {
+        format!("_doctest_main_{test_id}")
     } else {
-        let returns_result = everything_else.trim_end().ends_with("(())");
-        // Give each doctest main function a unique name.
-        // This is for example needed for the tooling around `-C instrument-coverage`.
-        let inner_fn_name = if let Some(test_id) = test_id {
-            format!("_doctest_main_{test_id}")
-        } else {
-            "_inner".into()
-        };
-        let inner_attr = if test_id.is_some() { "#[allow(non_snake_case)] " } else { "" };
-        let (main_pre, main_post) = if returns_result {
-            (
-                format!(
-                    "fn main() {{ {inner_attr}fn {inner_fn_name}() -> Result<(), impl core::fmt::Debug> {{\n",
-                ),
-                format!("\n}} {inner_fn_name}().unwrap() }}"),
-            )
-        } else if test_id.is_some() {
-            (
-                format!("fn main() {{ {inner_attr}fn {inner_fn_name}() {{\n",),
-                format!("\n}} {inner_fn_name}() }}"),
-            )
-        } else {
-            ("fn main() {\n".into(), "\n}".into())
-        };
-        // Note on newlines: We insert a line/newline *before*, and *after*
-        // the doctest and adjust the `line_offset` accordingly.
-        // In the case of `-C instrument-coverage`, this means that the generated
-        // inner `main` function spans from the doctest opening codeblock to the
-        // closing one. For example
-        // /// ``` <- start of the inner main
-        // /// <- code under doctest
-        // /// ``` <- end of the inner main
-        line_offset += 1;
-
-        prog.extend([&main_pre, everything_else, &main_post].iter().cloned());
+        "_inner".into()
+    };
+    let inner_attr = if test_id.is_some() { "#[allow(non_snake_case)] " } else { "" };
+    if returns_result {
+        (
+            format!(
+                "fn main() {{ {inner_attr}fn {inner_fn_name}() -> Result<(), impl core::fmt::Debug> {{\n",
+            ),
+            format!("\n}} {inner_fn_name}().unwrap() }}"),
+        )
+    } else if test_id.is_some() {
+        (
+            format!("fn main() {{ {inner_attr}fn {inner_fn_name}() {{\n",),
+            format!("\n}} {inner_fn_name}() }}"),
+        )
+    } else {
+        ("fn main() {\n".into(), "\n}".into())
     }
-
-    debug!("final doctest:\n{prog}");
-
-    (prog, line_offset, supports_color)
 }
 
 fn check_if_attr_is_complete(source: &str, edition: Edition) -> bool {
@@ -846,6 +1016,202 @@ enum PartitionState {
     (before, after, crates)
 }
 
+/// The doctests of an edition that are compiled together with `--merge-doctests`, each into a
+/// module of a single crate. The crate is compiled when the first of the tests runs, and each test
+/// then runs in its own process, by passing its index to the executable. Tests that cannot be
+/// merged, and all of the tests if the crate fails to compile, are compiled on their own instead.
+struct MergedDoctests {
+    crate_name: String,
+    edition: Edition,
+    opts: GlobalTestOptions,
+    rustdoc_options: RustdocOptions,
+    state: Mutex<MergedDoctestsState>,
+}
+
+struct MergedDoctestsState {
+    tests: Vec<MergedTest>,
+    /// The executable, once it has been compiled, or `None` if the crate could not be compiled.
+    binary: Option<Option<MergedBinary>>,
+}
+
+struct MergedTest {
+    code: String,
+    test_id: String,
+    /// The file and the line the test is reported at, as when it is compiled on its own.
+    path: PathBuf,
+    line: usize,
+}
+
+struct MergedBinary {
+    /// Keeps the temporary directory of the executable alive.
+    _outdir: DirState,
+    path: PathBuf,
+    /// Whether each test is part of the executable.
+    merged: Vec<bool>,
+}
+
+impl MergedDoctests {
+    fn new(
+        crate_name: String,
+        edition: Edition,
+        opts: GlobalTestOptions,
+        rustdoc_options: RustdocOptions,
+    ) -> MergedDoctests {
+        MergedDoctests {
+            crate_name,
+            edition,
+            opts,
+            rustdoc_options,
+            state: Mutex::new(MergedDoctestsState { tests: Vec::new(), binary: None }),
+        }
+    }
+
+    /// Adds a test to the crate and returns its index.
+    fn add_test(&self, test: &str, test_id: &str, path: &Path, line: usize) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.tests.push(MergedTest {
+            code: test.to_owned(),
+            test_id: test_id.to_owned(),
+            path: path.to_owned(),
+            line,
+        });
+        state.tests.len() - 1
+    }
+
+    /// Runs the test with the given index, compiling the crate first if needed. Returns `None` if
+    /// the test is not part of the executable and has to be compiled on its own.
+    fn run_test(
+        &self,
+        index: usize,
+        should_panic: bool,
+        runtool: Option<String>,
+        runtool_args: Vec<String>,
+    ) -> Option<Result<(), TestFailure>> {
+        let mut state = self.state.lock().unwrap();
+        if state.binary.is_none() {
+            let binary = self.compile(&state.tests);
+            state.binary = Some(binary);
+        }
+        let binary = match &state.binary {
+            Some(Some(binary)) if binary.merged[index] => binary.path.clone(),
+            _ => return None,
+        };
+        let test_id = state.tests[index].test_id.clone();
+        drop(state);
+
+        let index = index.to_string();
+        Some(run_doctest_binary(
+            &binary,
+            &[index.as_str()],
+            &test_id,
+            should_panic,
+            &self.rustdoc_options,
+            runtool,
+            runtool_args,
+        ))
+    }
+
+    fn compile(&self, tests: &[MergedTest]) -> Option<MergedBinary> {
+        let mut modules = String::new();
+        let mut module_files = Vec::new();
+        let mut main_arms = String::new();
+        let mut merged = Vec::with_capacity(tests.len());
+        let mut inject_crate = false;
+        for (index, test) in tests.iter().enumerate() {
+            let module_name = format!("__doctest_{index}");
+            match make_merged_test(
+                &test.code,
+                Some(&self.crate_name),
+                &self.opts,
+                self.edition,
+                &test.test_id,
+                test.line,
+            ) {
+                Some((module, inject)) => {
+                    modules.push_str(&format!("mod {module_name};\n"));
+                    main_arms.push_str(&format!(
+                        "        Some(\"{index}\") => {module_name}::main(),\n"
+                    ));
+                    module_files.push((format!("{module_name}.rs"), module, &test.path));
+                    inject_crate |= inject;
+                    merged.push(true);
+                }
+                None => merged.push(false),
+            }
+        }
+        if !merged.contains(&true) {
+            return None;
+        }
+
+        let mut prog = String::new();
+        if self.opts.attrs.is_empty() {
+            prog.push_str("#![allow(unused)]\n");
+        }
+        for attr in &self.opts.attrs {
+            prog.push_str(&format!("#![{attr}]\n"));
+        }
+        if inject_crate {
+            prog.push_str(&format!("extern crate r#{};\n", self.crate_name));
+        }
+        prog.push_str(&modules);
+        prog.push_str(&format!(
+            "fn main() {{\n    match ::std::env::args().nth(1).as_deref() {{\n{main_arms}        \
+             _ => ::std::process::exit(1),\n    }}\n}}\n"
+        ));
+        debug!("merged doctests:\n{prog}");
+
+        let outdir = if let Some(mut path) = self.rustdoc_options.persist_doctests.clone() {
+            path.push(format!("merged_{}", self.edition));
+            std::fs::create_dir_all(&path)
+                .expect("Couldn't create directory for doctest executables");
+            DirState::Perm(path)
+        } else {
+            DirState::Temp(
+                TempFileBuilder::new()
+                    .prefix("rustdoctest")
+                    .tempdir()
+                    .expect("rustdoc needs a tempdir"),
+            )
+        };
+        let source = outdir.path().join("merged_doctests.rs");
+        let path = outdir.path().join("rust_out");
+        std::fs::write(&source, prog).ok()?;
+
+        let target = &self.rustdoc_options.target;
+        let mut compiler = doctest_compiler(&self.rustdoc_options, self.edition, target, false);
+        // Each test is in its own module file, which is remapped to the file of the test. As the
+        // module starts with blank lines up to the line of the code block, panics report the file
+        // and line of the test in its source. A test compiled on its own reports the line in the
+        // generated crate instead.
+        for (file_name, module, test_path) in module_files {
+            let module_path = outdir.path().join(file_name);
+            std::fs::write(&module_path, module).ok()?;
+            let mut remap = module_path.into_os_string();
+            remap.push("=");
+            remap.push(test_path);
+            compiler.arg("--remap-path-prefix").arg(remap);
+        }
+        compiler.arg("-o").arg(&path).arg(&source);
+        // The errors are reported when the tests are compiled on their own.
+        let output = compiler.output().expect("Failed to spawn rustc process");
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            debug!("merged doctests failed to compile:\n{stderr}");
+            // Written to stderr directly, since the output of the test that compiles the crate is
+            // captured.
+            let _ = writeln!(
+                io::stderr(),
+                "note: the doctests of edition {} failed to compile as one crate and are compiled \
+                 one by one instead",
+                self.edition
+            );
+            return None;
+        }
+
+        Some(MergedBinary { _outdir: outdir, path, merged })
+    }
+}
+
 pub(crate) trait Tester {
     fn add_test(&mut self, test: String, config: LangString, line: usize);
     fn get_line(&self) -> usize {
@@ -890,6 +1256,10 @@ pub(crate) struct Collector {
     visited_tests: FxHashMap<(String, usize), usize>,
     unused_extern_reports: Arc<Mutex<Vec<UnusedExterns>>>,
     compiling_test_count: AtomicUsize,
+    merged_doctests: FxHashMap<Edition, Arc<MergedDoctests>>,
+    /// The file names and the item paths of the tests, which are leaked once each since
+    /// `TestDesc` holds them as `&'static str`s.
+    leaked_strs: FxHashMap<String, &'static str>,
 }
 
 impl Collector {
@@ -916,18 +1286,30 @@ pub(crate) fn new(
             visited_tests: FxHashMap::default(),
             unused_extern_reports: Default::default(),
             compiling_test_count: AtomicUsize::new(0),
+            merged_doctests: FxHashMap::default(),
+            leaked_strs: FxHashMap::default(),
         }
     }
 
-    fn generate_name(&self, line: usize, filename: &FileName) -> String {
+    /// The path of the item, or the headers of the Markdown sections, the current test is in.
+    fn item_path(&self) -> String {
         let mut item_path = self.names.join("::");
         item_path.retain(|c| c != ' ');
+        item_path
+    }
+
+    fn generate_name(&self, line: usize, filename: &FileName) -> String {
+        let mut item_path = self.item_path();
         if !item_path.is_empty() {
             item_path.push(' ');
         }
         format!("{} - {}(line {})", filename.prefer_local(), item_path, line)
     }
 
+    fn leak_str(&mut self, s: String) -> &'static str {
+        *self.leaked_strs.entry(s).or_insert_with_key(|s| Box::leak(s.clone().into_boxed_str()))
+    }
+
     pub(crate) fn set_position(&mut self, position: Span) {
         self.position = position;
     }
@@ -967,6 +1349,7 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
         let target_str = target.to_string();
         let unused_externs = self.unused_extern_reports.clone();
         let no_run = config.no_run || rustdoc_options.no_run;
+        let json_test_results = rustdoc_options.json_test_results;
         if !config.compile_fail {
             self.compiling_test_count.fetch_add(1, Ordering::SeqCst);
         }
@@ -1000,7 +1383,13 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                 self.visited_tests.entry((file.clone(), line)).and_modify(|v| *v += 1).or_insert(0)
             },
         );
-        let outdir = if let Some(mut path) = rustdoc_options.persist_doctests.clone() {
+        // The executables are needed to read the coverage profiles, so they are kept along with
+        // them unless they are persisted elsewhere.
+        let persist_dir = rustdoc_options
+            .persist_doctests
+            .clone()
+            .or_else(|| rustdoc_options.doctest_coverage.clone());
+        let outdir = if let Some(mut path) = persist_dir {
             path.push(&test_id);
 
             std::fs::create_dir_all(&path)
@@ -1016,16 +1405,58 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
             )
         };
 
+        let ignore = match config.ignore {
+            Ignore::All => true,
+            Ignore::None => false,
+            Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
+        };
+
+        // Tests that only need to be compiled, or whose compilation is checked, are compiled on
+        // their own. So are tests of the 2015 edition, in which the paths in `use` items would be
+        // relative to the root of the merged crate instead of the test's module, and tests whose
+        // coverage is measured, so that each of them has its own executable next to its profile.
+        let mergeable = rustdoc_options.merge_doctests
+            && !ignore
+            && !no_run
+            && !config.compile_fail
+            && !config.test_harness
+            && !config.standalone_crate
+            && edition.rust_2018()
+            && !rustdoc_options.json_unused_externs.is_enabled()
+            && rustdoc_options.doctest_coverage.is_none();
+        let merged = if mergeable {
+            let merged = self.merged_doctests.entry(edition).or_insert_with(|| {
+                Arc::new(MergedDoctests::new(
+                    crate_name.clone(),
+                    edition,
+                    opts.clone(),
+                    rustdoc_options.clone(),
+                ))
+            });
+            let index = merged.add_test(&test, &test_id, &path, line);
+            Some((merged.clone(), index))
+        } else {
+            None
+        };
+
+        // The test is selected by `--at` on any line of the code block, from
+        // the opening to the closing fence.
+        let source_file = self.leak_str(filename.prefer_local().to_string());
+        let item_path = self.item_path();
+        let item_path = (!item_path.is_empty()).then(|| self.leak_str(item_path));
+        let end_line = line + test.lines().count() + 1;
+
         debug!("creating test {name}: {test}");
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
                 name: test::DynTestName(name),
-                ignore: match config.ignore {
-                    Ignore::All => true,
-                    Ignore::None => false,
-                    Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
-                },
+                ignore,
                 ignore_message: None,
+                source_file,
+                start_line: line,
+                end_line,
+                item_path,
+                timeout_secs: None,
                 // compiler failures are test failures
                 should_panic: test::ShouldPanic::No,
                 compile_fail: config.compile_fail,
@@ -1036,25 +1467,34 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                 let report_unused_externs = |uext| {
                     unused_externs.lock().unwrap().push(uext);
                 };
-                let res = run_test(
-                    &test,
-                    &crate_name,
-                    line,
-                    rustdoc_options,
-                    config,
-                    no_run,
-                    runtool,
-                    runtool_args,
-                    target,
-                    &opts,
-                    edition,
-                    outdir,
-                    path,
-                    &test_id,
-                    report_unused_externs,
-                );
+                let mut diagnostics = Vec::new();
+                let merged_res = merged.and_then(|(merged, index)| {
+                    let should_panic = config.should_panic;
+                    merged.run_test(index, should_panic, runtool.clone(), runtool_args.clone())
+                });
+                let res = merged_res.unwrap_or_else(|| {
+                    run_test(
+                        &test,
+                        &crate_name,
+                        line,
+                        rustdoc_options,
+                        config,
+                        no_run,
+                        runtool,
+                        runtool_args,
+                        target,
+                        &opts,
+                        edition,
+                        outdir,
+                        path,
+                        &test_id,
+                        report_unused_externs,
+                        &mut diagnostics,
+                    )
+                });
 
                 if let Err(err) = res {
+                    let failure_json = json_test_results.then(|| err.to_json(diagnostics));
                     match err {
                         TestFailure::CompileError => {
                             eprint!("Couldn't compile the test.");
@@ -1103,6 +1543,9 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                         }
                     }
 
+                    if let Some(json) = failure_json {
+                        panic::resume_unwind(box test::TestFailureReport { json });
+                    }
                     panic::resume_unwind(box ());
                 }
             }),
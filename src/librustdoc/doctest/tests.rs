@@ -1,4 +1,4 @@
-use super::{make_test, GlobalTestOptions};
+use super::{make_merged_test, make_test, GlobalTestOptions};
 use rustc_span::edition::DEFAULT_EDITION;
 
 #[test]
@@ -298,3 +298,58 @@ fn main() { #[allow(non_snake_case)] fn _doctest_main__some_unique_name() {
         make_test(input, None, false, &opts, DEFAULT_EDITION, Some("_some_unique_name"));
     assert_eq!((output, len), (expected, 2));
 }
+
+#[test]
+fn make_merged_test_module() {
+    // wraps the test in a `main` function, and keeps its `extern crate` items in the module
+    let opts = GlobalTestOptions::default();
+    let input = "extern crate other;
+use asdf::qwop;
+assert_eq!(2+2, 4);";
+    let expected = "extern crate other;
+pub(crate) fn main() { #[allow(non_snake_case)] fn _doctest_main_id() {
+use asdf::qwop;
+assert_eq!(2+2, 4);
+} _doctest_main_id() }
+"
+    .to_string();
+    let output = make_merged_test(input, Some("asdf"), &opts, DEFAULT_EDITION, "id", 1);
+    assert_eq!(output, Some((expected, true)));
+}
+
+#[test]
+fn make_merged_test_keeps_lines() {
+    // the code is on the same lines as when the test is compiled on its own
+    let opts = GlobalTestOptions::default();
+    let input = "// a comment
+
+assert_eq!(2+2, 4);";
+    let expected = "\n\n\n\npub(crate) fn main() { #[allow(non_snake_case)] fn _doctest_main_id() {
+assert_eq!(2+2, 4);
+} _doctest_main_id() }
+"
+    .to_string();
+    let output = make_merged_test(input, None, &opts, DEFAULT_EDITION, "id", 3);
+    assert_eq!(output, Some((expected, false)));
+}
+
+#[test]
+fn make_merged_test_standalone() {
+    // tests with a `main` function or crate attributes of their own are not merged
+    let opts = GlobalTestOptions::default();
+    let input = "fn main() {
+    assert_eq!(2+2, 4);
+}";
+    let output = make_merged_test(input, None, &opts, DEFAULT_EDITION, "id", 1);
+    assert_eq!(output, None);
+
+    let input = "#![feature(never_type)]
+assert_eq!(2+2, 4);";
+    let output = make_merged_test(input, None, &opts, DEFAULT_EDITION, "id", 1);
+    assert_eq!(output, None);
+
+    let input = "#[macro_use] extern crate asdf;
+assert_eq!(2+2, 4);";
+    let output = make_merged_test(input, Some("asdf"), &opts, DEFAULT_EDITION, "id", 1);
+    assert_eq!(output, None);
+}
@@ -121,6 +121,10 @@ pub(crate) struct Cache {
     pub(crate) intra_doc_links: FxHashMap<ItemId, Vec<clean::ItemLink>>,
     /// Cfg that have been hidden via #![doc(cfg_hide(...))]
     pub(crate) hidden_cfg: FxHashSet<clean::cfg::Cfg>,
+
+    /// External items referenced by the public API of the crate which weren't inlined, built
+    /// when `--json-external-items` is passed. Their inherent impls are stored in `impls`.
+    pub(crate) external_items: Vec<clean::Item>,
 }
 
 /// This struct is used to wrap the `cache` and `tcx` in order to run `DocFolder`.
@@ -332,7 +336,6 @@ fn fold_item(&mut self, item: clean::Item) -> Option<clean::Item> {
                                 &item,
                                 self.tcx,
                                 clean_impl_generics(self.cache.parent_stack.last()).as_ref(),
-                                self.cache,
                             ),
                             aliases: item.attrs.get_doc_aliases(),
                         });
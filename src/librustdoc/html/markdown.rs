@@ -843,6 +843,8 @@ pub(crate) struct LangString {
     pub(crate) rust: bool,
     pub(crate) test_harness: bool,
     pub(crate) compile_fail: bool,
+    /// Whether the test must not be merged with others by `--merge-doctests`.
+    pub(crate) standalone_crate: bool,
     pub(crate) error_codes: Vec<String>,
     pub(crate) edition: Option<Edition>,
 }
@@ -864,6 +866,7 @@ fn default() -> Self {
             rust: true,
             test_harness: false,
             compile_fail: false,
+            standalone_crate: false,
             error_codes: Vec::new(),
             edition: None,
         }
@@ -951,6 +954,10 @@ fn parse(
                     seen_rust_tags = !seen_other_tags || seen_rust_tags;
                     data.no_run = true;
                 }
+                "standalone_crate" => {
+                    data.standalone_crate = true;
+                    seen_rust_tags = !seen_other_tags || seen_rust_tags;
+                }
                 x if x.starts_with("edition") => {
                     data.edition = x[7..].parse::<Edition>().ok();
                 }
@@ -1249,6 +1256,112 @@ pub(crate) fn plain_text_summary(md: &str) -> String {
     s
 }
 
+/// Rewrites the provided markdown so it can be read without rustdoc's HTML processing, for the
+/// markdown output format.
+///
+/// - Intra-doc links become inline links to the `href` of the matching [`RenderedLink`].
+/// - ATX headings are moved down by `heading_offset` levels.
+/// - Hidden lines are removed from Rust code blocks, which are then tagged as `rust`.
+pub(crate) fn rewrite_markdown(
+    md: &str,
+    links: &[RenderedLink],
+    heading_offset: HeadingOffset,
+) -> String {
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links
+            .iter()
+            .find(|link| link.original_text.as_str() == &*broken_link.reference)
+            .map(|link| (link.href.as_str().into(), link.new_text.as_str().into()))
+    };
+    let mut p = Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer))
+        .into_offset_iter();
+
+    let mut s = String::with_capacity(md.len());
+    // Everything in `md` before this offset has already been pushed to `s`.
+    let mut written = 0;
+    while let Some((event, range)) = p.next() {
+        match event {
+            Event::Start(Tag::Heading(..)) if md[range.start..].starts_with('#') => {
+                s.push_str(&md[written..range.start]);
+                s.push_str(&"#".repeat(heading_offset as usize));
+                written = range.start;
+            }
+            Event::Start(Tag::Link(_, dest, _)) => {
+                // Shortcut and reference links were already given their `href` by `replacer`.
+                let Some(link) = links
+                    .iter()
+                    .find(|link| *link.original_text == *dest || *link.href == *dest)
+                else { continue };
+                let mut text = usize::MAX..0;
+                for (event, inner) in p.by_ref() {
+                    if let Event::End(Tag::Link(..)) = event {
+                        break;
+                    }
+                    text = text.start.min(inner.start)..text.end.max(inner.end);
+                }
+                let text = md.get(text).unwrap_or("");
+                s.push_str(&md[written..range.start]);
+                if text == link.original_text && link.original_text.starts_with('`') {
+                    write!(s, "[`{}`]({})", link.new_text, link.href).unwrap();
+                } else if text == link.original_text {
+                    write!(s, "[{}]({})", link.new_text, link.href).unwrap();
+                } else {
+                    write!(s, "[{}]({})", text, link.href).unwrap();
+                }
+                written = range.end;
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let mut code = String::new();
+                for (event, _) in p.by_ref() {
+                    match event {
+                        Event::Text(text) => code.push_str(&text),
+                        Event::End(Tag::CodeBlock(_)) => break,
+                        _ => {}
+                    }
+                }
+                let is_rust = match kind {
+                    CodeBlockKind::Fenced(ref lang) => {
+                        LangString::parse_without_check(lang, ErrorCodes::No, false).rust
+                    }
+                    CodeBlockKind::Indented => true,
+                };
+                // Code blocks nested in block quotes are left alone, as every line would need the
+                // quote's prefix.
+                let line_start = md[..range.start].rfind('\n').map_or(0, |i| i + 1);
+                let mut indent = &md[line_start..range.start];
+                if !is_rust || indent.contains(|c| c != ' ') {
+                    continue;
+                }
+                let fence = match kind {
+                    CodeBlockKind::Fenced(_) => {
+                        let fence_char = if md[range.start..].starts_with('~') { '~' } else { '`' };
+                        let len = md[range.start..].find(|c| c != fence_char).unwrap_or(3);
+                        &md[range.start..range.start + len]
+                    }
+                    CodeBlockKind::Indented => {
+                        // The fenced block replacing it doesn't need the code's own indentation.
+                        indent = &indent[..indent.len().saturating_sub(4)];
+                        "```"
+                    }
+                };
+                s.push_str(&md[written..line_start + indent.len()]);
+                writeln!(s, "{}rust", fence).unwrap();
+                for line in code.lines().filter_map(|line| map_line(line).for_html()) {
+                    writeln!(s, "{}{}", indent, line).unwrap();
+                }
+                write!(s, "{}{}", indent, fence).unwrap();
+                if md[..range.end].ends_with('\n') {
+                    s.push('\n');
+                }
+                written = range.end;
+            }
+            _ => {}
+        }
+    }
+    s.push_str(&md[written..]);
+    s
+}
+
 #[derive(Debug)]
 pub(crate) struct MarkdownLink {
     pub kind: LinkType,
@@ -1458,6 +1571,7 @@ fn init_id_map() -> FxHashMap<Cow<'static, str>, usize> {
     map.insert("render-detail".into(), 1);
     map.insert("toggle-all-docs".into(), 1);
     map.insert("all-types".into(), 1);
+    map.insert("all-changes".into(), 1);
     map.insert("default-settings".into(), 1);
     map.insert("rustdoc-vars".into(), 1);
     map.insert("sidebar-vars".into(), 1);
@@ -1,5 +1,6 @@
-use super::{find_testable_code, plain_text_summary, short_markdown_summary};
+use super::{find_testable_code, plain_text_summary, rewrite_markdown, short_markdown_summary};
 use super::{ErrorCodes, HeadingOffset, IdMap, Ignore, LangString, Markdown, MarkdownHtml};
+use crate::clean::RenderedLink;
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 
 #[test]
@@ -71,6 +72,11 @@ fn t(lg: LangString) {
         ..Default::default()
     });
     t(LangString { original: "no_run,example".into(), no_run: true, ..Default::default() });
+    t(LangString {
+        original: "standalone_crate".into(),
+        standalone_crate: true,
+        ..Default::default()
+    });
     t(LangString {
         original: "sh,should_panic".into(),
         should_panic: true,
@@ -310,3 +316,38 @@ fn add_test(&mut self, _test: String, _config: LangString, line: usize) {
     t("```rust\n```\n```rust\n```", &[1, 3]);
     t("```rust\n```\n ```rust\n```", &[1, 3]);
 }
+
+#[test]
+fn test_rewrite_markdown() {
+    fn t(input: &str, expect: &str) {
+        let links = [
+            RenderedLink {
+                original_text: "`Foo`".to_owned(),
+                new_text: "Foo".to_owned(),
+                href: "struct.Foo.md".to_owned(),
+            },
+            RenderedLink {
+                original_text: "fn@bar".to_owned(),
+                new_text: "bar".to_owned(),
+                href: "../fn.bar.md".to_owned(),
+            },
+        ];
+        let output = rewrite_markdown(input, &links, HeadingOffset::H2);
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    t("", "");
+    t("[`Foo`] and [fn@bar]", "[`Foo`](struct.Foo.md) and [bar](../fn.bar.md)");
+    t("[the function](fn@bar)", "[the function](../fn.bar.md)");
+    t("[a][b]\n\n[b]: fn@bar", "[a](../fn.bar.md)\n\n[b]: fn@bar");
+    t(
+        "[`Unresolved`] and [a link](https://example.com)",
+        "[`Unresolved`] and [a link](https://example.com)",
+    );
+    t("# Examples\n\ntext", "## Examples\n\ntext");
+    t("```\n# use a::b;\nlet x = 1;\n```\n", "```rust\nlet x = 1;\n```\n");
+    t("~~~should_panic\n# hidden\n~~~", "~~~rust\n~~~");
+    t("```text\n# shown\n```", "```text\n# shown\n```");
+    t("- item\n\n  ```\n  # hidden\n  shown();\n  ```", "- item\n\n  ```rust\n  shown();\n  ```");
+    t("    code();\n    # hidden\n", "```rust\ncode();\n```\n");
+}
@@ -0,0 +1,474 @@
+//! Comparison of the documented crate with the JSON documentation of one of its previous
+//! versions, given with `--compare-with`.
+//!
+//! Items are matched by their path, since their JSON ID changes along with the `-C metadata` of
+//! their crate. An item missing from the previous version is new, and an item found in both is
+//! changed if the part of its JSON representation making up its signature differs, once the IDs of
+//! the items it refers to are replaced by their paths as well.
+
+use std::{fs, mem};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::ty::TyCtxt;
+use rustdoc_json_types as types;
+
+use crate::clean::{self, ItemId};
+use crate::formats::cache::Cache;
+use crate::html::escape::Escape;
+use crate::html::format::{join_with_double_colon, Buffer};
+use crate::json::conversions::{from_clean_item, from_item_id};
+
+/// Loads the JSON documentation of the previous version of the crate from `path`.
+pub(crate) fn load_previous_version(
+    path: &str,
+    diag: &rustc_errors::Handler,
+) -> Result<types::Crate, i32> {
+    let inner = || {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("{} (for path {})", e, path))?;
+        let json: serde_json::Value =
+            serde_json::from_str(&contents).map_err(|e| format!("{} (for path {})", e, path))?;
+        // Check the version first, since an older format probably can't be deserialized.
+        match json.get("format_version").and_then(|v| v.as_u64()) {
+            Some(version) if version == u64::from(types::FORMAT_VERSION) => {}
+            Some(version) => {
+                return Err(format!(
+                    "{} uses format version {}, but rustdoc expects version {}",
+                    path,
+                    version,
+                    types::FORMAT_VERSION
+                ));
+            }
+            None => return Err(format!("{} isn't a rustdoc JSON file", path)),
+        }
+        serde_json::from_value(json).map_err(|e| format!("{} (for path {})", e, path))
+    };
+
+    inner().map_err(|e: String| {
+        diag.err(&format!("failed to load `--compare-with` file: {}", e));
+        1
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ChangeKind {
+    Added,
+    Changed,
+}
+
+impl ChangeKind {
+    /// The badge shown at the top of the documentation of the item.
+    pub(crate) fn badge(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "<div class=\"stab changed\">New in this version</div>",
+            ChangeKind::Changed => "<div class=\"stab changed\">Signature changed</div>",
+        }
+    }
+}
+
+/// An item listed on the changelog page.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ChangeEntry {
+    path: String,
+    /// The URL of the item's documentation, relative to the crate's directory. Removed items
+    /// don't have one.
+    url: Option<String>,
+}
+
+/// The public items which were added, changed or removed since the previous version.
+pub(crate) struct Changes {
+    /// The version of the crate used as reference, if it was given to `--crate-version`.
+    since: Option<String>,
+    kinds: FxHashMap<ItemId, ChangeKind>,
+    added: Vec<ChangeEntry>,
+    changed: Vec<ChangeEntry>,
+    removed: Vec<ChangeEntry>,
+}
+
+impl Changes {
+    pub(crate) fn new(
+        previous: &types::Crate,
+        krate: &clean::Crate,
+        cache: &Cache,
+        tcx: TyCtxt<'_>,
+    ) -> Changes {
+        let paths = cache
+            .paths
+            .iter()
+            .chain(&cache.external_paths)
+            .map(|(&did, (fqn, _))| (from_item_id(did.into(), tcx), join_with_double_colon(fqn)))
+            .collect();
+        let previous_paths = previous
+            .paths
+            .iter()
+            .map(|(id, summary)| (id.clone(), summary.path.join("::")))
+            .collect();
+        let mut collector = Collector {
+            previous,
+            cache,
+            tcx,
+            paths,
+            previous_paths,
+            previous_items: FxHashMap::default(),
+            seen: FxHashSet::default(),
+            changes: Changes {
+                since: previous.crate_version.clone(),
+                kinds: FxHashMap::default(),
+                added: Vec::new(),
+                changed: Vec::new(),
+                removed: Vec::new(),
+            },
+        };
+        let previous_root =
+            previous.index.get(&previous.root).and_then(|root| Some((root, root.name.as_deref()?)));
+        if let Some((root, name)) = previous_root {
+            collector.index_previous(root, name);
+        }
+        let mut path = vec![krate.name(tcx).to_string()];
+        collector.visit_module(&krate.module, &mut path, false);
+        if let Some((root, name)) = previous_root {
+            collector.visit_previous_members(root, name);
+        }
+
+        let mut changes = collector.changes;
+        changes.added.sort();
+        changes.changed.sort();
+        changes.removed.sort();
+        // An item can be reached through several re-exports.
+        changes.removed.dedup();
+        changes
+    }
+
+    pub(crate) fn kind(&self, item_id: ItemId) -> Option<ChangeKind> {
+        self.kinds.get(&item_id).copied()
+    }
+
+    /// Renders the changelog page.
+    pub(crate) fn print(&self, f: &mut Buffer) {
+        fn print_entries(f: &mut Buffer, entries: &[ChangeEntry], title: &str, id: &str) {
+            if entries.is_empty() {
+                return;
+            }
+            write!(f, "<h3 id=\"{}\">{}</h3><ul class=\"{} docblock\">", id, title, id);
+            for entry in entries {
+                match entry.url {
+                    Some(ref url) => {
+                        write!(f, "<li><a href=\"{}\">{}</a></li>", url, Escape(&entry.path))
+                    }
+                    None => write!(f, "<li>{}</li>", Escape(&entry.path)),
+                }
+            }
+            f.write_str("</ul>");
+        }
+
+        write!(
+            f,
+            "<h1 class=\"fqn\">\
+                 <span class=\"in-band\">Changes since {}</span>\
+             </h1>",
+            match self.since {
+                Some(ref version) => format!("version {}", Escape(version)),
+                None => "the previous version".to_owned(),
+            }
+        );
+        if self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty() {
+            f.write_str("<p>The public API of this crate didn't change.</p>");
+        }
+        print_entries(f, &self.added, "Added", "added");
+        print_entries(f, &self.changed, "Modified", "modified");
+        print_entries(f, &self.removed, "Removed", "removed");
+    }
+}
+
+struct Collector<'a, 'tcx> {
+    previous: &'a types::Crate,
+    cache: &'a Cache,
+    tcx: TyCtxt<'tcx>,
+    /// The paths of the items referred to by the current version, by JSON ID.
+    paths: FxHashMap<types::Id, String>,
+    /// The paths of the items referred to by the previous version, by JSON ID.
+    previous_paths: FxHashMap<types::Id, String>,
+    /// The items of the previous version by path. Items of different namespaces, like a function
+    /// and a macro, can share a path.
+    previous_items: FxHashMap<String, Vec<&'a types::Item>>,
+    /// The JSON IDs of the items of the previous version which are still there.
+    seen: FxHashSet<types::Id>,
+    changes: Changes,
+}
+
+impl<'a> Collector<'a, '_> {
+    /// Compares `item` with its previous version, if any. `in_added` is `true` if one of the
+    /// items containing it is new, in which case only this one is reported as new.
+    fn visit_item(
+        &mut self,
+        item: &clean::Item,
+        path: String,
+        url: String,
+        in_added: bool,
+    ) -> Option<ChangeKind> {
+        let kind = match self.previous_items.get(&path) {
+            None => Some(ChangeKind::Added),
+            Some(candidates) => {
+                let new = from_clean_item(item.clone(), self.tcx);
+                let old = candidates
+                    .iter()
+                    .find(|old| mem::discriminant(&old.inner) == mem::discriminant(&new))
+                    .or_else(|| candidates.first().filter(|_| candidates.len() == 1));
+                match old {
+                    None => Some(ChangeKind::Added),
+                    Some(old) => {
+                        self.seen.insert(old.id.clone());
+                        let old = with_paths(&old.inner, &self.previous_paths);
+                        let new = with_paths(&new, &self.paths);
+                        signature_changed(&old, &new).then_some(ChangeKind::Changed)
+                    }
+                }
+            }
+        };
+
+        match kind {
+            Some(ChangeKind::Added) if in_added => {}
+            Some(kind) => {
+                let entries = match kind {
+                    ChangeKind::Added => &mut self.changes.added,
+                    ChangeKind::Changed => &mut self.changes.changed,
+                };
+                entries.push(ChangeEntry { path, url: Some(url) });
+                self.changes.kinds.insert(item.item_id, kind);
+            }
+            None => {}
+        }
+        kind
+    }
+
+    fn visit_module(&mut self, module: &clean::Item, path: &mut Vec<String>, in_added: bool) {
+        let clean::ModuleItem(ref m) = *module.kind else { return };
+        let dir: String = path[1..].iter().map(|name| format!("{}/", name)).collect();
+        for item in &m.items {
+            let Some(name) = item.name else { continue };
+            if item.is_stripped() {
+                continue;
+            }
+            match *item.kind {
+                clean::ImportItem(..)
+                | clean::ImplItem(..)
+                | clean::ExternCrateItem { .. }
+                | clean::KeywordItem(..) => continue,
+                _ => {}
+            }
+
+            path.push(name.to_string());
+            let url = if item.is_mod() {
+                format!("{}{}/index.html", dir, name)
+            } else {
+                format!("{}{}.{}.html", dir, item.type_(), name)
+            };
+            let kind = self.visit_item(item, path.join("::"), url.clone(), in_added);
+            let in_added = in_added || kind == Some(ChangeKind::Added);
+            if item.is_mod() {
+                self.visit_module(item, path, in_added);
+            } else {
+                self.visit_members(item, &path.join("::"), &url, in_added);
+            }
+            path.pop();
+        }
+    }
+
+    fn visit_member(&mut self, item: &clean::Item, path: &str, url: &str, in_added: bool) {
+        let Some(name) = item.name else { return };
+        if item.is_stripped() {
+            return;
+        }
+        let url = format!("{}#{}.{}", url, item.type_(), name);
+        let kind = self.visit_item(item, format!("{}::{}", path, name), url.clone(), in_added);
+        if let clean::VariantItem(clean::Variant::Struct(ref s)) = *item.kind {
+            let in_added = in_added || kind == Some(ChangeKind::Added);
+            for field in &s.fields {
+                let Some(field_name) = field.name else { continue };
+                if field.is_stripped() {
+                    continue;
+                }
+                self.visit_item(
+                    field,
+                    format!("{}::{}::{}", path, name, field_name),
+                    format!("{}.field.{}", url, field_name),
+                    in_added,
+                );
+            }
+        }
+    }
+
+    fn visit_members(&mut self, item: &clean::Item, path: &str, url: &str, in_added: bool) {
+        match *item.kind {
+            clean::StructItem(clean::Struct { ref fields, .. })
+            | clean::UnionItem(clean::Union { ref fields, .. }) => {
+                for field in fields {
+                    self.visit_member(field, path, url, in_added);
+                }
+            }
+            clean::EnumItem(ref e) => {
+                for variant in &e.variants {
+                    self.visit_member(variant, path, url, in_added);
+                }
+            }
+            clean::TraitItem(ref t) => {
+                for trait_item in &t.items {
+                    self.visit_member(trait_item, path, url, in_added);
+                }
+                return;
+            }
+            clean::ForeignTypeItem => {}
+            _ => return,
+        }
+
+        // The inherent impls are documented on the page of the type.
+        let Some(did) = item.item_id.as_def_id() else { return };
+        let Some(impls) = self.cache.impls.get(&did) else { return };
+        for impl_ in impls.iter().filter(|i| i.inner_impl().trait_.is_none()) {
+            for impl_item in &impl_.inner_impl().items {
+                self.visit_member(impl_item, path, url, in_added);
+            }
+        }
+    }
+
+    /// Indexes the items of the previous version by path.
+    fn index_previous(&mut self, item: &'a types::Item, path: &str) {
+        for member in previous_members(self.previous, item) {
+            let Some(ref name) = member.name else { continue };
+            let path = format!("{}::{}", path, name);
+            self.previous_items.entry(path.clone()).or_default().push(member);
+            self.index_previous(member, &path);
+        }
+    }
+
+    /// Looks for the items of the previous version which weren't found in the current one.
+    fn visit_previous_members(&mut self, item: &'a types::Item, path: &str) {
+        for member in previous_members(self.previous, item) {
+            let Some(ref name) = member.name else { continue };
+            let path = format!("{}::{}", path, name);
+            if self.seen.contains(&member.id) {
+                self.visit_previous_members(member, &path);
+            } else {
+                // The items it contains are gone as well.
+                self.changes.removed.push(ChangeEntry { path, url: None });
+            }
+        }
+    }
+}
+
+/// Returns the items of the previous version which are documented as part of `item`: the items of
+/// a module, the fields and variants of a type along with its inherent associated items, and the
+/// items of a trait.
+fn previous_members<'a>(previous: &'a types::Crate, item: &'a types::Item) -> Vec<&'a types::Item> {
+    let (members, impls) = match item.inner {
+        types::ItemEnum::Module(ref m) => (&m.items, None),
+        types::ItemEnum::Struct(ref s) => (&s.fields, Some(&s.impls)),
+        types::ItemEnum::Union(ref u) => (&u.fields, Some(&u.impls)),
+        types::ItemEnum::Enum(ref e) => (&e.variants, Some(&e.impls)),
+        types::ItemEnum::Variant(types::Variant::Struct(ref fields)) => (fields, None),
+        types::ItemEnum::Trait(ref t) => (&t.items, None),
+        _ => return Vec::new(),
+    };
+    let impl_items =
+        impls.into_iter().flatten().filter_map(|impl_id| match previous.index.get(impl_id) {
+            Some(types::Item { inner: types::ItemEnum::Impl(ref impl_), .. })
+                if impl_.trait_.is_none() =>
+            {
+                Some(&impl_.items)
+            }
+            _ => None,
+        });
+    members
+        .iter()
+        .chain(impl_items.flatten())
+        .filter_map(|id| previous.index.get(id))
+        .filter(|member| {
+            !matches!(
+                member.inner,
+                types::ItemEnum::Import(_)
+                    | types::ItemEnum::Impl(_)
+                    | types::ItemEnum::ExternCrate { .. }
+            )
+        })
+        .collect()
+}
+
+/// Replaces the IDs of the items referred to by `item` with their paths, since the IDs change along
+/// with the `-C metadata` of the crates defining the items, like a new version of a dependency.
+fn with_paths(item: &types::ItemEnum, paths: &FxHashMap<types::Id, String>) -> types::ItemEnum {
+    // Items refer to each other through the `id` field of paths, wherever the paths are found.
+    fn replace_ids(value: &mut serde_json::Value, paths: &FxHashMap<types::Id, String>) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    match value {
+                        serde_json::Value::String(id) if key == "id" => {
+                            if let Some(path) = paths.get(&types::Id(id.clone())) {
+                                *id = path.clone();
+                            }
+                        }
+                        value => replace_ids(value, paths),
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => {
+                for value in values {
+                    replace_ids(value, paths);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(item).expect("failed to serialize an item");
+    replace_ids(&mut value, paths);
+    serde_json::from_value(value).expect("failed to deserialize an item")
+}
+
+/// Returns `true` if the parts of `old` and `new` which are visible to the users of the item
+/// differ. For types, only their header is considered, since their fields and methods are
+/// compared on their own.
+fn signature_changed(old: &types::ItemEnum, new: &types::ItemEnum) -> bool {
+    use types::ItemEnum::*;
+
+    fn decl_changed(old: &types::FnDecl, new: &types::FnDecl) -> bool {
+        // Renaming an argument doesn't change the signature.
+        !old.inputs.iter().map(|(_, ty)| ty).eq(new.inputs.iter().map(|(_, ty)| ty))
+            || old.output != new.output
+            || old.c_variadic != new.c_variadic
+    }
+
+    match (old, new) {
+        (Function(old), Function(new)) => {
+            decl_changed(&old.decl, &new.decl)
+                || old.generics != new.generics
+                || old.header != new.header
+        }
+        (Method(old), Method(new)) => {
+            decl_changed(&old.decl, &new.decl)
+                || old.generics != new.generics
+                || old.header != new.header
+                || old.has_body != new.has_body
+        }
+        (Struct(old), Struct(new)) => {
+            old.struct_type != new.struct_type || old.generics != new.generics
+        }
+        (Union(old), Union(new)) => old.generics != new.generics,
+        (Enum(old), Enum(new)) => old.generics != new.generics,
+        (Variant(types::Variant::Struct(_)), Variant(types::Variant::Struct(_))) => false,
+        (Trait(old), Trait(new)) => {
+            old.is_auto != new.is_auto
+                || old.is_unsafe != new.is_unsafe
+                || old.generics != new.generics
+                || old.bounds != new.bounds
+        }
+        (Constant(old), Constant(new)) => old.type_ != new.type_,
+        (Static(old), Static(new)) => old.type_ != new.type_ || old.mutable != new.mutable,
+        (AssocConst { type_: old, .. }, AssocConst { type_: new, .. }) => old != new,
+        (Module(_), Module(_))
+        | (Macro(_), Macro(_))
+        | (PrimitiveType(_), PrimitiveType(_))
+        | (ForeignType, ForeignType) => false,
+        (old, new) => old != new,
+    }
+}
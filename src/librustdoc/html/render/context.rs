@@ -13,6 +13,7 @@
 use rustc_span::source_map::FileName;
 use rustc_span::{sym, Symbol};
 
+use super::changes::Changes;
 use super::print_item::{full_path, item_path, print_item};
 use super::search_index::build_index;
 use super::write_shared::write_shared;
@@ -124,6 +125,8 @@ pub(crate) struct SharedContext<'tcx> {
     pub(crate) cache: Cache,
 
     pub(crate) call_locations: AllCallLocations,
+    /// The changes since the version of the crate given with `--compare-with`, if any.
+    pub(crate) changes: Option<Changes>,
 }
 
 impl SharedContext<'_> {
@@ -408,6 +411,7 @@ fn init(
             show_type_layout,
             generate_link_to_definition,
             call_locations,
+            compare_with,
             no_emit_shared,
             ..
         } = options;
@@ -471,6 +475,8 @@ fn init(
             generate_link_to_definition,
         );
 
+        let changes = compare_with.map(|previous| Changes::new(&previous, &krate, &cache, tcx));
+
         let (sender, receiver) = channel();
         let mut scx = SharedContext {
             tcx,
@@ -493,6 +499,7 @@ fn init(
             span_correspondance_map: matches,
             cache,
             call_locations,
+            changes,
         };
 
         // Add the default themes to the `Vec` of stylepaths
@@ -553,6 +560,7 @@ fn make_child_renderer(&self) -> Self {
     fn after_krate(&mut self) -> Result<(), Error> {
         let crate_name = self.tcx().crate_name(LOCAL_CRATE);
         let final_file = self.dst.join(crate_name.as_str()).join("all.html");
+        let changes_file = self.dst.join(crate_name.as_str()).join("changes.html");
         let settings_file = self.dst.join("settings.html");
         let scrape_examples_help_file = self.dst.join("scrape-examples-help.html");
 
@@ -579,12 +587,25 @@ fn after_krate(&mut self) -> Result<(), Error> {
         let v = layout::render(
             &shared.layout,
             &page,
-            sidebar,
+            sidebar.as_str(),
             |buf: &mut Buffer| all.print(buf),
             &shared.style_files,
         );
         shared.fs.write(final_file, v)?;
 
+        if let Some(ref changes) = shared.changes {
+            page.title = "Changes in this crate";
+            page.description = "List of the items changed since the previous version";
+            let v = layout::render(
+                &shared.layout,
+                &page,
+                sidebar,
+                |buf: &mut Buffer| changes.print(buf),
+                &shared.style_files,
+            );
+            shared.fs.write(changes_file, v)?;
+        }
+
         // Generating settings page.
         page.title = "Rustdoc settings";
         page.description = "Settings of Rustdoc";
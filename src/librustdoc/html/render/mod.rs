@@ -28,11 +28,13 @@
 #[cfg(test)]
 mod tests;
 
+mod changes;
 mod context;
 mod print_item;
 mod span_map;
 mod write_shared;
 
+pub(crate) use self::changes::load_previous_version;
 pub(crate) use self::context::*;
 pub(crate) use self::span_map::{collect_spans_and_sources, LinkFromSrc};
 
@@ -122,7 +124,9 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         let id = match &self.id {
             // 0 is a sentinel, everything else is one-indexed
             None => 0,
-            Some(RenderTypeId::Index(idx)) => idx + 1,
+            Some(RenderTypeId::Index(idx)) => *idx as isize + 1,
+            // type parameters are negative, also one-indexed
+            Some(RenderTypeId::Generic(idx)) => -(*idx as isize) - 1,
             _ => panic!("must convert render types to indexes before serializing"),
         };
         if let Some(generics) = &self.generics {
@@ -141,6 +145,9 @@ pub(crate) enum RenderTypeId {
     DefId(DefId),
     Primitive(clean::PrimitiveType),
     Index(usize),
+    /// A type parameter or `impl Trait` type of the function, numbered in the order they appear
+    /// in its signature.
+    Generic(usize),
 }
 
 /// Full type of functions/methods in the search index.
@@ -148,6 +155,8 @@ pub(crate) enum RenderTypeId {
 pub(crate) struct IndexItemFunctionType {
     inputs: Vec<RenderType>,
     output: Vec<RenderType>,
+    /// The trait bounds of each type parameter, indexed like `RenderTypeId::Generic`.
+    where_clause: Vec<Vec<RenderType>>,
 }
 
 impl Serialize for IndexItemFunctionType {
@@ -169,11 +178,15 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
                 [one] if one.generics.is_none() => seq.serialize_element(one)?,
                 _ => seq.serialize_element(&self.inputs)?,
             }
+            let has_bounds = self.where_clause.iter().any(|bounds| !bounds.is_empty());
             match &self.output[..] {
-                [] => {}
+                [] if !has_bounds => {}
                 [one] if one.generics.is_none() => seq.serialize_element(one)?,
                 _ => seq.serialize_element(&self.output)?,
             }
+            if has_bounds {
+                seq.serialize_element(&self.where_clause)?;
+            }
             seq.end()
         }
     }
@@ -603,6 +616,10 @@ fn short_item_info(
         extra_info.push(portability);
     }
 
+    if let Some(kind) = cx.shared.changes.as_ref().and_then(|changes| changes.kind(item.item_id)) {
+        extra_info.push(kind.badge().to_owned());
+    }
+
     extra_info
 }
 
@@ -950,7 +967,7 @@ fn render_assoc_item(
 const ALLOWED_ATTRIBUTES: &[Symbol] =
     &[sym::export_name, sym::link_section, sym::no_mangle, sym::repr, sym::non_exhaustive];
 
-fn attributes(it: &clean::Item) -> Vec<String> {
+pub(crate) fn attributes(it: &clean::Item) -> Vec<String> {
     it.attrs
         .other_attrs
         .iter()
@@ -1778,6 +1795,9 @@ fn print_sidebar(cx: &Context<'_>, it: &clean::Item, buffer: &mut Buffer) {
             write!(buffer, "<li class=\"version\">Version {}</li>", Escape(version));
         }
         write!(buffer, "<li><a id=\"all-types\" href=\"all.html\">All Items</a></li>");
+        if cx.shared.changes.is_some() {
+            write!(buffer, "<li><a id=\"all-changes\" href=\"changes.html\">Changes</a></li>");
+        }
         buffer.write_str("</ul></div>");
     }
 
@@ -2377,7 +2397,7 @@ fn sidebar_enum(cx: &Context<'_>, buf: &mut Buffer, it: &clean::Item, e: &clean:
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum ItemSection {
+pub(crate) enum ItemSection {
     Reexports,
     PrimitiveTypes,
     Modules,
@@ -2406,7 +2426,7 @@ enum ItemSection {
 }
 
 impl ItemSection {
-    const ALL: &'static [Self] = {
+    pub(crate) const ALL: &'static [Self] = {
         use ItemSection::*;
         // NOTE: The order here affects the order in the UI.
         &[
@@ -2468,7 +2488,7 @@ fn id(self) -> &'static str {
         }
     }
 
-    fn name(self) -> &'static str {
+    pub(crate) fn name(self) -> &'static str {
         match self {
             Self::Reexports => "Re-exports",
             Self::Modules => "Modules",
@@ -2499,7 +2519,7 @@ fn name(self) -> &'static str {
     }
 }
 
-fn item_ty_to_section(ty: ItemType) -> ItemSection {
+pub(crate) fn item_ty_to_section(ty: ItemType) -> ItemSection {
     match ty {
         ItemType::ExternCrate | ItemType::Import => ItemSection::Reexports,
         ItemType::Module => ItemSection::Modules,
@@ -9,7 +9,8 @@
 
 use crate::clean;
 use crate::clean::types::{
-    FnRetTy, Function, GenericBound, Generics, ItemId, Type, WherePredicate,
+    FnRetTy, Function, GenericArg, GenericArgs, GenericBound, Generics, ItemId, PrimitiveType,
+    Type, TypeBindingKind, WherePredicate,
 };
 use crate::formats::cache::{Cache, OrphanImplItem};
 use crate::formats::item_type::ItemType;
@@ -40,7 +41,7 @@ pub(crate) fn build_index<'tcx>(
                 desc,
                 parent: Some(parent),
                 parent_idx: None,
-                search_type: get_function_type_for_search(item, tcx, impl_generics.as_ref(), cache),
+                search_type: get_function_type_for_search(item, tcx, impl_generics.as_ref()),
                 aliases: item.attrs.get_doc_aliases(),
             });
         }
@@ -111,7 +112,7 @@ fn convert_render_type(
                     primitive.as_sym(),
                     ItemType::Primitive,
                 ),
-                RenderTypeId::Index(_) => return,
+                RenderTypeId::Index(_) | RenderTypeId::Generic(_) => return,
             };
             match itemid_to_pathid.entry(itemid) {
                 Entry::Occupied(entry) => ty.id = Some(RenderTypeId::Index(*entry.get())),
@@ -143,6 +144,15 @@ fn convert_render_type(
                     &mut crate_paths,
                 );
             }
+            for item in search_type.where_clause.iter_mut().flatten() {
+                convert_render_type(
+                    item,
+                    cache,
+                    &mut itemid_to_pathid,
+                    &mut lastpathid,
+                    &mut crate_paths,
+                );
+            }
         }
     }
 
@@ -296,131 +306,49 @@ pub(crate) fn get_function_type_for_search<'tcx>(
     item: &clean::Item,
     tcx: TyCtxt<'tcx>,
     impl_generics: Option<&(clean::Type, clean::Generics)>,
-    cache: &Cache,
 ) -> Option<IndexItemFunctionType> {
-    let (mut inputs, mut output) = match *item.kind {
-        clean::FunctionItem(ref f) => get_fn_inputs_and_outputs(f, tcx, impl_generics, cache),
-        clean::MethodItem(ref m, _) => get_fn_inputs_and_outputs(m, tcx, impl_generics, cache),
-        clean::TyMethodItem(ref m) => get_fn_inputs_and_outputs(m, tcx, impl_generics, cache),
+    let (inputs, output, where_clause) = match *item.kind {
+        clean::FunctionItem(ref f) => get_fn_inputs_and_outputs(f, tcx, impl_generics),
+        clean::MethodItem(ref m, _) => get_fn_inputs_and_outputs(m, tcx, impl_generics),
+        clean::TyMethodItem(ref m) => get_fn_inputs_and_outputs(m, tcx, impl_generics),
         _ => return None,
     };
 
-    inputs.retain(|a| a.id.is_some() || a.generics.is_some());
-    output.retain(|a| a.id.is_some() || a.generics.is_some());
-
-    Some(IndexItemFunctionType { inputs, output })
+    Some(IndexItemFunctionType { inputs, output, where_clause })
 }
 
-fn get_index_type(clean_type: &clean::Type, generics: Vec<RenderType>) -> RenderType {
-    RenderType {
-        id: get_index_type_id(clean_type),
-        generics: if generics.is_empty() { None } else { Some(generics) },
-    }
-}
-
-fn get_index_type_id(clean_type: &clean::Type) -> Option<RenderTypeId> {
-    match *clean_type {
-        clean::Type::Path { ref path, .. } => Some(RenderTypeId::DefId(path.def_id())),
-        clean::DynTrait(ref bounds, _) => {
-            let path = &bounds[0].trait_;
-            Some(RenderTypeId::DefId(path.def_id()))
-        }
-        clean::Primitive(p) => Some(RenderTypeId::Primitive(p)),
-        clean::BorrowedRef { ref type_, .. } | clean::RawPointer(_, ref type_) => {
-            get_index_type_id(type_)
-        }
-        clean::BareFunction(_)
-        | clean::Generic(_)
-        | clean::ImplTrait(_)
-        | clean::Tuple(_)
-        | clean::Slice(_)
-        | clean::Array(_, _)
-        | clean::QPath { .. }
-        | clean::Infer => None,
-    }
+/// The type parameters found in a function signature.
+///
+/// Both type parameters and `impl Trait` types are numbered in the order they are found, so that
+/// the search can tell whether two types of the signature are the same type parameter.
+#[derive(Default)]
+struct FnTypeParams {
+    /// The number of each named type parameter found so far.
+    names: FxHashMap<Symbol, usize>,
+    /// The trait bounds of each type parameter, from both its declaration and the `where` clause.
+    bounds: Vec<Vec<RenderType>>,
 }
 
-/// The point of this function is to replace bounds with types.
+/// Returns the type of `arg` for the search index, or `None` if it can't be searched for.
 ///
-/// i.e. `[T, U]` when you have the following bounds: `T: Display, U: Option<T>` will return
-/// `[Display, Option]`. If a type parameter has no trait bound, it is discarded.
+/// i.e. `fn foo<T: Display>(x: Option<&T>)` will return `Option<T>` for `x`, where `T` is the
+/// first type parameter, with `[Display]` as the bounds of `T` in `params`.
 ///
 /// Important note: It goes through generics recursively. So if you have
 /// `T: Option<Result<(), ()>>`, it'll go into `Option` and then into `Result`.
-#[instrument(level = "trace", skip(tcx, res, cache))]
-fn add_generics_and_bounds_as_types<'tcx, 'a>(
+#[instrument(level = "trace", skip(tcx, params))]
+fn simplify_fn_type<'tcx, 'a>(
     self_: Option<&'a Type>,
     generics: &Generics,
     arg: &'a Type,
     tcx: TyCtxt<'tcx>,
     recurse: usize,
-    res: &mut Vec<RenderType>,
-    cache: &Cache,
-) {
-    fn insert_ty(res: &mut Vec<RenderType>, ty: Type, mut generics: Vec<RenderType>) {
-        // generics and impl trait are both identified by their generics,
-        // rather than a type name itself
-        let anonymous = ty.is_full_generic() || ty.is_impl_trait();
-        let generics_empty = generics.is_empty();
-
-        if anonymous {
-            if generics_empty {
-                // This is a type parameter with no trait bounds (for example: `T` in
-                // `fn f<T>(p: T)`, so not useful for the rustdoc search because we would end up
-                // with an empty type with an empty name. Let's just discard it.
-                return;
-            } else if generics.len() == 1 {
-                // In this case, no need to go through an intermediate state if the type parameter
-                // contains only one trait bound.
-                //
-                // For example:
-                //
-                // `fn foo<T: Display>(r: Option<T>) {}`
-                //
-                // In this case, it would contain:
-                //
-                // ```
-                // [{
-                //     name: "option",
-                //     generics: [{
-                //         name: "",
-                //         generics: [
-                //             name: "Display",
-                //             generics: []
-                //         }]
-                //     }]
-                // }]
-                // ```
-                //
-                // After removing the intermediate (unnecessary) type parameter, it'll become:
-                //
-                // ```
-                // [{
-                //     name: "option",
-                //     generics: [{
-                //         name: "Display",
-                //         generics: []
-                //     }]
-                // }]
-                // ```
-                //
-                // To be noted that it can work if there is ONLY ONE trait bound, otherwise we still
-                // need to keep it as is!
-                res.push(generics.pop().unwrap());
-                return;
-            }
-        }
-        let index_ty = get_index_type(&ty, generics);
-        if index_ty.id.is_none() && generics_empty {
-            return;
-        }
-        res.push(index_ty);
-    }
-
+    params: &mut FnTypeParams,
+) -> Option<RenderType> {
     if recurse >= 10 {
         // FIXME: remove this whole recurse thing when the recursion bug is fixed
         // See #59502 for the original issue.
-        return;
+        return None;
     }
 
     // First, check if it's "Self".
@@ -434,108 +362,149 @@ fn insert_ty(res: &mut Vec<RenderType>, ty: Type, mut generics: Vec<RenderType>)
         arg
     };
 
-    // If this argument is a type parameter and not a trait bound or a type, we need to look
-    // for its bounds.
-    if let Type::Generic(arg_s) = *arg {
-        // First we check if the bounds are in a `where` predicate...
-        if let Some(where_pred) = generics.where_predicates.iter().find(|g| match g {
-            WherePredicate::BoundPredicate { ty, .. } => ty.def_id(cache) == arg.def_id(cache),
-            _ => false,
-        }) {
-            let mut ty_generics = Vec::new();
-            let bounds = where_pred.get_bounds().unwrap_or_else(|| &[]);
-            for bound in bounds.iter() {
-                if let GenericBound::TraitBound(poly_trait, _) = bound {
-                    for param_def in poly_trait.generic_params.iter() {
-                        match &param_def.kind {
-                            clean::GenericParamDefKind::Type { default: Some(ty), .. } => {
-                                add_generics_and_bounds_as_types(
-                                    self_,
-                                    generics,
-                                    ty,
-                                    tcx,
-                                    recurse + 1,
-                                    &mut ty_generics,
-                                    cache,
-                                )
+    let (id, type_generics) = match *arg {
+        Type::Generic(name) => {
+            let index = match params.names.get(&name) {
+                Some(&index) => index,
+                None => {
+                    // The parameter is registered before looking at its bounds, as they can refer
+                    // to it, like in `T: PartialEq<T>`.
+                    let index = params.bounds.len();
+                    params.names.insert(name, index);
+                    params.bounds.push(Vec::new());
+                    let inline_bounds = generics
+                        .params
+                        .iter()
+                        .filter(|param| param.is_type() && param.name == name)
+                        .flat_map(|param| param.get_bounds().unwrap_or(&[]));
+                    let where_bounds = generics
+                        .where_predicates
+                        .iter()
+                        .filter(|pred| match pred {
+                            WherePredicate::BoundPredicate { ty: Type::Generic(ty), .. } => {
+                                *ty == name
                             }
-                            _ => {}
-                        }
-                    }
+                            _ => false,
+                        })
+                        .flat_map(|pred| pred.get_bounds().unwrap_or(&[]));
+                    let bounds: Vec<_> = inline_bounds.chain(where_bounds).collect();
+                    params.bounds[index] =
+                        simplify_bounds(self_, generics, &bounds, tcx, recurse, params);
+                    index
                 }
-            }
-            insert_ty(res, arg.clone(), ty_generics);
+            };
+            (RenderTypeId::Generic(index), None)
         }
-        // Otherwise we check if the trait bounds are "inlined" like `T: Option<u32>`...
-        if let Some(bound) = generics.params.iter().find(|g| g.is_type() && g.name == arg_s) {
-            let mut ty_generics = Vec::new();
-            for bound in bound.get_bounds().unwrap_or(&[]) {
-                if let Some(path) = bound.get_trait_path() {
-                    let ty = Type::Path { path };
-                    add_generics_and_bounds_as_types(
-                        self_,
-                        generics,
-                        &ty,
-                        tcx,
-                        recurse + 1,
-                        &mut ty_generics,
-                        cache,
-                    );
-                }
-            }
-            insert_ty(res, arg.clone(), ty_generics);
+        Type::ImplTrait(ref bounds) => {
+            let index = params.bounds.len();
+            params.bounds.push(Vec::new());
+            let bounds: Vec<_> = bounds.iter().collect();
+            params.bounds[index] = simplify_bounds(self_, generics, &bounds, tcx, recurse, params);
+            (RenderTypeId::Generic(index), None)
         }
-    } else if let Type::ImplTrait(ref bounds) = *arg {
-        let mut ty_generics = Vec::new();
-        for bound in bounds {
-            if let Some(path) = bound.get_trait_path() {
-                let ty = Type::Path { path };
-                add_generics_and_bounds_as_types(
-                    self_,
-                    generics,
-                    &ty,
-                    tcx,
-                    recurse + 1,
-                    &mut ty_generics,
-                    cache,
-                );
-            }
+        Type::Path { ref path } => {
+            return Some(simplify_path(self_, generics, path, tcx, recurse, params));
         }
-        insert_ty(res, arg.clone(), ty_generics);
-    } else {
-        // This is not a type parameter. So for example if we have `T, U: Option<T>`, and we're
-        // looking at `Option`, we enter this "else" condition, otherwise if it's `T`, we don't.
-        //
-        // So in here, we can add it directly and look for its own type parameters (so for `Option`,
-        // we will look for them but not for `T`).
-        let mut ty_generics = Vec::new();
-        if let Some(arg_generics) = arg.generics() {
-            for gen in arg_generics.iter() {
-                add_generics_and_bounds_as_types(
-                    self_,
-                    generics,
-                    gen,
-                    tcx,
-                    recurse + 1,
-                    &mut ty_generics,
-                    cache,
-                );
-            }
+        Type::DynTrait(ref bounds, _) => {
+            let path = &bounds[0].trait_;
+            return Some(simplify_path(self_, generics, path, tcx, recurse, params));
+        }
+        Type::Primitive(p) => (RenderTypeId::Primitive(p), None),
+        Type::BorrowedRef { ref type_, .. } | Type::RawPointer(_, ref type_) => {
+            return simplify_fn_type(self_, generics, type_, tcx, recurse + 1, params);
+        }
+        // Slices, arrays and tuples are searched for like generic types, as in `slice<u8>`.
+        Type::Slice(ref type_) | Type::Array(ref type_, _) => {
+            let primitive =
+                if let Type::Slice(_) = *arg { PrimitiveType::Slice } else { PrimitiveType::Array };
+            let type_ = simplify_fn_type(self_, generics, type_, tcx, recurse + 1, params);
+            (RenderTypeId::Primitive(primitive), type_.map(|type_| vec![type_]))
         }
-        insert_ty(res, arg.clone(), ty_generics);
+        Type::Tuple(ref types) if !types.is_empty() => {
+            let types: Vec<_> = types
+                .iter()
+                .filter_map(|ty| simplify_fn_type(self_, generics, ty, tcx, recurse + 1, params))
+                .collect();
+            (
+                RenderTypeId::Primitive(PrimitiveType::Tuple),
+                if types.is_empty() { None } else { Some(types) },
+            )
+        }
+        Type::Tuple(_) | Type::BareFunction(_) | Type::QPath { .. } | Type::Infer => return None,
+    };
+    Some(RenderType { id: Some(id), generics: type_generics })
+}
+
+/// Returns the type of `path` for the search index, with its generic arguments.
+///
+/// Associated type bindings are treated like generic arguments, so `Iterator<Item = u8>` becomes
+/// `Iterator<u8>`, and so do the inputs and output of `Fn`-like traits.
+fn simplify_path<'tcx>(
+    self_: Option<&Type>,
+    generics: &Generics,
+    path: &clean::Path,
+    tcx: TyCtxt<'tcx>,
+    recurse: usize,
+    params: &mut FnTypeParams,
+) -> RenderType {
+    let mut types: Vec<&Type> = Vec::new();
+    match path.segments.last().map(|segment| &segment.args) {
+        Some(GenericArgs::AngleBracketed { args, bindings }) => {
+            types.extend(args.iter().filter_map(|arg| match arg {
+                GenericArg::Type(ty) => Some(ty),
+                _ => None,
+            }));
+            types.extend(bindings.iter().filter_map(|binding| match binding.kind {
+                TypeBindingKind::Equality { ref term } => term.ty(),
+                TypeBindingKind::Constraint { .. } => None,
+            }));
+        }
+        Some(GenericArgs::Parenthesized { inputs, output }) => {
+            types.extend(inputs.iter());
+            types.extend(output.as_deref());
+        }
+        None => {}
+    }
+    let type_generics: Vec<_> = types
+        .into_iter()
+        .filter_map(|ty| simplify_fn_type(self_, generics, ty, tcx, recurse + 1, params))
+        .collect();
+    RenderType {
+        id: Some(RenderTypeId::DefId(path.def_id())),
+        generics: if type_generics.is_empty() { None } else { Some(type_generics) },
     }
 }
 
-/// Return the full list of types when bounds have been resolved.
+/// Returns the traits of `bounds` as types for the search index.
+fn simplify_bounds<'tcx>(
+    self_: Option<&Type>,
+    generics: &Generics,
+    bounds: &[&GenericBound],
+    tcx: TyCtxt<'tcx>,
+    recurse: usize,
+    params: &mut FnTypeParams,
+) -> Vec<RenderType> {
+    bounds
+        .iter()
+        .filter_map(|bound| match bound {
+            GenericBound::TraitBound(poly_trait, _) => {
+                Some(simplify_path(self_, generics, &poly_trait.trait_, tcx, recurse + 1, params))
+            }
+            GenericBound::Outlives(_) => None,
+        })
+        .collect()
+}
+
+/// Return the full list of types when bounds have been resolved, along with the bounds of the
+/// type parameters.
 ///
 /// i.e. `fn foo<A: Display, B: Option<A>>(x: u32, y: B)` will return
-/// `[u32, Display, Option]`.
+/// `[u32, B]`, with `[Display]` as the bounds of `A` and `[Option<A>]` as the bounds of `B`.
 fn get_fn_inputs_and_outputs<'tcx>(
     func: &Function,
     tcx: TyCtxt<'tcx>,
     impl_generics: Option<&(clean::Type, clean::Generics)>,
-    cache: &Cache,
-) -> (Vec<RenderType>, Vec<RenderType>) {
+) -> (Vec<RenderType>, Vec<RenderType>, Vec<Vec<RenderType>>) {
     let decl = &func.decl;
 
     let combined_generics;
@@ -556,34 +525,21 @@ fn get_fn_inputs_and_outputs<'tcx>(
         (None, &func.generics)
     };
 
-    let mut all_types = Vec::new();
-    for arg in decl.inputs.values.iter() {
-        let mut args = Vec::new();
-        add_generics_and_bounds_as_types(self_, generics, &arg.type_, tcx, 0, &mut args, cache);
-        if !args.is_empty() {
-            all_types.extend(args);
-        } else {
-            all_types.push(get_index_type(&arg.type_, vec![]));
-        }
-    }
+    let mut params = FnTypeParams::default();
+    let inputs = decl
+        .inputs
+        .values
+        .iter()
+        .filter_map(|arg| simplify_fn_type(self_, generics, &arg.type_, tcx, 0, &mut params))
+        .collect();
 
-    let mut ret_types = Vec::new();
-    match decl.output {
+    let output = match decl.output {
         FnRetTy::Return(ref return_type) => {
-            add_generics_and_bounds_as_types(
-                self_,
-                generics,
-                return_type,
-                tcx,
-                0,
-                &mut ret_types,
-                cache,
-            );
-            if ret_types.is_empty() {
-                ret_types.push(get_index_type(return_type, vec![]));
-            }
+            simplify_fn_type(self_, generics, return_type, tcx, 0, &mut params)
+                .into_iter()
+                .collect()
         }
-        _ => {}
+        FnRetTy::DefaultReturn => Vec::new(),
     };
-    (all_types, ret_types)
+    (inputs, output, params.bounds)
 }
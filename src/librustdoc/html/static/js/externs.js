@@ -105,10 +105,18 @@ let Results;
  * in favor of the pair of types interpretation. This is why the `(number|Array<RawFunctionType>)`
  * is used instead of `(RawFunctionType|Array<RawFunctionType>)`.
  *
+ * If the type parameters of the function have trait bounds, they come third, as a list of bounds
+ * for each type parameter.
+ *
  * @typedef {(
  *     0 |
  *     [(number|Array<RawFunctionType>)] |
- *     [(number|Array<RawFunctionType>), (number|Array<RawFunctionType>)]
+ *     [(number|Array<RawFunctionType>), (number|Array<RawFunctionType>)] |
+ *     [
+ *         (number|Array<RawFunctionType>),
+ *         (number|Array<RawFunctionType>),
+ *         Array<Array<RawFunctionType>>
+ *     ]
  * )}
  */
 let RawFunctionSearchType;
@@ -118,7 +126,8 @@ let RawFunctionSearchType;
  * [path ID, generics].
  *
  * Numeric IDs are *ONE-indexed* into the paths array (`p`). Zero is used as a sentinel for `null`
- * because `null` is four bytes while `0` is one byte.
+ * because `null` is four bytes while `0` is one byte. Negative IDs are the type parameters of the
+ * function, so `-1` is the first one.
  *
  * @typedef {number | [number, Array<RawFunctionType>]}
  */
@@ -128,6 +137,7 @@ let RawFunctionType;
  * @typedef {{
  *     inputs: Array<FunctionType>,
  *     outputs: Array<FunctionType>,
+ *     where_clause: Array<Array<FunctionType>>,
  * }}
  */
 let FunctionSearchType;
@@ -136,6 +146,7 @@ let FunctionSearchType;
  * @typedef {{
  *     name: (null|string),
  *     ty: (null|number),
+ *     param: (null|number),
  *     generics: Array<FunctionType>,
  * }}
  */
@@ -854,6 +854,8 @@ function loadCss(cssFileName) {
              and <code>const</code>.",
             "Search functions by type signature (e.g., <code>vec -&gt; usize</code> or \
              <code>* -&gt; vec</code>)",
+            "Use single letters for any type in a type signature, as long as it's the same \
+             type everywhere (e.g., <code>Vec&lt;T&gt; -&gt; Option&lt;T&gt;</code>)",
             "Search multiple things at once by splitting your query with comma (e.g., \
              <code>str,u8</code> or <code>String,struct:Vec,test</code>)",
             "You can look for items with an exact name by putting double quotes around \
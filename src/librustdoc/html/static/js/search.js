@@ -115,12 +115,19 @@ function initSearch(rawSearchIndex) {
     const MAX_LEV_DISTANCE = 3;
     const MAX_RESULTS = 200;
     const NO_TYPE_FILTER = -1;
+    const MAX_UNIFY_DEPTH = 10;
     /**
      *  @type {Array<Row>}
      */
     let searchIndex;
     let currentResults;
     const ALIASES = Object.create(null);
+    /**
+     * The lowercase names of all the types which can be found in function signatures.
+     *
+     * @type {Set<string>}
+     */
+    const typeNames = new Set();
 
     function isWhitespace(c) {
         return " \t\n\r".indexOf(c) !== -1;
@@ -322,7 +329,8 @@ function initSearch(rawSearchIndex) {
                 } else if (
                     isStopCharacter(c) ||
                     isSpecialStartCharacter(c) ||
-                    isSeparatorCharacter(c)
+                    isSeparatorCharacter(c) ||
+                    c === "="
                 ) {
                     break;
                 } else if (c === ":") { // If we allow paths ("str::string" for example).
@@ -342,6 +350,56 @@ function initSearch(rawSearchIndex) {
         return end;
     }
 
+    /**
+     * Returns the position of the first character after `pos` which isn't a whitespace.
+     *
+     * @param {ParserState} parserState
+     * @param {integer} pos
+     *
+     * @return {integer}
+     */
+    function skipWhitespaces(parserState, pos) {
+        while (pos < parserState.length && isWhitespaceCharacter(parserState.userQuery[pos])) {
+            pos += 1;
+        }
+        return pos;
+    }
+
+    /**
+     * Moves the parser position after the `&`, `&mut`, `impl` and `dyn` in front of a type. They
+     * don't change which types match, but allow to write types like in a function signature, like
+     * `&mut Vec<u8>` or `impl Iterator`.
+     *
+     * A prefix is only skipped if a type follows it, so that a lone `&` is still reported as an
+     * error.
+     *
+     * @param {ParserState} parserState
+     */
+    function skipTypePrefixes(parserState) {
+        const userQuery = parserState.userQuery;
+        for (;;) {
+            let pos = parserState.pos;
+            if (userQuery[pos] === "&") {
+                pos = skipWhitespaces(parserState, pos + 1);
+                if (userQuery.startsWith("mut", pos) && isWhitespaceCharacter(userQuery[pos + 3])) {
+                    pos = skipWhitespaces(parserState, pos + 3);
+                }
+            } else {
+                const keyword = ["impl", "dyn"].find(word => userQuery.startsWith(word, pos));
+                if (keyword === undefined ||
+                    !isWhitespaceCharacter(userQuery[pos + keyword.length])
+                ) {
+                    return;
+                }
+                pos = skipWhitespaces(parserState, pos + keyword.length);
+            }
+            if (!isIdentCharacter(userQuery[pos]) && userQuery[pos] !== "&") {
+                return;
+            }
+            parserState.pos = pos;
+        }
+    }
+
     /**
      * @param {ParsedQuery} query
      * @param {ParserState} parserState
@@ -351,6 +409,7 @@ function initSearch(rawSearchIndex) {
     function getNextElem(query, parserState, elems, isInGenerics) {
         const generics = [];
 
+        skipTypePrefixes(parserState);
         let start = parserState.pos;
         let end;
         // We handle the strings on their own mostly to make code easier to follow.
@@ -360,13 +419,21 @@ function initSearch(rawSearchIndex) {
             end = parserState.pos - 1;
         } else {
             end = getIdentEndPosition(parserState);
+            const next = skipWhitespaces(parserState, parserState.pos);
+            if (parserState.userQuery[next] === "=") {
+                if (!isInGenerics || start >= end) {
+                    throw new Error("Unexpected `=`");
+                }
+                // An associated type binding like `Item=u8` matches like the type it binds.
+                parserState.pos = skipWhitespaces(parserState, next + 1);
+                getNextElem(query, parserState, elems, isInGenerics);
+                return;
+            }
         }
         if (parserState.pos < parserState.length &&
             parserState.userQuery[parserState.pos] === "<"
         ) {
-            if (isInGenerics) {
-                throw new Error("Unexpected `<` after `<`");
-            } else if (start >= end) {
+            if (start >= end) {
                 throw new Error("Found generics without a path");
             }
             parserState.pos += 1;
@@ -401,10 +468,12 @@ function initSearch(rawSearchIndex) {
      */
     function getItemsBefore(query, parserState, elems, endChar) {
         let foundStopChar = true;
+        let foundEndChar = false;
 
         while (parserState.pos < parserState.length) {
             const c = parserState.userQuery[parserState.pos];
             if (c === endChar) {
+                foundEndChar = true;
                 break;
             } else if (isSeparatorCharacter(c)) {
                 parserState.pos += 1;
@@ -437,6 +506,9 @@ function initSearch(rawSearchIndex) {
             }
             foundStopChar = false;
         }
+        if (!foundEndChar && endChar === ">") {
+            throw new Error("Unclosed `<`");
+        }
         // We are either at the end of the string or on the `endChar`` character, let's move forward
         // in any case.
         parserState.pos += 1;
@@ -881,160 +953,198 @@ function initSearch(rawSearchIndex) {
         }
 
         /**
-         * This function checks if the object (`row`) generics match the given type (`elem`)
-         * generics. If there are no generics on `row`, `defaultLev` is returned.
+         * Returns `true` if `elem` is a type variable of the query, like `T` in `Vec<T> -> T`:
+         * a single letter which isn't the name of a type.
          *
-         * @param {Row} row            - The object to check.
-         * @param {QueryElement} elem  - The element from the parsed query.
-         * @param {integer} defaultLev - This is the value to return in case there are no generics.
+         * @param {QueryElement} elem - The element from the parsed query.
          *
-         * @return {integer}           - Returns the best match (if any) or `MAX_LEV_DISTANCE + 1`.
+         * @return {boolean}
          */
-        function checkGenerics(row, elem, defaultLev) {
-            if (row.generics.length === 0) {
-                return elem.generics.length === 0 ? defaultLev : MAX_LEV_DISTANCE + 1;
-            } else if (row.generics.length > 0 && row.generics[0].name === null) {
-                return checkGenerics(row.generics[0], elem, defaultLev);
-            }
-            // The names match, but we need to be sure that all generics kinda
-            // match as well.
-            let elem_name;
-            if (elem.generics.length > 0 && row.generics.length >= elem.generics.length) {
-                const elems = Object.create(null);
-                for (const entry of row.generics) {
-                    elem_name = entry.name;
-                    if (elem_name === "") {
-                        // Pure generic, needs to check into it.
-                        if (checkGenerics(entry, elem, MAX_LEV_DISTANCE + 1) !== 0) {
-                            return MAX_LEV_DISTANCE + 1;
-                        }
-                        continue;
-                    }
-                    if (elems[elem_name] === undefined) {
-                        elems[elem_name] = 0;
+        function isTypeVariable(elem) {
+            return elem.fullPath.length === 1 &&
+                elem.generics.length === 0 &&
+                /^[a-z]$/.test(elem.name) &&
+                !typeNames.has(elem.name);
+        }
+
+        /**
+         * Returns a string identifying the type `row` of a function signature, used to check that
+         * a type variable of the query is always bound to the same type.
+         *
+         * @param {FunctionType} row
+         *
+         * @return {string}
+         */
+        function typeKey(row) {
+            if (row.param !== null) {
+                // Type names can't start with a `-`.
+                return "-" + row.param;
+            }
+            return row.name + "<" + row.generics.map(typeKey).join(",") + ">";
+        }
+
+        /**
+         * This function unifies the type `row` of the function signature `fnType` with the given
+         * type (`elem`), binding the type variables of the query to the types they match.
+         *
+         * A type parameter of the function matches the traits it's bound by, and the type we're
+         * looking for may also be inside the generics of `row`, which is a worse match.
+         *
+         * @param {FunctionType} row
+         * @param {QueryElement} elem           - The element from the parsed query.
+         * @param {FunctionSearchType} fnType   - The signature `row` comes from.
+         * @param {Map<string, string>} bindings - The types the type variables are bound to.
+         * @param {integer} typeFilter
+         * @param {boolean} exact               - Whether the names have to match exactly.
+         * @param {integer} depth
+         *
+         * @return {null|{lev: number, bindings: Map<string, string>}} - Returns the best match, if
+         *                                                                 any.
+         */
+        function unifyType(row, elem, fnType, bindings, typeFilter, exact, depth) {
+            if (depth > MAX_UNIFY_DEPTH) {
+                return null;
+            }
+            if (isTypeVariable(elem)) {
+                if (row.name === null && row.param === null) {
+                    return null;
+                }
+                const key = typeKey(row);
+                if (bindings.has(elem.name)) {
+                    return bindings.get(elem.name) === key ? {lev: 0, bindings: bindings} : null;
+                }
+                const newBindings = new Map(bindings);
+                newBindings.set(elem.name, key);
+                // A type parameter is the best match since it's as generic as the type variable.
+                return {lev: row.param === null ? 0.5 : 0, bindings: newBindings};
+            }
+            let best = null;
+            if (row.param !== null) {
+                for (const bound of fnType.where_clause[row.param] || []) {
+                    const match = unifyType(
+                        bound, elem, fnType, bindings, typeFilter, exact, depth + 1);
+                    if (match !== null && (best === null || match.lev < best.lev)) {
+                        best = match;
                     }
-                    elems[elem_name] += 1;
                 }
-                // We need to find the type that matches the most to remove it in order
-                // to move forward.
-                for (const generic of elem.generics) {
-                    let match = null;
-                    if (elems[generic.name]) {
-                        match = generic.name;
-                    } else {
-                        for (elem_name in elems) {
-                            if (!hasOwnPropertyRustdoc(elems, elem_name)) {
-                                continue;
-                            }
-                            if (elem_name === generic) {
-                                match = elem_name;
-                                break;
-                            }
-                        }
+                return best;
+            }
+            if (row.name !== null && typePassesFilter(typeFilter, row.ty)) {
+                const lev = exact ?
+                    (row.name === elem.pathLast ? 0 : MAX_LEV_DISTANCE + 1) :
+                    levenshtein(row.name, elem.pathLast);
+                if (elem.generics.length === 0) {
+                    // If the names don't match exactly, the type may still be in the generics.
+                    if (lev === 0 || (lev <= MAX_LEV_DISTANCE && row.generics.length === 0)) {
+                        best = {lev: lev, bindings: bindings};
                     }
-                    if (match === null) {
-                        return MAX_LEV_DISTANCE + 1;
+                } else if (lev <= MAX_LEV_DISTANCE) {
+                    const match = unifyGenerics(row.generics, elem, fnType, bindings, exact, depth);
+                    if (match !== null) {
+                        best = {lev: lev + match.lev, bindings: match.bindings};
                     }
-                    elems[match] -= 1;
-                    if (elems[match] === 0) {
-                        delete elems[match];
+                }
+            }
+            if (best === null || best.lev > 0) {
+                // The type we're looking for may be inside the generics, like `B<C>` in
+                // `A<B<C>>`. The match is "less good" so we add 0.5 to the distance.
+                for (const generic of row.generics) {
+                    const match = unifyType(
+                        generic, elem, fnType, bindings, typeFilter, true, depth + 1);
+                    if (match !== null && (best === null || match.lev + 0.5 < best.lev)) {
+                        best = {lev: match.lev + 0.5, bindings: match.bindings};
                     }
                 }
-                return 0;
             }
-            return MAX_LEV_DISTANCE + 1;
+            return best !== null && best.lev <= MAX_LEV_DISTANCE ? best : null;
         }
 
         /**
-          * This function checks if the object (`row`) matches the given type (`elem`) and its
-          * generics (if any).
-          *
-          * @param {Row} row
-          * @param {QueryElement} elem    - The element from the parsed query.
-          *
-          * @return {integer} - Returns a Levenshtein distance to the best match.
-          */
-        function checkIfInGenerics(row, elem) {
-            let lev = MAX_LEV_DISTANCE + 1;
-            for (const entry of row.generics) {
-                lev = Math.min(checkType(entry, elem, true), lev);
-                if (lev === 0) {
-                    break;
+         * This function unifies each generic of the given type (`elem`) with a different generic
+         * of a type from a function signature, in any order.
+         *
+         * @param {Array<FunctionType>} rowGenerics
+         * @param {QueryElement} elem           - The element from the parsed query.
+         * @param {FunctionSearchType} fnType   - The signature `rowGenerics` come from.
+         * @param {Map<string, string>} bindings - The types the type variables are bound to.
+         * @param {boolean} exact               - Whether the names have to match exactly.
+         * @param {integer} depth
+         *
+         * @return {null|{lev: number, bindings: Map<string, string>}} - Returns the best match, if
+         *                                                                 any, with the average
+         *                                                                 distance of the generics.
+         */
+        function unifyGenerics(rowGenerics, elem, fnType, bindings, exact, depth) {
+            const used = new Array(rowGenerics.length).fill(false);
+            let best = null;
+
+            function unifyFrom(i, totalLev, bindings) {
+                if (i === elem.generics.length) {
+                    if (best === null || totalLev < best.lev) {
+                        best = {lev: totalLev, bindings: bindings};
+                    }
+                    return;
+                }
+                for (let j = 0; j < rowGenerics.length && (best === null || best.lev > 0); ++j) {
+                    if (used[j]) {
+                        continue;
+                    }
+                    const generic = rowGenerics[j];
+                    let match = unifyType(
+                        generic, elem.generics[i], fnType, bindings, NO_TYPE_FILTER, exact,
+                        depth + 1);
+                    if (match === null &&
+                        generic.param !== null &&
+                        (fnType.where_clause[generic.param] || []).length === 0 &&
+                        !isTypeVariable(elem.generics[i])
+                    ) {
+                        // A type parameter without bounds can be any type, but a function using
+                        // the type we're looking for is a better match.
+                        match = {lev: 1, bindings: bindings};
+                    }
+                    if (match === null) {
+                        continue;
+                    }
+                    used[j] = true;
+                    unifyFrom(i + 1, totalLev + match.lev, match.bindings);
+                    used[j] = false;
                 }
             }
-            return lev;
+            unifyFrom(0, 0, bindings);
+            if (best !== null) {
+                best.lev /= elem.generics.length;
+            }
+            return best;
         }
 
         /**
-          * This function checks if the object (`row`) matches the given type (`elem`) and its
-          * generics (if any).
-          *
-          * @param {Row} row
-          * @param {QueryElement} elem      - The element from the parsed query.
-          * @param {boolean} literalSearch
-          *
-          * @return {integer} - Returns a Levenshtein distance to the best match. If there is
-          *                     no match, returns `MAX_LEV_DISTANCE + 1`.
-          */
-        function checkType(row, elem, literalSearch) {
-            if (row.name === null) {
-                // This is a pure "generic" search, no need to run other checks.
-                if (row.generics.length > 0) {
-                    return checkIfInGenerics(row, elem);
-                }
-                return MAX_LEV_DISTANCE + 1;
-            }
-
-            let lev = levenshtein(row.name, elem.name);
-            if (literalSearch) {
-                if (lev !== 0) {
-                    // The name didn't match, let's try to check if the generics do.
-                    if (elem.generics.length === 0) {
-                        const checkGeneric = row.generics.length > 0;
-                        if (checkGeneric && row.generics
-                            .findIndex(tmp_elem => tmp_elem.name === elem.name) !== -1) {
-                            return 0;
-                        }
-                    }
-                    return MAX_LEV_DISTANCE + 1;
-                } else if (elem.generics.length > 0) {
-                    return checkGenerics(row, elem, MAX_LEV_DISTANCE + 1);
-                }
-                return 0;
-            } else if (row.generics.length > 0) {
-                if (elem.generics.length === 0) {
+         * This function checks if one of the `types` of the function signature `fnType` matches
+         * the given type (`elem`).
+         *
+         * @param {Array<FunctionType>} types
+         * @param {FunctionSearchType} fnType
+         * @param {QueryElement} elem    - The element from the parsed query.
+         * @param {integer} typeFilter
+         *
+         * @return {number} - Returns a Levenshtein distance to the best match. If there is no
+         *                      match, returns `MAX_LEV_DISTANCE + 1`.
+         */
+        function checkTypes(types, fnType, elem, typeFilter) {
+            let lev = MAX_LEV_DISTANCE + 1;
+            // A type variable on its own matches any type, there's nothing to look for.
+            if (isTypeVariable(elem)) {
+                return lev;
+            }
+            for (const type of types) {
+                const match = unifyType(
+                    type, elem, fnType, new Map(), typeFilter, parsedQuery.literalSearch, 0);
+                if (match !== null) {
+                    lev = Math.min(lev, match.lev);
                     if (lev === 0) {
-                        return 0;
-                    }
-                    // The name didn't match so we now check if the type we're looking for is inside
-                    // the generics!
-                    lev = checkIfInGenerics(row, elem);
-                    // Now whatever happens, the returned distance is "less good" so we should mark
-                    // it as such, and so we add 0.5 to the distance to make it "less good".
-                    return lev + 0.5;
-                } else if (lev > MAX_LEV_DISTANCE) {
-                    // So our item's name doesn't match at all and has generics.
-                    //
-                    // Maybe it's present in a sub generic? For example "f<A<B<C>>>()", if we're
-                    // looking for "B<C>", we'll need to go down.
-                    return checkIfInGenerics(row, elem);
-                } else {
-                    // At this point, the name kinda match and we have generics to check, so
-                    // let's go!
-                    const tmp_lev = checkGenerics(row, elem, lev);
-                    if (tmp_lev > MAX_LEV_DISTANCE) {
-                        return MAX_LEV_DISTANCE + 1;
+                        break;
                     }
-                    // We compute the median value of both checks and return it.
-                    return (tmp_lev + lev) / 2;
                 }
-            } else if (elem.generics.length > 0) {
-                // In this case, we were expecting generics but there isn't so we simply reject this
-                // one.
-                return MAX_LEV_DISTANCE + 1;
             }
-            // No generics on our query or on the target type so we can return without doing
-            // anything else.
             return lev;
         }
 
@@ -1045,24 +1155,14 @@ function initSearch(rawSearchIndex) {
          * @param {QueryElement} elem    - The element from the parsed query.
          * @param {integer} typeFilter
          *
-         * @return {integer} - Returns a Levenshtein distance to the best match. If there is no
+         * @return {number} - Returns a Levenshtein distance to the best match. If there is no
          *                      match, returns `MAX_LEV_DISTANCE + 1`.
          */
         function findArg(row, elem, typeFilter) {
-            let lev = MAX_LEV_DISTANCE + 1;
-
-            if (row && row.type && row.type.inputs && row.type.inputs.length > 0) {
-                for (const input of row.type.inputs) {
-                    if (!typePassesFilter(typeFilter, input.ty)) {
-                        continue;
-                    }
-                    lev = Math.min(lev, checkType(input, elem, parsedQuery.literalSearch));
-                    if (lev === 0) {
-                        return 0;
-                    }
-                }
+            if (!row || !row.type) {
+                return MAX_LEV_DISTANCE + 1;
             }
-            return parsedQuery.literalSearch ? MAX_LEV_DISTANCE + 1 : lev;
+            return checkTypes(row.type.inputs, row.type, elem, typeFilter);
         }
 
         /**
@@ -1072,25 +1172,14 @@ function initSearch(rawSearchIndex) {
          * @param {QueryElement} elem   - The element from the parsed query.
          * @param {integer} typeFilter
          *
-         * @return {integer} - Returns a Levenshtein distance to the best match. If there is no
+         * @return {number} - Returns a Levenshtein distance to the best match. If there is no
          *                      match, returns `MAX_LEV_DISTANCE + 1`.
          */
         function checkReturned(row, elem, typeFilter) {
-            let lev = MAX_LEV_DISTANCE + 1;
-
-            if (row && row.type && row.type.output.length > 0) {
-                const ret = row.type.output;
-                for (const ret_ty of ret) {
-                    if (!typePassesFilter(typeFilter, ret_ty.ty)) {
-                        continue;
-                    }
-                    lev = Math.min(lev, checkType(ret_ty, elem, parsedQuery.literalSearch));
-                    if (lev === 0) {
-                        return 0;
-                    }
-                }
+            if (!row || !row.type) {
+                return MAX_LEV_DISTANCE + 1;
             }
-            return parsedQuery.literalSearch ? MAX_LEV_DISTANCE + 1 : lev;
+            return checkTypes(row.type.output, row.type, elem, typeFilter);
         }
 
         function checkPath(contains, ty) {
@@ -1235,10 +1324,18 @@ function initSearch(rawSearchIndex) {
          * @param {string} fullId
          * @param {integer} id
          * @param {integer} index
-         * @param {integer} lev
+         * @param {number} lev
+         * @param {number} maxLev - Results with a greater `lev` are ignored.
          */
-        function addIntoResults(results, fullId, id, index, lev) {
-            if (lev === 0 || (!parsedQuery.literalSearch && lev <= MAX_LEV_DISTANCE)) {
+        function addIntoResults(
+            results,
+            fullId,
+            id,
+            index,
+            lev,
+            maxLev = parsedQuery.literalSearch ? 0 : MAX_LEV_DISTANCE
+        ) {
+            if (lev <= maxLev) {
                 if (results[fullId] !== undefined) {
                     const result = results[fullId];
                     if (result.dontValidate || result.lev <= lev) {
@@ -1286,8 +1383,10 @@ function initSearch(rawSearchIndex) {
             const in_args = findArg(row, elem, parsedQuery.typeFilter);
             const returned = checkReturned(row, elem, parsedQuery.typeFilter);
 
-            addIntoResults(results_in_args, fullId, pos, index, in_args);
-            addIntoResults(results_returned, fullId, pos, index, returned);
+            // Even in literal searches, the names of the types match exactly so we keep the
+            // distance between the types to sort the results.
+            addIntoResults(results_in_args, fullId, pos, index, in_args, MAX_LEV_DISTANCE);
+            addIntoResults(results_returned, fullId, pos, index, returned, MAX_LEV_DISTANCE);
 
             if (!typePassesFilter(parsedQuery.typeFilter, row.ty)) {
                 return;
@@ -1301,15 +1400,6 @@ function initSearch(rawSearchIndex) {
                 return;
             }
 
-            // No need to check anything else if it's a "pure" generics search.
-            if (elem.name.length === 0) {
-                if (row.type !== null) {
-                    lev = checkGenerics(row.type, elem, MAX_LEV_DISTANCE + 1);
-                    addIntoResults(results_others, fullId, pos, index, lev);
-                }
-                return;
-            }
-
             if (elem.fullPath.length > 1) {
                 lev = checkPath(elem.pathWithoutLast, row);
                 if (lev > MAX_LEV_DISTANCE || (parsedQuery.literalSearch && lev !== 0)) {
@@ -1349,44 +1439,61 @@ function initSearch(rawSearchIndex) {
          * try to match the items which validates all the elements. For `aa -> bb` will look for
          * functions which have a parameter `aa` and has `bb` in its returned values.
          *
+         * Each element is matched with a different argument or returned value, and the type
+         * variables of the query (like `T` in `Vec<T> -> T`) must be bound to the same type in all
+         * of them.
+         *
          * @param {Row} row
          * @param {integer} pos      - Position in the `searchIndex`.
          * @param {Object} results
          */
         function handleArgs(row, pos, results) {
-            if (!row || (filterCrates !== null && row.crate !== filterCrates)) {
+            if (!row || (filterCrates !== null && row.crate !== filterCrates) || !row.type) {
                 return;
             }
+            const fnType = row.type;
+            const elems = parsedQuery.elems.map(elem => [elem, fnType.inputs])
+                .concat(parsedQuery.returned.map(elem => [elem, fnType.output]));
+            const used = new Set();
+            let bestLev = null;
 
-            let totalLev = 0;
-            let nbLev = 0;
-
-            // If the result is too "bad", we return false and it ends this search.
-            function checkArgs(elems, callback) {
-                for (const elem of elems) {
+            function unifyFrom(i, totalLev, bindings) {
+                if (i === elems.length) {
+                    if (bestLev === null || totalLev < bestLev) {
+                        bestLev = totalLev;
+                    }
+                    return;
+                }
+                const [elem, types] = elems[i];
+                for (const type of types) {
+                    if (used.has(type)) {
+                        continue;
+                    }
                     // There is more than one parameter to the query so all checks should be "exact"
-                    const lev = callback(row, elem, NO_TYPE_FILTER);
-                    if (lev <= 1) {
-                        nbLev += 1;
-                        totalLev += lev;
-                    } else {
-                        return false;
+                    const match = unifyType(
+                        type, elem, fnType, bindings, NO_TYPE_FILTER, parsedQuery.literalSearch, 0);
+                    // If the result is too "bad", this type isn't considered.
+                    if (match === null || match.lev > 1) {
+                        continue;
+                    }
+                    used.add(type);
+                    unifyFrom(i + 1, totalLev + match.lev, match.bindings);
+                    used.delete(type);
+                    if (bestLev === 0) {
+                        return;
                     }
                 }
-                return true;
-            }
-            if (!checkArgs(parsedQuery.elems, findArg)) {
-                return;
-            }
-            if (!checkArgs(parsedQuery.returned, checkReturned)) {
-                return;
             }
+            unifyFrom(0, 0, new Map());
 
-            if (nbLev === 0) {
+            if (bestLev === null) {
                 return;
             }
-            const lev = Math.round(totalLev / nbLev);
-            addIntoResults(results, row.id, pos, 0, lev);
+            // Functions with arguments or returned values which aren't in the query are worse
+            // matches.
+            const nbTypes = fnType.inputs.length + fnType.output.length;
+            const lev = bestLev / elems.length + (nbTypes - elems.length) / nbTypes / 2;
+            addIntoResults(results, row.id, pos, 0, lev, MAX_LEV_DISTANCE);
         }
 
         function innerRunQuery() {
@@ -1413,7 +1520,14 @@ function initSearch(rawSearchIndex) {
                     for (i = 0, nSearchWords = searchWords.length; i < nSearchWords; ++i) {
                         row = searchIndex[i];
                         in_returned = checkReturned(row, elem, parsedQuery.typeFilter);
-                        addIntoResults(results_others, row.id, i, -1, in_returned);
+                        addIntoResults(
+                            results_others,
+                            row.id,
+                            i,
+                            -1,
+                            in_returned,
+                            MAX_LEV_DISTANCE
+                        );
                     }
                 }
             } else if (parsedQuery.foundElems > 0) {
@@ -1825,6 +1939,46 @@ function initSearch(rawSearchIndex) {
             filterCrates);
     }
 
+    /**
+     * Convert a RawFunctionType / ID to object-based FunctionType.
+     *
+     * The format for individual function types is encoded in
+     * librustdoc/html/render/mod.rs: impl Serialize for RenderType
+     *
+     * @param {RawFunctionType|number} type
+     * @param {Array<{name: string, ty: number}>} lowercasePaths
+     *
+     * @return {FunctionType}
+     */
+    function buildItemSearchType(type, lowercasePaths) {
+        const PATH_INDEX_DATA = 0;
+        const GENERICS_DATA = 1;
+        let pathIndex, generics;
+        if (typeof type === "number") {
+            pathIndex = type;
+            generics = [];
+        } else {
+            pathIndex = type[PATH_INDEX_DATA];
+            generics = buildItemSearchTypeAll(type[GENERICS_DATA], lowercasePaths);
+        }
+        if (pathIndex < 0) {
+            // Type parameters are negative, and also one-indexed.
+            return {
+                name: null,
+                ty: null,
+                param: -pathIndex - 1,
+                generics: generics,
+            };
+        }
+        return {
+            // `0` is used as a sentinel because it's fewer bytes than `null`
+            name: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].name,
+            ty: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].ty,
+            param: null,
+            generics: generics,
+        };
+    }
+
     /**
      * Convert a list of RawFunctionType / ID to object-based FunctionType.
      *
@@ -1835,33 +1989,13 @@ function initSearch(rawSearchIndex) {
      * Even when a general-purpose compression algorithm is used, this is still a win. I checked.
      * https://github.com/rust-lang/rust/pull/98475#issue-1284395985
      *
-     * The format for individual function types is encoded in
-     * librustdoc/html/render/mod.rs: impl Serialize for RenderType
-     *
      * @param {null|Array<RawFunctionType>} types
      * @param {Array<{name: string, ty: number}>} lowercasePaths
      *
-     * @return {Array<FunctionSearchType>}
+     * @return {Array<FunctionType>}
      */
     function buildItemSearchTypeAll(types, lowercasePaths) {
-        const PATH_INDEX_DATA = 0;
-        const GENERICS_DATA = 1;
-        return types.map(type => {
-            let pathIndex, generics;
-            if (typeof type === "number") {
-                pathIndex = type;
-                generics = [];
-            } else {
-                pathIndex = type[PATH_INDEX_DATA];
-                generics = buildItemSearchTypeAll(type[GENERICS_DATA], lowercasePaths);
-            }
-            return {
-                // `0` is used as a sentinel because it's fewer bytes than `null`
-                name: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].name,
-                ty: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].ty,
-                generics: generics,
-            };
-        });
+        return types.map(type => buildItemSearchType(type, lowercasePaths));
     }
 
     /**
@@ -1882,37 +2016,34 @@ function initSearch(rawSearchIndex) {
     function buildFunctionSearchType(functionSearchType, lowercasePaths) {
         const INPUTS_DATA = 0;
         const OUTPUT_DATA = 1;
+        const WHERE_CLAUSE_DATA = 2;
         // `0` is used as a sentinel because it's fewer bytes than `null`
         if (functionSearchType === 0) {
             return null;
         }
-        let inputs, output;
+        let inputs, output, where_clause;
         if (typeof functionSearchType[INPUTS_DATA] === "number") {
-            const pathIndex = functionSearchType[INPUTS_DATA];
-            inputs = [{
-                name: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].name,
-                ty: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].ty,
-                generics: [],
-            }];
+            inputs = [buildItemSearchType(functionSearchType[INPUTS_DATA], lowercasePaths)];
         } else {
             inputs = buildItemSearchTypeAll(functionSearchType[INPUTS_DATA], lowercasePaths);
         }
         if (functionSearchType.length > 1) {
             if (typeof functionSearchType[OUTPUT_DATA] === "number") {
-                const pathIndex = functionSearchType[OUTPUT_DATA];
-                output = [{
-                    name: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].name,
-                    ty: pathIndex === 0 ? null : lowercasePaths[pathIndex - 1].ty,
-                    generics: [],
-                }];
+                output = [buildItemSearchType(functionSearchType[OUTPUT_DATA], lowercasePaths)];
             } else {
                 output = buildItemSearchTypeAll(functionSearchType[OUTPUT_DATA], lowercasePaths);
             }
         } else {
             output = [];
         }
+        if (functionSearchType.length > 2) {
+            where_clause = functionSearchType[WHERE_CLAUSE_DATA]
+                .map(bounds => buildItemSearchTypeAll(bounds, lowercasePaths));
+        } else {
+            where_clause = [];
+        }
         return {
-            inputs, output,
+            inputs, output, where_clause,
         };
     }
 
@@ -1951,6 +2082,8 @@ function initSearch(rawSearchIndex) {
              * of types representing the function's output. Tuples are flattened.
              * Types are also represented as arrays; the first item is an index into the `p`
              * array, while the second is a list of types representing any generic parameters.
+             * Negative indexes are the type parameters of the function, and if they have trait
+             * bounds, the third list item contains them, as a list of types for each parameter.
              *
              * `a` defines aliases with an Array of pairs: [name, offset], where `offset`
              * points into the n/t/d/q/i/f arrays.
@@ -2019,6 +2152,7 @@ function initSearch(rawSearchIndex) {
             let len = paths.length;
             for (i = 0; i < len; ++i) {
                 lowercasePaths.push({ty: paths[i][0], name: paths[i][1].toLowerCase()});
+                typeNames.add(lowercasePaths[i].name);
                 paths[i] = {ty: paths[i][0], name: paths[i][1]};
             }
 
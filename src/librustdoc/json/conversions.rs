@@ -177,7 +177,10 @@ fn from_tcx(kind: clean::TypeBindingKind, tcx: TyCtxt<'_>) -> Self {
 
 /// It generates an ID as follows:
 ///
-/// `CRATE_ID:ITEM_ID[:NAME_ID]` (if there is no name, NAME_ID is not generated).
+/// `CRATE_HASH:ITEM_HASH[:NAME]` (if there is no name, NAME is not generated).
+///
+/// Both hashes come from the item's `DefPathHash`, so the same item gets the same ID in the output
+/// of every crate referring to it.
 pub(crate) fn from_item_id(item_id: ItemId, tcx: TyCtxt<'_>) -> Id {
     from_item_id_with_name(item_id, tcx, None)
 }
@@ -190,14 +193,11 @@ pub(crate) fn from_item_id_with_name(item_id: ItemId, tcx: TyCtxt<'_>, name: Opt
     impl<'a> fmt::Display for DisplayDefId<'a> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             let name = match self.2 {
-                Some(name) => format!(":{}", name.as_u32()),
-                None => self
-                    .1
-                    .opt_item_name(self.0)
-                    .map(|n| format!(":{}", n.as_u32()))
-                    .unwrap_or_default(),
+                Some(name) => format!(":{}", name),
+                None => self.1.opt_item_name(self.0).map(|n| format!(":{}", n)).unwrap_or_default(),
             };
-            write!(f, "{}:{}{}", self.0.krate.as_u32(), u32::from(self.0.index), name)
+            let hash = self.1.def_path_hash(self.0);
+            write!(f, "{:016x}:{:016x}{}", hash.stable_crate_id().to_u64(), hash.local_hash(), name)
         }
     }
 
@@ -209,11 +209,13 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         ItemId::Auto { for_, trait_ } => {
             Id(format!("a:{}-{}", DisplayDefId(trait_, tcx, None), DisplayDefId(for_, tcx, name)))
         }
-        ItemId::Primitive(ty, krate) => Id(format!("p:{}:{}", krate.as_u32(), ty.as_sym())),
+        ItemId::Primitive(ty, krate) => {
+            Id(format!("p:{:016x}:{}", tcx.stable_crate_id(krate).to_u64(), ty.as_sym()))
+        }
     }
 }
 
-fn from_clean_item(item: clean::Item, tcx: TyCtxt<'_>) -> ItemEnum {
+pub(crate) fn from_clean_item(item: clean::Item, tcx: TyCtxt<'_>) -> ItemEnum {
     use clean::ItemKind::*;
     let name = item.name;
     let is_crate = item.is_crate();
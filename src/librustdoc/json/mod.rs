@@ -4,7 +4,7 @@
 //! output. See [the RFC](https://github.com/rust-lang/rfcs/pull/2963) and the [`types`] module
 //! docs for usage and details.
 
-mod conversions;
+pub(crate) mod conversions;
 
 use std::cell::RefCell;
 use std::fs::{create_dir_all, File};
@@ -38,6 +38,9 @@ pub(crate) struct JsonRenderer<'tcx> {
     /// The directory where the blob will be written to.
     out_path: PathBuf,
     cache: Rc<Cache>,
+    /// Whether the full items of referenced external items are output, see
+    /// `--json-external-items`.
+    external_items: bool,
 }
 
 impl<'tcx> JsonRenderer<'tcx> {
@@ -85,7 +88,7 @@ fn get_impls(&mut self, id: DefId) -> Vec<types::Id> {
                             }
                         }
 
-                        if item.item_id.is_local() || is_primitive_impl {
+                        if item.item_id.is_local() || is_primitive_impl || self.external_items {
                             self.item(item.clone()).unwrap();
                             Some(from_item_id_with_name(item.item_id, self.tcx, item.name))
                         } else {
@@ -102,11 +105,12 @@ fn get_trait_items(&mut self) -> Vec<(types::Id, types::Item)> {
             .traits
             .iter()
             .filter_map(|(&id, trait_item)| {
-                // only need to synthesize items for external traits
-                if !id.is_local() {
+                let item_id = from_item_id(id.into(), self.tcx);
+                // only need to synthesize items for external traits, unless they were output
+                // already with `--json-external-items`
+                if !id.is_local() && !self.index.borrow().contains_key(&item_id) {
                     let trait_item = &trait_item.trait_;
                     trait_item.items.clone().into_iter().for_each(|i| self.item(i).unwrap());
-                    let item_id = from_item_id(id.into(), self.tcx);
                     Some((
                         item_id.clone(),
                         types::Item {
@@ -162,6 +166,7 @@ fn init(
                 index: Rc::new(RefCell::new(FxHashMap::default())),
                 out_path: options.output,
                 cache: Rc::new(cache),
+                external_items: options.json_external_items,
             },
             krate,
         ))
@@ -250,6 +255,10 @@ fn after_krate(&mut self) -> Result<(), Error> {
             self.get_impls(*primitive);
         }
 
+        for item in Rc::clone(&self.cache).external_items.iter() {
+            self.item(item.clone())?;
+        }
+
         let e = ExternalCrate { crate_num: LOCAL_CRATE };
 
         let mut index = (*self.index).clone().into_inner();
@@ -258,7 +267,7 @@ fn after_krate(&mut self) -> Result<(), Error> {
         // rustdoc-json-types
         #[allow(rustc::default_hash_types)]
         let output = types::Crate {
-            root: types::Id(format!("0:0:{}", e.name(self.tcx).as_u32())),
+            root: from_item_id_with_name(e.def_id().into(), self.tcx, Some(e.name(self.tcx))),
             crate_version: self.cache.crate_version.clone(),
             includes_private: self.cache.document_private,
             index: index.into_iter().collect(),
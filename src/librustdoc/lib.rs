@@ -76,6 +76,7 @@
 use std::io;
 use std::process;
 
+use rustc_data_structures::OnDrop;
 use rustc_driver::{abort_on_err, describe_lints};
 use rustc_errors::ErrorGuaranteed;
 use rustc_interface::interface;
@@ -119,6 +120,7 @@ macro_rules! map {
 mod json;
 pub(crate) mod lint;
 mod markdown;
+mod md;
 mod passes;
 mod scrape_examples;
 mod theme;
@@ -158,6 +160,9 @@ pub fn main() {
         }
     }
 
+    // Proc macros that run with `-Z proc-macro-sandbox` run in a copy of this process.
+    rustc_expand::proc_macro_sandbox::run_sandbox_process_if_requested();
+
     rustc_driver::set_sigpipe_handler();
     rustc_driver::install_ice_hook();
 
@@ -514,6 +519,22 @@ fn opts() -> Vec<RustcOptGroup> {
                 "PATH",
             )
         }),
+        unstable("merge-doctests", |o| {
+            o.optflagmulti(
+                "",
+                "merge-doctests",
+                "compile compatible doctests together into a single executable",
+            )
+        }),
+        unstable("doctest-coverage", |o| {
+            o.optopt(
+                "",
+                "doctest-coverage",
+                "Compile doctests with coverage instrumentation, and write their profiles and \
+                 executables to PATH",
+                "PATH",
+            )
+        }),
         unstable("show-coverage", |o| {
             o.optflagmulti(
                 "",
@@ -572,6 +593,9 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("nocapture", |o| {
             o.optflag("", "nocapture", "Don't capture stdout and stderr of tests")
         }),
+        unstable("format", |o| {
+            o.optopt("", "format", "Print the doctest results in the given format", "json")
+        }),
         unstable("generate-link-to-definition", |o| {
             o.optflag(
                 "",
@@ -579,6 +603,24 @@ fn opts() -> Vec<RustcOptGroup> {
                 "Make the identifiers in the HTML source code pages navigable",
             )
         }),
+        unstable("json-external-items", |o| {
+            o.optflag(
+                "",
+                "json-external-items",
+                "Include the full items of referenced external items in the JSON output",
+            )
+        }),
+        unstable("man-pages", |o| {
+            o.optflag("", "man-pages", "Also write the markdown output as man pages in `man/man3`")
+        }),
+        unstable("compare-with", |o| {
+            o.optopt(
+                "",
+                "compare-with",
+                "Mark the items added or changed since the version documented in this JSON file",
+                "PATH",
+            )
+        }),
         unstable("scrape-examples-output-path", |o| {
             o.optopt(
                 "",
@@ -735,6 +777,8 @@ fn run_renderer<'tcx, T: formats::FormatRenderer<'tcx>>(
 
 fn main_options(options: config::Options) -> MainResult {
     let diag = core::new_handler(options.error_format, None, &options.debugging_opts);
+    // Write buffered diagnostics, like the SARIF log, on every return path.
+    let _finish_diag = OnDrop(|| diag.finish());
 
     match (options.should_test, options.markdown_input()) {
         (true, true) => return wrap_return(&diag, markdown::test(options)),
@@ -841,6 +885,9 @@ fn main_options(options: config::Options) -> MainResult {
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<md::MarkdownRenderer<'_>>(krate, render_opts, cache, tcx)
+                    }),
                 }
             })
         })
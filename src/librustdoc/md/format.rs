@@ -0,0 +1,605 @@
+//! Printing of item declarations for the markdown output format.
+//!
+//! Declarations are shown in Rust code blocks, which can't contain links, so unlike
+//! `html::format` everything here is printed as plain Rust code.
+
+use rustc_hir as hir;
+use rustc_hir::def::CtorKind;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::symbol::kw;
+
+use crate::clean::utils::find_nearest_parent_module;
+use crate::clean::{self, PrimitiveType, SelfTy};
+use crate::html::format::{print_abi_with_space, print_constness_with_space, PrintWithSpace};
+use crate::html::render::attributes;
+
+pub(super) struct Printer<'tcx> {
+    pub(super) tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> Printer<'tcx> {
+    /// Returns the declaration of `item` as it would be written in Rust, or `None` for items
+    /// without one, like modules.
+    pub(super) fn item(&self, item: &clean::Item) -> Option<String> {
+        let mut s = String::new();
+        for attr in attributes(item) {
+            s.push_str(&attr);
+            s.push('\n');
+        }
+        s.push_str(&self.item_without_attrs(item, "")?);
+        Some(s)
+    }
+
+    fn item_without_attrs(&self, item: &clean::Item, indent: &str) -> Option<String> {
+        let vis = self.visibility(item);
+        let name = item.name?;
+        let decl = match *item.kind {
+            clean::StructItem(ref s) => {
+                let generics = self.generics(&s.generics);
+                let body = self.fields(&s.fields, s.struct_type, &s.generics, indent);
+                format!("{vis}struct {name}{generics}{body}")
+            }
+            clean::UnionItem(ref u) => {
+                let generics = self.generics(&u.generics);
+                let body = self.fields(&u.fields, CtorKind::Fictive, &u.generics, indent);
+                format!("{vis}union {name}{generics}{body}")
+            }
+            clean::EnumItem(ref e) => {
+                let mut s = format!(
+                    "{vis}enum {name}{generics}{where_clause}",
+                    generics = self.generics(&e.generics),
+                    where_clause = self.where_clause(&e.generics, indent),
+                );
+                s.push_str(if e.generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+                for variant in e.variants() {
+                    s.push_str(indent);
+                    s.push_str("    ");
+                    s.push_str(&self.item_without_attrs(variant, &format!("{indent}    "))?);
+                    s.push_str(",\n");
+                }
+                if e.has_stripped_entries() {
+                    s.push_str(&format!("{indent}    // some variants omitted\n"));
+                }
+                s.push_str(indent);
+                s.push('}');
+                s
+            }
+            clean::VariantItem(ref v) => match v {
+                clean::Variant::CLike => name.to_string(),
+                clean::Variant::Tuple(fields) => {
+                    let body = self.fields(fields, CtorKind::Fn, &clean::Generics::default(), "");
+                    format!("{name}{}", body.trim_end_matches(';'))
+                }
+                clean::Variant::Struct(s) => {
+                    let body =
+                        self.fields(&s.fields, s.struct_type, &clean::Generics::default(), indent);
+                    format!("{name}{body}")
+                }
+            },
+            clean::StructFieldItem(ref ty) => format!("{vis}{name}: {}", self.type_(ty)),
+            clean::FunctionItem(ref f)
+            | clean::ForeignFunctionItem(ref f)
+            | clean::TyMethodItem(ref f)
+            | clean::MethodItem(ref f, _) => {
+                let header = item.fn_header(self.tcx)?;
+                format!(
+                    "{vis}{constness}{asyncness}{unsafety}{abi:#}fn {name}{generics}{decl}\
+                     {where_clause}",
+                    constness = print_constness_with_space(
+                        &header.constness,
+                        item.const_stability(self.tcx)
+                    ),
+                    asyncness = header.asyncness.print_with_space(),
+                    unsafety = header.unsafety.print_with_space(),
+                    abi = print_abi_with_space(header.abi),
+                    generics = self.generics(&f.generics),
+                    decl = self.fn_decl(&f.decl, header.asyncness),
+                    where_clause = self.where_clause(&f.generics, indent),
+                )
+            }
+            clean::TypedefItem(ref t) => format!(
+                "{vis}type {name}{generics}{where_clause} = {ty};",
+                generics = self.generics(&t.generics),
+                where_clause = self.where_clause(&t.generics, indent),
+                ty = self.type_(&t.type_),
+            ),
+            clean::OpaqueTyItem(ref t) => format!(
+                "type {name}{generics}{where_clause} = impl {bounds};",
+                generics = self.generics(&t.generics),
+                where_clause = self.where_clause(&t.generics, indent),
+                bounds = self.bounds(&t.bounds),
+            ),
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => format!(
+                "{vis}static {mutability}{name}: {ty};",
+                mutability = s.mutability.print_with_space(),
+                ty = self.type_(&s.type_),
+            ),
+            clean::ConstantItem(ref c) => format!(
+                "{vis}const {name}: {ty} = {expr};",
+                ty = self.type_(&c.type_),
+                expr = c.expr(self.tcx),
+            ),
+            clean::TraitItem(ref t) => {
+                let mut s = format!(
+                    "{vis}{unsafety}{auto}trait {name}{generics}{bounds}{where_clause}",
+                    unsafety = t.unsafety.print_with_space(),
+                    auto = if t.is_auto { "auto " } else { "" },
+                    generics = self.generics(&t.generics),
+                    bounds = if t.bounds.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", self.bounds(&t.bounds))
+                    },
+                    where_clause = self.where_clause(&t.generics, indent),
+                );
+                if t.items.is_empty() {
+                    s.push_str(" {}");
+                    return Some(s);
+                }
+                s.push_str(if t.generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+                let item_indent = format!("{indent}    ");
+                for trait_item in &t.items {
+                    if let Some(decl) = self.item_without_attrs(trait_item, &item_indent) {
+                        s.push_str(&item_indent);
+                        s.push_str(&decl);
+                        s.push_str(if trait_item.is_method() { " { ... }\n" } else { ";\n" });
+                    }
+                }
+                s.push_str(indent);
+                s.push('}');
+                s
+            }
+            clean::TraitAliasItem(ref t) => format!(
+                "trait {name}{generics} = {bounds}{where_clause};",
+                generics = self.generics(&t.generics),
+                bounds = self.bounds(&t.bounds),
+                where_clause = self.where_clause(&t.generics, indent),
+            ),
+            clean::TyAssocConstItem(ref ty) => format!("const {name}: {}", self.type_(ty)),
+            clean::AssocConstItem(ref ty, ref default) => format!(
+                "const {name}: {ty} = {default}",
+                ty = self.type_(ty),
+                default = default.expr(self.tcx),
+            ),
+            clean::TyAssocTypeItem(ref generics, ref bounds) => format!(
+                "type {name}{generics}{bounds}{where_clause}",
+                generics = self.generics(generics),
+                bounds = if bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", self.bounds(bounds))
+                },
+                where_clause = self.where_clause(generics, indent),
+            ),
+            clean::AssocTypeItem(ref t, _) => format!(
+                "type {name}{generics}{where_clause} = {ty}",
+                generics = self.generics(&t.generics),
+                where_clause = self.where_clause(&t.generics, indent),
+                ty = self.type_(&t.type_),
+            ),
+            clean::ForeignTypeItem => format!("{vis}type {name};"),
+            clean::MacroItem(ref m) => m.source.clone(),
+            clean::ProcMacroItem(ref m) => match m.kind {
+                MacroKind::Bang => format!("{name}!() {{ /* proc-macro */ }}"),
+                MacroKind::Attr => format!("#[{name}]"),
+                MacroKind::Derive => {
+                    let mut s = format!("#[derive({name})]");
+                    if !m.helpers.is_empty() {
+                        s.push_str("\n\n// Helper attributes:");
+                        for helper in &m.helpers {
+                            s.push_str(&format!("\n#[{helper}]"));
+                        }
+                    }
+                    s
+                }
+            },
+            clean::ExternCrateItem { .. }
+            | clean::ImportItem(_)
+            | clean::ModuleItem(_)
+            | clean::ImplItem(_)
+            | clean::PrimitiveItem(_)
+            | clean::KeywordItem(_)
+            | clean::StrippedItem(_) => return None,
+        };
+        Some(decl)
+    }
+
+    /// Prints a re-export, like `pub use std::vec::Vec as List;`.
+    pub(super) fn reexport(&self, item: &clean::Item) -> Option<String> {
+        let vis = self.visibility(item);
+        match *item.kind {
+            clean::ExternCrateItem { src: Some(src) } => {
+                Some(format!("{vis}extern crate {src} as {};", item.name?))
+            }
+            clean::ExternCrateItem { src: None } => {
+                Some(format!("{vis}extern crate {};", item.name?))
+            }
+            clean::ImportItem(ref import) => {
+                let path = import.source.path.whole_name();
+                Some(match import.kind {
+                    clean::ImportKind::Simple(name)
+                        if import.source.path.last_opt() == Some(name) =>
+                    {
+                        format!("{vis}use {path};")
+                    }
+                    clean::ImportKind::Simple(name) => format!("{vis}use {path} as {name};"),
+                    clean::ImportKind::Glob if path.is_empty() => format!("{vis}use *;"),
+                    clean::ImportKind::Glob => format!("{vis}use {path}::*;"),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Prints the first line of an impl block, like `impl<T: Clone> Clone for Vec<T>`.
+    pub(super) fn impl_header(&self, i: &clean::Impl) -> String {
+        let mut s = format!("{}impl{} ", i.unsafety.print_with_space(), self.generics(&i.generics));
+        if let Some(ref trait_) = i.trait_ {
+            if i.polarity == ty::ImplPolarity::Negative {
+                s.push('!');
+            }
+            s.push_str(&self.path(trait_, false));
+            s.push_str(" for ");
+        }
+        match i.kind.as_blanket_ty() {
+            Some(ty) => s.push_str(&self.type_(ty)),
+            None => s.push_str(&self.type_(&i.for_)),
+        }
+        s.push_str(&self.where_clause(&i.generics, ""));
+        s
+    }
+
+    /// Prints the fields of a struct, union or variant, including the where clause that comes
+    /// before the braces of a struct.
+    fn fields(
+        &self,
+        fields: &[clean::Item],
+        ctor_kind: CtorKind,
+        generics: &clean::Generics,
+        indent: &str,
+    ) -> String {
+        let where_clause = self.where_clause(generics, indent);
+        match ctor_kind {
+            CtorKind::Fictive => {
+                let mut s = where_clause;
+                s.push_str(if generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+                for field in fields {
+                    if let clean::StructFieldItem(ref ty) = *field.kind {
+                        s.push_str(&format!(
+                            "{indent}    {vis}{name}: {ty},\n",
+                            vis = self.visibility(field),
+                            name = field.name.unwrap(),
+                            ty = self.type_(ty),
+                        ));
+                    }
+                }
+                if fields.iter().any(|field| field.is_stripped()) {
+                    s.push_str(&format!("{indent}    /* private fields */\n"));
+                }
+                s.push_str(indent);
+                s.push('}');
+                s
+            }
+            CtorKind::Fn => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|field| match *field.kind {
+                        clean::StructFieldItem(ref ty) => {
+                            format!("{}{}", self.visibility(field), self.type_(ty))
+                        }
+                        _ => "_".to_owned(),
+                    })
+                    .collect();
+                format!("({}){where_clause};", fields.join(", "))
+            }
+            CtorKind::Const => format!("{where_clause};"),
+        }
+    }
+
+    fn visibility(&self, item: &clean::Item) -> String {
+        match item.visibility {
+            clean::Visibility::Public => "pub ".to_owned(),
+            clean::Visibility::Inherited => String::new(),
+            clean::Visibility::Restricted(vis_did) => {
+                let parent_module = item
+                    .item_id
+                    .as_def_id()
+                    .and_then(|did| find_nearest_parent_module(self.tcx, did));
+                if vis_did.is_crate_root() {
+                    "pub(crate) ".to_owned()
+                } else if parent_module == Some(vis_did) {
+                    // `pub(in path)` where `path` is the parent module is the same as no
+                    // visibility.
+                    String::new()
+                } else {
+                    format!("pub(in {}) ", self.tcx.def_path_str(vis_did))
+                }
+            }
+        }
+    }
+
+    fn generics(&self, generics: &clean::Generics) -> String {
+        let params: Vec<_> = generics
+            .params
+            .iter()
+            .filter(|param| !param.is_synthetic_type_param())
+            .map(|param| self.generic_param(param))
+            .collect();
+        if params.is_empty() { String::new() } else { format!("<{}>", params.join(", ")) }
+    }
+
+    fn generic_param(&self, param: &clean::GenericParamDef) -> String {
+        match param.kind {
+            clean::GenericParamDefKind::Lifetime { ref outlives } => {
+                let mut s = param.name.to_string();
+                if !outlives.is_empty() {
+                    let outlives: Vec<_> =
+                        outlives.iter().map(|lt| lt.print().to_string()).collect();
+                    s.push_str(": ");
+                    s.push_str(&outlives.join(" + "));
+                }
+                s
+            }
+            clean::GenericParamDefKind::Type { ref bounds, ref default, .. } => {
+                let mut s = param.name.to_string();
+                if !bounds.is_empty() {
+                    s.push_str(": ");
+                    s.push_str(&self.bounds(bounds));
+                }
+                if let Some(ref ty) = default {
+                    s.push_str(" = ");
+                    s.push_str(&self.type_(ty));
+                }
+                s
+            }
+            clean::GenericParamDefKind::Const { ref ty, ref default, .. } => {
+                let mut s = format!("const {}: {}", param.name, self.type_(ty));
+                if let Some(ref default) = default {
+                    s.push_str(" = ");
+                    s.push_str(default);
+                }
+                s
+            }
+        }
+    }
+
+    /// Prints the where clause of `generics` with one predicate per line, or nothing if there
+    /// are no predicates.
+    fn where_clause(&self, generics: &clean::Generics, indent: &str) -> String {
+        if generics.where_predicates.is_empty() {
+            return String::new();
+        }
+        let mut s = format!("\n{indent}where");
+        for predicate in &generics.where_predicates {
+            let predicate = match predicate {
+                clean::WherePredicate::BoundPredicate { ty, bounds, bound_params } => {
+                    let hrtb = if bound_params.is_empty() {
+                        String::new()
+                    } else {
+                        let params: Vec<_> =
+                            bound_params.iter().map(|lt| lt.print().to_string()).collect();
+                        format!("for<{}> ", params.join(", "))
+                    };
+                    format!("{hrtb}{}: {}", self.type_(ty), self.bounds(bounds))
+                }
+                clean::WherePredicate::RegionPredicate { lifetime, bounds } => {
+                    format!("{}: {}", lifetime.print(), self.bounds(bounds))
+                }
+                clean::WherePredicate::EqPredicate { lhs, rhs } => {
+                    format!("{} == {}", self.type_(lhs), self.term(rhs))
+                }
+            };
+            s.push_str(&format!("\n{indent}    {predicate},"));
+        }
+        s
+    }
+
+    fn bounds(&self, bounds: &[clean::GenericBound]) -> String {
+        let bounds: Vec<_> = bounds
+            .iter()
+            .map(|bound| match bound {
+                clean::GenericBound::TraitBound(poly_trait, modifier) => {
+                    let modifier = match modifier {
+                        hir::TraitBoundModifier::None => "",
+                        hir::TraitBoundModifier::Maybe => "?",
+                        hir::TraitBoundModifier::MaybeConst => "~const ",
+                    };
+                    format!("{modifier}{}", self.poly_trait(poly_trait))
+                }
+                clean::GenericBound::Outlives(lt) => lt.print().to_string(),
+            })
+            .collect();
+        bounds.join(" + ")
+    }
+
+    fn poly_trait(&self, poly_trait: &clean::PolyTrait) -> String {
+        let mut s = String::new();
+        if !poly_trait.generic_params.is_empty() {
+            let params: Vec<_> =
+                poly_trait.generic_params.iter().map(|param| self.generic_param(param)).collect();
+            s.push_str(&format!("for<{}> ", params.join(", ")));
+        }
+        s.push_str(&self.path(&poly_trait.trait_, false));
+        s
+    }
+
+    fn fn_decl(&self, decl: &clean::FnDecl, asyncness: hir::IsAsync) -> String {
+        let mut args: Vec<_> = decl
+            .inputs
+            .values
+            .iter()
+            .map(|input| match input.to_self() {
+                Some(SelfTy::SelfValue) => "self".to_owned(),
+                Some(SelfTy::SelfBorrowed(lt, mutability)) => format!(
+                    "&{}{}self",
+                    lt.map(|lt| format!("{} ", lt.print())).unwrap_or_default(),
+                    mutability.print_with_space(),
+                ),
+                Some(SelfTy::SelfExplicit(ref ty)) => format!("self: {}", self.type_(ty)),
+                None => {
+                    let mut s = String::new();
+                    if input.is_const {
+                        s.push_str("const ");
+                    }
+                    if !input.name.is_empty() {
+                        s.push_str(&format!("{}: ", input.name));
+                    }
+                    s.push_str(&self.type_(&input.type_));
+                    s
+                }
+            })
+            .collect();
+        if decl.c_variadic {
+            args.push("...".to_owned());
+        }
+        let output = match asyncness {
+            hir::IsAsync::Async => decl.sugared_async_return_type(),
+            hir::IsAsync::NotAsync => decl.output.clone(),
+        };
+        format!("({}){}", args.join(", "), self.fn_ret_ty(&output))
+    }
+
+    fn fn_ret_ty(&self, output: &clean::FnRetTy) -> String {
+        match output {
+            clean::FnRetTy::Return(clean::Tuple(tys)) if tys.is_empty() => String::new(),
+            clean::FnRetTy::Return(ty) => format!(" -> {}", self.type_(ty)),
+            clean::FnRetTy::DefaultReturn => String::new(),
+        }
+    }
+
+    pub(super) fn type_(&self, ty: &clean::Type) -> String {
+        match ty {
+            clean::Type::Path { path } => self.path(path, path.is_assoc_ty()),
+            clean::DynTrait(bounds, lt) => {
+                let bounds: Vec<_> = bounds.iter().map(|bound| self.poly_trait(bound)).collect();
+                let mut s = format!("dyn {}", bounds.join(" + "));
+                if let Some(lt) = lt {
+                    s.push_str(&format!(" + {}", lt.print()));
+                }
+                s
+            }
+            clean::Generic(name) => name.to_string(),
+            clean::Primitive(PrimitiveType::Never) => "!".to_owned(),
+            clean::Primitive(prim) => prim.as_sym().to_string(),
+            clean::BareFunction(decl) => {
+                let hrtb = if decl.generic_params.is_empty() {
+                    String::new()
+                } else {
+                    let params: Vec<_> =
+                        decl.generic_params.iter().map(|param| self.generic_param(param)).collect();
+                    format!("for<{}> ", params.join(", "))
+                };
+                format!(
+                    "{hrtb}{unsafety}{abi:#}fn{decl}",
+                    unsafety = decl.unsafety.print_with_space(),
+                    abi = print_abi_with_space(decl.abi),
+                    decl = self.fn_decl(&decl.decl, hir::IsAsync::NotAsync),
+                )
+            }
+            clean::Tuple(tys) => match &tys[..] {
+                [ty] => format!("({},)", self.type_(ty)),
+                tys => {
+                    let tys: Vec<_> = tys.iter().map(|ty| self.type_(ty)).collect();
+                    format!("({})", tys.join(", "))
+                }
+            },
+            clean::Slice(ty) => format!("[{}]", self.type_(ty)),
+            clean::Array(ty, len) => format!("[{}; {}]", self.type_(ty), len),
+            clean::RawPointer(mutability, ty) => {
+                let mutability = match mutability {
+                    hir::Mutability::Mut => "mut",
+                    hir::Mutability::Not => "const",
+                };
+                format!("*{mutability} {}", self.type_(ty))
+            }
+            clean::BorrowedRef { lifetime, mutability, type_ } => {
+                let lt = lifetime.as_ref().map(|lt| format!("{} ", lt.print())).unwrap_or_default();
+                let ty = match **type_ {
+                    clean::DynTrait(ref bounds, ref trait_lt)
+                        if bounds.len() > 1 || trait_lt.is_some() =>
+                    {
+                        format!("({})", self.type_(type_))
+                    }
+                    clean::ImplTrait(ref bounds) if bounds.len() > 1 => {
+                        format!("({})", self.type_(type_))
+                    }
+                    _ => self.type_(type_),
+                };
+                format!("&{lt}{}{ty}", mutability.print_with_space())
+            }
+            clean::QPath { assoc, self_type, should_show_cast, trait_ } => {
+                let assoc = format!("{}{}", assoc.name, self.generic_args(&assoc.args));
+                if *should_show_cast {
+                    let trait_ = self.path(trait_, false);
+                    format!("<{} as {trait_}>::{assoc}", self.type_(self_type))
+                } else {
+                    format!("{}::{assoc}", self.type_(self_type))
+                }
+            }
+            clean::Infer => "_".to_owned(),
+            clean::ImplTrait(bounds) => format!("impl {}", self.bounds(bounds)),
+        }
+    }
+
+    /// Prints the last segment of `path` with its generic arguments, and also the names of the
+    /// segments before it if `print_all` is set.
+    fn path(&self, path: &clean::Path, print_all: bool) -> String {
+        let mut s = String::new();
+        let Some((last, init)) = path.segments.split_last() else { return s };
+        if print_all {
+            for segment in init {
+                if segment.name != kw::PathRoot {
+                    s.push_str(segment.name.as_str());
+                }
+                s.push_str("::");
+            }
+        }
+        s.push_str(last.name.as_str());
+        s.push_str(&self.generic_args(&last.args));
+        s
+    }
+
+    fn generic_args(&self, args: &clean::GenericArgs) -> String {
+        match args {
+            clean::GenericArgs::AngleBracketed { args, bindings } => {
+                if args.is_empty() && bindings.is_empty() {
+                    return String::new();
+                }
+                let args = args.iter().map(|arg| match arg {
+                    clean::GenericArg::Lifetime(lt) => lt.print().to_string(),
+                    clean::GenericArg::Type(ty) => self.type_(ty),
+                    clean::GenericArg::Const(ct) => format!("{:#}", ct.print(self.tcx)),
+                    clean::GenericArg::Infer => "_".to_owned(),
+                });
+                let bindings = bindings.iter().map(|binding| {
+                    let assoc =
+                        format!("{}{}", binding.assoc.name, self.generic_args(&binding.assoc.args));
+                    match binding.kind {
+                        clean::TypeBindingKind::Equality { ref term } => {
+                            format!("{assoc} = {}", self.term(term))
+                        }
+                        clean::TypeBindingKind::Constraint { ref bounds } => {
+                            format!("{assoc}: {}", self.bounds(bounds))
+                        }
+                    }
+                });
+                let args: Vec<_> = args.chain(bindings).collect();
+                format!("<{}>", args.join(", "))
+            }
+            clean::GenericArgs::Parenthesized { inputs, output } => {
+                let inputs: Vec<_> = inputs.iter().map(|ty| self.type_(ty)).collect();
+                let output =
+                    output.as_ref().map(|ty| format!(" -> {}", self.type_(ty))).unwrap_or_default();
+                format!("({}){output}", inputs.join(", "))
+            }
+        }
+    }
+
+    fn term(&self, term: &clean::Term) -> String {
+        match term {
+            clean::Term::Type(ty) => self.type_(ty),
+            clean::Term::Constant(ct) => format!("{:#}", ct.print(self.tcx)),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Conversion of the markdown pages to roff man pages.
+//!
+//! Man pages are written in section 3, like the library documentation of C, and use only the
+//! `man` macros understood by both groff and mandoc. Links can't be followed in a terminal, so
+//! only their text is kept.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+/// Renders the markdown `page` documenting `name` as a man page. `source` is the crate the item
+/// comes from, along with its version if it's known.
+pub(super) fn render(name: &str, source: &str, page: &str) -> String {
+    let mut out = format!(
+        ".TH \"{}\" 3 \"\" \"{}\" \"Rust Library Documentation\"\n",
+        escape(name),
+        escape(source)
+    );
+    let mut in_code_block = false;
+    let mut in_title = false;
+    for event in Parser::new_ext(page, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::Heading(level, ..)) => {
+                in_title = level == HeadingLevel::H1;
+                out.push_str(match level {
+                    HeadingLevel::H1 => ".SH NAME\n",
+                    HeadingLevel::H2 => ".SH ",
+                    _ => ".SS ",
+                });
+            }
+            Event::End(Tag::Heading(..)) => {
+                in_title = false;
+                out.push('\n');
+            }
+            Event::Start(Tag::Paragraph) => out.push_str(".PP\n"),
+            Event::End(Tag::Paragraph) => out.push('\n'),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                if let CodeBlockKind::Indented = kind {
+                    // indented blocks aren't preceded by a paragraph break
+                    out.push_str(".PP\n");
+                }
+                out.push_str(".RS 4\n.nf\n");
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                in_code_block = false;
+                out.push_str(".fi\n.RE\n");
+            }
+            Event::Start(Tag::Item) => out.push_str(".IP \\(bu 2\n"),
+            Event::End(Tag::Item) => out.push('\n'),
+            Event::Start(Tag::Strong) => out.push_str("\\fB"),
+            Event::Start(Tag::Emphasis) => out.push_str("\\fI"),
+            Event::End(Tag::Strong | Tag::Emphasis) => out.push_str("\\fR"),
+            Event::Text(text) if in_code_block => {
+                for line in text.lines() {
+                    out.push_str(&escape_line(line));
+                    out.push('\n');
+                }
+            }
+            Event::Text(text) => out.push_str(&escape_line(&text.replace('\n', " "))),
+            Event::Code(code) if in_title => out.push_str(&escape_line(&code)),
+            Event::Code(code) => {
+                out.push_str("\\fB");
+                out.push_str(&escape_line(&code));
+                out.push_str("\\fR");
+            }
+            Event::SoftBreak => out.push('\n'),
+            Event::HardBreak => out.push_str("\n.br\n"),
+            Event::Rule => out.push_str(".PP\n\\(em\\(em\\(em\n"),
+            // HTML is only used for the anchors of the members, which aren't needed here, and
+            // links are replaced by their text.
+            _ => {}
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-").replace('"', "\\(dq")
+}
+
+/// Escapes `text`, which starts a line, so that it isn't interpreted as a request.
+fn escape_line(text: &str) -> String {
+    let text = escape(text);
+    if text.starts_with('.') || text.starts_with('\'') { format!("\\&{}", text) } else { text }
+}
@@ -0,0 +1,429 @@
+//! Rustdoc's markdown backend
+//!
+//! This module renders a crate as one markdown file per module and item, laid out like the HTML
+//! output, so that the documentation can be read in a terminal or shipped without a browser.
+//! Doc comments are kept as markdown, with intra-doc links pointing at the other markdown files.
+//! With `--man-pages`, every page is also written as a roff man page, see the [`man`] module.
+
+mod format;
+mod man;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{DefIdTree, TyCtxt};
+use rustc_span::def_id::LOCAL_CRATE;
+use rustc_span::hygiene::MacroKind;
+use rustc_span::Symbol;
+
+use crate::clean::{self, ExternalLocation, ItemLink, RenderedLink};
+use crate::config::RenderOptions;
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::{FormatRenderer, Impl};
+use crate::html::format::{href_relative_parts, join_with_double_colon};
+use crate::html::markdown::{plain_text_summary, rewrite_markdown, HeadingOffset};
+use crate::html::render::{item_ty_to_section, ItemSection};
+use crate::md::format::Printer;
+use crate::try_err;
+
+#[derive(Clone)]
+pub(crate) struct MarkdownRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    cache: Rc<Cache>,
+    /// The path of the module being rendered, starting with the crate name.
+    current: Vec<Symbol>,
+    /// The directory where the pages of the current module are written.
+    dst: PathBuf,
+    /// The directory where man pages are written, if `--man-pages` was passed.
+    man_dir: Option<PathBuf>,
+    /// Whether the current module was stripped, in which case its pages aren't written. Stripped
+    /// modules are still visited, as they may contain impls for public types.
+    in_stripped_module: bool,
+}
+
+impl<'tcx> MarkdownRenderer<'tcx> {
+    fn printer(&self) -> Printer<'tcx> {
+        Printer { tcx: self.tcx }
+    }
+
+    /// Returns the link to the page documenting `did` from the pages of the current module, or
+    /// `None` if there is no such page.
+    ///
+    /// Items of other crates link to their HTML documentation when it has a known location.
+    fn href(&self, did: DefId) -> Option<String> {
+        let did = match self.tcx.def_kind(did) {
+            DefKind::AssocTy | DefKind::AssocFn | DefKind::AssocConst | DefKind::Variant => {
+                // documented on their parent's page
+                self.tcx.parent(did)
+            }
+            _ => did,
+        };
+        let cache = &self.cache;
+        if !did.is_local()
+            && !cache.access_levels.is_public(did)
+            && !cache.document_private
+            && !cache.primitive_locations.values().any(|&id| id == did)
+        {
+            return None;
+        }
+
+        let (fqp, shortty, remote) = match cache.paths.get(&did) {
+            Some(&(ref fqp, shortty)) => (fqp, shortty, None),
+            None => {
+                let &(ref fqp, shortty) = cache.external_paths.get(&did)?;
+                match cache.extern_locations[&did.krate] {
+                    ExternalLocation::Remote(ref url) => {
+                        (fqp, shortty, Some(url.trim_end_matches('/')))
+                    }
+                    ExternalLocation::Local => (fqp, shortty, None),
+                    ExternalLocation::Unknown => return None,
+                }
+            }
+        };
+        let module_fqp = if shortty == ItemType::Module { &fqp[..] } else { &fqp[..fqp.len() - 1] };
+        let (mut parts, extension): (Vec<_>, _) = match remote {
+            Some(url) => (
+                Some(url.to_owned())
+                    .into_iter()
+                    .chain(module_fqp.iter().map(|s| s.to_string()))
+                    .collect(),
+                "html",
+            ),
+            None => (
+                href_relative_parts(module_fqp, &self.current).map(|s| s.to_string()).collect(),
+                "md",
+            ),
+        };
+        parts.push(match shortty {
+            ItemType::Module => format!("index.{}", extension),
+            _ => format!("{}.{}.{}", shortty, fqp.last().unwrap(), extension),
+        });
+        Some(parts.join("/"))
+    }
+
+    fn links(&self, item: &clean::Item) -> Vec<RenderedLink> {
+        self.cache
+            .intra_doc_links
+            .get(&item.item_id)
+            .map_or(&[][..], |v| v.as_slice())
+            .iter()
+            .filter_map(|ItemLink { link: s, link_text, did, ref fragment }| {
+                let mut href = self.href(*did)?;
+                if let Some(ref fragment) = *fragment {
+                    fragment.render(&mut href, self.tcx)
+                }
+                Some(RenderedLink { original_text: s.clone(), new_text: link_text.clone(), href })
+            })
+            .collect()
+    }
+
+    /// Writes the deprecation notice and the docs of `item`.
+    fn document(&self, s: &mut String, item: &clean::Item, heading_offset: HeadingOffset) {
+        if let Some(depr) = item.deprecation(self.tcx) {
+            s.push_str("**Deprecated**");
+            if let Some(since) = depr.since {
+                write!(s, " since {}", since).unwrap();
+            }
+            if let Some(note) = depr.note {
+                write!(s, ": {}", note).unwrap();
+            }
+            s.push_str("\n\n");
+        }
+        if let Some(docs) = item.collapsed_doc_value() {
+            let docs = rewrite_markdown(&docs, &self.links(item), heading_offset);
+            if !docs.trim().is_empty() {
+                s.push_str(docs.trim_end());
+                s.push_str("\n\n");
+            }
+        }
+    }
+
+    /// Writes a member of the item of the current page, like a field or a method, with the
+    /// anchor used by the intra-doc links to it.
+    fn document_member(&self, s: &mut String, member: &clean::Item) {
+        let name = member.name.unwrap();
+        writeln!(s, "<a id=\"{}.{}\"></a>\n\n### `{}`\n", member.type_(), name, name).unwrap();
+        if let Some(decl) = self.printer().item(member) {
+            writeln!(s, "```rust\n{}\n```\n", decl).unwrap();
+        }
+        self.document(s, member, HeadingOffset::H4);
+    }
+
+    fn page_title(&self, item: &clean::Item) -> String {
+        let kind = match *item.kind {
+            clean::ModuleItem(_) if item.is_crate() => "Crate",
+            clean::ModuleItem(_) => "Module",
+            clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "Function",
+            clean::TraitItem(..) => "Trait",
+            clean::StructItem(..) => "Struct",
+            clean::UnionItem(..) => "Union",
+            clean::EnumItem(..) => "Enum",
+            clean::TypedefItem(..) => "Type Definition",
+            clean::MacroItem(..) => "Macro",
+            clean::ProcMacroItem(ref mac) => match mac.kind {
+                MacroKind::Bang => "Macro",
+                MacroKind::Attr => "Attribute Macro",
+                MacroKind::Derive => "Derive Macro",
+            },
+            clean::PrimitiveItem(..) => "Primitive Type",
+            clean::StaticItem(..) | clean::ForeignStaticItem(..) => "Static",
+            clean::ConstantItem(..) => "Constant",
+            clean::ForeignTypeItem => "Foreign Type",
+            clean::KeywordItem(..) => "Keyword",
+            clean::OpaqueTyItem(..) => "Opaque Type",
+            clean::TraitAliasItem(..) => "Trait Alias",
+            _ => unreachable!("no page is written for {:?}", item.type_()),
+        };
+        format!("{} `{}`", kind, self.full_path(item))
+    }
+
+    fn full_path(&self, item: &clean::Item) -> String {
+        if item.is_mod() {
+            join_with_double_colon(&self.current)
+        } else {
+            let mut path = self.current.clone();
+            path.push(item.name.unwrap());
+            join_with_double_colon(&path)
+        }
+    }
+
+    fn render_module(&self, item: &clean::Item, module: &clean::Module) -> String {
+        let mut s = format!("# {}\n\n", self.page_title(item));
+        self.document(&mut s, item, HeadingOffset::H2);
+
+        let mut items: Vec<_> = module
+            .items
+            .iter()
+            .filter(|it| !it.is_stripped() && it.type_() != ItemType::Impl)
+            .filter(|it| match *it.kind {
+                clean::ImportItem(ref import) => import.should_be_displayed,
+                _ => it.name.is_some(),
+            })
+            .collect();
+        items.sort_by_cached_key(|it| it.name.map(|name| name.to_string()));
+        for &section in ItemSection::ALL {
+            let mut section_items =
+                items.iter().filter(|it| item_ty_to_section(it.type_()) == section).peekable();
+            if section_items.peek().is_none() {
+                continue;
+            }
+            writeln!(s, "## {}\n", section.name()).unwrap();
+            for it in section_items {
+                if let Some(reexport) = self.printer().reexport(it) {
+                    writeln!(s, "- `{}`", reexport).unwrap();
+                    continue;
+                }
+                let name = it.name.unwrap();
+                let href = match it.type_() {
+                    ItemType::Module => format!("{}/index.md", name),
+                    ty => format!("{}.{}.md", ty, name),
+                };
+                write!(s, "- [`{}`]({})", name, href).unwrap();
+                let summary = plain_text_summary(&it.doc_value().unwrap_or_default());
+                if !summary.is_empty() {
+                    write!(s, ": {}", summary).unwrap();
+                }
+                s.push('\n');
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    fn render_item(&self, item: &clean::Item) -> String {
+        let mut s = format!("# {}\n\n", self.page_title(item));
+        if let Some(decl) = self.printer().item(item) {
+            writeln!(s, "```rust\n{}\n```\n", decl).unwrap();
+        }
+        self.document(&mut s, item, HeadingOffset::H2);
+
+        let members =
+            |s: &mut String, title: &str, members: &mut dyn Iterator<Item = &clean::Item>| {
+                let mut members = members.peekable();
+                if members.peek().is_some() {
+                    writeln!(s, "## {}\n", title).unwrap();
+                    members.for_each(|member| self.document_member(s, member));
+                }
+            };
+        match *item.kind {
+            clean::StructItem(clean::Struct { ref fields, .. })
+            | clean::UnionItem(clean::Union { ref fields, .. }) => {
+                members(&mut s, "Fields", &mut fields.iter().filter(|f| !f.is_stripped()));
+            }
+            clean::EnumItem(ref e) => members(&mut s, "Variants", &mut e.variants()),
+            clean::TraitItem(ref t) => {
+                for (title, ty) in [
+                    ("Associated Types", ItemType::AssocType),
+                    ("Associated Constants", ItemType::AssocConst),
+                    ("Required Methods", ItemType::TyMethod),
+                    ("Provided Methods", ItemType::Method),
+                ] {
+                    members(&mut s, title, &mut t.items.iter().filter(|it| it.type_() == ty));
+                }
+                if let Some(implementors) =
+                    self.cache.implementors.get(&item.item_id.expect_def_id())
+                {
+                    self.impl_list(&mut s, "Implementors", implementors.iter());
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(impls) = item.item_id.as_def_id().and_then(|did| self.cache.impls.get(&did)) {
+            let impls: Vec<_> = impls.iter().filter(|i| !i.impl_item.is_stripped()).collect();
+            let inherent: Vec<_> =
+                impls.iter().filter(|i| i.inner_impl().trait_.is_none()).collect();
+            if !inherent.is_empty() {
+                s.push_str("## Implementations\n\n");
+                for i in inherent {
+                    writeln!(s, "```rust\n{}\n```\n", self.printer().impl_header(i.inner_impl()))
+                        .unwrap();
+                    for member in i.inner_impl().items.iter().filter(|it| !it.is_stripped()) {
+                        self.document_member(&mut s, member);
+                    }
+                }
+            }
+            let trait_impls = impls.iter().filter(|i| i.inner_impl().trait_.is_some());
+            let (synthetic, concrete): (Vec<_>, Vec<_>) =
+                trait_impls.partition(|i| i.inner_impl().kind.is_auto());
+            let (blanket, concrete): (Vec<_>, Vec<_>) =
+                concrete.into_iter().partition(|i| i.inner_impl().kind.is_blanket());
+            self.impl_list(&mut s, "Trait Implementations", concrete.into_iter().copied());
+            self.impl_list(&mut s, "Auto Trait Implementations", synthetic.into_iter().copied());
+            self.impl_list(&mut s, "Blanket Implementations", blanket.into_iter().copied());
+        }
+        s
+    }
+
+    /// Lists impl blocks by their header, linking to the page of their trait.
+    fn impl_list<'a>(&self, s: &mut String, title: &str, impls: impl Iterator<Item = &'a Impl>) {
+        let mut impls = impls.peekable();
+        if impls.peek().is_none() {
+            return;
+        }
+        writeln!(s, "## {}\n", title).unwrap();
+        for i in impls {
+            let header = self.printer().impl_header(i.inner_impl());
+            match i.trait_did().and_then(|did| self.href(did)) {
+                Some(href) => writeln!(s, "- [`{}`]({})", header.replace('\n', " "), href),
+                None => writeln!(s, "- `{}`", header.replace('\n', " ")),
+            }
+            .unwrap();
+        }
+        s.push('\n');
+    }
+
+    /// Writes a page to `file_name` in the directory of the current module, and to a man page
+    /// if requested.
+    fn write_page(&self, item: &clean::Item, file_name: &str, page: String) -> Result<(), Error> {
+        try_err!(fs::create_dir_all(&self.dst), &self.dst);
+        let path = self.dst.join(file_name);
+        if let Some(ref man_dir) = self.man_dir {
+            let name = self.full_path(item);
+            let source = match self.cache.crate_version {
+                Some(ref version) => format!("{} {}", self.current[0], version),
+                None => self.current[0].to_string(),
+            };
+            // Like the other pages, the file is named after the type of the item, which keeps
+            // apart items of different namespaces, like `mod vec` and the macro `vec!`.
+            let file_stem = match name.rsplit_once("::") {
+                Some((parent, last)) => {
+                    format!("{}.{}.{}", parent.replace("::", "."), item.type_(), last)
+                }
+                None => format!("{}.{}", item.type_(), name),
+            };
+            let man_path = man_dir.join(format!("{}.3", file_stem));
+            try_err!(fs::write(&man_path, man::render(&name, &source, &page)), &man_path);
+        }
+        try_err!(fs::write(&path, page), &path);
+        Ok(())
+    }
+}
+
+impl<'tcx> FormatRenderer<'tcx> for MarkdownRenderer<'tcx> {
+    fn descr() -> &'static str {
+        "markdown"
+    }
+
+    const RUN_ON_MODULE: bool = true;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        debug!("Initializing markdown renderer");
+        let man_dir = if options.man_pages {
+            let man_dir = options.output.join("man").join("man3");
+            try_err!(fs::create_dir_all(&man_dir), &man_dir);
+            Some(man_dir)
+        } else {
+            None
+        };
+        Ok((
+            MarkdownRenderer {
+                tcx,
+                cache: Rc::new(cache),
+                current: Vec::new(),
+                dst: options.output,
+                man_dir,
+                in_stripped_module: false,
+            },
+            krate,
+        ))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        self.clone()
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() {
+            return Ok(());
+        }
+        match *item.kind {
+            clean::ImportItem(_) | clean::ExternCrateItem { .. } | clean::ImplItem(_) => Ok(()),
+            _ => {
+                let file_name = format!("{}.{}.md", item.type_(), item.name.unwrap());
+                let page = self.render_item(&item);
+                self.write_page(&item, &file_name, page)
+            }
+        }
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        let item_name = item.name.unwrap();
+        self.dst.push(item_name.as_str());
+        self.current.push(item_name);
+        self.in_stripped_module |= item.is_stripped();
+        if self.in_stripped_module {
+            return Ok(());
+        }
+        let clean::ModuleItem(ref module) = *item.kind else { unreachable!() };
+        let page = self.render_module(item, module);
+        self.write_page(item, "index.md", page)
+    }
+
+    fn mod_item_out(&mut self) -> Result<(), Error> {
+        self.dst.pop();
+        self.current.pop();
+        Ok(())
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        debug!("Done with crate {}", self.tcx.crate_name(LOCAL_CRATE));
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
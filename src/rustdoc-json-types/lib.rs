@@ -9,7 +9,7 @@
 use serde::{Deserialize, Serialize};
 
 /// rustdoc format-version.
-pub const FORMAT_VERSION: u32 = 15;
+pub const FORMAT_VERSION: u32 = 16;
 
 /// A `Crate` is the root of the emitted JSON blob. It contains all type/documentation information
 /// about the language items in the local crate, as well as info about external items to allow
@@ -23,7 +23,8 @@ pub struct Crate {
     /// Whether or not the output includes private items.
     pub includes_private: bool,
     /// A collection of all items in the local crate as well as some external traits and their
-    /// items that are referenced locally.
+    /// items that are referenced locally. With `--json-external-items`, it also contains the
+    /// external items referenced by the local crate, along with their inherent impls.
     pub index: HashMap<Id, Item>,
     /// Maps IDs to fully qualified paths and other info helpful for generating links.
     pub paths: HashMap<Id, ItemSummary>,
@@ -156,6 +157,10 @@ pub enum TypeBindingKind {
     Constraint(Vec<GenericBound>),
 }
 
+/// An opaque identifier for an item.
+///
+/// An item has the same `Id` in the output of every crate referring to it, as long as its own crate
+/// was compiled with the same crate name and `-C metadata`.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Id(pub String);
 
@@ -0,0 +1,63 @@
+// We specify incremental here because we want to test the partitioning for
+// incremental compilation
+// incremental
+// compile-flags:-Zprint-mono-items=eager -Zcgu-partitioning-strategy=affinity
+
+#![allow(dead_code)]
+#![crate_type="lib"]
+
+// This test makes sure that the `affinity` partitioning strategy places generic
+// instantiations in the codegen unit that calls them the most, instead of in a
+// volatile codegen unit next to their definition like the default strategy.
+
+//~ MONO_ITEM fn generic::<u32> @@ affinity_generic[External]
+//~ MONO_ITEM fn generic::<u64> @@ affinity_generic-mod2[External]
+//~ MONO_ITEM fn generic::<char> @@ affinity_generic-mod1-mod1[External]
+//~ MONO_ITEM fn generic::<&str> @@ affinity_generic-mod2[External]
+pub fn generic<T>(x: T) -> T { x }
+
+// Instantiations called by other instantiations follow them.
+//~ MONO_ITEM fn wrapper::<char> @@ affinity_generic-mod1-mod1[External]
+pub fn wrapper<T>(x: T) -> T { generic(x) }
+
+//~ MONO_ITEM fn user @@ affinity_generic[Internal]
+fn user() {
+    let _ = generic(0u32);
+}
+
+mod mod1 {
+    pub use super::generic;
+
+    //~ MONO_ITEM fn mod1::user @@ affinity_generic-mod1[Internal]
+    fn user() {
+        let _ = generic(0u64);
+    }
+
+    mod mod1 {
+        use super::super::wrapper;
+
+        //~ MONO_ITEM fn mod1::mod1::user @@ affinity_generic-mod1-mod1[Internal]
+        fn user() {
+            let _ = wrapper('c');
+        }
+    }
+}
+
+mod mod2 {
+    use super::generic;
+
+    //~ MONO_ITEM fn mod2::user @@ affinity_generic-mod2[Internal]
+    fn user() {
+        let _ = generic("abc");
+    }
+
+    //~ MONO_ITEM fn mod2::user2 @@ affinity_generic-mod2[Internal]
+    fn user2() {
+        let _ = generic(1u64);
+    }
+
+    //~ MONO_ITEM fn mod2::user3 @@ affinity_generic-mod2[Internal]
+    fn user3() {
+        let _ = generic(2u64);
+    }
+}
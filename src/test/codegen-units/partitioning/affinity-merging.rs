@@ -0,0 +1,42 @@
+// We specify incremental here because we want to test the partitioning for
+// incremental compilation
+// incremental
+// compile-flags:-Zprint-mono-items=lazy -Zcgu-partitioning-strategy=affinity
+// compile-flags:-Ccodegen-units=3
+
+#![crate_type = "rlib"]
+
+// This test makes sure that the `affinity` partitioning strategy merges the
+// smallest CGU into the CGU it calls into, instead of into the second smallest
+// CGU like the default strategy.
+//
+// We expect CGU `aaa`, the smallest one, to be merged into `ccc`, while `bbb`
+// and `ddd` are supposed to stay untouched.
+
+pub mod aaa {
+    //~ MONO_ITEM fn aaa::foo @@ affinity_merging-aaa--affinity_merging-ccc[External]
+    pub fn foo() -> u64 {
+        crate::ccc::foo(1, 2, 3)
+    }
+}
+
+pub mod bbb {
+    //~ MONO_ITEM fn bbb::foo @@ affinity_merging-bbb[External]
+    pub fn foo(a: u64, b: u64) -> u64 {
+        a + b + 1
+    }
+}
+
+pub mod ccc {
+    //~ MONO_ITEM fn ccc::foo @@ affinity_merging-aaa--affinity_merging-ccc[External]
+    pub fn foo(a: u64, b: u64, c: u64) -> u64 {
+        a + b + c + 1
+    }
+}
+
+pub mod ddd {
+    //~ MONO_ITEM fn ddd::foo @@ affinity_merging-ddd[External]
+    pub fn foo(a: u64, b: u64, c: u64, d: u64) -> u64 {
+        a + b + c + d + 1
+    }
+}
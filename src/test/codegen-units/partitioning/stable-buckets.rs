@@ -0,0 +1,32 @@
+// compile-flags:-Zprint-mono-items=lazy -Zcgu-partitioning-strategy=stable
+// compile-flags:-Ccodegen-units=4
+
+#![crate_type = "rlib"]
+
+// This test makes sure that the `stable` partitioning strategy places items
+// into one of `-Ccodegen-units` buckets by the hash of their name, regardless
+// of the module they are defined in, and does not merge or rename the buckets.
+
+pub mod aaa {
+    //~ MONO_ITEM fn aaa::foo @@ stable_buckets-bucket.1[External]
+    pub fn foo() {}
+
+    //~ MONO_ITEM fn aaa::bar @@ stable_buckets-bucket.2[External]
+    pub fn bar() {}
+}
+
+pub mod bbb {
+    //~ MONO_ITEM fn bbb::foo @@ stable_buckets-bucket.1[External]
+    pub fn foo() {}
+
+    //~ MONO_ITEM fn bbb::bar @@ stable_buckets-bucket.1[External]
+    pub fn bar() {}
+}
+
+pub mod ccc {
+    //~ MONO_ITEM fn ccc::foo @@ stable_buckets-bucket.0[External]
+    pub fn foo() {}
+
+    //~ MONO_ITEM fn ccc::bar @@ stable_buckets-bucket.2[External]
+    pub fn bar() {}
+}
@@ -0,0 +1,19 @@
+/// ```
+/// assert_eq!(t::answer(), 42);
+/// ```
+pub fn answer() -> u32 {
+    42
+}
+
+/// ```
+/// let answer = t::answer();
+/// assert_eq!(answer, 0, "wrong answer");
+/// ```
+pub fn wrong() {}
+
+/// ```
+/// fn main() {
+///     assert_eq!(t::answer(), 42);
+/// }
+/// ```
+pub fn standalone() {}
@@ -0,0 +1,6 @@
+#![warn(unused_variables)]
+
+fn main() {
+    let x = 1;
+    let y = 2;
+}
@@ -0,0 +1,44 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+
+def load_run(path):
+    with open(path) as f:
+        log = json.load(f)
+    assert log["version"] == "2.1.0", log["version"]
+    assert len(log["runs"]) == 1
+    run = log["runs"][0]
+    return run["tool"]["driver"]["rules"], run["results"]
+
+
+# The unused variable lints know that their level was set by an attribute and
+# suggest prefixing the variable with an underscore.
+rules, results = load_run(sys.argv[1])
+assert len(results) == 2, results
+for result in results:
+    assert result["level"] == "warning", result
+    assert result["ruleId"] == "unused_variables", result
+    source = result["properties"]["lintLevelSource"]
+    assert source["kind"] == "attribute", source
+    assert source["location"]["region"]["startLine"] == 1
+    [fix] = result["fixes"]
+    [change] = fix["artifactChanges"]
+    [replacement] = change["replacements"]
+    assert replacement["insertedContent"]["text"].startswith("_")
+
+# Both lints share a single rule.
+assert len(rules) == 1, rules
+assert results[0]["ruleIndex"] == results[1]["ruleIndex"] == 0
+
+# The mismatched types error uses its error index entry as the rule.
+rules, results = load_run(sys.argv[2])
+[error] = [r for r in results if r["level"] == "error"]
+assert error["ruleId"] == "E0308", error
+rule = rules[error["ruleIndex"]]
+assert rule["id"] == "E0308"
+assert rule["helpUri"].endswith("#E0308")
+[location] = error["locations"]
+assert location["physicalLocation"]["artifactLocation"]["uri"] == "error.rs"
+assert location["physicalLocation"]["region"]["startLine"] == 2
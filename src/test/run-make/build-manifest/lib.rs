@@ -0,0 +1,7 @@
+#![crate_type = "rlib"]
+
+pub static GREETING: &str = env!("BUILD_MANIFEST_GREETING");
+
+pub fn greeting() -> &'static str {
+    GREETING
+}
@@ -0,0 +1,12 @@
+#![feature(proc_macro_tracked_env, track_path)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::*;
+
+#[proc_macro]
+pub fn track_deps(_: TokenStream) -> TokenStream {
+    let _ = tracked_env::var("EXISTING_PROC_MACRO_ENV");
+    tracked_path::path("tracked.txt");
+    TokenStream::new()
+}
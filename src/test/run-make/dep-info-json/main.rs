@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate macro_def;
+
+track_deps!();
+
+const TEXT: &str = include_str!("included.txt");
+const DATA: &[u8] = include_bytes!("data.bin");
+const NUMBER: u32 = include!("included.rs");
+
+fn main() {
+    env!("EXISTING_ENV");
+    option_env!("NONEXISTENT_OPT_ENV");
+    assert_eq!(TEXT.len() + DATA.len(), NUMBER as usize);
+}
@@ -0,0 +1,7 @@
+fn answer() -> u32 {
+    43
+}
+
+fn main() {
+    println!("{}", answer());
+}
@@ -0,0 +1,17 @@
+fn identity<T>(x: T) -> T {
+    x
+}
+
+fn wrap<T>(x: T) -> Option<T> {
+    Some(identity(x))
+}
+
+fn not_generic() -> Option<u32> {
+    wrap(3)
+}
+
+fn main() {
+    wrap(1u8);
+    wrap(2u16);
+    not_generic();
+}
@@ -0,0 +1,19 @@
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::*;
+use std::net::TcpStream;
+
+// Runs as soon as the dylib is loaded, before any macro is expanded.
+#[used]
+#[link_section = ".init_array"]
+static CONNECT_ON_LOAD: extern "C" fn() = connect_on_load;
+
+extern "C" fn connect_on_load() {
+    let _ = TcpStream::connect("127.0.0.1:1");
+}
+
+#[proc_macro]
+pub fn nothing(_: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
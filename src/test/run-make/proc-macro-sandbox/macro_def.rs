@@ -0,0 +1,31 @@
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::*;
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+
+/// Expands to a constant with the contents of the file.
+#[proc_macro]
+pub fn read_file(input: TokenStream) -> TokenStream {
+    let path = input.to_string();
+    let contents = std::fs::read_to_string(path.trim_matches('"')).unwrap();
+    format!("const CONTENTS: &str = {:?};", contents).parse().unwrap()
+}
+
+#[proc_macro]
+pub fn connect(_: TokenStream) -> TokenStream {
+    let _ = TcpStream::connect("127.0.0.1:1");
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn connect_unix(_: TokenStream) -> TokenStream {
+    let _ = UnixStream::connect("/var/run/docker.sock");
+    TokenStream::new()
+}
+
+#[proc_macro_derive(Answer, attributes(answer))]
+pub fn derive_answer(_: TokenStream) -> TokenStream {
+    "fn answer() -> u32 { 42 }".parse().unwrap()
+}
@@ -0,0 +1,26 @@
+#[macro_use]
+extern crate macro_def;
+#[cfg(ctor)]
+#[macro_use]
+extern crate macro_ctor;
+
+#[cfg(allowed)]
+read_file!("allowed.txt");
+#[cfg(denied)]
+read_file!("denied.txt");
+#[cfg(network)]
+connect!();
+#[cfg(unix_socket)]
+connect_unix!();
+#[cfg(ctor)]
+nothing!();
+
+#[derive(Answer)]
+#[answer]
+struct S;
+
+fn main() {
+    #[cfg(allowed)]
+    assert_eq!(CONTENTS, "allowed\n");
+    assert_eq!(answer(), 42);
+}
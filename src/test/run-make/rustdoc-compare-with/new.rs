@@ -0,0 +1,31 @@
+pub struct Foo {
+    pub x: u64,
+    pub y: bool,
+}
+
+impl Foo {
+    pub fn new(x: u64) -> Foo {
+        Foo { x, y: false }
+    }
+
+    // Renaming an argument doesn't change the signature.
+    pub fn set(&mut self, value: u32) {
+        self.x = value.into();
+    }
+}
+
+pub fn unchanged() {}
+
+// The ID of `Foo` depends on `-C metadata`, but it's still the same type.
+pub fn unchanged_with_foo(foo: Foo) -> Foo {
+    foo
+}
+
+pub fn added() {}
+
+pub mod inner {
+    // A new item isn't listed if the module containing it is new as well.
+    pub fn add(left: u32, right: u32) -> u32 {
+        left + right
+    }
+}
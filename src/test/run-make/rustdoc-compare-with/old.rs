@@ -0,0 +1,26 @@
+pub struct Foo {
+    pub x: u32,
+}
+
+impl Foo {
+    pub fn new(x: u32) -> Foo {
+        Foo { x }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.x
+    }
+
+    pub fn set(&mut self, x: u32) {
+        self.x = x;
+    }
+}
+
+pub fn unchanged() {}
+
+// The ID of `Foo` depends on `-C metadata`, but it's still the same type.
+pub fn unchanged_with_foo(foo: Foo) -> Foo {
+    foo
+}
+
+pub fn removed() {}
@@ -0,0 +1,32 @@
+//! The crate docs, linking to [`Foo`].
+
+pub mod inner {
+    /// Returns a [`Foo`](crate::Foo).
+    ///
+    /// ```
+    /// # let hidden = ();
+    /// let foo = foobar::inner::make();
+    /// ```
+    pub fn make() -> crate::Foo {
+        crate::Foo { x: 0 }
+    }
+}
+
+/// A macro named like a module.
+#[macro_export]
+macro_rules! inner {
+    () => {};
+}
+
+/// A struct, made by [`inner::make`].
+pub struct Foo {
+    /// A field.
+    pub x: u32,
+}
+
+impl Foo {
+    /// Links to [`Foo::x`].
+    pub fn get(&self) -> u32 {
+        self.x
+    }
+}
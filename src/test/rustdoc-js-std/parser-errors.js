@@ -37,6 +37,8 @@ const QUERY = [
     "mod : :",
     "a!a",
     "a!!",
+    "a=b",
+    "a<=b>",
 ];
 
 const PARSED = [
@@ -382,4 +384,22 @@ const PARSED = [
         userQuery: "a!!",
         error: 'Cannot have more than one `!` in an ident',
     },
+    {
+        elems: [],
+        foundElems: 0,
+        original: "a=b",
+        returned: [],
+        typeFilter: -1,
+        userQuery: "a=b",
+        error: 'Unexpected `=`',
+    },
+    {
+        elems: [],
+        foundElems: 0,
+        original: "a<=b>",
+        returned: [],
+        typeFilter: -1,
+        userQuery: "a<=b>",
+        error: 'Unexpected `=`',
+    },
 ];
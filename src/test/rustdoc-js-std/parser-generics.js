@@ -1,4 +1,10 @@
-const QUERY = ['A<B<C<D>,  E>', 'p<> u8', '"p"<a>'];
+const QUERY = [
+    'A<B<C<D>,  E>',
+    'p<> u8',
+    '"p"<a>',
+    'impl Iterator<Item = &mut u8>',
+    '&dyn Fn<&str> -> Option<&u8>',
+];
 
 const PARSED = [
     {
@@ -8,7 +14,7 @@ const PARSED = [
         returned: [],
         typeFilter: -1,
         userQuery: 'a<b<c<d>,  e>',
-        error: 'Unexpected `<` after `<`',
+        error: 'Unclosed `<`',
     },
     {
         elems: [
@@ -59,4 +65,70 @@ const PARSED = [
         userQuery: '"p"<a>',
         error: null,
     },
+    {
+        elems: [
+            {
+                name: "iterator",
+                fullPath: ["iterator"],
+                pathWithoutLast: [],
+                pathLast: "iterator",
+                generics: [
+                    {
+                        name: "u8",
+                        fullPath: ["u8"],
+                        pathWithoutLast: [],
+                        pathLast: "u8",
+                        generics: [],
+                    },
+                ],
+            },
+        ],
+        foundElems: 1,
+        original: 'impl Iterator<Item = &mut u8>',
+        returned: [],
+        typeFilter: -1,
+        userQuery: 'impl iterator<item = &mut u8>',
+        error: null,
+    },
+    {
+        elems: [
+            {
+                name: "fn",
+                fullPath: ["fn"],
+                pathWithoutLast: [],
+                pathLast: "fn",
+                generics: [
+                    {
+                        name: "str",
+                        fullPath: ["str"],
+                        pathWithoutLast: [],
+                        pathLast: "str",
+                        generics: [],
+                    },
+                ],
+            },
+        ],
+        foundElems: 2,
+        original: '&dyn Fn<&str> -> Option<&u8>',
+        returned: [
+            {
+                name: "option",
+                fullPath: ["option"],
+                pathWithoutLast: [],
+                pathLast: "option",
+                generics: [
+                    {
+                        name: "u8",
+                        fullPath: ["u8"],
+                        pathWithoutLast: [],
+                        pathLast: "u8",
+                        generics: [],
+                    },
+                ],
+            },
+        ],
+        typeFilter: -1,
+        userQuery: '&dyn fn<&str> -> option<&u8>',
+        error: null,
+    },
 ];
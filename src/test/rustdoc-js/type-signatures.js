@@ -0,0 +1,50 @@
+// exact-check
+
+const QUERY = [
+    'Vec<T> -> Option<T>',
+    'Option<T> -> Vec<T>',
+    'T -> Option<T>',
+    'impl Iterator<Item=u8> -> String',
+    'IntoIterator<Item = u64> -> u64',
+    '-> Option<T>',
+];
+
+const EXPECTED = [
+    {
+        // Vec<T> -> Option<T>
+        'others': [
+            { 'path': 'type_signatures', 'name': 'first' },
+            { 'path': 'type_signatures', 'name': 'first_byte' },
+        ],
+    },
+    {
+        // Option<T> -> Vec<T>
+        'others': [
+            { 'path': 'type_signatures', 'name': 'into_vec' },
+        ],
+    },
+    {
+        // T -> Option<T>
+        'others': [],
+    },
+    {
+        // impl Iterator<Item=u8> -> String
+        'others': [
+            { 'path': 'type_signatures', 'name': 'decode' },
+        ],
+    },
+    {
+        // IntoIterator<Item = u64> -> u64
+        'others': [
+            { 'path': 'type_signatures', 'name': 'sum_all' },
+        ],
+    },
+    {
+        // -> Option<T>
+        'others': [
+            { 'path': 'type_signatures', 'name': 'first' },
+            { 'path': 'type_signatures', 'name': 'first_byte' },
+            { 'path': 'type_signatures', 'name': 'wrong_byte' },
+        ],
+    },
+];
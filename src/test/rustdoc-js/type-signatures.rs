@@ -0,0 +1,9 @@
+pub fn first<T>(_v: Vec<T>) -> Option<T> { loop {} }
+pub fn first_byte(_v: Vec<u8>) -> Option<u8> { loop {} }
+pub fn wrong_byte(_v: Vec<u8>) -> Option<u16> { loop {} }
+pub fn into_vec<T>(_o: Option<T>) -> Vec<T> { loop {} }
+
+pub fn decode(_bytes: impl Iterator<Item = u8>) -> String { loop {} }
+pub fn decode_chars(_chars: impl Iterator<Item = char>) -> String { loop {} }
+
+pub fn sum_all<I>(_iter: I) -> u64 where I: IntoIterator<Item = u64> { loop {} }
@@ -0,0 +1,20 @@
+#![feature(no_core)]
+#![no_core]
+
+pub struct Inner;
+
+pub struct Secret;
+
+pub struct Unused;
+
+/// An external struct.
+pub struct Thing {
+    pub inner: Inner,
+    private: Secret,
+}
+
+impl Thing {
+    pub fn get(&self) -> &Inner {
+        &self.inner
+    }
+}
@@ -0,0 +1,29 @@
+// aux-build:external_items_dep.rs
+// compile-flags: -Z unstable-options --json-external-items
+
+#![feature(no_core)]
+#![no_core]
+
+extern crate external_items_dep;
+
+// @set thing = external_items.json "$.index[*][?(@.name=='Thing')].id"
+// @is - "$.index[*][?(@.name=='Thing')].docs" '"An external struct."'
+// @count - "$.index[*][?(@.name=='Thing')].inner.fields[*]" 1
+// @is - "$.index[*][?(@.name=='Thing')].inner.fields_stripped" true
+// @count - "$.index[*][?(@.name=='Thing')].inner.impls[*]" 1
+// @has - "$.index[*][?(@.name=='get')]"
+// @is - "$.index[*][?(@.name=='make')].inner.decl.output.inner.id" $thing
+pub fn make() -> external_items_dep::Thing {
+    loop {}
+}
+
+// Items referenced by the public API of external items are included too.
+// @set inner = - "$.index[*][?(@.name=='Inner')].id"
+// @is - "$.index[*][?(@.name=='inner')].inner.inner.id" $inner
+
+// Items only referenced by private items are not included.
+// @!has - "$.index[*][?(@.name=='Secret')]"
+// @!has - "$.index[*][?(@.name=='Unused')]"
+fn unused() -> external_items_dep::Unused {
+    loop {}
+}
@@ -0,0 +1,6 @@
+// This test purpose is to check that the "--compare-with" option can only be used with HTML
+// generation.
+
+// compile-flags: -Zunstable-options --compare-with previous.json --output-format json
+
+pub fn f() {}
@@ -0,0 +1,32 @@
+// Checks that `--format json` reports where each doctest comes from, and why it failed.
+
+// compile-flags:--test --test-args --test-threads=1 -Z unstable-options --format json
+// rustc-env:RUST_BACKTRACE=0
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test "(exec|start)_time(.): [0-9.]+" -> "${1}_time${2}: $$TIME"
+// normalize-stdout-test "(byte_(start|end).:)[0-9]+" -> "${1}$$BYTE"
+// normalize-stdout-test "(explanation.:.)([^\x22\\]|\\.)*" -> "${1}$$EXPLANATION"
+// failure-status: 101
+// ignore-windows
+
+/// ```
+/// assert_eq!(1 + 1, 2);
+/// ```
+pub struct Bar;
+
+/// ```
+/// no
+/// ```
+pub struct Broken;
+
+/// ```compile_fail,E0004
+/// let x: () = 5i32;
+/// ```
+pub struct MissingCode;
+
+pub mod foo {
+    /// ```
+    /// panic!("oh no");
+    /// ```
+    pub fn baz() {}
+}
@@ -0,0 +1,20 @@
+// Checks that the doctests are compiled on their own if the crate with the merged doctests fails
+// to compile, here because both tests define the same symbol.
+
+// check-pass
+// compile-flags:--test -Z unstable-options --merge-doctests --edition 2018
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+/// ```
+/// #[no_mangle]
+/// pub extern "C" fn doctest_merge_fallback() {}
+/// doctest_merge_fallback();
+/// ```
+///
+/// ```
+/// #[no_mangle]
+/// pub extern "C" fn doctest_merge_fallback() {}
+/// doctest_merge_fallback();
+/// ```
+pub struct Foo;
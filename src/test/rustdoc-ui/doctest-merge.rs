@@ -0,0 +1,52 @@
+// Checks that `--merge-doctests` runs the doctests that can be merged, and those that have to be
+// compiled on their own.
+
+// check-pass
+// compile-flags:--test -Z unstable-options --merge-doctests --edition 2018
+// normalize-stdout-test: "src/test/rustdoc-ui" -> "$$DIR"
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+/// Each merged test has its own module.
+///
+/// ```
+/// struct Item;
+/// fn helper() -> u32 { 1 }
+/// assert_eq!(helper(), 1);
+/// ```
+///
+/// ```
+/// struct Item(u32);
+/// assert_eq!(Item(2).0, 2);
+/// ```
+///
+/// Each merged test runs in its own process.
+///
+/// ```should_panic
+/// std::process::exit(1);
+/// ```
+///
+/// ```
+/// use std::io;
+/// io::Result::Ok(())
+/// ```
+pub struct Merged;
+
+/// ```
+/// fn main() {
+///     assert!(true);
+/// }
+/// ```
+///
+/// ```
+/// #![allow(dead_code)]
+/// assert!(true);
+/// ```
+///
+/// ```standalone_crate
+/// assert!(true);
+/// ```
+///
+/// ```edition2015
+/// assert!(true);
+/// ```
+pub struct Standalone;
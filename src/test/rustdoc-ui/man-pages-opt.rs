@@ -0,0 +1,6 @@
+// This test purpose is to check that the "--man-pages" option can only be used with markdown
+// generation.
+
+// compile-flags: -Zunstable-options --man-pages --output-format json
+
+pub fn f() {}
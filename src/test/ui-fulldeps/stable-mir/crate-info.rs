@@ -0,0 +1,119 @@
+// run-pass
+// Test that users are able to use stable mir APIs to retrieve information of the current crate
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// edition: 2021
+
+#![feature(rustc_private)]
+
+extern crate rustc_smir;
+
+use rustc_smir::rustc_internal::StableMir;
+use rustc_smir::stable_mir::mir::{Operand, TerminatorKind};
+use rustc_smir::stable_mir::ty::{RigidTy, TyKind, UintTy};
+use rustc_smir::stable_mir::{self, ItemKind};
+use std::io::Write;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to get information about the test crate.
+fn test_stable_mir() {
+    // Get the local crate using stable_mir API.
+    let local = stable_mir::local_crate();
+    assert_eq!(&local.name, CRATE_NAME);
+    assert!(local.is_local);
+    assert_eq!(stable_mir::find_crate(CRATE_NAME), Some(local));
+
+    // Find items in the local crate.
+    let items = stable_mir::all_local_items();
+    assert!(get_item(&items, "input::foo").is_some());
+
+    let entry = stable_mir::entry_fn().unwrap();
+    assert_eq!(entry.name(), "input::main");
+    assert_eq!(entry.kind(), ItemKind::Fn);
+
+    // Find the `std` crate.
+    assert!(stable_mir::find_crate("std").is_some());
+    assert!(stable_mir::external_crates().iter().any(|krate| krate.name == "std"));
+
+    let bar = get_item(&items, "input::bar").unwrap();
+    let body = bar.body();
+    assert_eq!(body.arg_count, 1);
+    assert_eq!(body.blocks.len(), 1);
+    match &body.blocks[0].terminator.kind {
+        TerminatorKind::Return => {}
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(body.ret_local().ty.kind(), TyKind::RigidTy(RigidTy::Uint(UintTy::U32)));
+
+    let foo_bar = get_item(&items, "input::foo_bar").unwrap();
+    let body = foo_bar.body();
+    assert_eq!(body.arg_locals().len(), 1);
+    let call = body
+        .blocks
+        .iter()
+        .find_map(|block| match &block.terminator.kind {
+            TerminatorKind::Call { func: Operand::Constant(func), .. } => Some(func.literal.ty),
+            _ => None,
+        })
+        .unwrap();
+    match call.kind() {
+        TyKind::RigidTy(RigidTy::FnDef(def, args)) => {
+            assert_eq!(def.name(), "input::bar");
+            let instance = stable_mir::mir::mono::Instance::resolve(&def, &args).unwrap();
+            assert_eq!(instance.body().unwrap().arg_count, 1);
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+// Use internal API to find a function in a crate.
+fn get_item<'a>(
+    items: &'a stable_mir::CrateItems,
+    name: &str,
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| crate_item.name() == name)
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "input.rs";
+    generate_input(path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    StableMir::new(args, test_stable_mir).run().unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub fn main() {{
+        foo_bar(2);
+    }}
+
+    pub fn foo() -> u32 {{
+        10
+    }}
+
+    pub fn bar(x: u32) -> u32 {{
+        x
+    }}
+
+    pub fn foo_bar(x: u32) -> u32 {{
+        let y = bar(x);
+        if y > 4 {{ y * 2 }} else {{ y + foo() }}
+    }}"#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,7 @@
+// compile-flags: --test
+
+#[test_setup] //~ ERROR use of unstable library feature 'test_hooks'
+fn setup() {}
+
+#[test_teardown] //~ ERROR use of unstable library feature 'test_hooks'
+fn teardown() {}
@@ -0,0 +1,44 @@
+// compile-flags: -Z print-type-sizes -Z print-type-sizes-format=json
+// compile-flags: -Z print-type-sizes-filter=json::wrappers
+// build-pass
+// ignore-pass
+// ^-- needed because `--pass check` does not emit the output needed.
+//     FIXME: consider using an attribute instead of side-effects.
+
+// This file illustrates the JSON output of `-Z print-type-sizes`, and that
+// `-Z print-type-sizes-filter` leaves out the types whose path, including the
+// name of the local crate, does not match.
+//
+// It avoids using u64/i64 because on some targets that is only 4-byte
+// aligned (while on most it is 8-byte aligned) and so the resulting
+// padding and overall computed sizes can be quite different.
+
+#![feature(start)]
+#![allow(dead_code)]
+
+use std::num::NonZeroU32;
+
+mod wrappers {
+    pub struct Wrapper<T> {
+        pub flag: bool,
+        pub value: T,
+    }
+
+    pub enum WrapperEnum {
+        A(u8, u32),
+        B(u16),
+    }
+}
+
+pub struct Unrelated {
+    a: u8,
+    b: u32,
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _w = wrappers::Wrapper { flag: true, value: NonZeroU32::new(1).unwrap() };
+    let _u = Unrelated { a: 0, b: 0 };
+    let _e = wrappers::WrapperEnum::B(0);
+    0
+}
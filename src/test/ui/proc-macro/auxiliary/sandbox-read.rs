@@ -0,0 +1,15 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+/// Expands to the length of the file, or 0 if it can't be read.
+#[proc_macro]
+pub fn file_len(input: TokenStream) -> TokenStream {
+    let path = input.to_string();
+    let len = std::fs::read(path.trim_matches('"')).map_or(0, |contents| contents.len());
+    format!("{}", len).parse().unwrap()
+}
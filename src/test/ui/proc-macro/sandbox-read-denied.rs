@@ -0,0 +1,16 @@
+// aux-build:sandbox-read.rs
+// compile-flags: -Z proc-macro-sandbox
+// only-linux
+// only-x86_64
+// The kernel also has to support Landlock, which is the case since Linux 5.13.
+
+// Tests that `-Z proc-macro-sandbox` reports a file that a proc macro is not allowed to
+// read, even if the macro handles the error instead of panicking.
+
+#[macro_use]
+extern crate sandbox_read;
+
+const LEN: usize = file_len!("/proc/self/cmdline");
+//~^ ERROR proc macro attempted I/O that is not allowed
+
+fn main() {}
@@ -0,0 +1,33 @@
+// run-pass
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --at=test-filter-at.rs:21 --filter-glob=b::*_b
+// check-run-results
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// ignore-emscripten no threads support
+
+mod a {
+    #[test]
+    fn test_a() {
+        panic!("this should not run");
+    }
+
+    #[test]
+    fn test_b() {
+        panic!("this should not run");
+    }
+
+    #[test]
+    fn test_c() {
+        // `--at` selects the test that contains this line.
+    }
+}
+
+mod b {
+    #[test]
+    fn test_a() {
+        panic!("this should not run");
+    }
+
+    #[test]
+    fn test_b() {}
+}
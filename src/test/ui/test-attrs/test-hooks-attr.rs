@@ -0,0 +1,12 @@
+// compile-flags: --test
+
+#![feature(test_hooks)]
+
+#[test_setup]
+fn with_args(_: u32) {} //~ ERROR may only be used on a function without parameters
+
+#[test_teardown]
+fn generic<T>() {} //~ ERROR may only be used on a function without parameters
+
+#[test_setup]
+static NOT_A_FUNCTION: u32 = 0; //~ ERROR may only be used on a function without parameters
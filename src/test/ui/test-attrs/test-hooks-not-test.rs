@@ -0,0 +1,15 @@
+// check-pass
+
+// Like `#[test]` functions, the setup and teardown functions are removed
+// when not building for test, so they are not reported as dead code.
+
+#![feature(test_hooks)]
+#![deny(dead_code)]
+
+#[test_setup]
+fn setup() {}
+
+#[test_teardown]
+fn teardown() {}
+
+fn main() {}
@@ -0,0 +1,23 @@
+// run-fail
+// compile-flags: --test
+// run-flags: --test-threads=1
+// check-run-results
+// exec-env:RUST_BACKTRACE=0
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// ignore-emscripten no threads support
+
+#![feature(test_hooks)]
+
+#[test_setup]
+fn connect() {
+    println!("connecting");
+    panic!("the server is not up");
+}
+
+#[test_teardown]
+fn stop_server() {}
+
+#[test]
+fn query() {
+    panic!("the tests should not run after a failed setup");
+}
@@ -0,0 +1,36 @@
+// run-pass
+// compile-flags: --test
+// run-flags: --test-threads=1
+// check-run-results
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// ignore-emscripten no threads support
+
+#![feature(test_hooks)]
+
+use std::env;
+
+#[test_setup]
+fn start_server() {
+    env::set_var("SERVER", "up");
+}
+
+mod tests {
+    use std::env;
+
+    // Runs after `start_server`, as the hooks run in the order they are declared.
+    #[test_setup]
+    fn connect() {
+        assert_eq!(env::var("SERVER").as_deref(), Ok("up"));
+        env::set_var("CONNECTED", "1");
+    }
+
+    #[test_teardown]
+    fn stop_server() -> Result<(), String> {
+        Ok(())
+    }
+
+    #[test]
+    fn query() {
+        assert_eq!(env::var("CONNECTED").as_deref(), Ok("1"));
+    }
+}
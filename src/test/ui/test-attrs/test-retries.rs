@@ -0,0 +1,25 @@
+// compile-flags: --test
+// run-fail
+// run-flags: --test-threads=1 -Zunstable-options --retries=2
+// check-run-results
+// exec-env:RUST_BACKTRACE=0
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn it_works() {}
+
+#[test]
+fn it_is_flaky() {
+    let attempt = ATTEMPTS.fetch_add(1, Ordering::SeqCst) + 1;
+    println!("attempt {}", attempt);
+    assert!(attempt > 1, "failed on attempt {}", attempt);
+}
+
+#[test]
+fn it_fails() {
+    panic!("always fails");
+}
@@ -0,0 +1,11 @@
+// compile-flags: --test
+
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout = "soon"] //~ ERROR the timeout of a test must be a positive number of seconds
+fn test1() {}
+
+#[test]
+#[test_timeout = "0"] //~ ERROR the timeout of a test must be a positive number of seconds
+fn test2() {}
@@ -912,6 +912,11 @@ pub fn make_test_description<R: Read>(
         name,
         ignore,
         ignore_message,
+        source_file: "",
+        start_line: 0,
+        end_line: 0,
+        item_path: None,
+        timeout_secs: None,
         should_panic,
         compile_fail: false,
         no_run: false,
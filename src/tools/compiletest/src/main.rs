@@ -491,6 +491,8 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         exclude_should_panic: false,
         filters: config.filters.clone(),
         filter_exact: config.filter_exact,
+        filter_patterns: vec![],
+        filter_locations: vec![],
         run_ignored: if config.run_ignored { test::RunIgnored::Yes } else { test::RunIgnored::No },
         format: if config.quiet { test::OutputFormat::Terse } else { test::OutputFormat::Pretty },
         logfile: config.logfile.clone(),
@@ -503,11 +505,15 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         color: config.color,
         shuffle: false,
         shuffle_seed: None,
+        shard: None,
         test_threads: None,
         skip: config.skip.clone(),
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
+        retries: 0,
+        bench_baseline: None,
         force_run_in_process: false,
     }
 }
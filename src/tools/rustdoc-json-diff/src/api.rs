@@ -0,0 +1,214 @@
+//! Collects the public API of a crate from its rustdoc JSON output.
+
+use std::collections::{BTreeMap, HashSet};
+
+use rustdoc_json_types::{Crate, Id, Item, ItemEnum, ItemKind, Variant, Visibility};
+
+/// An item of the public API.
+pub struct Entry<'a> {
+    pub id: &'a Id,
+    /// `None` for re-exports of items from other crates, which are only known by their path.
+    pub item: Option<&'a Item>,
+    pub kind: ItemKind,
+}
+
+/// The public API of a crate, indexed by the paths its items can be named with.
+///
+/// Fields, variants, trait items and inherent associated items are included as well, under the
+/// path of their parent.
+pub struct PublicApi<'a> {
+    pub krate: &'a Crate,
+    pub entries: BTreeMap<String, Entry<'a>>,
+}
+
+impl<'a> PublicApi<'a> {
+    pub fn new(krate: &'a Crate) -> Self {
+        let mut api = PublicApi { krate, entries: BTreeMap::new() };
+        if let Some(root) = krate.index.get(&krate.root) {
+            let name = root.name.clone().unwrap_or_default();
+            api.visit_module(root, &name, &mut HashSet::new());
+        }
+        api
+    }
+
+    pub fn item(&self, path: &str) -> Option<&'a Item> {
+        self.entries.get(path).and_then(|entry| entry.item)
+    }
+
+    /// `stack` holds the modules being visited, to not loop forever on glob re-exports of a parent
+    /// module.
+    fn visit_module(&mut self, module: &'a Item, path: &str, stack: &mut HashSet<&'a Id>) {
+        let items = match &module.inner {
+            ItemEnum::Module(m) => &m.items,
+            _ => return,
+        };
+        if !stack.insert(&module.id) {
+            return;
+        }
+        for id in items {
+            let item = match self.krate.index.get(id) {
+                Some(item) if item.visibility == Visibility::Public => item,
+                _ => continue,
+            };
+            match &item.inner {
+                ItemEnum::Import(import) => {
+                    let target = import.id.as_ref();
+                    if import.glob {
+                        if let Some(module) = target.and_then(|id| self.krate.index.get(id)) {
+                            self.visit_module(module, path, stack);
+                        }
+                    } else if let Some(target) = target {
+                        let path = format!("{}::{}", path, import.name);
+                        match self.krate.index.get(target) {
+                            Some(item) => self.add_item(item, path, stack),
+                            None => self.add_external(target, path),
+                        }
+                    }
+                }
+                ItemEnum::Impl(_) | ItemEnum::ExternCrate { .. } => {}
+                _ => {
+                    if let Some(name) = &item.name {
+                        self.add_item(item, format!("{}::{}", path, name), stack);
+                    }
+                }
+            }
+        }
+        stack.remove(&module.id);
+    }
+
+    fn add_item(&mut self, item: &'a Item, path: String, stack: &mut HashSet<&'a Id>) {
+        let krate = self.krate;
+        self.entries
+            .insert(path.clone(), Entry { id: &item.id, item: Some(item), kind: kind(item) });
+        match &item.inner {
+            ItemEnum::Module(_) => self.visit_module(item, &path, stack),
+            ItemEnum::Struct(s) => {
+                self.add_children(&s.fields, &path);
+                self.add_inherent_impls(&s.impls, &path);
+            }
+            ItemEnum::Union(u) => {
+                self.add_children(&u.fields, &path);
+                self.add_inherent_impls(&u.impls, &path);
+            }
+            ItemEnum::Enum(e) => {
+                for variant in e.variants.iter().filter_map(|id| krate.index.get(id)) {
+                    let variant_path = self.add_child(variant, &path);
+                    if let (ItemEnum::Variant(Variant::Struct(fields)), Some(variant_path)) =
+                        (&variant.inner, variant_path)
+                    {
+                        self.add_children(fields, &variant_path);
+                    }
+                }
+                self.add_inherent_impls(&e.impls, &path);
+            }
+            ItemEnum::Trait(t) => self.add_children(&t.items, &path),
+            _ => {}
+        }
+    }
+
+    fn add_external(&mut self, id: &'a Id, path: String) {
+        if let Some(summary) = self.krate.paths.get(id) {
+            self.entries.insert(path, Entry { id, item: None, kind: summary.kind.clone() });
+        }
+    }
+
+    /// Adds the items of `ids` under `parent`, skipping the private fields of structs. Variants
+    /// and trait items have the default visibility, and are public if their parent is.
+    fn add_children(&mut self, ids: &'a [Id], parent: &str) {
+        let krate = self.krate;
+        for child in ids.iter().filter_map(|id| krate.index.get(id)) {
+            self.add_child(child, parent);
+        }
+    }
+
+    fn add_child(&mut self, child: &'a Item, parent: &str) -> Option<String> {
+        if !matches!(child.visibility, Visibility::Public | Visibility::Default) {
+            return None;
+        }
+        let path = format!("{}::{}", parent, child.name.as_ref()?);
+        self.entries
+            .insert(path.clone(), Entry { id: &child.id, item: Some(child), kind: kind(child) });
+        Some(path)
+    }
+
+    fn add_inherent_impls(&mut self, impls: &'a [Id], parent: &str) {
+        let krate = self.krate;
+        for id in impls {
+            if let Some(ItemEnum::Impl(impl_)) = krate.index.get(id).map(|item| &item.inner) {
+                if impl_.trait_.is_none() {
+                    let items = impl_
+                        .items
+                        .iter()
+                        .filter_map(|id| krate.index.get(id))
+                        .filter(|item| item.visibility == Visibility::Public);
+                    for item in items {
+                        self.add_child(item, parent);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn kind(item: &Item) -> ItemKind {
+    match &item.inner {
+        ItemEnum::Module(_) => ItemKind::Module,
+        ItemEnum::ExternCrate { .. } => ItemKind::ExternCrate,
+        ItemEnum::Import(_) => ItemKind::Import,
+        ItemEnum::Union(_) => ItemKind::Union,
+        ItemEnum::Struct(_) => ItemKind::Struct,
+        ItemEnum::StructField(_) => ItemKind::StructField,
+        ItemEnum::Enum(_) => ItemKind::Enum,
+        ItemEnum::Variant(_) => ItemKind::Variant,
+        ItemEnum::Function(_) => ItemKind::Function,
+        ItemEnum::Trait(_) => ItemKind::Trait,
+        ItemEnum::TraitAlias(_) => ItemKind::TraitAlias,
+        ItemEnum::Method(_) => ItemKind::Method,
+        ItemEnum::Impl(_) => ItemKind::Impl,
+        ItemEnum::Typedef(_) => ItemKind::Typedef,
+        ItemEnum::OpaqueTy(_) => ItemKind::OpaqueTy,
+        ItemEnum::Constant(_) => ItemKind::Constant,
+        ItemEnum::Static(_) => ItemKind::Static,
+        ItemEnum::ForeignType => ItemKind::ForeignType,
+        ItemEnum::Macro(_) => ItemKind::Macro,
+        ItemEnum::ProcMacro(p) => match p.kind {
+            rustdoc_json_types::MacroKind::Bang => ItemKind::Macro,
+            rustdoc_json_types::MacroKind::Attr => ItemKind::ProcAttribute,
+            rustdoc_json_types::MacroKind::Derive => ItemKind::ProcDerive,
+        },
+        ItemEnum::PrimitiveType(_) => ItemKind::Primitive,
+        ItemEnum::AssocConst { .. } => ItemKind::AssocConst,
+        ItemEnum::AssocType { .. } => ItemKind::AssocType,
+    }
+}
+
+/// The name of the kind of item, as used in the report.
+pub fn kind_name(kind: &ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Module => "module",
+        ItemKind::ExternCrate => "extern crate",
+        ItemKind::Import => "import",
+        ItemKind::Struct => "struct",
+        ItemKind::StructField => "field",
+        ItemKind::Union => "union",
+        ItemKind::Enum => "enum",
+        ItemKind::Variant => "variant",
+        ItemKind::Function => "function",
+        ItemKind::Typedef => "type alias",
+        ItemKind::OpaqueTy => "opaque type",
+        ItemKind::Constant => "constant",
+        ItemKind::Trait => "trait",
+        ItemKind::TraitAlias => "trait alias",
+        ItemKind::Method => "method",
+        ItemKind::Impl => "impl",
+        ItemKind::Static => "static",
+        ItemKind::ForeignType => "foreign type",
+        ItemKind::Macro => "macro",
+        ItemKind::ProcAttribute => "attribute macro",
+        ItemKind::ProcDerive => "derive macro",
+        ItemKind::AssocConst => "associated constant",
+        ItemKind::AssocType => "associated type",
+        ItemKind::Primitive => "primitive",
+        ItemKind::Keyword => "keyword",
+    }
+}
@@ -0,0 +1,42 @@
+use getopts::Options;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    /// The rustdoc JSON output of the previous version of the crate
+    pub old: String,
+    /// The rustdoc JSON output of the new version of the crate
+    pub new: String,
+    /// How the changes are printed
+    pub format: OutputFormat,
+}
+
+/// Create a Config from a vector of command-line arguments
+pub fn parse_config(args: Vec<String>) -> Result<Config, String> {
+    let mut opts = Options::new();
+    opts.optopt("", "format", "Output format of the report (default: human)", "human|json")
+        .optflag("h", "help", "show this message");
+
+    let (argv0, args_) = args.split_first().unwrap();
+    let usage = || opts.usage(&format!("Usage: {} [options] <old.json> <new.json>", argv0));
+
+    let matches = opts.parse(args_).map_err(|e| format!("{}\n\n{}", e, usage()))?;
+
+    if matches.opt_present("h") || matches.free.len() != 2 {
+        println!("{}", usage());
+        std::process::exit(1);
+    }
+
+    let format = match matches.opt_str("format").as_deref() {
+        None | Some("human") => OutputFormat::Human,
+        Some("json") => OutputFormat::Json,
+        Some(other) => return Err(format!("unknown output format `{}`", other)),
+    };
+
+    Ok(Config { old: matches.free[0].clone(), new: matches.free[1].clone(), format })
+}
@@ -0,0 +1,228 @@
+//! Compares the public API of two versions of a crate.
+
+use std::collections::{BTreeSet, HashSet};
+
+use rustdoc_json_types::{Crate, Item, ItemEnum, Struct, Type, Variant, Visibility};
+
+use crate::api::{kind_name, Entry, PublicApi};
+use crate::render::Renderer;
+use crate::report::{Change, ChangeKind};
+
+const AUTO_TRAITS: &[&str] = &["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
+/// Lists the changes from `old` to `new`.
+///
+/// The fields, variants and associated items of an item that was removed or added are not
+/// reported on their own.
+pub fn diff(old: &PublicApi<'_>, new: &PublicApi<'_>) -> Vec<Change> {
+    let old_renderer = Renderer::new(old.krate);
+    let new_renderer = Renderer::new(new.krate);
+    let mut changes = Vec::new();
+
+    let removed: Vec<&str> = old
+        .entries
+        .keys()
+        .filter(|path| !new.entries.contains_key(*path))
+        .map(|path| path.as_str())
+        .collect();
+    let new_only: HashSet<&str> = new
+        .entries
+        .keys()
+        .filter(|path| !old.entries.contains_key(*path))
+        .map(|path| path.as_str())
+        .collect();
+    let old_only: HashSet<&str> = removed.iter().copied().collect();
+    let mut added: BTreeSet<&str> = new_only.iter().copied().collect();
+
+    for &path in &removed {
+        if old_only.contains(parent(path)) {
+            continue;
+        }
+        let entry = &old.entries[path];
+        let item_kind = kind_name(&entry.kind);
+        match find_renamed(path, entry, old, new, &added, &old_renderer, &new_renderer) {
+            Some(new_path) => {
+                added.remove(new_path);
+                let new_path = Some(new_path.to_owned());
+                changes.push(Change::new(ChangeKind::Renamed, item_kind, path, None, new_path));
+            }
+            None => {
+                let decl = declaration(&old_renderer, entry, path);
+                changes.push(Change::new(ChangeKind::Removed, item_kind, path, decl, None));
+            }
+        }
+    }
+
+    for &path in &added {
+        if new_only.contains(parent(path)) {
+            continue;
+        }
+        let entry = &new.entries[path];
+        let kind = match (old.item(parent(path)), entry.item) {
+            (Some(parent), Some(item)) => addition_kind(old.krate, parent, item),
+            _ => ChangeKind::Added,
+        };
+        let decl = declaration(&new_renderer, entry, path);
+        changes.push(Change::new(kind, kind_name(&entry.kind), path, None, decl));
+    }
+
+    for (path, old_entry) in &old.entries {
+        let new_entry = match new.entries.get(path) {
+            Some(new_entry) => new_entry,
+            None => continue,
+        };
+        let (old_item, new_item) = match (old_entry.item, new_entry.item) {
+            (Some(old_item), Some(new_item)) => (old_item, new_item),
+            _ => continue,
+        };
+        let item_kind = kind_name(&new_entry.kind);
+
+        if signature(&old_renderer, old_entry, path) != signature(&new_renderer, new_entry, path) {
+            let kind = ChangeKind::SignatureChanged;
+            let old_decl = declaration(&old_renderer, old_entry, path);
+            let new_decl = declaration(&new_renderer, new_entry, path);
+            changes.push(Change::new(kind, item_kind, path, old_decl, new_decl));
+        }
+
+        match (is_non_exhaustive(old_item), is_non_exhaustive(new_item)) {
+            (false, true) => {
+                let kind = ChangeKind::NonExhaustiveAdded;
+                changes.push(Change::new(kind, item_kind, path, None, None));
+            }
+            (true, false) => {
+                let kind = ChangeKind::NonExhaustiveRemoved;
+                changes.push(Change::new(kind, item_kind, path, None, None));
+            }
+            _ => {}
+        }
+
+        if let (Some(old_traits), Some(new_traits)) =
+            (auto_traits(old.krate, old_item), auto_traits(new.krate, new_item))
+        {
+            for &auto_trait in AUTO_TRAITS {
+                let trait_name = Some(auto_trait.to_owned());
+                match (old_traits.contains(auto_trait), new_traits.contains(auto_trait)) {
+                    (true, false) => {
+                        let kind = ChangeKind::AutoTraitRemoved;
+                        changes.push(Change::new(kind, item_kind, path, trait_name, None));
+                    }
+                    (false, true) => {
+                        let kind = ChangeKind::AutoTraitAdded;
+                        changes.push(Change::new(kind, item_kind, path, None, trait_name));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+/// Finds where the item removed from `path` went, if it was moved or renamed: either the same item
+/// is found at another path, or there's a single new item with the same declaration next to
+/// where it was.
+fn find_renamed<'n>(
+    path: &str,
+    entry: &Entry<'_>,
+    old: &PublicApi<'_>,
+    new: &PublicApi<'_>,
+    added: &BTreeSet<&'n str>,
+    old_renderer: &Renderer<'_>,
+    new_renderer: &Renderer<'_>,
+) -> Option<&'n str> {
+    if let Some(&moved) = added.iter().find(|&&added| new.entries[added].id == entry.id) {
+        return Some(moved);
+    }
+
+    let old_decl = old.item(path).and_then(|item| old_renderer.signature(item, "_"))?;
+    let mut candidates = added.iter().copied().filter(|&added| {
+        let new_entry = &new.entries[added];
+        parent(added) == parent(path)
+            && new_entry.kind == entry.kind
+            && new_entry.item.and_then(|item| new_renderer.signature(item, "_")).as_ref()
+                == Some(&old_decl)
+    });
+    let renamed = candidates.next()?;
+    if candidates.next().is_some() {
+        None
+    } else {
+        Some(renamed)
+    }
+}
+
+fn addition_kind(old_krate: &Crate, old_parent: &Item, item: &Item) -> ChangeKind {
+    match (&old_parent.inner, &item.inner) {
+        (ItemEnum::Enum(_), ItemEnum::Variant(_)) if !is_non_exhaustive(old_parent) => {
+            ChangeKind::VariantAdded
+        }
+        // Other crates could construct the struct, or match it without `..`. The fields of a
+        // variant are always public, also in a `#[non_exhaustive]` enum.
+        (ItemEnum::Struct(s), ItemEnum::StructField(_))
+            if !is_non_exhaustive(old_parent) && has_only_public_fields(old_krate, s) =>
+        {
+            ChangeKind::FieldAdded
+        }
+        (ItemEnum::Variant(Variant::Struct(_)), ItemEnum::StructField(_))
+            if !is_non_exhaustive(old_parent) =>
+        {
+            ChangeKind::FieldAdded
+        }
+        (ItemEnum::Trait(_), ItemEnum::Method(m)) if !m.has_body => {
+            ChangeKind::RequiredTraitItemAdded
+        }
+        (
+            ItemEnum::Trait(_),
+            ItemEnum::AssocConst { default: None, .. } | ItemEnum::AssocType { default: None, .. },
+        ) => ChangeKind::RequiredTraitItemAdded,
+        _ => ChangeKind::Added,
+    }
+}
+
+fn declaration(renderer: &Renderer<'_>, entry: &Entry<'_>, path: &str) -> Option<String> {
+    let name = path.rsplit("::").next().unwrap_or(path);
+    entry.item.and_then(|item| renderer.declaration(item, name))
+}
+
+fn signature(renderer: &Renderer<'_>, entry: &Entry<'_>, path: &str) -> Option<String> {
+    let name = path.rsplit("::").next().unwrap_or(path);
+    entry.item.and_then(|item| renderer.signature(item, name))
+}
+
+fn has_only_public_fields(krate: &Crate, s: &Struct) -> bool {
+    !s.fields_stripped
+        && s.fields.iter().all(|id| {
+            krate.index.get(id).map_or(false, |field| field.visibility == Visibility::Public)
+        })
+}
+
+fn is_non_exhaustive(item: &Item) -> bool {
+    item.attrs.iter().any(|attr| attr == "#[non_exhaustive]")
+}
+
+/// Returns the auto traits implemented by a struct, enum or union, whether rustdoc synthesized
+/// their impls or they were written by hand.
+fn auto_traits<'a>(krate: &'a Crate, item: &Item) -> Option<HashSet<&'a str>> {
+    let impls = match &item.inner {
+        ItemEnum::Struct(s) => &s.impls,
+        ItemEnum::Enum(e) => &e.impls,
+        ItemEnum::Union(u) => &u.impls,
+        _ => return None,
+    };
+    let traits = impls
+        .iter()
+        .filter_map(|id| match &krate.index.get(id)?.inner {
+            ItemEnum::Impl(impl_) if !impl_.negative => match &impl_.trait_ {
+                Some(Type::ResolvedPath { name, .. }) => name.rsplit("::").next(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|name| AUTO_TRAITS.contains(name))
+        .collect();
+    Some(traits)
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once("::").map_or("", |(parent, _)| parent)
+}
@@ -0,0 +1,61 @@
+//! Compares the rustdoc JSON output of two versions of a crate, and reports the changes to its
+//! public API along with the semver bump they require.
+
+use std::{env, fs, process};
+
+use rustdoc_json_types::{Crate, FORMAT_VERSION};
+use serde::Deserialize;
+
+mod api;
+mod config;
+mod diff;
+mod render;
+mod report;
+
+#[cfg(test)]
+mod tests;
+
+use api::PublicApi;
+use config::{parse_config, OutputFormat};
+use report::Report;
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let config = parse_config(env::args().collect())?;
+
+    let old = load_crate(&config.old)?;
+    let new = load_crate(&config.new)?;
+    let report = Report::new(diff::diff(&PublicApi::new(&old), &PublicApi::new(&new)));
+
+    match config.format {
+        OutputFormat::Human => print!("{}", report.to_human()),
+        OutputFormat::Json => println!("{}", report.to_json()),
+    }
+    Ok(())
+}
+
+fn load_crate(path: &str) -> Result<Crate, String> {
+    #[derive(Deserialize)]
+    struct Version {
+        format_version: u32,
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    // Check the version first, as other versions may not deserialize at all.
+    let version: Version = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a rustdoc JSON output: {}", path, e))?;
+    if version.format_version != FORMAT_VERSION {
+        return Err(format!(
+            "{} has format version {}, but only version {} is supported",
+            path, version.format_version, FORMAT_VERSION
+        ));
+    }
+    serde_json::from_str(&contents).map_err(|e| format!("failed to parse {}: {}", path, e))
+}
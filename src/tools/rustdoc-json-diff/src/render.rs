@@ -0,0 +1,398 @@
+//! Renders item declarations as Rust-like strings, used both to compare two versions of an item
+//! and to show them in the report.
+
+use rustdoc_json_types::{
+    Abi, Crate, FnDecl, GenericArg, GenericArgs, GenericBound, GenericParamDef,
+    GenericParamDefKind, Generics, Header, Id, Item, ItemEnum, StructType, Term,
+    TraitBoundModifier, Type, TypeBindingKind, Variant, WherePredicate,
+};
+
+#[derive(Clone, Copy)]
+pub struct Renderer<'a> {
+    krate: &'a Crate,
+    /// Whether the names of function arguments are rendered, or only their types.
+    argument_names: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(krate: &'a Crate) -> Self {
+        Renderer { krate, argument_names: true }
+    }
+
+    /// Returns the declaration of `item` as if it was named `name`, without the names of its
+    /// arguments, which aren't part of its signature: renaming them isn't a breaking change.
+    pub fn signature(&self, item: &Item, name: &str) -> Option<String> {
+        Renderer { argument_names: false, ..*self }.declaration(item, name)
+    }
+
+    /// Returns the declaration of `item` as if it was named `name`, or `None` for items which
+    /// don't have a signature to compare, like modules.
+    pub fn declaration(&self, item: &Item, name: &str) -> Option<String> {
+        let decl = match &item.inner {
+            ItemEnum::Function(f) => self.function(name, &f.header, &f.generics, &f.decl),
+            ItemEnum::Method(m) => self.function(name, &m.header, &m.generics, &m.decl),
+            ItemEnum::Struct(s) => {
+                let body = match s.struct_type {
+                    StructType::Plain => " { .. }",
+                    StructType::Tuple => "(..)",
+                    StructType::Unit => ";",
+                };
+                format!(
+                    "struct {}{}{}",
+                    self.named(name, &s.generics),
+                    body,
+                    self.where_(&s.generics)
+                )
+            }
+            ItemEnum::Union(u) => {
+                format!("union {}{}", self.named(name, &u.generics), self.where_(&u.generics))
+            }
+            ItemEnum::Enum(e) => {
+                format!("enum {}{}", self.named(name, &e.generics), self.where_(&e.generics))
+            }
+            ItemEnum::Variant(Variant::Plain) => name.to_owned(),
+            ItemEnum::Variant(Variant::Tuple(types)) => format!("{}({})", name, self.types(types)),
+            ItemEnum::Variant(Variant::Struct(_)) => format!("{} {{ .. }}", name),
+            ItemEnum::StructField(ty) => format!("{}: {}", name, self.type_(ty)),
+            ItemEnum::Trait(t) => {
+                let mut decl = String::new();
+                if t.is_unsafe {
+                    decl.push_str("unsafe ");
+                }
+                if t.is_auto {
+                    decl.push_str("auto ");
+                }
+                decl.push_str("trait ");
+                decl.push_str(&self.named(name, &t.generics));
+                if !t.bounds.is_empty() {
+                    decl.push_str(": ");
+                    decl.push_str(&self.bounds(&t.bounds));
+                }
+                decl.push_str(&self.where_(&t.generics));
+                decl
+            }
+            ItemEnum::TraitAlias(t) => format!(
+                "trait {} = {}{}",
+                self.named(name, &t.generics),
+                self.bounds(&t.params),
+                self.where_(&t.generics)
+            ),
+            ItemEnum::Typedef(t) => format!(
+                "type {} = {}{}",
+                self.named(name, &t.generics),
+                self.type_(&t.type_),
+                self.where_(&t.generics)
+            ),
+            ItemEnum::OpaqueTy(t) => format!(
+                "type {} = impl {}{}",
+                self.named(name, &t.generics),
+                self.bounds(&t.bounds),
+                self.where_(&t.generics)
+            ),
+            ItemEnum::Constant(c) => format!("const {}: {}", name, self.type_(&c.type_)),
+            ItemEnum::Static(s) => format!(
+                "static {}{}: {}",
+                if s.mutable { "mut " } else { "" },
+                name,
+                self.type_(&s.type_)
+            ),
+            ItemEnum::AssocConst { type_, .. } => format!("const {}: {}", name, self.type_(type_)),
+            ItemEnum::AssocType { generics, bounds, .. } => {
+                let mut decl = format!("type {}", self.named(name, generics));
+                if !bounds.is_empty() {
+                    decl.push_str(": ");
+                    decl.push_str(&self.bounds(bounds));
+                }
+                decl.push_str(&self.where_(generics));
+                decl
+            }
+            ItemEnum::ForeignType => format!("type {}", name),
+            ItemEnum::Module(_)
+            | ItemEnum::ExternCrate { .. }
+            | ItemEnum::Import(_)
+            | ItemEnum::Impl(_)
+            | ItemEnum::Macro(_)
+            | ItemEnum::ProcMacro(_)
+            | ItemEnum::PrimitiveType(_) => return None,
+        };
+        Some(decl)
+    }
+
+    fn function(&self, name: &str, header: &Header, generics: &Generics, decl: &FnDecl) -> String {
+        format!(
+            "{}fn {}{}{}",
+            self.header(header),
+            self.named(name, generics),
+            self.fn_decl(decl),
+            self.where_(generics)
+        )
+    }
+
+    fn header(&self, header: &Header) -> String {
+        let mut s = String::new();
+        if header.const_ {
+            s.push_str("const ");
+        }
+        if header.async_ {
+            s.push_str("async ");
+        }
+        if header.unsafe_ {
+            s.push_str("unsafe ");
+        }
+        let abi = match &header.abi {
+            Abi::Rust => return s,
+            Abi::C { unwind } => abi_name("C", *unwind),
+            Abi::Cdecl { unwind } => abi_name("cdecl", *unwind),
+            Abi::Stdcall { unwind } => abi_name("stdcall", *unwind),
+            Abi::Fastcall { unwind } => abi_name("fastcall", *unwind),
+            Abi::Aapcs { unwind } => abi_name("aapcs", *unwind),
+            Abi::Win64 { unwind } => abi_name("win64", *unwind),
+            Abi::SysV64 { unwind } => abi_name("sysv64", *unwind),
+            Abi::System { unwind } => abi_name("system", *unwind),
+            Abi::Other(abi) => abi.clone(),
+        };
+        s.push_str(&format!("extern \"{}\" ", abi));
+        s
+    }
+
+    fn fn_decl(&self, decl: &FnDecl) -> String {
+        let mut inputs: Vec<_> = decl
+            .inputs
+            .iter()
+            .map(|(name, ty)| match (name.as_str(), ty) {
+                ("self", Type::Generic(s)) if s == "Self" => "self".to_owned(),
+                ("self", Type::BorrowedRef { lifetime, mutable, type_ })
+                    if matches!(&**type_, Type::Generic(s) if s == "Self") =>
+                {
+                    let mutability = if *mutable { "mut " } else { "" };
+                    format!("&{}{}self", lifetime_prefix(lifetime), mutability)
+                }
+                _ if self.argument_names => format!("{}: {}", name, self.type_(ty)),
+                _ => self.type_(ty),
+            })
+            .collect();
+        if decl.c_variadic {
+            inputs.push("...".to_owned());
+        }
+        format!("({}){}", inputs.join(", "), self.output(decl.output.as_ref()))
+    }
+
+    fn output(&self, output: Option<&Type>) -> String {
+        output.map(|ty| format!(" -> {}", self.type_(ty))).unwrap_or_default()
+    }
+
+    fn named(&self, name: &str, generics: &Generics) -> String {
+        format!("{}{}", name, self.generic_params(&generics.params))
+    }
+
+    fn generic_params(&self, params: &[GenericParamDef]) -> String {
+        let params: Vec<_> = params
+            .iter()
+            .filter(|param| {
+                !matches!(param.kind, GenericParamDefKind::Type { synthetic: true, .. })
+            })
+            .map(|param| self.generic_param(param))
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn generic_param(&self, param: &GenericParamDef) -> String {
+        match &param.kind {
+            GenericParamDefKind::Lifetime { outlives } if outlives.is_empty() => param.name.clone(),
+            GenericParamDefKind::Lifetime { outlives } => {
+                format!("{}: {}", param.name, outlives.join(" + "))
+            }
+            GenericParamDefKind::Type { bounds, default, .. } => {
+                let mut s = param.name.clone();
+                if !bounds.is_empty() {
+                    s.push_str(": ");
+                    s.push_str(&self.bounds(bounds));
+                }
+                if let Some(default) = default {
+                    s.push_str(" = ");
+                    s.push_str(&self.type_(default));
+                }
+                s
+            }
+            GenericParamDefKind::Const { type_, default } => {
+                let mut s = format!("const {}: {}", param.name, self.type_(type_));
+                if let Some(default) = default {
+                    s.push_str(" = ");
+                    s.push_str(default);
+                }
+                s
+            }
+        }
+    }
+
+    fn higher_ranked(&self, params: &[GenericParamDef]) -> String {
+        if params.is_empty() {
+            return String::new();
+        }
+        let params: Vec<_> = params.iter().map(|param| self.generic_param(param)).collect();
+        format!("for<{}> ", params.join(", "))
+    }
+
+    fn where_(&self, generics: &Generics) -> String {
+        if generics.where_predicates.is_empty() {
+            return String::new();
+        }
+        let predicates: Vec<_> = generics
+            .where_predicates
+            .iter()
+            .map(|predicate| match predicate {
+                WherePredicate::BoundPredicate { type_, bounds, generic_params } => format!(
+                    "{}{}: {}",
+                    self.higher_ranked(generic_params),
+                    self.type_(type_),
+                    self.bounds(bounds)
+                ),
+                WherePredicate::RegionPredicate { lifetime, bounds } => {
+                    format!("{}: {}", lifetime, self.bounds(bounds))
+                }
+                WherePredicate::EqPredicate { lhs, rhs } => {
+                    format!("{} == {}", self.type_(lhs), self.term(rhs))
+                }
+            })
+            .collect();
+        format!(" where {}", predicates.join(", "))
+    }
+
+    fn bounds(&self, bounds: &[GenericBound]) -> String {
+        let bounds: Vec<_> = bounds
+            .iter()
+            .map(|bound| match bound {
+                GenericBound::TraitBound { trait_, generic_params, modifier } => {
+                    let modifier = match modifier {
+                        TraitBoundModifier::None => "",
+                        TraitBoundModifier::Maybe => "?",
+                        TraitBoundModifier::MaybeConst => "~const ",
+                    };
+                    format!(
+                        "{}{}{}",
+                        self.higher_ranked(generic_params),
+                        modifier,
+                        self.type_(trait_)
+                    )
+                }
+                GenericBound::Outlives(lifetime) => lifetime.clone(),
+            })
+            .collect();
+        bounds.join(" + ")
+    }
+
+    fn types(&self, types: &[Type]) -> String {
+        types.iter().map(|ty| self.type_(ty)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn term(&self, term: &Term) -> String {
+        match term {
+            Term::Type(ty) => self.type_(ty),
+            Term::Constant(c) => c.expr.clone(),
+        }
+    }
+
+    pub fn type_(&self, ty: &Type) -> String {
+        match ty {
+            Type::ResolvedPath { name, id, args, param_names } => {
+                let mut s =
+                    format!("{}{}", self.path(name, id), self.generic_args(args.as_deref()));
+                if !param_names.is_empty() {
+                    s = format!("dyn {} + {}", s, self.bounds(param_names));
+                }
+                s
+            }
+            Type::Generic(name) | Type::Primitive(name) => name.clone(),
+            Type::FunctionPointer(f) => format!(
+                "{}{}fn{}",
+                self.higher_ranked(&f.generic_params),
+                self.header(&f.header),
+                self.fn_decl(&f.decl)
+            ),
+            Type::Tuple(types) if types.len() == 1 => format!("({},)", self.type_(&types[0])),
+            Type::Tuple(types) => format!("({})", self.types(types)),
+            Type::Slice(ty) => format!("[{}]", self.type_(ty)),
+            Type::Array { type_, len } => format!("[{}; {}]", self.type_(type_), len),
+            Type::ImplTrait(bounds) => format!("impl {}", self.bounds(bounds)),
+            Type::Infer => "_".to_owned(),
+            Type::RawPointer { mutable, type_ } => {
+                format!("*{} {}", if *mutable { "mut" } else { "const" }, self.type_(type_))
+            }
+            Type::BorrowedRef { lifetime, mutable, type_ } => format!(
+                "&{}{}{}",
+                lifetime_prefix(lifetime),
+                if *mutable { "mut " } else { "" },
+                self.type_(type_)
+            ),
+            Type::QualifiedPath { name, args, self_type, trait_ } => format!(
+                "<{} as {}>::{}{}",
+                self.type_(self_type),
+                self.type_(trait_),
+                name,
+                self.generic_args(Some(args))
+            ),
+        }
+    }
+
+    /// Uses the full path of the item when it is known, so that the same type is always rendered
+    /// the same way, however it was named in the source.
+    fn path(&self, name: &str, id: &Id) -> String {
+        match self.krate.paths.get(id) {
+            Some(summary) => summary.path.join("::"),
+            None => name.to_owned(),
+        }
+    }
+
+    fn generic_args(&self, args: Option<&GenericArgs>) -> String {
+        match args {
+            None => String::new(),
+            Some(GenericArgs::AngleBracketed { args, bindings }) => {
+                let mut all: Vec<_> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        GenericArg::Lifetime(lifetime) => lifetime.clone(),
+                        GenericArg::Type(ty) => self.type_(ty),
+                        GenericArg::Const(c) => c.expr.clone(),
+                        GenericArg::Infer => "_".to_owned(),
+                    })
+                    .collect();
+                all.extend(bindings.iter().map(|binding| {
+                    let name =
+                        format!("{}{}", binding.name, self.generic_args(Some(&binding.args)));
+                    match &binding.binding {
+                        TypeBindingKind::Equality(term) => {
+                            format!("{} = {}", name, self.term(term))
+                        }
+                        TypeBindingKind::Constraint(bounds) => {
+                            format!("{}: {}", name, self.bounds(bounds))
+                        }
+                    }
+                }));
+                if all.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", all.join(", "))
+                }
+            }
+            Some(GenericArgs::Parenthesized { inputs, output }) => {
+                format!("({}){}", self.types(inputs), self.output(output.as_ref()))
+            }
+        }
+    }
+}
+
+fn abi_name(name: &str, unwind: bool) -> String {
+    if unwind {
+        format!("{}-unwind", name)
+    } else {
+        name.to_owned()
+    }
+}
+
+fn lifetime_prefix(lifetime: &Option<String>) -> String {
+    lifetime.as_ref().map(|lifetime| format!("{} ", lifetime)).unwrap_or_default()
+}
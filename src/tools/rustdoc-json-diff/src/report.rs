@@ -0,0 +1,162 @@
+//! The changes found between two versions of a crate, and how they are printed.
+
+use std::fmt::{self, Write};
+
+use serde::Serialize;
+
+/// The part of the version that has to be bumped for a change to be released, following semver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Patch => "patch",
+            Severity::Minor => "minor",
+            Severity::Major => "major",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The item can't be named at this path anymore.
+    Removed,
+    /// The item was removed, but the same item, or one with the same signature, is now found at
+    /// the `new` path.
+    Renamed,
+    Added,
+    /// The declaration of the item changed, from `old` to `new`.
+    SignatureChanged,
+    /// An item without a default was added to a trait, which breaks its implementors.
+    RequiredTraitItemAdded,
+    /// A variant was added to an enum that isn't `#[non_exhaustive]`, which breaks exhaustive
+    /// matches.
+    VariantAdded,
+    /// A field was added to a struct or struct variant that isn't `#[non_exhaustive]` and has
+    /// only public fields, which breaks the expressions and patterns that list all of them.
+    FieldAdded,
+    NonExhaustiveAdded,
+    NonExhaustiveRemoved,
+    /// The type doesn't implement the auto trait `old` anymore.
+    AutoTraitRemoved,
+    /// The type now implements the auto trait `new`.
+    AutoTraitAdded,
+}
+
+impl ChangeKind {
+    fn severity(self) -> Severity {
+        match self {
+            ChangeKind::Removed
+            | ChangeKind::Renamed
+            | ChangeKind::SignatureChanged
+            | ChangeKind::RequiredTraitItemAdded
+            | ChangeKind::VariantAdded
+            | ChangeKind::FieldAdded
+            | ChangeKind::NonExhaustiveAdded
+            | ChangeKind::AutoTraitRemoved => Severity::Major,
+            ChangeKind::Added | ChangeKind::NonExhaustiveRemoved | ChangeKind::AutoTraitAdded => {
+                Severity::Minor
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub severity: Severity,
+    pub kind: ChangeKind,
+    /// The kind of the changed item, like `function` or `struct`.
+    pub item_kind: &'static str,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+impl Change {
+    pub fn new(
+        kind: ChangeKind,
+        item_kind: &'static str,
+        path: &str,
+        old: Option<String>,
+        new: Option<String>,
+    ) -> Self {
+        Change { severity: kind.severity(), kind, item_kind, path: path.to_owned(), old, new }
+    }
+
+    fn describe(&self) -> String {
+        let (path, item_kind) = (&self.path, self.item_kind);
+        let old = self.old.as_deref().unwrap_or_default();
+        let new = self.new.as_deref().unwrap_or_default();
+        match self.kind {
+            ChangeKind::Removed => format!("{} `{}` was removed", item_kind, path),
+            ChangeKind::Renamed => format!("{} `{}` was renamed to `{}`", item_kind, path, new),
+            ChangeKind::Added => format!("{} `{}` was added", item_kind, path),
+            ChangeKind::SignatureChanged => format!(
+                "the declaration of {} `{}` changed\n    old: {}\n    new: {}",
+                item_kind, path, old, new
+            ),
+            ChangeKind::RequiredTraitItemAdded => {
+                format!("required {} `{}` was added to its trait", item_kind, path)
+            }
+            ChangeKind::VariantAdded => {
+                format!("variant `{}` was added to an exhaustive enum", path)
+            }
+            ChangeKind::FieldAdded => {
+                format!("field `{}` was added to an exhaustive struct or variant", path)
+            }
+            ChangeKind::NonExhaustiveAdded => {
+                format!("{} `{}` is now `#[non_exhaustive]`", item_kind, path)
+            }
+            ChangeKind::NonExhaustiveRemoved => {
+                format!("{} `{}` is no longer `#[non_exhaustive]`", item_kind, path)
+            }
+            ChangeKind::AutoTraitRemoved => {
+                format!("{} `{}` no longer implements `{}`", item_kind, path, old)
+            }
+            ChangeKind::AutoTraitAdded => {
+                format!("{} `{}` now implements `{}`", item_kind, path, new)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// The highest severity of the changes, or `patch` if the public API didn't change.
+    pub required_bump: Severity,
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    /// The changes are listed from the most to the least severe, then by path.
+    pub fn new(mut changes: Vec<Change>) -> Self {
+        changes.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.path.cmp(&b.path)));
+        let required_bump = changes.first().map_or(Severity::Patch, |change| change.severity);
+        Report { required_bump, changes }
+    }
+
+    pub fn to_human(&self) -> String {
+        let mut out = String::new();
+        for change in &self.changes {
+            writeln!(out, "{}: {}", change.severity, change.describe()).unwrap();
+        }
+        if !self.changes.is_empty() {
+            out.push('\n');
+        }
+        writeln!(out, "required version bump: {}", self.required_bump).unwrap();
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
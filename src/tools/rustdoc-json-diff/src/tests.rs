@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use rustdoc_json_types::*;
+
+use crate::api::PublicApi;
+use crate::diff::diff;
+use crate::report::{Change, ChangeKind, Report, Severity};
+
+fn item(id: &str, name: &str, inner: ItemEnum) -> Item {
+    Item {
+        id: Id(id.to_owned()),
+        crate_id: 0,
+        name: Some(name.to_owned()),
+        span: None,
+        visibility: Visibility::Public,
+        docs: None,
+        links: HashMap::new(),
+        attrs: Vec::new(),
+        deprecation: None,
+        inner,
+    }
+}
+
+fn function(id: &str, name: &str, input: &str) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Function(Function {
+            decl: FnDecl {
+                inputs: vec![("x".to_owned(), Type::Primitive(input.to_owned()))],
+                output: None,
+                c_variadic: false,
+            },
+            generics: Generics::default(),
+            header: Header { const_: false, unsafe_: false, async_: false, abi: Abi::Rust },
+        }),
+    )
+}
+
+fn enum_(id: &str, name: &str, variants: &[&str]) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Enum(Enum {
+            generics: Generics::default(),
+            variants_stripped: false,
+            variants: variants.iter().map(|&id| Id(id.to_owned())).collect(),
+            impls: Vec::new(),
+        }),
+    )
+}
+
+fn variant(id: &str, name: &str) -> Item {
+    Item { visibility: Visibility::Default, ..item(id, name, ItemEnum::Variant(Variant::Plain)) }
+}
+
+fn method(id: &str, name: &str, has_body: bool) -> Item {
+    let decl = FnDecl { inputs: Vec::new(), output: None, c_variadic: false };
+    let header = Header { const_: false, unsafe_: false, async_: false, abi: Abi::Rust };
+    let method = Method { decl, generics: Generics::default(), header, has_body };
+    Item { visibility: Visibility::Default, ..item(id, name, ItemEnum::Method(method)) }
+}
+
+fn trait_(id: &str, name: &str, items: &[&str]) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Trait(Trait {
+            is_auto: false,
+            is_unsafe: false,
+            items: items.iter().map(|&id| Id(id.to_owned())).collect(),
+            generics: Generics::default(),
+            bounds: Vec::new(),
+            implementations: Vec::new(),
+        }),
+    )
+}
+
+fn struct_(id: &str, name: &str, impls: &[&str]) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Struct(Struct {
+            struct_type: StructType::Unit,
+            generics: Generics::default(),
+            fields_stripped: false,
+            fields: Vec::new(),
+            impls: impls.iter().map(|&id| Id(id.to_owned())).collect(),
+        }),
+    )
+}
+
+fn plain_struct(id: &str, name: &str, fields: &[&str]) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Struct(Struct {
+            struct_type: StructType::Plain,
+            generics: Generics::default(),
+            fields_stripped: false,
+            fields: fields.iter().map(|&id| Id(id.to_owned())).collect(),
+            impls: Vec::new(),
+        }),
+    )
+}
+
+fn struct_variant(id: &str, name: &str, fields: &[&str]) -> Item {
+    let variant = Variant::Struct(fields.iter().map(|&id| Id(id.to_owned())).collect());
+    Item { visibility: Visibility::Default, ..item(id, name, ItemEnum::Variant(variant)) }
+}
+
+fn field(id: &str, name: &str, visibility: Visibility) -> Item {
+    Item { visibility, ..item(id, name, ItemEnum::StructField(Type::Primitive("u8".to_owned()))) }
+}
+
+fn auto_impl(id: &str, trait_name: &str, for_: &str) -> Item {
+    let impl_ = Impl {
+        is_unsafe: false,
+        generics: Generics::default(),
+        provided_trait_methods: Vec::new(),
+        trait_: Some(Type::ResolvedPath {
+            name: trait_name.to_owned(),
+            id: Id(format!("{}-trait", id)),
+            args: None,
+            param_names: Vec::new(),
+        }),
+        for_: Type::ResolvedPath {
+            name: for_.to_owned(),
+            id: Id(for_.to_owned()),
+            args: None,
+            param_names: Vec::new(),
+        },
+        items: Vec::new(),
+        negative: false,
+        synthetic: true,
+        blanket_impl: None,
+    };
+    Item { name: None, ..item(id, "", ItemEnum::Impl(impl_)) }
+}
+
+/// Builds a crate named `krate` whose root module contains `root_items`.
+fn krate(root_items: &[&str], items: Vec<Item>) -> Crate {
+    let root = item(
+        "root",
+        "krate",
+        ItemEnum::Module(Module {
+            is_crate: true,
+            items: root_items.iter().map(|&id| Id(id.to_owned())).collect(),
+        }),
+    );
+    Crate {
+        root: root.id.clone(),
+        crate_version: None,
+        includes_private: false,
+        index: items.into_iter().chain([root]).map(|item| (item.id.clone(), item)).collect(),
+        paths: HashMap::new(),
+        external_crates: HashMap::new(),
+        format_version: FORMAT_VERSION,
+    }
+}
+
+fn changes(old: &Crate, new: &Crate) -> Vec<Change> {
+    Report::new(diff(&PublicApi::new(old), &PublicApi::new(new))).changes
+}
+
+fn kinds(changes: &[Change]) -> Vec<(ChangeKind, &str)> {
+    changes.iter().map(|change| (change.kind, change.path.as_str())).collect()
+}
+
+#[test]
+fn unchanged() {
+    let old = krate(&["f"], vec![function("f", "f", "u32")]);
+    let report = Report::new(diff(&PublicApi::new(&old), &PublicApi::new(&old)));
+    assert_eq!(report.required_bump, Severity::Patch);
+    assert!(report.changes.is_empty());
+    assert_eq!(report.to_human(), "required version bump: patch\n");
+}
+
+#[test]
+fn removed_and_added() {
+    let old = krate(&["f"], vec![function("f", "f", "u32")]);
+    let new = krate(&["g"], vec![function("g", "g", "u64")]);
+    let changes = changes(&old, &new);
+    assert_eq!(
+        kinds(&changes),
+        [(ChangeKind::Removed, "krate::f"), (ChangeKind::Added, "krate::g")]
+    );
+    assert_eq!(changes[0].old.as_deref(), Some("fn f(x: u32)"));
+    assert_eq!(changes[1].severity, Severity::Minor);
+}
+
+#[test]
+fn renamed() {
+    let old = krate(&["f"], vec![function("f", "f", "u32")]);
+    let new = krate(&["g"], vec![function("g", "g", "u32")]);
+    let changes = changes(&old, &new);
+    assert_eq!(kinds(&changes), [(ChangeKind::Renamed, "krate::f")]);
+    assert_eq!(changes[0].new.as_deref(), Some("krate::g"));
+}
+
+#[test]
+fn moved() {
+    let module = |items: &[&str]| {
+        ItemEnum::Module(Module {
+            is_crate: false,
+            items: items.iter().map(|&id| Id(id.to_owned())).collect(),
+        })
+    };
+    let old = krate(&["m"], vec![item("m", "m", module(&["f"])), function("f", "f", "u32")]);
+    let new = krate(&["m", "f"], vec![item("m", "m", module(&[])), function("f", "f", "u32")]);
+    let changes = changes(&old, &new);
+    assert_eq!(kinds(&changes), [(ChangeKind::Renamed, "krate::m::f")]);
+    assert_eq!(changes[0].new.as_deref(), Some("krate::f"));
+}
+
+#[test]
+fn signature_changed() {
+    let old = krate(&["f"], vec![function("f", "f", "u32")]);
+    let new = krate(&["f"], vec![function("f", "f", "u64")]);
+    let report = Report::new(diff(&PublicApi::new(&old), &PublicApi::new(&new)));
+    assert_eq!(report.required_bump, Severity::Major);
+    assert_eq!(
+        report.to_human(),
+        "major: the declaration of function `krate::f` changed\n    old: fn f(x: u32)\n    new: \
+         fn f(x: u64)\n\nrequired version bump: major\n"
+    );
+}
+
+#[test]
+fn argument_renamed() {
+    let old = krate(&["f"], vec![function("f", "f", "u32")]);
+    let mut new = old.clone();
+    if let ItemEnum::Function(f) = &mut new.index.get_mut(&Id("f".to_owned())).unwrap().inner {
+        f.decl.inputs[0].0 = "y".to_owned();
+    }
+    assert!(changes(&old, &new).is_empty());
+}
+
+#[test]
+fn variant_added() {
+    let old = krate(&["e"], vec![enum_("e", "E", &["a"]), variant("a", "A")]);
+    let new =
+        krate(&["e"], vec![enum_("e", "E", &["a", "b"]), variant("a", "A"), variant("b", "B")]);
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::VariantAdded, "krate::E::B")]);
+
+    let non_exhaustive = |e: Item| Item { attrs: vec!["#[non_exhaustive]".to_owned()], ..e };
+    let old = krate(&["e"], vec![non_exhaustive(enum_("e", "E", &["a"])), variant("a", "A")]);
+    let new = krate(
+        &["e"],
+        vec![non_exhaustive(enum_("e", "E", &["a", "b"])), variant("a", "A"), variant("b", "B")],
+    );
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::Added, "krate::E::B")]);
+}
+
+#[test]
+fn field_added() {
+    let old =
+        krate(&["s"], vec![plain_struct("s", "S", &["a"]), field("a", "a", Visibility::Public)]);
+    let new = krate(
+        &["s"],
+        vec![
+            plain_struct("s", "S", &["a", "b"]),
+            field("a", "a", Visibility::Public),
+            field("b", "b", Visibility::Public),
+        ],
+    );
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::FieldAdded, "krate::S::b")]);
+
+    // Other crates can't construct a struct with a private field.
+    let old =
+        krate(&["s"], vec![plain_struct("s", "S", &["a"]), field("a", "a", Visibility::Crate)]);
+    let new = krate(
+        &["s"],
+        vec![
+            plain_struct("s", "S", &["a", "b"]),
+            field("a", "a", Visibility::Crate),
+            field("b", "b", Visibility::Public),
+        ],
+    );
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::Added, "krate::S::b")]);
+
+    let old = krate(
+        &["e"],
+        vec![
+            enum_("e", "E", &["v"]),
+            struct_variant("v", "V", &["a"]),
+            field("a", "a", Visibility::Default),
+        ],
+    );
+    let new = krate(
+        &["e"],
+        vec![
+            enum_("e", "E", &["v"]),
+            struct_variant("v", "V", &["a", "b"]),
+            field("a", "a", Visibility::Default),
+            field("b", "b", Visibility::Default),
+        ],
+    );
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::FieldAdded, "krate::E::V::b")]);
+}
+
+#[test]
+fn non_exhaustive_added() {
+    let old = krate(&["e"], vec![enum_("e", "E", &[])]);
+    let new = krate(
+        &["e"],
+        vec![Item { attrs: vec!["#[non_exhaustive]".to_owned()], ..enum_("e", "E", &[]) }],
+    );
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::NonExhaustiveAdded, "krate::E")]);
+    assert_eq!(kinds(&changes(&new, &old)), [(ChangeKind::NonExhaustiveRemoved, "krate::E")]);
+}
+
+#[test]
+fn trait_item_added() {
+    let old = krate(&["t"], vec![trait_("t", "T", &[])]);
+    let new = krate(
+        &["t"],
+        vec![
+            trait_("t", "T", &["a", "b"]),
+            method("a", "required", false),
+            method("b", "provided", true),
+        ],
+    );
+    assert_eq!(
+        kinds(&changes(&old, &new)),
+        [
+            (ChangeKind::RequiredTraitItemAdded, "krate::T::required"),
+            (ChangeKind::Added, "krate::T::provided")
+        ]
+    );
+}
+
+#[test]
+fn auto_trait_removed() {
+    let old = krate(
+        &["s"],
+        vec![
+            struct_("s", "S", &["send", "sync"]),
+            auto_impl("send", "Send", "s"),
+            auto_impl("sync", "Sync", "s"),
+        ],
+    );
+    let new = krate(&["s"], vec![struct_("s", "S", &["sync"]), auto_impl("sync", "Sync", "s")]);
+    let changes = changes(&old, &new);
+    assert_eq!(kinds(&changes), [(ChangeKind::AutoTraitRemoved, "krate::S")]);
+    assert_eq!(changes[0].old.as_deref(), Some("Send"));
+}
+
+#[test]
+fn children_of_removed_items() {
+    let old = krate(&["e"], vec![enum_("e", "E", &["a"]), variant("a", "A")]);
+    let new = krate(&[], Vec::new());
+    assert_eq!(kinds(&changes(&old, &new)), [(ChangeKind::Removed, "krate::E")]);
+}